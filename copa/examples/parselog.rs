@@ -37,6 +37,10 @@ impl Perform for Log {
         );
     }
 
+    fn apc_dispatch(&mut self, bytes: &[u8]) {
+        println!("[apc_dispatch] bytes={:?}", bytes);
+    }
+
     fn csi_dispatch(
         &mut self,
         params: &Params,
@@ -24,6 +24,7 @@
 //!
 //! * UTF-8 Support for Input
 //! * OSC Strings can be terminated by 0x07
+//! * APC Strings are collected and dispatched, other SOS/PM strings are ignored
 //! * Only supports 7-bit codes. Some 8-bit codes are still supported, but they no longer work in
 //!   all states.
 //!
@@ -84,6 +85,7 @@ pub struct Parser<const OSC_RAW_BUF_SIZE: usize = MAX_OSC_RAW> {
     osc_raw: Vec<u8>,
     osc_params: [(usize, usize); MAX_OSC_PARAMS],
     osc_num_params: usize,
+    apc_active: bool,
     ignoring: bool,
     utf8_parser: utf8::Parser,
 }
@@ -178,6 +180,15 @@ impl<const OSC_RAW_BUF_SIZE: usize> Parser<OSC_RAW_BUF_SIZE> {
 
         match state {
             State::Anywhere => {
+                // APC strings share the SOS/PM state, but their content is kept.
+                if self.apc_active {
+                    if let (State::SosPmApcString, Action::Ignore) = (self.state, action)
+                    {
+                        self.apc_put(byte);
+                        return;
+                    }
+                }
+
                 // Just run the action
                 self.perform_action(performer, action, byte);
             }
@@ -189,6 +200,10 @@ impl<const OSC_RAW_BUF_SIZE: usize> Parser<OSC_RAW_BUF_SIZE> {
                     State::OscString => {
                         self.perform_action(performer, Action::OscEnd, byte);
                     }
+                    State::SosPmApcString if self.apc_active => {
+                        self.apc_active = false;
+                        performer.apc_dispatch(&self.osc_raw);
+                    }
                     _ => (),
                 }
 
@@ -204,6 +219,11 @@ impl<const OSC_RAW_BUF_SIZE: usize> Parser<OSC_RAW_BUF_SIZE> {
                     State::OscString => {
                         self.perform_action(performer, Action::OscStart, byte);
                     }
+                    // Only APC (ESC _) is collected, SOS and PM are dropped.
+                    State::SosPmApcString if byte == b'_' => {
+                        self.osc_raw.clear();
+                        self.apc_active = true;
+                    }
                     _ => (),
                 }
 
@@ -213,6 +233,22 @@ impl<const OSC_RAW_BUF_SIZE: usize> Parser<OSC_RAW_BUF_SIZE> {
         }
     }
 
+    /// Collect a byte of an APC string.
+    ///
+    /// APC and OSC strings can never be active at the same time, so the OSC
+    /// buffer is reused to store the APC content.
+    #[inline]
+    fn apc_put(&mut self, byte: u8) {
+        #[cfg(feature = "no_std")]
+        {
+            if self.osc_raw.is_full() {
+                return;
+            }
+        }
+
+        self.osc_raw.push(byte);
+    }
+
     /// Separate method for osc_dispatch that borrows self as read-only
     ///
     /// The aliasing is needed here for multiple slices into self.osc_raw
@@ -429,6 +465,12 @@ pub trait Perform {
     /// Dispatch an operating system command.
     fn osc_dispatch(&mut self, _params: &[&[u8]], _bell_terminated: bool) {}
 
+    /// Dispatch an application program command.
+    ///
+    /// The full content between `ESC _` and the string terminator is passed
+    /// without any interpretation, e.g. kitty graphics commands start with `G`.
+    fn apc_dispatch(&mut self, _bytes: &[u8]) {}
+
     /// A final character has arrived for a CSI sequence
     ///
     /// The `ignore` flag indicates that either more than two intermediates arrived
@@ -480,6 +522,7 @@ mod tests {
         DcsHook(Vec<Vec<u16>>, Vec<u8>, bool, char),
         DcsPut(u8),
         DcsUnhook,
+        Apc(Vec<u8>),
     }
 
     impl Perform for Dispatcher {
@@ -521,6 +564,10 @@ mod tests {
         fn unhook(&mut self) {
             self.dispatched.push(Sequence::DcsUnhook);
         }
+
+        fn apc_dispatch(&mut self, bytes: &[u8]) {
+            self.dispatched.push(Sequence::Apc(bytes.to_vec()));
+        }
     }
 
     #[test]
@@ -657,6 +704,43 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_apc() {
+        static INPUT: &[u8] = b"\x1b_Gf=100,a=T;AAAA\x1b\\";
+        let mut dispatcher = Dispatcher::default();
+        let mut parser = Parser::new();
+
+        for byte in INPUT {
+            parser.advance(&mut dispatcher, *byte);
+        }
+
+        assert_eq!(dispatcher.dispatched.len(), 2);
+        assert_eq!(
+            dispatcher.dispatched[0],
+            Sequence::Apc(b"Gf=100,a=T;AAAA".to_vec())
+        );
+        assert_eq!(
+            dispatcher.dispatched[1],
+            Sequence::Esc(vec![], false, b'\\')
+        );
+    }
+
+    #[test]
+    fn sos_and_pm_are_ignored() {
+        static INPUT: &[u8] = b"\x1bXsos\x1b\\\x1b^pm\x1b\\";
+        let mut dispatcher = Dispatcher::default();
+        let mut parser = Parser::new();
+
+        for byte in INPUT {
+            parser.advance(&mut dispatcher, *byte);
+        }
+
+        assert!(dispatcher
+            .dispatched
+            .iter()
+            .all(|sequence| !matches!(sequence, Sequence::Apc(_))));
+    }
+
     // #[test]
     // fn issue_191() {
     //     use crate::std::string::{String, ToString};
@@ -129,6 +129,8 @@ mod token;
 
 pub mod channel;
 #[cfg(unix)]
+pub mod listener;
+#[cfg(unix)]
 pub mod stream;
 pub mod timer;
 
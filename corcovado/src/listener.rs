@@ -0,0 +1,127 @@
+use std::io;
+use std::os::unix::net;
+use std::os::unix::prelude::*;
+use std::path::Path;
+
+use crate::event::Evented;
+use crate::stream::UnixStream;
+use crate::unix::EventedFd;
+use crate::{Poll, PollOpt, Ready, Token};
+
+/// A structure representing a Unix domain socket server.
+///
+/// This listener can be used to accept new streams connected to a remote
+/// endpoint, through which the `read` and `write` methods can be used to
+/// communicate.
+///
+/// Like `UnixStream`, the listener is in nonblocking mode, and accepting a
+/// connection returns `None` when no client is waiting yet.
+#[derive(Debug)]
+pub struct UnixListener {
+    inner: net::UnixListener,
+}
+
+impl UnixListener {
+    /// Creates a new `UnixListener` bound to the specified socket.
+    pub fn bind<P: AsRef<Path>>(path: P) -> io::Result<UnixListener> {
+        UnixListener::from_listener(net::UnixListener::bind(path)?)
+    }
+
+    /// Consumes a standard library `UnixListener` and returns a wrapped
+    /// `UnixListener` compatible with mio.
+    ///
+    /// The returned listener is moved into nonblocking mode and is otherwise
+    /// ready to get associated with an event loop.
+    pub fn from_listener(listener: net::UnixListener) -> io::Result<UnixListener> {
+        listener.set_nonblocking(true)?;
+        Ok(UnixListener { inner: listener })
+    }
+
+    /// Accepts a new incoming connection to this listener.
+    ///
+    /// When established, the corresponding `UnixStream` and the remote
+    /// peer's address will be returned as `Ok(Some(...))`. If there is no
+    /// connection waiting to be accepted, then `Ok(None)` is returned.
+    pub fn accept(&self) -> io::Result<Option<(UnixStream, net::SocketAddr)>> {
+        match self.accept_std()? {
+            Some((stream, addr)) => Ok(Some((UnixStream::from_stream(stream)?, addr))),
+            None => Ok(None),
+        }
+    }
+
+    /// Accepts a new incoming connection to this listener, returning the
+    /// standard library stream, which is left in blocking mode.
+    pub fn accept_std(&self) -> io::Result<Option<(net::UnixStream, net::SocketAddr)>> {
+        match self.inner.accept() {
+            Ok((stream, addr)) => {
+                // The accepted stream inherits the mode of the listener on
+                // some platforms.
+                stream.set_nonblocking(false)?;
+                Ok(Some((stream, addr)))
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Creates a new independently owned handle to the underlying socket.
+    pub fn try_clone(&self) -> io::Result<UnixListener> {
+        self.inner.try_clone().map(|l| UnixListener { inner: l })
+    }
+
+    /// Returns the local socket address of this listener.
+    pub fn local_addr(&self) -> io::Result<net::SocketAddr> {
+        self.inner.local_addr()
+    }
+
+    /// Returns the value of the `SO_ERROR` option.
+    pub fn take_error(&self) -> io::Result<Option<io::Error>> {
+        self.inner.take_error()
+    }
+}
+
+impl Evented for UnixListener {
+    fn register(
+        &self,
+        poll: &Poll,
+        token: Token,
+        events: Ready,
+        opts: PollOpt,
+    ) -> io::Result<()> {
+        EventedFd(&self.as_raw_fd()).register(poll, token, events, opts)
+    }
+
+    fn reregister(
+        &self,
+        poll: &Poll,
+        token: Token,
+        events: Ready,
+        opts: PollOpt,
+    ) -> io::Result<()> {
+        EventedFd(&self.as_raw_fd()).reregister(poll, token, events, opts)
+    }
+
+    fn deregister(&self, poll: &Poll) -> io::Result<()> {
+        EventedFd(&self.as_raw_fd()).deregister(poll)
+    }
+}
+
+impl AsRawFd for UnixListener {
+    fn as_raw_fd(&self) -> i32 {
+        self.inner.as_raw_fd()
+    }
+}
+
+impl IntoRawFd for UnixListener {
+    fn into_raw_fd(self) -> i32 {
+        self.inner.into_raw_fd()
+    }
+}
+
+impl FromRawFd for UnixListener {
+    unsafe fn from_raw_fd(fd: i32) -> UnixListener {
+        UnixListener {
+            inner: net::UnixListener::from_raw_fd(fd),
+        }
+    }
+}
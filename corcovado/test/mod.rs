@@ -29,6 +29,8 @@ mod test_tcp_level;
 mod test_tcp_shutdown;
 mod test_udp_level;
 mod test_udp_socket;
+#[cfg(unix)]
+mod test_unix_listener;
 mod test_write_then_drop;
 
 #[cfg(target_os = "fuchsia")]
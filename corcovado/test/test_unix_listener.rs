@@ -0,0 +1,43 @@
+use corcovado::listener::UnixListener;
+use corcovado::stream::UnixStream;
+use corcovado::{Events, Poll, PollOpt, Ready, Token};
+use std::io::{Read, Write};
+use std::time::Duration;
+use tempdir::TempDir;
+
+const SERVER: Token = Token(0);
+
+#[test]
+pub fn test_unix_listener_accept() {
+    let dir = TempDir::new("corcovado").unwrap();
+    let path = dir.path().join("sock");
+
+    let poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(16);
+
+    let listener = UnixListener::bind(&path).unwrap();
+    poll.register(&listener, SERVER, Ready::readable(), PollOpt::level())
+        .unwrap();
+
+    // Nothing is waiting to be accepted yet.
+    assert!(listener.accept().unwrap().is_none());
+
+    let mut client = std::os::unix::net::UnixStream::connect(&path).unwrap();
+    poll.poll(&mut events, Some(Duration::from_secs(5)))
+        .unwrap();
+    assert!(events.iter().any(|event| event.token() == SERVER));
+
+    let (mut server, _) = listener.accept_std().unwrap().unwrap();
+    client.write_all(b"ping").unwrap();
+    let mut buf = [0; 4];
+    server.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"ping");
+
+    // Streams accepted as corcovado streams are nonblocking.
+    let _client = UnixStream::connect(&path).unwrap();
+    poll.poll(&mut events, Some(Duration::from_secs(5)))
+        .unwrap();
+    let (mut stream, _) = listener.accept().unwrap().unwrap();
+    let err = stream.read(&mut buf).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::WouldBlock);
+}
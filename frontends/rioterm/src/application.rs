@@ -1,13 +1,22 @@
+use crate::context::grid::SplitDirection;
+use crate::context::PaneConfig;
 use crate::event::{ClickState, EventPayload, EventProxy, RioEvent, RioEventType};
 use crate::ime::Preedit;
+use crate::notifications::{Notifier, SystemNotifications};
 use crate::renderer::utils::update_colors_based_on_theme;
 use crate::router::{routes::RoutePath, Router};
 use crate::scheduler::{Scheduler, TimerId, Topic};
+use crate::screen::search_history::SearchHistory;
 use crate::screen::touch::on_touch;
+use crate::session::{Session, TabSession, WindowSession};
 use crate::watcher::configuration_file_updates;
 use raw_window_handle::HasDisplayHandle;
 use rio_backend::clipboard::{Clipboard, ClipboardType};
 use rio_backend::config::colors::ColorRgb;
+use rio_backend::config::layouts::LayoutSplit;
+use rio_backend::config::{ConfigError, Shell};
+use rio_backend::crosswords::grid::Dimensions;
+use rio_backend::ipc::{PaneInfo, Request, Response, Target};
 use rio_window::application::ApplicationHandler;
 use rio_window::event::{
     ElementState, Hook, Ime, MouseButton, MouseScrollDelta, StartCause, TouchPhase,
@@ -21,7 +30,7 @@ use rio_window::platform::macos::ActiveEventLoopExtMacOS;
 #[cfg(target_os = "macos")]
 use rio_window::platform::macos::WindowExtMacOS;
 use rio_window::window::WindowId;
-use rio_window::window::{CursorIcon, Fullscreen};
+use rio_window::window::{CursorIcon, Fullscreen, UserAttentionType};
 use std::error::Error;
 use std::time::{Duration, Instant};
 
@@ -30,6 +39,9 @@ pub struct Application<'a> {
     event_proxy: EventProxy,
     router: Router<'a>,
     scheduler: Scheduler,
+    notifier: Notifier<SystemNotifications>,
+    /// Configuration values changed through the IPC socket.
+    config_overrides: Vec<String>,
 }
 
 impl Application<'_> {
@@ -43,7 +55,8 @@ impl Application<'_> {
         let clipboard =
             unsafe { Clipboard::new(event_loop.display_handle().unwrap().as_raw()) };
 
-        let mut router = Router::new(config.fonts.to_owned(), clipboard);
+        let search_history = SearchHistory::new(config.search.history_size);
+        let mut router = Router::new(config.fonts.to_owned(), clipboard, search_history);
         if let Some(error) = config_error {
             router.propagate_error_to_next_route(error.into());
         }
@@ -60,11 +73,23 @@ impl Application<'_> {
         #[cfg(target_os = "macos")]
         event_loop.set_confirm_before_quit(config.confirm_before_quit);
 
+        let notifier = Notifier::new(config.notifications.policy, SystemNotifications);
+
+        #[cfg(unix)]
+        if config.ipc_socket {
+            match crate::ipc::spawn_ipc_socket(event_proxy.clone()) {
+                Ok(path) => tracing::info!("listening on ipc socket {path:?}"),
+                Err(err) => tracing::warn!("unable to create the ipc socket: {err:?}"),
+            }
+        }
+
         Application {
             config,
             event_proxy,
             router,
             scheduler,
+            notifier,
+            config_overrides: Vec::new(),
         }
     }
 
@@ -89,6 +114,284 @@ impl Application<'_> {
         )
     }
 
+    /// Store the windows, tabs and splits in the session file, the window
+    /// of the configuration editor is left out.
+    fn save_session(&self) {
+        let scrollback_lines = self.config.session.scrollback_lines;
+        let windows = self
+            .router
+            .routes
+            .iter()
+            .filter(|(id, _)| self.router.config_route != Some(**id))
+            .map(|(_, route)| {
+                let window = &route.window.winit_window;
+                let size = window.inner_size().to_logical::<i32>(window.scale_factor());
+                let position = window.outer_position().ok();
+                let context_manager = &route.window.screen.context_manager;
+
+                WindowSession {
+                    width: size.width,
+                    height: size.height,
+                    x: position.map(|position| position.x),
+                    y: position.map(|position| position.y),
+                    maximized: window.is_maximized(),
+                    current_tab: context_manager.current_index(),
+                    tabs: context_manager
+                        .contexts()
+                        .iter()
+                        .map(|grid| {
+                            TabSession::new(
+                                grid,
+                                &context_manager.config.shell,
+                                scrollback_lines,
+                            )
+                        })
+                        .collect(),
+                }
+            })
+            .collect();
+
+        Session { windows }.save();
+    }
+
+    /// Load the configuration file again, with the values changed through
+    /// the IPC socket, and apply it to every window.
+    fn reload_config(&mut self) {
+        let (config, config_error) =
+            match rio_backend::config::Config::try_load_with_overrides(
+                &self.config_overrides,
+            ) {
+                Ok(config) => (config, None),
+                Err(error) => (rio_backend::config::Config::default(), Some(error)),
+            };
+
+        let has_font_updates = self.config.fonts != config.fonts;
+
+        let font_library_errors = if has_font_updates {
+            let new_font_library =
+                rio_backend::sugarloaf::font::FontLibrary::new(config.fonts.to_owned());
+            self.router.font_library = Box::new(new_font_library.0);
+            new_font_library.1
+        } else {
+            None
+        };
+
+        self.config = config;
+        self.config.layout = None;
+        self.notifier.policy = self.config.notifications.policy;
+        for (_id, route) in self.router.routes.iter_mut() {
+            if has_font_updates {
+                if let Some(ref err) = font_library_errors {
+                    route
+                        .window
+                        .screen
+                        .context_manager
+                        .report_error_fonts_not_found(err.fonts_not_found.clone());
+                }
+            }
+
+            route.update_config(
+                &self.config,
+                &self.router.font_library,
+                has_font_updates,
+            );
+            route.window.configure_window(&self.config);
+
+            if let Some(error) = &config_error {
+                route.report_error(&error.to_owned().into());
+            } else {
+                route.clear_errors();
+            }
+        }
+    }
+
+    /// Answer a request received on the IPC socket.
+    fn handle_ipc_request(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        request: Request,
+    ) -> Result<Response, String> {
+        match request {
+            Request::CreateWindow {
+                command,
+                working_dir,
+            } => {
+                let mut config = self.config.clone();
+                if let Some(shell) = shell_from_command(command) {
+                    config.shell = shell;
+                    config.use_fork = false;
+                }
+                if working_dir.is_some() {
+                    config.working_dir = working_dir;
+                }
+
+                let window_id = self.router.create_window(
+                    event_loop,
+                    self.event_proxy.clone(),
+                    &config,
+                    None,
+                );
+                let route = &self.router.routes[&window_id];
+                Ok(Response::Pane {
+                    window: window_id.into(),
+                    pane: route.window.screen.ctx().current_route(),
+                })
+            }
+            Request::CreateTab {
+                window,
+                command,
+                working_dir,
+            } => {
+                let window_id = self.ipc_window(window)?;
+                let route = self.router.routes.get_mut(&window_id).unwrap();
+                let pane = PaneConfig {
+                    shell: shell_from_command(command),
+                    working_dir,
+                    ..PaneConfig::default()
+                };
+                if !route.window.screen.create_tab_with_pane(&pane) {
+                    return Err(String::from("unable to create a tab"));
+                }
+
+                Ok(Response::Pane {
+                    window: window_id.into(),
+                    pane: route.window.screen.ctx().current_route(),
+                })
+            }
+            Request::Split {
+                target,
+                direction,
+                command,
+                working_dir,
+            } => {
+                let (window_id, tab, split) = self.ipc_pane(&target)?;
+                let route = self.router.routes.get_mut(&window_id).unwrap();
+                let pane = PaneConfig {
+                    shell: shell_from_command(command),
+                    working_dir,
+                    ..PaneConfig::default()
+                };
+                route.window.screen.context_manager.select_split(tab, split);
+                if !route
+                    .window
+                    .screen
+                    .split_with_pane(direction == LayoutSplit::Down, &pane)
+                {
+                    return Err(String::from("unable to split the pane"));
+                }
+
+                Ok(Response::Pane {
+                    window: window_id.into(),
+                    pane: route.window.screen.ctx().current_route(),
+                })
+            }
+            Request::SendText { target, text } => {
+                let (window_id, tab, split) = self.ipc_pane(&target)?;
+                let context_manager =
+                    &self.router.routes[&window_id].window.screen.context_manager;
+                context_manager.contexts()[tab].contexts()[split]
+                    .context()
+                    .messenger
+                    .send_write(text.into_bytes());
+                Ok(Response::Ok)
+            }
+            Request::ListPanes => {
+                let focused = self.router.get_focused_route();
+                let mut panes = Vec::new();
+                for (window_id, route) in &self.router.routes {
+                    let context_manager = &route.window.screen.context_manager;
+                    for (tab, grid) in context_manager.contexts().iter().enumerate() {
+                        for item in grid.contexts() {
+                            let context = item.context();
+                            let working_dir = context.current_directory();
+                            let terminal = context.terminal.lock();
+                            panes.push(PaneInfo {
+                                window: (*window_id).into(),
+                                tab,
+                                pane: context.route_id,
+                                title: terminal.title.to_owned(),
+                                working_dir,
+                                columns: terminal.columns(),
+                                lines: terminal.screen_lines(),
+                                focused: focused == Some(*window_id)
+                                    && context.route_id
+                                        == context_manager.current_route(),
+                            });
+                        }
+                    }
+                }
+                panes.sort_by_key(|pane| (pane.window, pane.tab, pane.pane));
+
+                Ok(Response::Panes { panes })
+            }
+            Request::GetText { target, lines } => {
+                let (window_id, tab, split) = self.ipc_pane(&target)?;
+                let context_manager =
+                    &self.router.routes[&window_id].window.screen.context_manager;
+                let text = context_manager.contexts()[tab].contexts()[split]
+                    .context()
+                    .terminal
+                    .lock()
+                    .last_lines_to_string(lines);
+                Ok(Response::Text { text })
+            }
+            Request::SetConfig { values, reset } => {
+                let mut overrides = if reset {
+                    Vec::new()
+                } else {
+                    self.config_overrides.clone()
+                };
+                overrides.extend(values);
+
+                // Invalid values are refused instead of being reported in
+                // every window.
+                if let Err(error) =
+                    rio_backend::config::Config::try_load_with_overrides(&overrides)
+                {
+                    return Err(match error {
+                        ConfigError::ErrLoadingConfig(message)
+                        | ConfigError::ErrLoadingTheme(message) => message,
+                        ConfigError::PathNotFound => {
+                            String::from("configuration file was not found")
+                        }
+                    });
+                }
+
+                self.config_overrides = overrides;
+                self.reload_config();
+                Ok(Response::Ok)
+            }
+        }
+    }
+
+    /// Window targeted by an IPC request, the focused one when unset.
+    fn ipc_window(&self, window: Option<u64>) -> Result<WindowId, String> {
+        match window {
+            Some(window) => Some(WindowId::from(window))
+                .filter(|window_id| self.router.routes.contains_key(window_id))
+                .ok_or_else(|| format!("window {window} was not found")),
+            None => self
+                .router
+                .get_focused_route()
+                .or_else(|| self.router.routes.keys().next().copied())
+                .ok_or_else(|| String::from("no window is open")),
+        }
+    }
+
+    /// Window, tab and split of the pane targeted by an IPC request, the
+    /// current pane of the window when unset.
+    fn ipc_pane(&self, target: &Target) -> Result<(WindowId, usize, usize), String> {
+        let window_id = self.ipc_window(target.window)?;
+        let context_manager =
+            &self.router.routes[&window_id].window.screen.context_manager;
+        let route_id = target.pane.unwrap_or(context_manager.current_route());
+        let (tab, split) = context_manager
+            .find_route(route_id)
+            .ok_or_else(|| format!("pane {route_id} was not found"))?;
+
+        Ok((window_id, tab, split))
+    }
+
     pub fn run(
         &mut self,
         event_loop: EventLoop<EventPayload>,
@@ -128,12 +431,38 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
 
         update_colors_based_on_theme(&mut self.config, event_loop.system_theme());
 
-        self.router.create_window(
-            event_loop,
-            self.event_proxy.clone(),
-            &self.config,
-            None,
-        );
+        // Layouts take precedence over the last session.
+        let session = if cause == StartCause::Init
+            && self.config.session.restore
+            && self.config.layout.is_none()
+        {
+            Session::load().filter(|session| !session.windows.is_empty())
+        } else {
+            None
+        };
+
+        match session {
+            Some(session) => {
+                for window in &session.windows {
+                    self.router.restore_window(
+                        event_loop,
+                        self.event_proxy.clone(),
+                        &self.config,
+                        window,
+                    );
+                }
+            }
+            None => {
+                self.router.create_window(
+                    event_loop,
+                    self.event_proxy.clone(),
+                    &self.config,
+                    None,
+                );
+            }
+        }
+        // The layout only opens in the first window.
+        self.config.layout = None;
 
         tracing::info!("Initialisation complete");
     }
@@ -153,6 +482,13 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
                 }
             }
             RioEventType::Rio(RioEvent::RenderRoute(route_id)) => {
+                // New output changes the matches of an active search.
+                if let Some(route) = self.router.routes.get_mut(&window_id) {
+                    if route_id == route.window.screen.ctx().current_route() {
+                        route.window.screen.outdate_search_count();
+                    }
+                }
+
                 if self.config.renderer.strategy.is_event_based() {
                     if let Some(route) = self.router.routes.get_mut(&window_id) {
                         if self.config.renderer.disable_unfocused_render
@@ -225,54 +561,26 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
                     route.report_error(&error);
                 }
             }
-            RioEventType::Rio(RioEvent::UpdateConfig) => {
-                let (config, config_error) = match rio_backend::config::Config::try_load()
-                {
-                    Ok(config) => (config, None),
-                    Err(error) => (rio_backend::config::Config::default(), Some(error)),
-                };
-
-                let has_font_updates = self.config.fonts != config.fonts;
-
-                let font_library_errors = if has_font_updates {
-                    let new_font_library = rio_backend::sugarloaf::font::FontLibrary::new(
-                        config.fonts.to_owned(),
-                    );
-                    self.router.font_library = Box::new(new_font_library.0);
-                    new_font_library.1
-                } else {
-                    None
-                };
-
-                self.config = config;
-                for (_id, route) in self.router.routes.iter_mut() {
-                    if has_font_updates {
-                        if let Some(ref err) = font_library_errors {
-                            route
-                                .window
-                                .screen
-                                .context_manager
-                                .report_error_fonts_not_found(
-                                    err.fonts_not_found.clone(),
-                                );
-                        }
-                    }
-
-                    route.update_config(
-                        &self.config,
-                        &self.router.font_library,
-                        has_font_updates,
-                    );
-                    route.window.configure_window(&self.config);
-
-                    if let Some(error) = &config_error {
-                        route.report_error(&error.to_owned().into());
-                    } else {
-                        route.clear_errors();
-                    }
+            RioEventType::Rio(RioEvent::CommandPalette) => {
+                if let Some(route) = self.router.routes.get_mut(&window_id) {
+                    route.open_command_palette();
+                    route.request_redraw();
                 }
             }
+            RioEventType::Rio(RioEvent::UpdateConfig) => {
+                self.reload_config();
+            }
+            RioEventType::Rio(RioEvent::Ipc(request, sender)) => {
+                let response = self
+                    .handle_ipc_request(event_loop, request)
+                    .unwrap_or_else(|message| Response::Error { message });
+                let _ = sender.send(response);
+            }
             RioEventType::Rio(RioEvent::Exit) => {
+                if self.config.session.restore {
+                    self.save_session();
+                }
+
                 if let Some(route) = self.router.routes.get_mut(&window_id) {
                     if cfg!(target_os = "macos") && self.config.confirm_before_quit {
                         route.confirm_quit();
@@ -296,6 +604,10 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
                         self.scheduler.unschedule_window(route_id);
 
                         if self.router.routes.is_empty() {
+                            // Every pane has exited, nothing is left to restore.
+                            if self.config.session.restore {
+                                self.save_session();
+                            }
                             event_loop.exit();
                         }
                     } else {
@@ -304,6 +616,9 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
                     }
                 }
             }
+            RioEventType::Rio(RioEvent::SaveSession) => {
+                self.save_session();
+            }
             RioEventType::Rio(RioEvent::CursorBlinkingChange) => {
                 if let Some(route) = self.router.routes.get_mut(&window_id) {
                     route.request_redraw();
@@ -378,6 +693,42 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
                     route.set_window_subtitle(&subtitle);
                 }
             }
+            RioEventType::Rio(RioEvent::Notification {
+                title,
+                body,
+                route_id,
+            }) => {
+                if let Some(route) = self.router.routes.get(&window_id) {
+                    let is_focused = route.window.is_focused
+                        && route.window.screen.ctx().current_route() == route_id;
+                    self.notifier.notify(&title, &body, is_focused);
+                }
+            }
+            RioEventType::Rio(RioEvent::Bell {
+                route_id,
+                urgency_hint,
+            }) => {
+                if let Some(route) = self.router.routes.get_mut(&window_id) {
+                    let screen = &mut route.window.screen;
+                    if screen.context_manager.ring_bell(route_id) {
+                        screen.renderer.visual_bell.ring();
+                    }
+
+                    if let Some(command) = &self.config.bell.command {
+                        if screen.renderer.bell_command.should_run() {
+                            screen.exec(&command.program, &command.args);
+                        }
+                    }
+
+                    if urgency_hint && !route.window.is_focused {
+                        route.window.winit_window.request_user_attention(Some(
+                            UserAttentionType::Informational,
+                        ));
+                    }
+
+                    route.request_redraw();
+                }
+            }
             RioEventType::Rio(RioEvent::MouseCursorDirty) => {
                 if let Some(route) = self.router.routes.get_mut(&window_id) {
                     route.window.screen.reset_mouse();
@@ -633,6 +984,14 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
             return;
         }
 
+        // Closing the last window quits Rio.
+        if event == WindowEvent::CloseRequested
+            && self.config.session.restore
+            && self.router.routes.len() == 1
+        {
+            self.save_session();
+        }
+
         let route = match self.router.routes.get_mut(&window_id) {
             Some(window) => window,
             None => return,
@@ -703,6 +1062,13 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
 
                 match state {
                     ElementState::Pressed => {
+                        // Borders between splits are dragged to resize them.
+                        if button == MouseButton::Left
+                            && route.window.screen.start_split_drag()
+                        {
+                            return;
+                        }
+
                         // In case need to switch grid current
                         route.window.screen.select_current_based_on_mouse();
 
@@ -730,8 +1096,9 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
                                 .window
                                 .screen
                                 .mouse_report(code, ElementState::Pressed);
-
-                            route.window.screen.process_mouse_bindings(button);
+                        } else if route.window.screen.process_mouse_bindings(button) {
+                            // Bound buttons don't change the click state or the selection.
+                            route.request_redraw();
                         } else {
                             // Calculate time since the last click to handle double/triple clicks.
                             let now = Instant::now();
@@ -769,9 +1136,14 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
 
                             route.request_redraw();
                         }
-                        route.window.screen.process_mouse_bindings(button);
                     }
                     ElementState::Released => {
+                        if button == MouseButton::Left
+                            && route.window.screen.stop_split_drag()
+                        {
+                            return;
+                        }
+
                         if !route.window.screen.modifiers.state().shift_key()
                             && route.window.screen.mouse_mode()
                         {
@@ -850,6 +1222,26 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
                 route.window.screen.mouse.x = x;
                 route.window.screen.mouse.y = y;
 
+                if route.window.screen.is_dragging_split() {
+                    route.window.screen.drag_split();
+                    return;
+                }
+
+                if !lmb_pressed && !rmb_pressed {
+                    if let Some(border) = route.window.screen.split_border_at_mouse() {
+                        let cursor_icon = if border.direction == SplitDirection::Right {
+                            CursorIcon::ColResize
+                        } else {
+                            CursorIcon::RowResize
+                        };
+                        route.window.winit_window.set_cursor(cursor_icon);
+                        // The border is outside of the text area, leaving it
+                        // restores the cursor.
+                        route.window.screen.mouse.inside_text_area = false;
+                        return;
+                    }
+                }
+
                 let point = route.window.screen.mouse_position(display_offset);
 
                 let square_changed = old_point != point;
@@ -1002,6 +1394,14 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
                     return;
                 }
 
+                if route.path == RoutePath::CommandPalette {
+                    if let Ime::Commit(text) = ime {
+                        route.palette.push_str(&text);
+                        route.request_redraw();
+                    }
+                    return;
+                }
+
                 match ime {
                     Ime::Commit(text) => {
                         // Don't use bracketed paste for single char input.
@@ -1060,6 +1460,7 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
                 route.window.is_focused = focused;
 
                 if has_regained_focus {
+                    route.window.winit_window.request_user_attention(None);
                     route.request_redraw();
                 }
 
@@ -1128,13 +1529,17 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
                             .screen
                             .render_dialog("Do you want to leave Rio?");
                     }
+                    RoutePath::CommandPalette => {
+                        route.window.screen.render_command_palette(&route.palette);
+                    }
                 }
                 // println!("Time elapsed in render() is: {:?}", duration);
                 // }
 
-                if self.config.renderer.strategy.is_continuous()
+                if (self.config.renderer.strategy.is_continuous()
                     && !self.config.renderer.disable_unfocused_render
-                    && route.window.is_focused
+                    && route.window.is_focused)
+                    || route.window.screen.renderer.visual_bell.is_animating()
                 {
                     route.request_frame(&mut self.scheduler);
                 }
@@ -1221,6 +1626,9 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
         // Renderer and contexts ran.
         self.router.routes.clear();
 
+        #[cfg(unix)]
+        crate::ipc::remove_socket();
+
         // SAFETY: The clipboard must be dropped before the event loop, so use the nop clipboard
         // as a safe placeholder.
         std::mem::swap(
@@ -1231,3 +1639,16 @@ impl ApplicationHandler<EventPayload> for Application<'_> {
         std::process::exit(0);
     }
 }
+
+/// Program of an IPC request, the configured shell runs when it is empty.
+fn shell_from_command(command: Vec<String>) -> Option<Shell> {
+    let (program, args) = command.split_first()?;
+    if program.is_empty() {
+        return None;
+    }
+
+    Some(Shell {
+        program: program.clone(),
+        args: args.to_vec(),
+    })
+}
@@ -4,23 +4,30 @@
 
 pub mod kitty_keyboard;
 
+use crate::context::grid::SplitDirection;
 use crate::crosswords::vi_mode::ViMotion;
 use crate::crosswords::Mode;
 use bitflags::bitflags;
 use rio_backend::config::bindings::KeyBinding as ConfigKeyBinding;
+use rio_backend::config::bindings::MouseBinding as ConfigMouseBinding;
+use rio_backend::config::hints::Hints as ConfigHints;
 use rio_backend::config::keyboard::Keyboard as ConfigKeyboard;
+use rio_backend::config::Shell;
 use rio_window::event::MouseButton;
 use rio_window::keyboard::Key::*;
 use rio_window::keyboard::NamedKey::*;
 use rio_window::keyboard::{Key, KeyLocation, ModifiersState, PhysicalKey};
 use std::fmt::Debug;
+use std::mem;
+use std::time::{Duration, Instant};
 // use rio_window::platform::scancode::PhysicalKeyExtScancode;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum FontSizeAction {
     Increase,
     Decrease,
     Reset,
+    Set(f32),
 }
 
 /// Mouse binding specific actions.
@@ -28,6 +35,8 @@ pub enum FontSizeAction {
 pub enum MouseAction {
     /// Expand the selection to the current mouse cursor position.
     ExpandSelection,
+    /// Open the hyperlink or URL under the mouse cursor.
+    OpenHyperlink,
 }
 
 impl From<MouseAction> for Action {
@@ -56,6 +65,12 @@ pub enum SearchAction {
     SearchHistoryPrevious,
     /// Go to the next regex in the search history.
     SearchHistoryNext,
+    /// Cycle between smart case, case sensitive and case insensitive search.
+    SearchToggleCase,
+    /// Toggle matching whole words only.
+    SearchToggleWholeWord,
+    /// Toggle matching the search literally instead of as a regex.
+    SearchToggleLiteral,
 }
 
 impl From<SearchAction> for Action {
@@ -64,7 +79,7 @@ impl From<SearchAction> for Action {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Binding<T> {
     /// Modifier keys required to activate binding.
     pub mods: ModifiersState,
@@ -82,6 +97,20 @@ pub struct Binding<T> {
     ///
     /// For example, this might be a key like "G", or a mouse button.
     pub trigger: T,
+
+    /// Keys and modifiers pressed in order before the trigger, for bindings
+    /// made of a sequence of keys like `control + a` then `c`.
+    pub sequence: Vec<(T, ModifiersState)>,
+}
+
+/// How the keys typed so far match the sequence of a binding.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SequenceMatch {
+    None,
+    /// The keys start the sequence, more keys are expected.
+    Prefix,
+    /// The keys are the whole sequence.
+    Complete,
 }
 
 impl<T: Eq> Binding<T> {
@@ -97,17 +126,68 @@ impl<T: Eq> Binding<T> {
         // checks to be short circuited.
         self.trigger == *input
             && self.mods == mods
-            && mode.contains(self.mode.clone())
-            && !mode.intersects(self.notmode.clone())
+            && self.sequence.is_empty()
+            && self.is_active(&mode)
+    }
+
+    /// Keys of the sequence followed by the trigger.
+    pub fn keys(&self) -> Vec<(T, ModifiersState)>
+    where
+        T: Clone,
+    {
+        let mut keys = self.sequence.clone();
+        keys.push((self.trigger.clone(), self.mods));
+        keys
+    }
+
+    #[inline]
+    fn is_active(&self, mode: &BindingMode) -> bool {
+        mode.contains(self.mode.clone()) && !mode.intersects(self.notmode.clone())
+    }
+
+    /// Match the keys typed so far against the sequence of the binding.
+    pub fn sequence_match(
+        &self,
+        mode: &BindingMode,
+        keys: &[(T, ModifiersState)],
+    ) -> SequenceMatch {
+        if self.sequence.is_empty() || keys.is_empty() || !self.is_active(mode) {
+            return SequenceMatch::None;
+        }
+
+        let len = self.sequence.len() + 1;
+        let is_prefix = keys.len() <= len
+            && keys.iter().enumerate().all(|(index, (input, mods))| {
+                match self.sequence.get(index) {
+                    Some((trigger, trigger_mods)) => {
+                        trigger == input && trigger_mods == mods
+                    }
+                    None => self.trigger == *input && self.mods == *mods,
+                }
+            });
+
+        match is_prefix {
+            true if keys.len() == len => SequenceMatch::Complete,
+            true => SequenceMatch::Prefix,
+            false => SequenceMatch::None,
+        }
     }
 
     #[inline]
     pub fn triggers_match(&self, binding: &Binding<T>) -> bool {
         // Check the binding's key and modifiers.
-        if self.trigger != binding.trigger || self.mods != binding.mods {
+        if self.trigger != binding.trigger
+            || self.mods != binding.mods
+            || self.sequence != binding.sequence
+        {
             return false;
         }
 
+        self.modes_overlap(binding)
+    }
+
+    /// Whether both bindings can be active at the same time.
+    fn modes_overlap(&self, binding: &Binding<T>) -> bool {
         let selfmode = if self.mode.is_empty() {
             BindingMode::all()
         } else {
@@ -145,6 +225,94 @@ pub enum BindingKey {
     },
 }
 
+impl BindingKey {
+    /// Name of the key with its modifiers, like `control+a`.
+    pub fn name(&self, mods: ModifiersState) -> String {
+        let mut name = String::new();
+        for (modifier, modifier_name) in [
+            (ModifiersState::CONTROL, "control+"),
+            (ModifiersState::ALT, "alt+"),
+            (ModifiersState::SHIFT, "shift+"),
+            (ModifiersState::SUPER, "super+"),
+        ] {
+            if mods.contains(modifier) {
+                name.push_str(modifier_name);
+            }
+        }
+
+        match self {
+            BindingKey::Keycode {
+                key: Key::Character(c),
+                ..
+            } => name.push_str(c),
+            BindingKey::Keycode {
+                key: Key::Named(named),
+                ..
+            } => name.push_str(&format!("{named:?}").to_lowercase()),
+            key => name.push_str(&format!("{key:?}")),
+        }
+
+        name
+    }
+}
+
+/// Names of keys pressed in order, like `control+a c`.
+pub fn sequence_name(keys: &[(BindingKey, ModifiersState)]) -> String {
+    keys.iter()
+        .map(|(key, mods)| key.name(*mods))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Keys typed so far of a key binding sequence.
+pub struct PendingKeys {
+    keys: Vec<(BindingKey, ModifiersState)>,
+    last_key: Instant,
+    timeout: Duration,
+}
+
+impl PendingKeys {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            keys: Vec::new(),
+            last_key: Instant::now(),
+            timeout,
+        }
+    }
+
+    #[inline]
+    pub fn keys(&self) -> &[(BindingKey, ModifiersState)] {
+        &self.keys
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    #[inline]
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    pub fn push(&mut self, key: BindingKey, mods: ModifiersState, now: Instant) {
+        self.keys.push((key, mods));
+        self.last_key = now;
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.keys.clear();
+    }
+
+    /// Time left to type the next key, `None` once the sequence expired.
+    pub fn remaining(&self, now: Instant) -> Option<Duration> {
+        self.timeout
+            .checked_sub(now.saturating_duration_since(self.last_key))
+            .filter(|remaining| !remaining.is_zero())
+    }
+}
+
 pub type KeyBinding = Binding<BindingKey>;
 pub type KeyBindings = Vec<KeyBinding>;
 
@@ -206,109 +374,224 @@ impl Program {
             Program::WithArgs { args, .. } => args,
         }
     }
-}
 
-impl From<String> for Action {
-    fn from(action: String) -> Action {
-        let action = action.to_lowercase();
-
-        let action_from_string = match action.as_str() {
-            "paste" => Some(Action::Paste),
-            "quit" => Some(Action::Quit),
-            "copy" => Some(Action::Copy),
-            "searchforward" => Some(Action::SearchForward),
-            "searchbackward" => Some(Action::SearchBackward),
-            "searchconfirm" => Some(Action::Search(SearchAction::SearchConfirm)),
-            "searchcancel" => Some(Action::Search(SearchAction::SearchCancel)),
-            "searchclear" => Some(Action::Search(SearchAction::SearchClear)),
-            "searchfocusnext" => Some(Action::Search(SearchAction::SearchFocusNext)),
-            "searchfocusprevious" => {
-                Some(Action::Search(SearchAction::SearchFocusPrevious))
-            }
-            "searchdeleteword" => Some(Action::Search(SearchAction::SearchDeleteWord)),
-            "searchhistorynext" => Some(Action::Search(SearchAction::SearchHistoryNext)),
-            "searchhistoryprevious" => {
-                Some(Action::Search(SearchAction::SearchHistoryPrevious))
-            }
-            "clearhistory" => Some(Action::ClearHistory),
-            "resetfontsize" => Some(Action::ResetFontSize),
-            "increasefontsize" => Some(Action::IncreaseFontSize),
-            "decreasefontsize" => Some(Action::DecreaseFontSize),
-            "createwindow" => Some(Action::WindowCreateNew),
-            "createtab" => Some(Action::TabCreateNew),
-            "closetab" => Some(Action::TabCloseCurrent),
-            "closecurrenttaborsplit" => Some(Action::CloseCurrentSplitOrTab),
-            "closeunfocusedtabs" => Some(Action::TabCloseUnfocused),
-            "openconfigeditor" => Some(Action::ConfigEditor),
-            "selectprevtab" => Some(Action::SelectPrevTab),
-            "selectnexttab" => Some(Action::SelectNextTab),
-            "selectlasttab" => Some(Action::SelectLastTab),
-            "receivechar" => Some(Action::ReceiveChar),
-            "scrollhalfpageup" => Some(Action::ScrollHalfPageUp),
-            "scrollhalfpagedown" => Some(Action::ScrollHalfPageDown),
-            "scrolltotop" => Some(Action::ScrollToTop),
-            "scrolltobottom" => Some(Action::ScrollToBottom),
-            "splitright" => Some(Action::SplitRight),
-            "splitdown" => Some(Action::SplitDown),
-            "selectnextsplit" => Some(Action::SelectNextSplit),
-            "selectprevsplit" => Some(Action::SelectPrevSplit),
-            "togglevimode" => Some(Action::ToggleViMode),
-            "togglefullscreen" => Some(Action::ToggleFullscreen),
-            "none" => Some(Action::None),
-            _ => None,
+    /// Replace the arguments which are exactly `{cwd}` or `{selection}`.
+    /// The selection is untrusted terminal output, so it is never spliced
+    /// into a larger argument, like the script of `sh -c`.
+    pub fn expand(&self, cwd: &str, selection: &str) -> Shell {
+        let expand = |value: &String| match value.as_str() {
+            "{cwd}" => cwd.to_owned(),
+            "{selection}" => selection.to_owned(),
+            _ => value.to_owned(),
         };
 
-        if action_from_string.is_some() {
-            return action_from_string.unwrap_or(Action::None);
+        Shell {
+            program: self.program().to_owned(),
+            args: self.args().iter().map(expand).collect(),
         }
+    }
+}
 
-        let re = regex::Regex::new(r"selecttab\(([^()]+)\)").unwrap();
-        for capture in re.captures_iter(&action) {
-            if let Some(matched) = capture.get(1) {
-                let matched_string = matched.as_str().to_string();
-                let parsed_matched_string: usize = matched_string.parse().unwrap_or(0);
-                return Action::SelectTab(parsed_matched_string);
+/// Program and arguments of an action, the text between quotes is kept as a
+/// single argument.
+pub fn parse_program(command: &str) -> Option<Program> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut has_arg = false;
+    let mut quote = None;
+
+    for c in command.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => current.push(c),
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                has_arg = true;
+            }
+            None if c.is_whitespace() => {
+                if has_arg {
+                    args.push(mem::take(&mut current));
+                    has_arg = false;
+                }
+            }
+            None => {
+                current.push(c);
+                has_arg = true;
             }
         }
+    }
 
-        let re = regex::Regex::new(r"run\(([^()]+)\)").unwrap();
-        for capture in re.captures_iter(&action) {
-            if let Some(matched) = capture.get(1) {
-                let matched_string = matched.as_str().to_string();
-                if matched_string.contains(' ') {
-                    let mut vec_program_with_args: Vec<String> =
-                        matched_string.split(' ').map(|s| s.to_string()).collect();
-                    if vec_program_with_args.is_empty() {
-                        continue;
-                    }
+    if has_arg {
+        args.push(current);
+    }
 
-                    let program = vec_program_with_args[0].to_string();
-                    vec_program_with_args.remove(0);
+    if args.is_empty() {
+        return None;
+    }
 
-                    return Action::Run(Program::WithArgs {
-                        program,
-                        args: vec_program_with_args,
-                    });
-                } else {
-                    return Action::Run(Program::Just(matched_string));
-                }
-            }
-        }
+    let program = args.remove(0);
+    if args.is_empty() {
+        Some(Program::Just(program))
+    } else {
+        Some(Program::WithArgs { program, args })
+    }
+}
 
-        let re = regex::Regex::new(r"scroll\(([^()]+)\)").unwrap();
-        for capture in re.captures_iter(&action) {
-            if let Some(matched) = capture.get(1) {
-                let matched_string = matched.as_str().to_string();
-                let parsed_matched_string: i32 = matched_string.parse().unwrap_or(1);
-                return Action::Scroll(parsed_matched_string);
-            }
-        }
+/// Action with arguments written like a call, e.g. `SetFontSize(18)`. The
+/// arguments keep their case.
+fn parametrised_action(action: &str) -> Option<Action> {
+    let (name, args) = action.trim().strip_suffix(')')?.split_once('(')?;
+    let args = args.trim();
+
+    match name.trim().to_lowercase().as_str() {
+        "run" => parse_program(args).map(Action::Run),
+        "selecttab" => args.parse().ok().map(Action::SelectTab),
+        "scroll" | "scrolllines" => args.parse().ok().map(Action::Scroll),
+        "setfontsize" => args
+            .parse::<f32>()
+            .ok()
+            .filter(|size| size.is_finite() && *size > 0.)
+            .map(Action::SetFontSize),
+        "splitright" => Some(Action::SplitRight(parse_program(args))),
+        "splitdown" => Some(Action::SplitDown(parse_program(args))),
+        "focussplit" => parse_direction(args).map(Action::FocusSplit),
+        "swapsplit" => parse_direction(args).map(Action::SwapSplit),
+        "growsplit" => parse_direction(args).map(Action::GrowSplit),
+        "shrinksplit" => parse_direction(args).map(Action::ShrinkSplit),
+        _ => None,
+    }
+}
 
-        Action::None
+fn parse_direction(direction: &str) -> Option<SplitDirection> {
+    match direction.to_lowercase().as_str() {
+        "left" => Some(SplitDirection::Left),
+        "right" => Some(SplitDirection::Right),
+        "up" => Some(SplitDirection::Up),
+        "down" => Some(SplitDirection::Down),
+        _ => None,
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// Actions without arguments and their names in the configuration, which are
+/// case insensitive.
+pub const NAMED_ACTIONS: &[(&str, Action)] = &[
+    ("Paste", Action::Paste),
+    ("PasteSelection", Action::PasteSelection),
+    (
+        "ExpandSelection",
+        Action::Mouse(MouseAction::ExpandSelection),
+    ),
+    ("OpenHyperlink", Action::Mouse(MouseAction::OpenHyperlink)),
+    ("Quit", Action::Quit),
+    ("SaveSession", Action::SaveSession),
+    ("Copy", Action::Copy),
+    ("CopyAnsi", Action::CopyAnsi),
+    ("CopyHtml", Action::CopyHtml),
+    ("CommandPalette", Action::CommandPalette),
+    ("SearchForward", Action::SearchForward),
+    ("SearchBackward", Action::SearchBackward),
+    ("SearchConfirm", Action::Search(SearchAction::SearchConfirm)),
+    ("SearchCancel", Action::Search(SearchAction::SearchCancel)),
+    ("SearchClear", Action::Search(SearchAction::SearchClear)),
+    (
+        "SearchFocusNext",
+        Action::Search(SearchAction::SearchFocusNext),
+    ),
+    (
+        "SearchFocusPrevious",
+        Action::Search(SearchAction::SearchFocusPrevious),
+    ),
+    (
+        "SearchDeleteWord",
+        Action::Search(SearchAction::SearchDeleteWord),
+    ),
+    (
+        "SearchHistoryNext",
+        Action::Search(SearchAction::SearchHistoryNext),
+    ),
+    (
+        "SearchHistoryPrevious",
+        Action::Search(SearchAction::SearchHistoryPrevious),
+    ),
+    (
+        "SearchToggleCase",
+        Action::Search(SearchAction::SearchToggleCase),
+    ),
+    (
+        "SearchToggleWholeWord",
+        Action::Search(SearchAction::SearchToggleWholeWord),
+    ),
+    (
+        "SearchToggleLiteral",
+        Action::Search(SearchAction::SearchToggleLiteral),
+    ),
+    ("ClearHistory", Action::ClearHistory),
+    ("ClearSelection", Action::ClearSelection),
+    ("ResetFontSize", Action::ResetFontSize),
+    ("IncreaseFontSize", Action::IncreaseFontSize),
+    ("DecreaseFontSize", Action::DecreaseFontSize),
+    ("CreateWindow", Action::WindowCreateNew),
+    ("CreateTab", Action::TabCreateNew),
+    ("CloseTab", Action::TabCloseCurrent),
+    ("CloseCurrentTabOrSplit", Action::CloseCurrentSplitOrTab),
+    ("CloseUnfocusedTabs", Action::TabCloseUnfocused),
+    ("OpenConfigEditor", Action::ConfigEditor),
+    ("SelectPrevTab", Action::SelectPrevTab),
+    ("SelectNextTab", Action::SelectNextTab),
+    ("SelectLastTab", Action::SelectLastTab),
+    ("ReceiveChar", Action::ReceiveChar),
+    ("ScrollPageUp", Action::ScrollPageUp),
+    ("ScrollPageDown", Action::ScrollPageDown),
+    ("ScrollHalfPageUp", Action::ScrollHalfPageUp),
+    ("ScrollHalfPageDown", Action::ScrollHalfPageDown),
+    ("ScrollToTop", Action::ScrollToTop),
+    ("ScrollToBottom", Action::ScrollToBottom),
+    ("ScrollToPrevPrompt", Action::ScrollToPrevPrompt),
+    ("ScrollToNextPrompt", Action::ScrollToNextPrompt),
+    ("SelectLastCommandOutput", Action::SelectLastCommandOutput),
+    ("CopyLastCommandOutput", Action::CopyLastCommandOutput),
+    ("SplitRight", Action::SplitRight(None)),
+    ("SplitDown", Action::SplitDown(None)),
+    ("SelectNextSplit", Action::SelectNextSplit),
+    ("SelectPrevSplit", Action::SelectPrevSplit),
+    ("FocusSplitLeft", Action::FocusSplit(SplitDirection::Left)),
+    ("FocusSplitRight", Action::FocusSplit(SplitDirection::Right)),
+    ("FocusSplitUp", Action::FocusSplit(SplitDirection::Up)),
+    ("FocusSplitDown", Action::FocusSplit(SplitDirection::Down)),
+    ("SwapSplitLeft", Action::SwapSplit(SplitDirection::Left)),
+    ("SwapSplitRight", Action::SwapSplit(SplitDirection::Right)),
+    ("SwapSplitUp", Action::SwapSplit(SplitDirection::Up)),
+    ("SwapSplitDown", Action::SwapSplit(SplitDirection::Down)),
+    ("RotateSplits", Action::RotateSplits),
+    ("ToggleSplitZoom", Action::ToggleSplitZoom),
+    ("GrowSplitLeft", Action::GrowSplit(SplitDirection::Left)),
+    ("GrowSplitRight", Action::GrowSplit(SplitDirection::Right)),
+    ("GrowSplitUp", Action::GrowSplit(SplitDirection::Up)),
+    ("GrowSplitDown", Action::GrowSplit(SplitDirection::Down)),
+    ("ShrinkSplitLeft", Action::ShrinkSplit(SplitDirection::Left)),
+    (
+        "ShrinkSplitRight",
+        Action::ShrinkSplit(SplitDirection::Right),
+    ),
+    ("ShrinkSplitUp", Action::ShrinkSplit(SplitDirection::Up)),
+    ("ShrinkSplitDown", Action::ShrinkSplit(SplitDirection::Down)),
+    ("ToggleViMode", Action::ToggleViMode),
+    ("ToggleFullscreen", Action::ToggleFullscreen),
+    ("None", Action::None),
+];
+
+impl From<String> for Action {
+    fn from(action: String) -> Action {
+        NAMED_ACTIONS
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(action.trim()))
+            .map(|(_, action)| action.clone())
+            .or_else(|| parametrised_action(&action))
+            .unwrap_or(Action::None)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Action {
     /// Write an escape sequence.
     Esc(String),
@@ -316,11 +599,14 @@ pub enum Action {
     /// Run given command.
     Run(Program),
 
-    /// Scroll
+    /// Scroll the given number of lines, positive values scroll up.
     Scroll(i32),
 
-    // Regex keyboard hints.
-    // Hint(Hint),
+    /// Set the font size.
+    SetFontSize(f32),
+
+    /// Start the keyboard hint mode of the hint rule at the given index.
+    Hint(usize),
 
     // Move vi mode cursor.
     ViMotion(ViMotion),
@@ -336,6 +622,13 @@ pub enum Action {
     /// Store current selection into clipboard.
     Copy,
 
+    /// Store current selection into clipboard, with its colors and attributes
+    /// as ANSI escape sequences.
+    CopyAnsi,
+
+    /// Store current selection into clipboard as HTML with inline styles.
+    CopyHtml,
+
     #[cfg(not(any(target_os = "macos", windows)))]
     #[allow(dead_code)]
     /// Store current selection into selection buffer.
@@ -371,6 +664,18 @@ pub enum Action {
     /// Scroll all the way to the bottom.
     ScrollToBottom,
 
+    /// Scroll to the previous prompt marked by the shell.
+    ScrollToPrevPrompt,
+
+    /// Scroll to the next prompt marked by the shell.
+    ScrollToNextPrompt,
+
+    /// Select the output of the last command.
+    SelectLastCommandOutput,
+
+    /// Store the output of the last command into clipboard.
+    CopyLastCommandOutput,
+
     /// Clear the display buffer(s) to remove history.
     ClearHistory,
 
@@ -390,6 +695,9 @@ pub enum Action {
     /// Quit Rio.
     Quit,
 
+    /// Save the windows, tabs and splits to the session file.
+    SaveSession,
+
     /// Clear warning and error notices.
     ClearLogNotice,
 
@@ -440,6 +748,9 @@ pub enum Action {
     /// Toggle vi mode.
     ToggleViMode,
 
+    /// Open the command palette.
+    CommandPalette,
+
     // Tab selections
     SelectTab(usize),
     SelectLastTab,
@@ -451,15 +762,33 @@ pub enum Action {
     /// Start a backward buffer search.
     SearchBackward,
 
-    /// Split horizontally
-    SplitRight,
+    /// Split horizontally, optionally running a program in the new split.
+    SplitRight(Option<Program>),
 
-    /// Split vertically
-    SplitDown,
+    /// Split vertically, optionally running a program in the new split.
+    SplitDown(Option<Program>),
 
     SelectNextSplit,
     SelectPrevSplit,
 
+    /// Select the closest split on the given side of the current one.
+    FocusSplit(SplitDirection),
+
+    /// Swap the current split with the closest split on the given side.
+    SwapSplit(SplitDirection),
+
+    /// Move every split of the tab to the position of the next one.
+    RotateSplits,
+
+    /// Maximise the current split within the tab, or restore the layout.
+    ToggleSplitZoom,
+
+    /// Grow the current split by a column or a line on the given side.
+    GrowSplit(SplitDirection),
+
+    /// Shrink the current split by a column or a line on the given side.
+    ShrinkSplit(SplitDirection),
+
     /// Allow receiving char input.
     ReceiveChar,
 
@@ -529,6 +858,7 @@ macro_rules! bindings {
                 mode: _mode,
                 notmode: _notmode,
                 action: $action.into(),
+                sequence: Vec::new(),
             });
         )*
 
@@ -560,13 +890,17 @@ macro_rules! trigger {
     }};
 }
 
-pub fn default_mouse_bindings() -> Vec<MouseBinding> {
-    bindings!(
+pub fn default_mouse_bindings(
+    unprocessed_config_mouse_bindings: Vec<ConfigMouseBinding>,
+) -> Vec<MouseBinding> {
+    let bindings = bindings!(
         MouseBinding;
         MouseButton::Right;                            MouseAction::ExpandSelection;
         MouseButton::Right,   ModifiersState::CONTROL; MouseAction::ExpandSelection;
         MouseButton::Middle, ~BindingMode::VI;         Action::PasteSelection;
-    )
+    );
+
+    config_mouse_bindings(unprocessed_config_mouse_bindings, bindings)
 }
 
 pub fn default_key_bindings(
@@ -613,8 +947,6 @@ pub fn default_key_bindings(
         "c", ModifiersState::CONTROL, +BindingMode::VI; Action::ToggleViMode;
         Key::Named(Escape), +BindingMode::VI; Action::ClearSelection;
         "i", +BindingMode::VI, ~BindingMode::SEARCH; Action::ScrollToBottom;
-        "g", +BindingMode::VI, ~BindingMode::SEARCH; Action::ScrollToTop;
-        "g", ModifiersState::SHIFT, +BindingMode::VI, ~BindingMode::SEARCH; Action::ScrollToBottom;
         "b", ModifiersState::CONTROL, +BindingMode::VI, ~BindingMode::SEARCH; Action::ScrollPageUp;
         "f", ModifiersState::CONTROL, +BindingMode::VI, ~BindingMode::SEARCH; Action::ScrollPageDown;
         "u", ModifiersState::CONTROL, +BindingMode::VI, ~BindingMode::SEARCH; Action::ScrollHalfPageUp;
@@ -850,13 +1182,51 @@ pub struct ModeWrapper {
 
 #[inline]
 fn convert(config_key_binding: ConfigKeyBinding) -> Result<KeyBinding, String> {
-    let (key, location) = if config_key_binding.key.chars().count() == 1 {
+    let mut trigger = convert_key(&config_key_binding.key)?;
+    let mut mods = convert_modifiers(&config_key_binding.with);
+
+    // The trigger is the last key of a sequence, the keys before it are
+    // pressed in order.
+    let mut sequence = Vec::with_capacity(config_key_binding.then.len());
+    for stroke in &config_key_binding.then {
+        let key = convert_key(&stroke.key)?;
+        let key_mods = convert_modifiers(&stroke.with);
+        sequence.push((mem::replace(&mut trigger, key), mods));
+        mods = key_mods;
+    }
+
+    let mut action: Action = config_key_binding.action.into();
+    if !config_key_binding.text.is_empty() {
+        action = Action::Esc(config_key_binding.text);
+    }
+
+    if !config_key_binding.bytes.is_empty() {
+        if let Ok(str_from_bytes) = std::str::from_utf8(&config_key_binding.bytes) {
+            action = Action::Esc(str_from_bytes.into());
+        }
+    }
+
+    let res_mode = convert_mode(&config_key_binding.mode);
+
+    Ok(KeyBinding {
+        trigger,
+        mods,
+        action,
+        mode: res_mode.mode,
+        notmode: res_mode.not_mode,
+        sequence,
+    })
+}
+
+#[inline]
+fn convert_key(key: &str) -> Result<BindingKey, String> {
+    let (key, location) = if key.chars().count() == 1 {
         (
-            Key::Character(config_key_binding.key.to_lowercase().into()),
+            Key::Character(key.to_lowercase().into()),
             KeyLocation::Standard,
         )
     } else {
-        match config_key_binding.key.to_lowercase().as_str() {
+        match key.to_lowercase().as_str() {
             "home" => (Key::Named(Home), KeyLocation::Standard),
             "space" => (Key::Named(Space), KeyLocation::Standard),
             "delete" => (Key::Named(Delete), KeyLocation::Standard),
@@ -923,10 +1293,13 @@ fn convert(config_key_binding: ConfigKeyBinding) -> Result<KeyBinding, String> {
         }
     };
 
-    let trigger = BindingKey::Keycode { key, location };
+    Ok(BindingKey::Keycode { key, location })
+}
 
+#[inline]
+fn convert_modifiers(with: &str) -> ModifiersState {
     let mut res = ModifiersState::empty();
-    for modifier in config_key_binding.with.split('|') {
+    for modifier in with.split('|') {
         match modifier.trim().to_lowercase().as_str() {
             "command" | "super" => res.insert(ModifiersState::SUPER),
             "shift" => res.insert(ModifiersState::SHIFT),
@@ -937,23 +1310,17 @@ fn convert(config_key_binding: ConfigKeyBinding) -> Result<KeyBinding, String> {
         }
     }
 
-    let mut action: Action = config_key_binding.action.into();
-    if !config_key_binding.text.is_empty() {
-        action = Action::Esc(config_key_binding.text);
-    }
-
-    if !config_key_binding.bytes.is_empty() {
-        if let Ok(str_from_bytes) = std::str::from_utf8(&config_key_binding.bytes) {
-            action = Action::Esc(str_from_bytes.into());
-        }
-    }
+    res
+}
 
+#[inline]
+fn convert_mode(mode: &str) -> ModeWrapper {
     let mut res_mode = ModeWrapper {
         mode: BindingMode::empty(),
         not_mode: BindingMode::empty(),
     };
 
-    for modifier in config_key_binding.mode.split('|') {
+    for modifier in mode.split('|') {
         match modifier.trim().to_lowercase().as_str() {
             "appcursor" => res_mode.mode |= BindingMode::APP_CURSOR,
             "~appcursor" => res_mode.not_mode |= BindingMode::APP_CURSOR,
@@ -970,15 +1337,70 @@ fn convert(config_key_binding: ConfigKeyBinding) -> Result<KeyBinding, String> {
         }
     }
 
-    Ok(KeyBinding {
+    res_mode
+}
+
+#[inline]
+fn convert_mouse(
+    config_mouse_binding: ConfigMouseBinding,
+) -> Result<MouseBinding, String> {
+    let button = config_mouse_binding.button.to_lowercase();
+    let trigger = match button.as_str() {
+        "left" => MouseButton::Left,
+        "middle" => MouseButton::Middle,
+        "right" => MouseButton::Right,
+        "back" => MouseButton::Back,
+        "forward" => MouseButton::Forward,
+        _ => match button.parse() {
+            Ok(index) => MouseButton::Other(index),
+            Err(_) => return Err("Unable to find defined 'button'".to_string()),
+        },
+    };
+
+    let res_mode = convert_mode(&config_mouse_binding.mode);
+
+    Ok(MouseBinding {
         trigger,
-        mods: res,
-        action,
+        mods: convert_modifiers(&config_mouse_binding.with),
+        action: config_mouse_binding.action.into(),
         mode: res_mode.mode,
         notmode: res_mode.not_mode,
+        sequence: Vec::new(),
     })
 }
 
+/// Add the configured mouse bindings, replacing the bindings with the same
+/// button, modifiers and mode.
+pub fn config_mouse_bindings(
+    config_mouse_bindings: Vec<ConfigMouseBinding>,
+    mut bindings: Vec<MouseBinding>,
+) -> Vec<MouseBinding> {
+    for cmb in config_mouse_bindings {
+        match convert_mouse(cmb) {
+            Ok(mouse_binding) => {
+                bindings.retain(|binding| {
+                    let overwritten = binding.triggers_match(&mouse_binding);
+                    if overwritten {
+                        tracing::warn!(
+                            "overwritten a previous mouse_binding with new one: {:?}",
+                            mouse_binding
+                        );
+                    }
+                    !overwritten
+                });
+
+                tracing::info!("added a new mouse_binding: {:?}", mouse_binding);
+                bindings.push(mouse_binding);
+            }
+            Err(err_message) => {
+                tracing::error!("error loading a mouse binding: {:?}", err_message);
+            }
+        }
+    }
+
+    bindings
+}
+
 pub fn config_key_bindings(
     config_key_bindings: Vec<ConfigKeyBinding>,
     mut bindings: Vec<KeyBinding>,
@@ -1025,6 +1447,107 @@ pub fn config_key_bindings(
     bindings
 }
 
+/// Describe the key binding sequences which can't be completed because
+/// another binding is triggered by their first keys.
+pub fn key_binding_conflicts(bindings: &[KeyBinding]) -> Vec<String> {
+    let mut conflicts = Vec::new();
+    for (index, binding) in bindings.iter().enumerate() {
+        if binding.sequence.is_empty() {
+            continue;
+        }
+
+        let sequence = binding.keys();
+        let (first_key, first_mods) = &sequence[0];
+        for (other_index, other) in bindings.iter().enumerate() {
+            if other_index == index
+                || !binding.modes_overlap(other)
+                || matches!(other.action, Action::ReceiveChar | Action::None)
+            {
+                continue;
+            }
+
+            if other.sequence.is_empty() {
+                if other.trigger == *first_key && other.mods == *first_mods {
+                    let conflict = format!(
+                        "`{}` starts the sequence `{}` and is also bound to {:?}",
+                        first_key.name(*first_mods),
+                        sequence_name(&sequence),
+                        other.action
+                    );
+                    if !conflicts.contains(&conflict) {
+                        conflicts.push(conflict);
+                    }
+                }
+                continue;
+            }
+
+            // Pairs of sequences are only checked once.
+            if other_index > index {
+                continue;
+            }
+
+            let other_sequence = other.keys();
+            let len = sequence.len().min(other_sequence.len());
+            if sequence[..len] == other_sequence[..len] {
+                let (shorter, longer) = if sequence.len() < other_sequence.len() {
+                    (&sequence, &other_sequence)
+                } else {
+                    (&other_sequence, &sequence)
+                };
+
+                let conflict = if shorter.len() == longer.len() {
+                    format!("`{}` is bound more than once", sequence_name(shorter))
+                } else {
+                    format!(
+                        "`{}` prevents `{}` from being completed",
+                        sequence_name(shorter),
+                        sequence_name(longer)
+                    )
+                };
+                if !conflicts.contains(&conflict) {
+                    conflicts.push(conflict);
+                }
+            }
+        }
+    }
+
+    conflicts
+}
+
+/// Key bindings of the hint rules which define one.
+pub fn hint_key_bindings(hints: &ConfigHints) -> Vec<KeyBinding> {
+    let mut bindings = Vec::new();
+
+    for (index, hint) in hints.rules.iter().enumerate() {
+        let Some(binding) = &hint.binding else {
+            continue;
+        };
+
+        let config_key_binding = ConfigKeyBinding {
+            key: binding.key.to_owned(),
+            with: binding.with.to_owned(),
+            action: String::default(),
+            text: String::default(),
+            bytes: Vec::default(),
+            mode: String::default(),
+            then: Vec::default(),
+        };
+
+        match convert(config_key_binding) {
+            Ok(mut key_binding) => {
+                key_binding.action = Action::Hint(index);
+                key_binding.notmode |= BindingMode::SEARCH;
+                bindings.push(key_binding);
+            }
+            Err(err_message) => {
+                tracing::error!("error loading a hint binding: {:?}", err_message);
+            }
+        }
+    }
+
+    bindings
+}
+
 // Macos
 #[cfg(all(target_os = "macos", not(test)))]
 pub fn platform_key_bindings(
@@ -1055,6 +1578,7 @@ pub fn platform_key_bindings(
         "q", ModifiersState::SUPER; Action::Quit;
         "n", ModifiersState::SUPER; Action::WindowCreateNew;
         ",", ModifiersState::SUPER; Action::ConfigEditor;
+        "p", ModifiersState::SUPER | ModifiersState::SHIFT, ~BindingMode::SEARCH; Action::CommandPalette;
 
         // Search
         "f", ModifiersState::SUPER, ~BindingMode::SEARCH; Action::SearchForward;
@@ -1066,6 +1590,9 @@ pub fn platform_key_bindings(
         "n", ModifiersState::CONTROL,  +BindingMode::SEARCH; SearchAction::SearchHistoryNext;
         Key::Named(ArrowUp), +BindingMode::SEARCH; SearchAction::SearchHistoryPrevious;
         Key::Named(ArrowDown), +BindingMode::SEARCH; SearchAction::SearchHistoryNext;
+        "c", ModifiersState::ALT, +BindingMode::SEARCH; SearchAction::SearchToggleCase;
+        "w", ModifiersState::ALT, +BindingMode::SEARCH; SearchAction::SearchToggleWholeWord;
+        "r", ModifiersState::ALT, +BindingMode::SEARCH; SearchAction::SearchToggleLiteral;
     );
 
     if use_navigation_key_bindings {
@@ -1102,8 +1629,8 @@ pub fn platform_key_bindings(
     if use_splits {
         key_bindings.extend(bindings!(
             KeyBinding;
-            "d", ModifiersState::SUPER, ~BindingMode::SEARCH, ~BindingMode::VI; Action::SplitRight;
-            "d", ModifiersState::SUPER | ModifiersState::SHIFT, ~BindingMode::SEARCH, ~BindingMode::VI; Action::SplitDown;
+            "d", ModifiersState::SUPER, ~BindingMode::SEARCH, ~BindingMode::VI; Action::SplitRight(None);
+            "d", ModifiersState::SUPER | ModifiersState::SHIFT, ~BindingMode::SEARCH, ~BindingMode::VI; Action::SplitDown(None);
             "]", ModifiersState::SUPER, ~BindingMode::SEARCH, ~BindingMode::VI; Action::SelectNextSplit;
             "[", ModifiersState::SUPER, ~BindingMode::SEARCH, ~BindingMode::VI; Action::SelectPrevSplit;
         ));
@@ -1134,6 +1661,7 @@ pub fn platform_key_bindings(
         "-", ModifiersState::CONTROL;  Action::DecreaseFontSize;
         "n", ModifiersState::CONTROL | ModifiersState::SHIFT; Action::WindowCreateNew;
         ",", ModifiersState::CONTROL | ModifiersState::SHIFT; Action::ConfigEditor;
+        "p", ModifiersState::CONTROL | ModifiersState::SHIFT, ~BindingMode::SEARCH; Action::CommandPalette;
 
         // Search
         "f", ModifiersState::CONTROL | ModifiersState::SHIFT, ~BindingMode::SEARCH; Action::SearchForward;
@@ -1145,6 +1673,9 @@ pub fn platform_key_bindings(
         "n", ModifiersState::CONTROL,  +BindingMode::SEARCH; SearchAction::SearchHistoryNext;
         Key::Named(ArrowUp), +BindingMode::SEARCH; SearchAction::SearchHistoryPrevious;
         Key::Named(ArrowDown), +BindingMode::SEARCH; SearchAction::SearchHistoryNext;
+        "c", ModifiersState::ALT, +BindingMode::SEARCH; SearchAction::SearchToggleCase;
+        "w", ModifiersState::ALT, +BindingMode::SEARCH; SearchAction::SearchToggleWholeWord;
+        "r", ModifiersState::ALT, +BindingMode::SEARCH; SearchAction::SearchToggleLiteral;
     );
 
     if use_navigation_key_bindings {
@@ -1162,8 +1693,8 @@ pub fn platform_key_bindings(
     if use_splits {
         key_bindings.extend(bindings!(
             KeyBinding;
-            "r", ModifiersState::CONTROL | ModifiersState::SHIFT, ~BindingMode::SEARCH, ~BindingMode::VI; Action::SplitRight;
-            "d", ModifiersState::CONTROL | ModifiersState::SHIFT, ~BindingMode::SEARCH, ~BindingMode::VI; Action::SplitDown;
+            "r", ModifiersState::CONTROL | ModifiersState::SHIFT, ~BindingMode::SEARCH, ~BindingMode::VI; Action::SplitRight(None);
+            "d", ModifiersState::CONTROL | ModifiersState::SHIFT, ~BindingMode::SEARCH, ~BindingMode::VI; Action::SplitDown(None);
             "]", ModifiersState::CONTROL | ModifiersState::SHIFT, ~BindingMode::SEARCH, ~BindingMode::VI; Action::SelectNextSplit;
             "[", ModifiersState::CONTROL | ModifiersState::SHIFT, ~BindingMode::SEARCH, ~BindingMode::VI; Action::SelectPrevSplit;
         ));
@@ -1194,6 +1725,7 @@ pub fn platform_key_bindings(
         Key::Named(Enter), ModifiersState::ALT; Action::ToggleFullscreen;
         "n", ModifiersState::CONTROL | ModifiersState::SHIFT; Action::WindowCreateNew;
         ",", ModifiersState::CONTROL | ModifiersState::SHIFT; Action::ConfigEditor;
+        "p", ModifiersState::CONTROL | ModifiersState::SHIFT, ~BindingMode::SEARCH; Action::CommandPalette;
         // This is actually a Windows Powershell shortcut
         // https://github.com/alacritty/alacritty/issues/2930
         // https://github.com/raphamorim/rio/issues/220#issuecomment-1761651339
@@ -1210,6 +1742,9 @@ pub fn platform_key_bindings(
         "n", ModifiersState::CONTROL,  +BindingMode::SEARCH; SearchAction::SearchHistoryNext;
         Key::Named(ArrowUp), +BindingMode::SEARCH; SearchAction::SearchHistoryPrevious;
         Key::Named(ArrowDown), +BindingMode::SEARCH; SearchAction::SearchHistoryNext;
+        "c", ModifiersState::ALT, +BindingMode::SEARCH; SearchAction::SearchToggleCase;
+        "w", ModifiersState::ALT, +BindingMode::SEARCH; SearchAction::SearchToggleWholeWord;
+        "r", ModifiersState::ALT, +BindingMode::SEARCH; SearchAction::SearchToggleLiteral;
     );
 
     if use_navigation_key_bindings {
@@ -1227,8 +1762,8 @@ pub fn platform_key_bindings(
     if use_splits {
         key_bindings.extend(bindings!(
             KeyBinding;
-            "r", ModifiersState::CONTROL | ModifiersState::SHIFT, ~BindingMode::SEARCH, ~BindingMode::VI; Action::SplitRight;
-            "d", ModifiersState::CONTROL | ModifiersState::SHIFT, ~BindingMode::SEARCH, ~BindingMode::VI; Action::SplitDown;
+            "r", ModifiersState::CONTROL | ModifiersState::SHIFT, ~BindingMode::SEARCH, ~BindingMode::VI; Action::SplitRight(None);
+            "d", ModifiersState::CONTROL | ModifiersState::SHIFT, ~BindingMode::SEARCH, ~BindingMode::VI; Action::SplitDown(None);
             "]", ModifiersState::CONTROL | ModifiersState::SHIFT, ~BindingMode::SEARCH, ~BindingMode::VI; Action::SelectNextSplit;
             "[", ModifiersState::CONTROL | ModifiersState::SHIFT, ~BindingMode::SEARCH, ~BindingMode::VI; Action::SelectPrevSplit;
         ));
@@ -1258,6 +1793,7 @@ mod tests {
                 mode: BindingMode::empty(),
                 notmode: BindingMode::empty(),
                 trigger: Default::default(),
+                sequence: Vec::new(),
             }
         }
     }
@@ -1511,6 +2047,7 @@ mod tests {
             bytes: vec![],
             text: String::from(""),
             mode: String::from(""),
+            then: vec![],
         }];
 
         let new_bindings = config_key_bindings(config_bindings, bindings);
@@ -1518,4 +2055,307 @@ mod tests {
         assert_eq!(new_bindings.len(), 2);
         assert_eq!(new_bindings[1].action, Action::ReceiveChar);
     }
+
+    #[test]
+    fn hint_bindings() {
+        use rio_backend::config::hints::{Hint, HintAction, HintBinding};
+
+        let hint = Hint {
+            regex: Some(String::from("[0-9a-f]{7,40}")),
+            hyperlinks: false,
+            post_processing: true,
+            action: HintAction::Copy,
+            command: None,
+            binding: None,
+        };
+        let hints = ConfigHints {
+            alphabet: String::from("asdf"),
+            rules: vec![
+                hint.clone(),
+                Hint {
+                    binding: Some(HintBinding {
+                        key: String::from("h"),
+                        with: String::from("control | shift"),
+                    }),
+                    ..hint
+                },
+            ],
+        };
+
+        let bindings = hint_key_bindings(&hints);
+        assert_eq!(bindings.len(), 1);
+        assert_eq!(bindings[0].action, Action::Hint(1));
+        assert_eq!(
+            bindings[0].mods,
+            ModifiersState::CONTROL | ModifiersState::SHIFT
+        );
+        assert_eq!(bindings[0].notmode, BindingMode::SEARCH);
+    }
+
+    #[test]
+    fn mouse_bindings() {
+        let mouse_binding = |button: &str, with: &str, action: &str| ConfigMouseBinding {
+            button: String::from(button),
+            with: String::from(with),
+            action: String::from(action),
+            mode: String::default(),
+        };
+
+        let bindings = default_mouse_bindings(vec![
+            mouse_binding("middle", "", "paste"),
+            mouse_binding("left", "control", "openhyperlink"),
+            mouse_binding("forward", "shift", "selectnexttab"),
+            mouse_binding("8", "", "selectprevtab"),
+            mouse_binding("wheel", "", "paste"),
+        ]);
+
+        // The middle button binding replaces the default one.
+        assert_eq!(bindings.len(), 6);
+        let middle = bindings
+            .iter()
+            .find(|binding| binding.trigger == MouseButton::Middle)
+            .unwrap();
+        assert_eq!(middle.action, Action::Paste);
+
+        let left = bindings
+            .iter()
+            .find(|binding| binding.trigger == MouseButton::Left)
+            .unwrap();
+        assert_eq!(left.mods, ModifiersState::CONTROL);
+        assert_eq!(left.action, Action::Mouse(MouseAction::OpenHyperlink));
+
+        assert!(bindings
+            .iter()
+            .any(|binding| binding.trigger == MouseButton::Forward
+                && binding.mods == ModifiersState::SHIFT
+                && binding.action == Action::SelectNextTab));
+        assert!(bindings
+            .iter()
+            .any(|binding| binding.trigger == MouseButton::Other(8)
+                && binding.action == Action::SelectPrevTab));
+    }
+
+    #[test]
+    fn sequence_match() {
+        let binding = MockBinding {
+            trigger: 3,
+            sequence: vec![(1, ModifiersState::CONTROL), (2, ModifiersState::empty())],
+            ..MockBinding::default()
+        };
+        let mode = BindingMode::empty();
+
+        assert_eq!(
+            binding.sequence_match(&mode, &[(1, ModifiersState::CONTROL)]),
+            SequenceMatch::Prefix
+        );
+        assert_eq!(
+            binding.sequence_match(
+                &mode,
+                &[(1, ModifiersState::CONTROL), (2, ModifiersState::empty())]
+            ),
+            SequenceMatch::Prefix
+        );
+        assert_eq!(
+            binding.sequence_match(
+                &mode,
+                &[
+                    (1, ModifiersState::CONTROL),
+                    (2, ModifiersState::empty()),
+                    (3, ModifiersState::empty())
+                ]
+            ),
+            SequenceMatch::Complete
+        );
+        assert_eq!(
+            binding.sequence_match(&mode, &[(1, ModifiersState::empty())]),
+            SequenceMatch::None
+        );
+        assert_eq!(
+            binding.sequence_match(&BindingMode::VI, &[(1, ModifiersState::CONTROL)]),
+            SequenceMatch::Prefix
+        );
+
+        // The sequence is never triggered as a single key.
+        assert!(!binding.is_triggered_by(mode.clone(), ModifiersState::empty(), &3));
+        assert_eq!(
+            MockBinding::default().sequence_match(&mode, &[(0, ModifiersState::empty())]),
+            SequenceMatch::None
+        );
+    }
+
+    #[test]
+    fn key_sequences() {
+        use rio_backend::config::bindings::KeyStroke;
+
+        let key_binding = |key: &str, with: &str, then: Vec<KeyStroke>, action: &str| {
+            ConfigKeyBinding {
+                key: String::from(key),
+                with: String::from(with),
+                action: String::from(action),
+                text: String::default(),
+                bytes: Vec::default(),
+                mode: String::default(),
+                then,
+            }
+        };
+        let stroke = |key: &str, with: &str| KeyStroke {
+            key: String::from(key),
+            with: String::from(with),
+        };
+
+        let binding = convert(key_binding(
+            "a",
+            "control",
+            vec![stroke("w", ""), stroke("n", "shift")],
+            "splitright",
+        ))
+        .unwrap();
+        let key = |c: &str| BindingKey::Keycode {
+            key: Key::Character(c.into()),
+            location: KeyLocation::Standard,
+        };
+        assert_eq!(binding.trigger, key("n"));
+        assert_eq!(binding.mods, ModifiersState::SHIFT);
+        assert_eq!(
+            binding.sequence,
+            vec![
+                (key("a"), ModifiersState::CONTROL),
+                (key("w"), ModifiersState::empty())
+            ]
+        );
+        assert_eq!(
+            crate::bindings::sequence_name(&binding.sequence),
+            "control+a w"
+        );
+
+        let bindings = config_key_bindings(
+            vec![
+                key_binding("a", "control", vec![], "copy"),
+                key_binding("a", "control", vec![stroke("c", "")], "createtab"),
+                key_binding("a", "control", vec![stroke("c", "")], "closetab"),
+                key_binding("b", "control", vec![stroke("c", "")], "createtab"),
+                key_binding(
+                    "b",
+                    "control",
+                    vec![stroke("c", ""), stroke("d", "")],
+                    "closetab",
+                ),
+                key_binding("e", "control", vec![stroke("c", "")], "createtab"),
+            ],
+            vec![],
+        );
+
+        assert_eq!(
+            key_binding_conflicts(&bindings),
+            vec![
+                "`control+a` starts the sequence `control+a c` and is also bound to Copy",
+                "`control+a c` is bound more than once",
+                "`control+b c` prevents `control+b c d` from being completed",
+            ]
+        );
+    }
+
+    #[test]
+    fn pending_keys_timeout() {
+        let mut pending_keys = PendingKeys::new(Duration::from_millis(1000));
+        let now = Instant::now();
+        let key = BindingKey::Keycode {
+            key: Key::Character("a".into()),
+            location: KeyLocation::Standard,
+        };
+
+        pending_keys.push(key, ModifiersState::CONTROL, now);
+        assert!(!pending_keys.is_empty());
+        assert_eq!(
+            pending_keys.remaining(now + Duration::from_millis(400)),
+            Some(Duration::from_millis(600))
+        );
+        assert_eq!(
+            pending_keys.remaining(now + Duration::from_millis(1000)),
+            None
+        );
+
+        pending_keys.clear();
+        assert!(pending_keys.keys().is_empty());
+    }
+
+    #[test]
+    fn parametrised_actions() {
+        let action = |action: &str| Action::from(action.to_string());
+
+        assert_eq!(
+            action("Run(code ~/Documents/Notes.md)"),
+            Action::Run(Program::WithArgs {
+                program: String::from("code"),
+                args: vec![String::from("~/Documents/Notes.md")],
+            })
+        );
+        assert_eq!(
+            action(r#"run(notes "{selection}" {cwd})"#),
+            Action::Run(Program::WithArgs {
+                program: String::from("notes"),
+                args: vec![String::from("{selection}"), String::from("{cwd}")],
+            })
+        );
+        assert_eq!(
+            action("Run(htop)"),
+            Action::Run(Program::Just(String::from("htop")))
+        );
+        assert_eq!(action("Run()"), Action::None);
+
+        assert_eq!(action("SetFontSize(18.5)"), Action::SetFontSize(18.5));
+        assert_eq!(action("SetFontSize(0)"), Action::None);
+        assert_eq!(action("SelectTab(12)"), Action::SelectTab(12));
+        assert_eq!(action("SelectTab(last)"), Action::None);
+        assert_eq!(action("ScrollLines(-5)"), Action::Scroll(-5));
+        assert_eq!(action("Scroll(8)"), Action::Scroll(8));
+
+        assert_eq!(
+            action("FocusSplit(Left)"),
+            Action::FocusSplit(SplitDirection::Left)
+        );
+        assert_eq!(
+            action("focussplitdown"),
+            Action::FocusSplit(SplitDirection::Down)
+        );
+        assert_eq!(
+            action("SwapSplit(up)"),
+            Action::SwapSplit(SplitDirection::Up)
+        );
+        assert_eq!(
+            action("GrowSplit(RIGHT)"),
+            Action::GrowSplit(SplitDirection::Right)
+        );
+        assert_eq!(action("ShrinkSplit(sideways)"), Action::None);
+
+        assert_eq!(action("SplitRight"), Action::SplitRight(None));
+        assert_eq!(
+            action("SplitDown(lazygit -p {cwd})"),
+            Action::SplitDown(Some(Program::WithArgs {
+                program: String::from("lazygit"),
+                args: vec![String::from("-p"), String::from("{cwd}")],
+            }))
+        );
+    }
+
+    #[test]
+    fn program_placeholders() {
+        let program = Program::WithArgs {
+            program: String::from("{cwd}/script.sh"),
+            args: vec![
+                String::from("{selection}"),
+                String::from("{cwd}"),
+                String::from("--text={selection}"),
+            ],
+        };
+
+        let shell = program.expand("/home/rio", "'; rm -rf ~; '");
+        // Only whole arguments are replaced, the selection stays a single
+        // argument whatever it contains.
+        assert_eq!(shell.program, "{cwd}/script.sh");
+        assert_eq!(
+            shell.args,
+            ["'; rm -rf ~; '", "/home/rio", "--text={selection}"]
+        );
+    }
 }
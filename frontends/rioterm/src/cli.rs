@@ -1,8 +1,10 @@
 // cli.rs was retired originally from https://github.com/alacritty/alacritty/blob/e35e5ad14fce8456afdd89f2b392b9924bb27471/alacritty/src/cli.rs
 // which is licensed under Apache 2.0 license.
 
-use clap::{Args, Parser, ValueHint};
+use clap::{Args, Parser, Subcommand, ValueHint};
+use rio_backend::config::layouts::LayoutSplit;
 use rio_backend::config::Shell;
+use rio_backend::ipc::{Request, Target};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -12,6 +14,15 @@ pub struct Cli {
     /// Options which can be passed via IPC.
     #[clap(flatten)]
     pub window_options: WindowOptions,
+
+    #[clap(subcommand)]
+    pub subcommands: Option<Subcommands>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Subcommands {
+    /// Send a request to a running Rio.
+    Msg(MessageOptions),
 }
 
 #[derive(Serialize, Deserialize, Args, Default, Clone, Debug, PartialEq, Eq)]
@@ -42,6 +53,10 @@ pub struct TerminalOptions {
     /// Start window with specified title
     #[clap(long)]
     pub title: Option<String>,
+
+    /// Open the tabs and splits of a layout defined in the config.
+    #[clap(long, value_name = "NAME")]
+    pub layout: Option<String>,
 }
 
 impl TerminalOptions {
@@ -74,3 +89,195 @@ impl TerminalOptions {
     //     pty_config.hold |= self.hold;
     // }
 }
+
+#[derive(Args, Debug)]
+pub struct MessageOptions {
+    /// Socket of the Rio process, defaults to the one running the current
+    /// terminal or else the last started one.
+    #[clap(short, long, value_hint = ValueHint::FilePath)]
+    pub socket: Option<PathBuf>,
+
+    #[clap(subcommand)]
+    pub message: Message,
+}
+
+#[derive(Subcommand, Debug, PartialEq, Eq)]
+pub enum Message {
+    /// Open a window.
+    CreateWindow(PaneOptions),
+    /// Open a tab in a window.
+    CreateTab {
+        /// Window of the tab, defaults to the focused one.
+        #[clap(long)]
+        window: Option<u64>,
+        #[clap(flatten)]
+        pane: PaneOptions,
+    },
+    /// Split a pane.
+    Split {
+        #[clap(flatten)]
+        target: TargetOptions,
+        /// Open the new pane below instead of on the right side.
+        #[clap(long)]
+        down: bool,
+        #[clap(flatten)]
+        pane: PaneOptions,
+    },
+    /// Write text to a pane, like if it was typed.
+    SendText {
+        #[clap(flatten)]
+        target: TargetOptions,
+        text: String,
+    },
+    /// List the panes of every window.
+    ListPanes,
+    /// Print the text of a pane.
+    GetText {
+        #[clap(flatten)]
+        target: TargetOptions,
+        /// Print the last lines including the scrollback instead of the screen.
+        #[clap(long)]
+        lines: Option<usize>,
+    },
+    /// Change configuration values until Rio quits, like `fonts.size=18`.
+    Config {
+        /// Drop the values changed by previous messages.
+        #[clap(long)]
+        reset: bool,
+        values: Vec<String>,
+    },
+}
+
+#[derive(Args, Default, Debug, Clone, PartialEq, Eq)]
+pub struct PaneOptions {
+    /// Command and args to execute (must be last argument).
+    #[clap(short = 'e', long, allow_hyphen_values = true, num_args = 1..)]
+    pub command: Vec<String>,
+
+    /// Start the shell in the specified working directory.
+    #[clap(short, long, value_hint = ValueHint::DirPath)]
+    pub working_dir: Option<String>,
+}
+
+impl PaneOptions {
+    /// The working directory is sent as an absolute path since Rio runs in
+    /// another directory.
+    fn working_dir(&self) -> Option<String> {
+        let dir = self.working_dir.as_ref()?;
+        Some(
+            std::path::absolute(dir)
+                .map(|dir| dir.display().to_string())
+                .unwrap_or_else(|_| dir.clone()),
+        )
+    }
+}
+
+#[derive(Args, Default, Debug, Clone, PartialEq, Eq)]
+pub struct TargetOptions {
+    /// Window of the pane, defaults to the focused one.
+    #[clap(long)]
+    pub window: Option<u64>,
+
+    /// Pane, defaults to the current one of the window.
+    #[clap(long)]
+    pub pane: Option<usize>,
+}
+
+impl From<TargetOptions> for Target {
+    fn from(target: TargetOptions) -> Target {
+        Target {
+            window: target.window,
+            pane: target.pane,
+        }
+    }
+}
+
+impl From<Message> for Request {
+    fn from(message: Message) -> Request {
+        match message {
+            Message::CreateWindow(pane) => Request::CreateWindow {
+                working_dir: pane.working_dir(),
+                command: pane.command,
+            },
+            Message::CreateTab { window, pane } => Request::CreateTab {
+                window,
+                working_dir: pane.working_dir(),
+                command: pane.command,
+            },
+            Message::Split { target, down, pane } => Request::Split {
+                target: target.into(),
+                direction: if down {
+                    LayoutSplit::Down
+                } else {
+                    LayoutSplit::Right
+                },
+                working_dir: pane.working_dir(),
+                command: pane.command,
+            },
+            Message::SendText { target, text } => Request::SendText {
+                target: target.into(),
+                text,
+            },
+            Message::ListPanes => Request::ListPanes,
+            Message::GetText { target, lines } => Request::GetText {
+                target: target.into(),
+                lines,
+            },
+            Message::Config { reset, values } => Request::SetConfig { values, reset },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_msg_subcommand() {
+        let cli = Cli::parse_from([
+            "rio", "msg", "split", "--pane", "3", "--down", "-e", "htop", "-d",
+        ]);
+        let Some(Subcommands::Msg(options)) = cli.subcommands else {
+            panic!("expected msg subcommand");
+        };
+        assert_eq!(options.socket, None);
+        assert_eq!(
+            Request::from(options.message),
+            Request::Split {
+                target: Target {
+                    window: None,
+                    pane: Some(3),
+                },
+                direction: LayoutSplit::Down,
+                command: vec![String::from("htop"), String::from("-d")],
+                working_dir: None,
+            }
+        );
+
+        let cli = Cli::parse_from([
+            "rio",
+            "msg",
+            "--socket",
+            "/tmp/rio.sock",
+            "config",
+            "--reset",
+            "fonts.size=18",
+        ]);
+        let Some(Subcommands::Msg(options)) = cli.subcommands else {
+            panic!("expected msg subcommand");
+        };
+        assert_eq!(options.socket, Some(PathBuf::from("/tmp/rio.sock")));
+        assert_eq!(
+            Request::from(options.message),
+            Request::SetConfig {
+                values: vec![String::from("fonts.size=18")],
+                reset: true,
+            }
+        );
+
+        // Without a subcommand Rio starts as usual.
+        let cli = Cli::parse_from(["rio", "-e", "htop"]);
+        assert!(cli.subcommands.is_none());
+        assert_eq!(cli.window_options.terminal_options.command, ["htop"]);
+    }
+}
@@ -58,13 +58,76 @@ pub struct Delta<T: Default> {
     pub bottom_y: T,
 }
 
+/// Side of a split, used to grow or shrink it and to find its borders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl SplitDirection {
+    /// Whether the border on this side moves horizontally.
+    #[inline]
+    fn is_horizontal(self) -> bool {
+        matches!(self, SplitDirection::Left | SplitDirection::Right)
+    }
+}
+
+/// Border on the right or the bottom side of a split, grabbed with the mouse
+/// to resize the splits around it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SplitBorder {
+    pub index: usize,
+    pub direction: SplitDirection,
+}
+
+/// Position and size of a split, with the first split at the origin.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct SplitRect {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+}
+
+impl SplitRect {
+    /// Start and end of the split on the horizontal or vertical axis.
+    #[inline]
+    fn span(&self, horizontal: bool) -> (f32, f32) {
+        if horizontal {
+            (self.x, self.x + self.width)
+        } else {
+            (self.y, self.y + self.height)
+        }
+    }
+}
+
+/// Tolerance comparing the edges of splits.
+const EDGE_EPSILON: f32 = 0.5;
+
+/// Width and height taken by the splits.
+fn extent(rects: &[SplitRect]) -> (f32, f32) {
+    rects.iter().fold((0., 0.), |extent, rect| {
+        (
+            extent.0.max(rect.x + rect.width),
+            extent.1.max(rect.y + rect.height),
+        )
+    })
+}
+
 pub struct ContextGrid<T: EventListener> {
     pub width: f32,
     pub height: f32,
     pub current: usize,
     pub margin: Delta<f32>,
+    /// Whether a context of the grid rang the bell since it was last selected.
+    pub has_bell: bool,
     border_color: [f32; 4],
     inner: Vec<ContextGridItem<T>>,
+    /// Split maximised within the grid, with its dimension to restore.
+    zoomed: Option<(usize, ContextDimension)>,
 }
 
 pub struct ContextGridItem<T: EventListener> {
@@ -85,7 +148,6 @@ impl<T: rio_backend::event::EventListener> ContextGridItem<T> {
 
 impl<T: rio_backend::event::EventListener> ContextGridItem<T> {
     #[inline]
-    #[allow(unused)]
     pub fn context(&self) -> &Context<T> {
         &self.val
     }
@@ -94,6 +156,18 @@ impl<T: rio_backend::event::EventListener> ContextGridItem<T> {
     pub fn context_mut(&mut self) -> &mut Context<T> {
         &mut self.val
     }
+
+    /// Index of the split on the right side.
+    #[inline]
+    pub fn right(&self) -> Option<usize> {
+        self.right
+    }
+
+    /// Index of the split on the bottom side.
+    #[inline]
+    pub fn down(&self) -> Option<usize> {
+        self.down
+    }
 }
 
 impl<T: rio_backend::event::EventListener> ContextGrid<T> {
@@ -105,9 +179,11 @@ impl<T: rio_backend::event::EventListener> ContextGrid<T> {
             inner,
             current: 0,
             margin,
+            has_bell: false,
             width,
             height,
             border_color,
+            zoomed: None,
         }
     }
 
@@ -122,17 +198,22 @@ impl<T: rio_backend::event::EventListener> ContextGrid<T> {
     }
 
     #[inline]
-    #[allow(unused)]
-    pub fn contexts(&mut self) -> &Vec<ContextGridItem<T>> {
+    pub fn contexts(&self) -> &Vec<ContextGridItem<T>> {
         &self.inner
     }
 
+    #[inline]
+    pub fn contains_route(&self, route_id: usize) -> bool {
+        self.inner.iter().any(|item| item.val.route_id == route_id)
+    }
+
     #[inline]
     pub fn select_next_split(&mut self) {
         if self.inner.len() == 1 {
             return;
         }
 
+        self.unzoom();
         if self.current >= self.inner.len() - 1 {
             self.current = 0;
         } else {
@@ -146,6 +227,7 @@ impl<T: rio_backend::event::EventListener> ContextGrid<T> {
             return;
         }
 
+        self.unzoom();
         if self.current == 0 {
             self.current = self.inner.len() - 1;
         } else {
@@ -179,7 +261,14 @@ impl<T: rio_backend::event::EventListener> ContextGrid<T> {
         let mut objects = Vec::with_capacity(len);
 
         // In case there's only 1 context then ignore quad
-        if len == 1 {
+        if let Some((index, _)) = self.zoomed {
+            if let Some(item) = self.inner.get(index) {
+                objects.push(Object::RichText(RichText {
+                    id: item.val.rich_text_id,
+                    position: [self.margin.x, self.margin.top_y],
+                }));
+            }
+        } else if len == 1 {
             if let Some(item) = self.inner.first() {
                 objects.push(Object::RichText(RichText {
                     id: item.val.rich_text_id,
@@ -194,7 +283,7 @@ impl<T: rio_backend::event::EventListener> ContextGrid<T> {
 
     pub fn current_context_with_computed_dimension(&self) -> (&Context<T>, Delta<f32>) {
         let len = self.inner.len();
-        if len <= 1 {
+        if len <= 1 || self.zoomed.is_some() {
             return (&self.inner[self.current].val, self.margin);
         }
 
@@ -355,6 +444,9 @@ impl<T: rio_backend::event::EventListener> ContextGrid<T> {
         for context in &mut self.inner {
             context.val.dimension.update_margin(self.margin);
         }
+        if let Some((_, dimension)) = &mut self.zoomed {
+            dimension.update_margin(self.margin);
+        }
     }
 
     pub fn update_dimensions(&mut self, sugarloaf: &Sugarloaf) {
@@ -362,71 +454,579 @@ impl<T: rio_backend::event::EventListener> ContextGrid<T> {
             let layout = sugarloaf.rich_text_layout(&context.val.rich_text_id);
             context.val.dimension.update_dimensions(layout.dimensions);
         }
+        if let Some((index, dimension)) = &mut self.zoomed {
+            dimension.update_dimensions(self.inner[*index].val.dimension.dimension);
+        }
     }
 
+    /// Resize the grid, every split keeps its proportion of the grid.
     pub fn resize(&mut self, new_width: f32, new_height: f32) {
+        let zoomed = self.zoomed.map(|(index, _)| index);
+        self.unzoom();
+
         let width_difference = new_width - self.width;
         let height_difference = new_height - self.height;
         self.width = new_width;
         self.height = new_height;
 
-        let mut vector = vec![(0., 0.); self.inner.len()];
-        self.resize_context(&mut vector, 0, width_difference, height_difference);
+        let rects = self.layout();
+        let gap = self.scaled_padding();
+        let extent = extent(&rects);
+        let new_extent = (extent.0 + width_difference, extent.1 + height_difference);
+
+        // Position of the start of a split on the resized grid.
+        let scale_start = |start: f32, extent: f32, new_extent: f32| {
+            if extent > 0. {
+                start * new_extent / extent
+            } else {
+                start
+            }
+        };
+        // Ends touching the end of the grid stick to it, other ends stay
+        // before the start of the next split.
+        let scale_end = |end: f32, extent: f32, new_extent: f32| {
+            if (end - extent).abs() < EDGE_EPSILON {
+                new_extent
+            } else {
+                scale_start(end + gap, extent, new_extent) - gap
+            }
+        };
 
-        for (index, val) in vector.into_iter().enumerate() {
-            let context = &mut self.inner[index];
-            let current_width = context.val.dimension.width;
-            context.val.dimension.update_width(current_width + val.0);
+        for (index, rect) in rects.iter().enumerate() {
+            let start_x = scale_start(rect.x, extent.0, new_extent.0);
+            let start_y = scale_start(rect.y, extent.1, new_extent.1);
+            let end_x = scale_end(rect.x + rect.width, extent.0, new_extent.0);
+            let end_y = scale_end(rect.y + rect.height, extent.1, new_extent.1);
 
-            let current_height = context.val.dimension.height;
-            context.val.dimension.update_height(current_height + val.1);
+            let dimension = &mut self.inner[index].val.dimension;
+            dimension.update_width(end_x - start_x);
+            dimension.update_height(end_y - start_y);
+            self.request_resize(index);
+        }
 
-            let mut terminal = context.val.terminal.lock();
-            terminal.resize::<ContextDimension>(context.val.dimension);
-            drop(terminal);
-            let winsize =
-                crate::renderer::utils::terminal_dimensions(&context.val.dimension);
-            let _ = context.val.messenger.send_resize(winsize);
+        if zoomed == Some(self.current) {
+            self.toggle_zoom();
         }
     }
 
-    // TODO: It works partially, if the panels have different dimensions it gets a bit funky
-    fn resize_context(
+    /// Position and size of every split, following the right and down
+    /// children from the first split.
+    fn layout(&self) -> Vec<SplitRect> {
+        let mut rects = vec![SplitRect::default(); self.inner.len()];
+        if self.inner.is_empty() {
+            return rects;
+        }
+
+        let gap = self.scaled_padding();
+        let mut stack = vec![(0, 0., 0.)];
+        while let Some((index, x, y)) = stack.pop() {
+            let Some(item) = self.inner.get(index) else {
+                continue;
+            };
+            let rect = SplitRect {
+                x,
+                y,
+                width: item.val.dimension.width,
+                height: item.val.dimension.height,
+            };
+            rects[index] = rect;
+
+            if let Some(right) = item.right {
+                stack.push((right, x + rect.width + gap, y));
+            }
+            if let Some(down) = item.down {
+                stack.push((down, x, y + rect.height + gap));
+            }
+        }
+
+        rects
+    }
+
+    #[inline]
+    fn scaled_padding(&self) -> f32 {
+        PADDING * self.inner[self.current].val.dimension.dimension.scale
+    }
+
+    /// Position and size of each split on the screen, from the objects of
+    /// the grid.
+    fn screen_rects(&self) -> Vec<(usize, SplitRect)> {
+        self.objects()
+            .into_iter()
+            .filter_map(|obj| {
+                let Object::RichText(rich_text_obj) = obj else {
+                    return None;
+                };
+                let index = self.find_by_rich_text_id(rich_text_obj.id)?;
+                let dimension = &self.inner[index].val.dimension;
+                let scale = dimension.dimension.scale;
+                Some((
+                    index,
+                    SplitRect {
+                        x: rich_text_obj.position[0] * scale,
+                        y: rich_text_obj.position[1] * scale,
+                        width: dimension.width,
+                        height: dimension.height,
+                    },
+                ))
+            })
+            .collect()
+    }
+
+    /// Closest split on the given side of a split. Among splits at the same
+    /// distance, the one sharing the longest border wins.
+    fn split_in_direction(
         &self,
-        vector: &mut Vec<(f32, f32)>,
         index: usize,
-        available_width: f32,
-        available_height: f32,
-    ) -> (f32, f32) {
-        if let Some(item) = self.inner.get(index) {
-            let mut current_available_width = available_width;
-            let mut current_available_heigth = available_height;
-            if let Some(right_item) = item.right {
-                let (new_available_width, _) = self.resize_context(
-                    vector,
-                    right_item,
-                    available_width / 2.,
-                    available_height,
-                );
-                current_available_width = new_available_width;
+        direction: SplitDirection,
+    ) -> Option<usize> {
+        let rects = self.screen_rects();
+        let (_, current) = rects.iter().find(|(position, _)| *position == index)?;
+        let horizontal = direction.is_horizontal();
+        let (start, end) = current.span(horizontal);
+        let (cross_start, cross_end) = current.span(!horizontal);
+
+        rects
+            .iter()
+            .filter(|(position, _)| *position != index)
+            .filter_map(|(position, rect)| {
+                let (rect_start, rect_end) = rect.span(horizontal);
+                let distance = match direction {
+                    SplitDirection::Right | SplitDirection::Down => rect_start - end,
+                    SplitDirection::Left | SplitDirection::Up => start - rect_end,
+                };
+                let (rect_cross_start, rect_cross_end) = rect.span(!horizontal);
+                let overlap =
+                    rect_cross_end.min(cross_end) - rect_cross_start.max(cross_start);
+
+                (distance > -EDGE_EPSILON && overlap > EDGE_EPSILON)
+                    .then_some((*position, (distance, -overlap)))
+            })
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(position, _)| position)
+    }
+
+    /// Select the closest split on the given side of the current one.
+    /// Returns whether there is one.
+    pub fn select_split_in_direction(&mut self, direction: SplitDirection) -> bool {
+        self.unzoom();
+        let Some(index) = self.split_in_direction(self.current, direction) else {
+            return false;
+        };
+
+        // Reset old cursor to hollow
+        self.inner[self.current]
+            .val
+            .renderable_content
+            .mark_pending_updates();
+        self.current = index;
+        true
+    }
+
+    /// Swap the current split with the closest split on the given side, the
+    /// current split keeps the focus. Returns whether there is one.
+    pub fn swap_split_in_direction(&mut self, direction: SplitDirection) -> bool {
+        self.unzoom();
+        let Some(index) = self.split_in_direction(self.current, direction) else {
+            return false;
+        };
+
+        self.swap_contexts(self.current, index);
+        self.current = index;
+        true
+    }
+
+    /// Move every split to the position of the next one, from left to right
+    /// and top to bottom, the current split keeps the focus.
+    pub fn rotate_splits(&mut self) {
+        self.unzoom();
+        if self.inner.len() <= 1 {
+            return;
+        }
+
+        let mut rects = self.screen_rects();
+        rects.sort_by(|(_, a), (_, b)| {
+            (a.y, a.x)
+                .partial_cmp(&(b.y, b.x))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        let order: Vec<usize> = rects.into_iter().map(|(index, _)| index).collect();
+
+        for position in (1..order.len()).rev() {
+            self.swap_contexts(order[position], order[position - 1]);
+        }
+
+        if let Some(position) = order.iter().position(|index| *index == self.current) {
+            self.current = order[(position + 1) % order.len()];
+        }
+    }
+
+    /// Swap the contexts of two splits, each split keeps its dimension.
+    fn swap_contexts(&mut self, first: usize, second: usize) {
+        if first == second {
+            return;
+        }
+
+        let (low, high) = (first.min(second), first.max(second));
+        let (head, tail) = self.inner.split_at_mut(high);
+        std::mem::swap(&mut head[low].val, &mut tail[0].val);
+        std::mem::swap(&mut head[low].val.dimension, &mut tail[0].val.dimension);
+
+        for index in [low, high] {
+            self.inner[index]
+                .val
+                .renderable_content
+                .mark_pending_updates();
+            self.request_resize(index);
+        }
+    }
+
+    /// Maximise the current split within the grid, or restore the layout.
+    pub fn toggle_zoom(&mut self) {
+        if self.zoomed.is_some() {
+            self.unzoom();
+            return;
+        }
+
+        if self.inner.len() <= 1 {
+            return;
+        }
+
+        let dimension = self.inner[self.current].val.dimension;
+        self.zoomed = Some((self.current, dimension));
+        self.inner[self.current].val.dimension = self.grid_dimension();
+        self.request_resize(self.current);
+    }
+
+    /// Restore the layout of a zoomed split.
+    pub fn unzoom(&mut self) {
+        if let Some((index, dimension)) = self.zoomed.take() {
+            if let Some(item) = self.inner.get_mut(index) {
+                item.val.dimension = dimension;
+                self.request_resize(index);
             }
+        }
+    }
 
-            if let Some(down_item) = item.down {
-                let (_, new_available_heigth) = self.resize_context(
-                    vector,
-                    down_item,
-                    available_width,
-                    available_height / 2.,
-                );
-                current_available_heigth = new_available_heigth;
+    /// Width and height of every split, a zoomed split has the size it is
+    /// restored to.
+    pub fn split_sizes(&self) -> Vec<(f32, f32)> {
+        self.inner
+            .iter()
+            .enumerate()
+            .map(|(index, item)| match self.zoomed {
+                Some((zoomed, dimension)) if zoomed == index => {
+                    (dimension.width, dimension.height)
+                }
+                _ => (item.val.dimension.width, item.val.dimension.height),
+            })
+            .collect()
+    }
+
+    /// Give the splits the sizes they had in a grid of `width` and `height`,
+    /// like in a saved session, then fit them into the grid keeping their
+    /// proportions.
+    pub fn restore_sizes(
+        &mut self,
+        width: f32,
+        height: f32,
+        sizes: &[(usize, f32, f32)],
+    ) {
+        self.unzoom();
+        let (grid_width, grid_height) = (self.width, self.height);
+
+        for (index, split_width, split_height) in sizes {
+            if let Some(item) = self.inner.get_mut(*index) {
+                item.val.dimension.update_width(*split_width);
+                item.val.dimension.update_height(*split_height);
+            }
+        }
+
+        self.width = width;
+        self.height = height;
+        self.resize(grid_width, grid_height);
+    }
+
+    /// Grow or shrink the current split by a column or a line, moving its
+    /// border on the given side. Returns whether the splits were resized.
+    pub fn resize_current(&mut self, direction: SplitDirection, grow: bool) -> bool {
+        let dimension = self.inner[self.current].val.dimension.dimension;
+        let step = if direction.is_horizontal() {
+            dimension.width
+        } else {
+            dimension.height
+        };
+        let delta = match (direction, grow) {
+            (SplitDirection::Right | SplitDirection::Down, true)
+            | (SplitDirection::Left | SplitDirection::Up, false) => step,
+            _ => -step,
+        };
+
+        self.move_border(self.current, direction, delta) != 0.
+    }
+
+    /// Share the space of splits placed one after the other, from the left
+    /// or the top, following their ratios. Ratios which aren't positive
+    /// count as one.
+    pub fn set_split_ratios(
+        &mut self,
+        indexes: &[usize],
+        direction: SplitDirection,
+        ratios: &[f32],
+    ) {
+        if indexes.len() < 2 || indexes.len() != ratios.len() {
+            return;
+        }
+
+        let horizontal = direction.is_horizontal();
+        let gap = self.scaled_padding();
+        let rects = self.layout();
+        let (start, _) = rects[indexes[0]].span(horizontal);
+        let (_, end) = rects[indexes[indexes.len() - 1]].span(horizontal);
+        let available = end - start - gap * (indexes.len() - 1) as f32;
+
+        let ratios: Vec<f32> = ratios
+            .iter()
+            .map(|ratio| if *ratio > 0. { *ratio } else { 1. })
+            .collect();
+        let total: f32 = ratios.iter().sum();
+
+        let mut border = start;
+        for (index, ratio) in indexes.iter().zip(&ratios).take(indexes.len() - 1) {
+            border += available * ratio / total;
+            let (_, split_end) = self.layout()[*index].span(horizontal);
+            self.move_border(*index, direction, border - split_end);
+            border += gap;
+        }
+    }
+
+    /// Move the border on one side of a split, positive values move it right
+    /// or down. The splits along the border grow or shrink with it, down to
+    /// their minimum size. Returns how far the border moved.
+    pub fn move_border(
+        &mut self,
+        index: usize,
+        direction: SplitDirection,
+        delta: f32,
+    ) -> f32 {
+        if index >= self.inner.len() || delta == 0. {
+            return 0.;
+        }
+
+        self.unzoom();
+
+        let rects = self.layout();
+        let gap = self.scaled_padding();
+        let horizontal = direction.is_horizontal();
+        let (start, end) = rects[index].span(horizontal);
+        // End of the splits before the border, the splits after it start
+        // after the padding.
+        let border = match direction {
+            SplitDirection::Right | SplitDirection::Down => end,
+            SplitDirection::Left | SplitDirection::Up => start - gap,
+        };
+
+        let (before, after) = self.splits_along_border(&rects, index, border, horizontal);
+        if before.is_empty() || after.is_empty() {
+            return 0.;
+        }
+
+        // Splits shrinking on one side of the border limit how far it moves.
+        let shrinking = if delta > 0. { &after } else { &before };
+        let available = shrinking
+            .iter()
+            .map(|index| {
+                let dimension = &self.inner[*index].val.dimension;
+                let (size, min_size) = if horizontal {
+                    (dimension.width, dimension.dimension.width * MIN_COLS as f32)
+                } else {
+                    (
+                        dimension.height,
+                        dimension.dimension.height * MIN_LINES as f32,
+                    )
+                };
+                (size - min_size).max(0.)
+            })
+            .fold(f32::MAX, f32::min);
+        let delta = delta.clamp(-available, available);
+        if delta == 0. {
+            return 0.;
+        }
+
+        for (indexes, change) in [(&before, delta), (&after, -delta)] {
+            for index in indexes {
+                let context = &mut self.inner[*index].val;
+                let (columns, lines) =
+                    (context.dimension.columns, context.dimension.lines);
+                if horizontal {
+                    context.dimension.increase_width(change);
+                } else {
+                    context.dimension.increase_height(change);
+                }
+
+                // Only splits changing their number of columns or lines
+                // notify their terminal.
+                if context.dimension.columns != columns
+                    || context.dimension.lines != lines
+                {
+                    self.request_resize(*index);
+                }
+            }
+        }
+
+        delta
+    }
+
+    /// Splits ending right before the border and starting right after it,
+    /// which have to grow or shrink together with the split at `index`.
+    fn splits_along_border(
+        &self,
+        rects: &[SplitRect],
+        index: usize,
+        border: f32,
+        horizontal: bool,
+    ) -> (Vec<usize>, Vec<usize>) {
+        let gap = self.scaled_padding();
+        let is_near = |a: f32, b: f32| (a - b).abs() < EDGE_EPSILON;
+        let overlaps = |a: &SplitRect, b: &SplitRect| {
+            let (a_start, a_end) = a.span(!horizontal);
+            let (b_start, b_end) = b.span(!horizontal);
+            a_start < b_end - EDGE_EPSILON && b_start < a_end - EDGE_EPSILON
+        };
+
+        // Whether each split is before the border, after it or neither.
+        let side: Vec<Option<bool>> = rects
+            .iter()
+            .map(|rect| {
+                let (start, end) = rect.span(horizontal);
+                if is_near(end, border) {
+                    Some(true)
+                } else if is_near(start, border + gap) {
+                    Some(false)
+                } else {
+                    None
+                }
+            })
+            .collect();
+        if side[index].is_none() {
+            return (vec![], vec![]);
+        }
+
+        let mut members = vec![false; rects.len()];
+        members[index] = true;
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for candidate in 0..rects.len() {
+                let Some(is_before) = side[candidate] else {
+                    continue;
+                };
+                if members[candidate] {
+                    continue;
+                }
+
+                // Splits overlapping a member on the other side of the border.
+                let mut is_member = (0..rects.len()).any(|member| {
+                    members[member]
+                        && side[member] == Some(!is_before)
+                        && overlaps(&rects[member], &rects[candidate])
+                });
+
+                // Splits after the border are placed from the split above or
+                // on the left of them, so they move together.
+                if !is_member && !is_before {
+                    is_member = (0..rects.len()).any(|member| {
+                        members[member]
+                            && side[member] == Some(false)
+                            && (self.is_stacked(member, candidate, horizontal)
+                                || self.is_stacked(candidate, member, horizontal))
+                    });
+                }
+
+                if is_member {
+                    members[candidate] = true;
+                    changed = true;
+                }
+            }
+        }
+
+        let mut before = vec![];
+        let mut after = vec![];
+        for (index, is_member) in members.into_iter().enumerate() {
+            match side[index] {
+                Some(true) if is_member => before.push(index),
+                Some(false) if is_member => after.push(index),
+                _ => {}
             }
+        }
+        (before, after)
+    }
 
-            vector[index] = (current_available_width, current_available_heigth);
+    /// Whether `child` is placed right under `parent` for horizontal borders,
+    /// or right next to it for vertical ones.
+    #[inline]
+    fn is_stacked(&self, parent: usize, child: usize, horizontal: bool) -> bool {
+        let item = &self.inner[parent];
+        if horizontal {
+            item.down == Some(child)
+        } else {
+            item.right == Some(child)
+        }
+    }
 
-            return (current_available_width, current_available_heigth);
+    /// Border between splits under the mouse, with a few pixels of tolerance
+    /// around the padding.
+    pub fn border_at_mouse(&self, mouse: &Mouse) -> Option<SplitBorder> {
+        if self.inner.len() <= 1 || self.zoomed.is_some() {
+            return None;
         }
 
-        (available_width, available_height)
+        let rects = self.layout();
+        let (extent_x, extent_y) = extent(&rects);
+        let (mouse_x, mouse_y) = (mouse.x as f32, mouse.y as f32);
+
+        for obj in self.objects() {
+            let Object::RichText(rich_text_obj) = obj else {
+                continue;
+            };
+            let Some(index) = self.find_by_rich_text_id(rich_text_obj.id) else {
+                continue;
+            };
+
+            let dimension = &self.inner[index].val.dimension;
+            let scale = dimension.dimension.scale;
+            let tolerance = PADDING * scale * 2.;
+            let x = rich_text_obj.position[0] * scale;
+            let y = rich_text_obj.position[1] * scale;
+            let right = x + dimension.width;
+            let bottom = y + dimension.height;
+            let rect = rects[index];
+
+            if rect.x + rect.width < extent_x - EDGE_EPSILON
+                && mouse_x >= right - tolerance
+                && mouse_x <= right + tolerance
+                && mouse_y >= y
+                && mouse_y <= bottom
+            {
+                return Some(SplitBorder {
+                    index,
+                    direction: SplitDirection::Right,
+                });
+            }
+
+            if rect.y + rect.height < extent_y - EDGE_EPSILON
+                && mouse_y >= bottom - tolerance
+                && mouse_y <= bottom + tolerance
+                && mouse_x >= x
+                && mouse_x <= right
+            {
+                return Some(SplitBorder {
+                    index,
+                    direction: SplitDirection::Down,
+                });
+            }
+        }
+
+        None
     }
 
     fn request_resize(&mut self, index: usize) {
@@ -439,6 +1039,8 @@ impl<T: rio_backend::event::EventListener> ContextGrid<T> {
     }
 
     pub fn remove_current(&mut self) {
+        self.unzoom();
+
         // Note: if is to_be_removed is first item then do not look for parenting,
         // should not exist an item without parenting and isn't zero as index
         let to_be_removed = self.current;
@@ -728,6 +1330,7 @@ impl<T: rio_backend::event::EventListener> ContextGrid<T> {
     }
 
     pub fn split_right(&mut self, context: Context<T>) {
+        self.unzoom();
         let old_right = self.inner[self.current].right;
         // let margin_x = self.margin.x;
 
@@ -786,6 +1389,7 @@ impl<T: rio_backend::event::EventListener> ContextGrid<T> {
     }
 
     pub fn split_down(&mut self, context: Context<T>) {
+        self.unzoom();
         let old_down = self.inner[self.current].down;
 
         let old_grid_item_height = self.inner[self.current].val.dimension.height;
@@ -2272,9 +2876,418 @@ pub mod test {
         assert_eq!(grid.width, 600.0);
         assert_eq!(grid.height, 600.0);
 
+        let sizes = |grid: &ContextGrid<VoidListener>| {
+            grid.contexts()
+                .iter()
+                .map(|item| (item.val.dimension.width, item.val.dimension.height))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(sizes(&grid), [(296., 600.), (300., 296.), (300., 300.)]);
+
+        // Splits keep their proportion of the grid.
         grid.resize(1200.0, 600.0);
+        assert_eq!(sizes(&grid), [(596., 600.), (600., 296.), (600., 300.)]);
+        assert_eq!(grid.contexts()[0].val.dimension.columns, 42);
+        assert_eq!(grid.contexts()[1].val.dimension.columns, 42);
+
+        grid.resize(1200.0, 1200.0);
+        assert_eq!(sizes(&grid), [(596., 1200.), (600., 596.), (600., 600.)]);
+
+        grid.resize(600.0, 600.0);
+        assert_eq!(sizes(&grid), [(296., 600.), (300., 296.), (300., 300.)]);
+    }
+
+    fn create_split_grid(
+        count: usize,
+    ) -> (ContextGrid<VoidListener>, Vec<Context<VoidListener>>) {
+        let context_dimension = ContextDimension::build(
+            1200.0,
+            800.0,
+            SugarDimensions {
+                scale: 1.,
+                width: 14.,
+                height: 8.,
+            },
+            1.0,
+            Delta::<f32>::default(),
+        );
+
+        let mut contexts: Vec<Context<VoidListener>> = (0..count)
+            .map(|rich_text_id| {
+                create_mock_context(
+                    VoidListener {},
+                    WindowId::from(0),
+                    0,
+                    rich_text_id,
+                    context_dimension,
+                )
+            })
+            .collect();
+
+        let grid = ContextGrid::<VoidListener>::new(
+            contexts.remove(0),
+            Delta::<f32>::default(),
+            [0., 0., 0., 0.],
+        );
+        (grid, contexts)
+    }
+
+    fn split_rects(grid: &ContextGrid<VoidListener>) -> Vec<[f32; 4]> {
+        grid.layout()
+            .into_iter()
+            .map(|rect| [rect.x, rect.y, rect.width, rect.height])
+            .collect()
+    }
+
+    #[test]
+    fn test_resize_current_split() {
+        let (mut grid, mut contexts) = create_split_grid(2);
+        grid.split_right(contexts.remove(0));
+        assert_eq!(
+            split_rects(&grid),
+            [[0., 0., 598., 800.], [600., 0., 600., 800.]]
+        );
+
+        // The border on the left of the second split moves by a column.
+        assert!(grid.resize_current(SplitDirection::Left, true));
+        assert_eq!(
+            split_rects(&grid),
+            [[0., 0., 584., 800.], [586., 0., 614., 800.]]
+        );
+        assert!(grid.resize_current(SplitDirection::Left, false));
+        assert_eq!(
+            split_rects(&grid),
+            [[0., 0., 598., 800.], [600., 0., 600., 800.]]
+        );
+
+        // Nothing is on the right of the second split.
+        assert!(!grid.resize_current(SplitDirection::Right, true));
+        assert!(!grid.resize_current(SplitDirection::Down, true));
+
+        grid.current = 0;
+        assert!(grid.resize_current(SplitDirection::Right, true));
+        assert_eq!(
+            split_rects(&grid),
+            [[0., 0., 612., 800.], [614., 0., 586., 800.]]
+        );
+        assert_eq!(grid.contexts()[0].val.dimension.columns, 43);
+        assert_eq!(grid.contexts()[1].val.dimension.columns, 41);
+
+        // Splits stop shrinking at their minimum size.
+        assert_eq!(grid.move_border(0, SplitDirection::Right, 1000.), 558.);
+        assert_eq!(
+            split_rects(&grid),
+            [[0., 0., 1170., 800.], [1172., 0., 28., 800.]]
+        );
+        assert_eq!(grid.move_border(0, SplitDirection::Right, 10.), 0.);
+        assert_eq!(grid.contexts()[1].val.dimension.columns, MIN_COLS);
+    }
+
+    #[test]
+    fn test_set_split_ratios() {
+        let (mut grid, mut contexts) = create_split_grid(4);
+        grid.split_right(contexts.remove(0));
+        grid.split_right(contexts.remove(0));
+        grid.set_split_ratios(&[0, 1, 2], SplitDirection::Right, &[2., 1., 1.]);
+        assert_eq!(
+            split_rects(&grid),
+            [
+                [0., 0., 598., 800.],
+                [600., 0., 299., 800.],
+                [901., 0., 299., 800.]
+            ]
+        );
+
+        grid.set_split_ratios(&[0, 1, 2], SplitDirection::Right, &[1., 2., 0.]);
+        assert_eq!(
+            split_rects(&grid),
+            [
+                [0., 0., 299., 800.],
+                [301., 0., 598., 800.],
+                [901., 0., 299., 800.]
+            ]
+        );
+
+        // Splits inside a split only share its space.
+        let (mut grid, mut contexts) = create_split_grid(3);
+        grid.split_down(contexts.remove(0));
+        grid.set_split_ratios(&[0, 1], SplitDirection::Down, &[3., 1.]);
+        grid.current = 0;
+        grid.split_right(contexts.remove(0));
+        grid.set_split_ratios(&[0, 2], SplitDirection::Right, &[1., 1.]);
+        assert_eq!(
+            split_rects(&grid),
+            [
+                [0., 0., 599., 598.5],
+                [0., 600.5, 1200., 199.5],
+                [601., 0., 599., 598.5]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_restore_split_sizes() {
+        let (mut grid, mut contexts) = create_split_grid(3);
+        grid.split_right(contexts.remove(0));
+        grid.current = 0;
+        grid.split_down(contexts.remove(0));
+        grid.move_border(0, SplitDirection::Right, -100.);
+        grid.toggle_zoom();
+        let sizes = grid.split_sizes();
+        assert_eq!(sizes, [(498., 398.), (700., 800.), (498., 400.)]);
+        assert_eq!(grid.contexts()[0].right(), Some(1));
+        assert_eq!(grid.contexts()[0].down(), Some(2));
+        assert_eq!(grid.contexts()[1].right(), None);
+
+        // The same splits, created in a grid of the same size.
+        let (mut restored, mut contexts) = create_split_grid(3);
+        restored.split_right(contexts.remove(0));
+        restored.current = 0;
+        restored.split_down(contexts.remove(0));
+        let sizes: Vec<(usize, f32, f32)> = sizes
+            .iter()
+            .enumerate()
+            .map(|(index, (width, height))| (index, *width, *height))
+            .collect();
+        restored.restore_sizes(grid.width, grid.height, &sizes);
+        grid.unzoom();
+        assert_eq!(split_rects(&restored), split_rects(&grid));
+
+        // Sizes saved in a larger grid keep their proportions.
+        restored.restore_sizes(
+            2400.,
+            1600.,
+            &[(0, 1198., 798.), (1, 1200., 1600.), (2, 1198., 800.)],
+        );
+        assert_eq!(restored.width, 1200.);
+        assert_eq!(
+            split_rects(&restored),
+            [
+                [0., 0., 598., 398.],
+                [600., 0., 600., 800.],
+                [0., 400., 598., 400.]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resize_split_down() {
+        let (mut grid, mut contexts) = create_split_grid(2);
+        grid.split_down(contexts.remove(0));
+        assert_eq!(
+            split_rects(&grid),
+            [[0., 0., 1200., 398.], [0., 400., 1200., 400.]]
+        );
+
+        grid.current = 0;
+        assert!(grid.resize_current(SplitDirection::Down, true));
+        assert!(grid.resize_current(SplitDirection::Down, true));
+        assert_eq!(
+            split_rects(&grid),
+            [[0., 0., 1200., 414.], [0., 416., 1200., 384.]]
+        );
+
+        assert!(grid.resize_current(SplitDirection::Down, false));
+        assert!(!grid.resize_current(SplitDirection::Up, true));
+        assert_eq!(
+            split_rects(&grid),
+            [[0., 0., 1200., 406.], [0., 408., 1200., 392.]]
+        );
+    }
+
+    #[test]
+    fn test_resize_splits_along_border() {
+        // Two columns, each one split in two rows: the second row of the
+        // right column is placed under its first row, so moving the border
+        // of any row moves the whole column.
+        let (mut grid, mut contexts) = create_split_grid(4);
+        grid.split_right(contexts.remove(0));
+        grid.split_down(contexts.remove(0));
+        grid.current = 0;
+        grid.split_down(contexts.remove(0));
+        assert_eq!(
+            split_rects(&grid),
+            [
+                [0., 0., 598., 398.],
+                [600., 0., 600., 398.],
+                [600., 400., 600., 400.],
+                [0., 400., 598., 400.],
+            ]
+        );
+
+        assert_eq!(grid.move_border(3, SplitDirection::Right, 14.), 14.);
+        assert_eq!(
+            split_rects(&grid),
+            [
+                [0., 0., 612., 398.],
+                [614., 0., 586., 398.],
+                [614., 400., 586., 400.],
+                [0., 400., 612., 400.],
+            ]
+        );
+
+        // Two rows, each one split in two columns: the rows are independent.
+        let (mut grid, mut contexts) = create_split_grid(4);
+        grid.split_down(contexts.remove(0));
+        grid.current = 0;
+        grid.split_right(contexts.remove(0));
+        grid.current = 1;
+        grid.split_right(contexts.remove(0));
+        assert_eq!(
+            split_rects(&grid),
+            [
+                [0., 0., 598., 398.],
+                [0., 400., 598., 400.],
+                [600., 0., 600., 398.],
+                [600., 400., 600., 400.],
+            ]
+        );
+
+        assert_eq!(grid.move_border(0, SplitDirection::Right, -14.), -14.);
+        assert_eq!(
+            split_rects(&grid),
+            [
+                [0., 0., 584., 398.],
+                [0., 400., 598., 400.],
+                [586., 0., 614., 398.],
+                [600., 400., 600., 400.],
+            ]
+        );
+    }
+
+    fn rich_text_ids(grid: &ContextGrid<VoidListener>) -> Vec<usize> {
+        grid.contexts()
+            .iter()
+            .map(|item| item.val.rich_text_id)
+            .collect()
+    }
+
+    #[test]
+    fn test_select_split_in_direction() {
+        let (mut grid, mut contexts) = create_split_grid(4);
+        grid.split_right(contexts.remove(0));
+        grid.split_down(contexts.remove(0));
+        grid.current = 0;
+        grid.split_down(contexts.remove(0));
+        assert_eq!(grid.current_index(), 3);
+
+        assert!(grid.select_split_in_direction(SplitDirection::Up));
+        assert_eq!(grid.current_index(), 0);
+        assert!(!grid.select_split_in_direction(SplitDirection::Up));
+        assert!(!grid.select_split_in_direction(SplitDirection::Left));
+        assert!(grid.select_split_in_direction(SplitDirection::Right));
+        assert_eq!(grid.current_index(), 1);
+        assert!(grid.select_split_in_direction(SplitDirection::Down));
+        assert_eq!(grid.current_index(), 2);
+        assert!(grid.select_split_in_direction(SplitDirection::Left));
+        assert_eq!(grid.current_index(), 3);
+
+        // The split sharing the longest border wins.
+        let (mut grid, mut contexts) = create_split_grid(3);
+        grid.split_right(contexts.remove(0));
+        grid.split_down(contexts.remove(0));
+        grid.move_border(1, SplitDirection::Down, 200.);
+        grid.current = 0;
+        assert!(grid.select_split_in_direction(SplitDirection::Right));
+        assert_eq!(grid.current_index(), 1);
+    }
+
+    #[test]
+    fn test_swap_and_rotate_splits() {
+        let (mut grid, mut contexts) = create_split_grid(4);
+        grid.split_right(contexts.remove(0));
+        grid.split_down(contexts.remove(0));
+        grid.current = 0;
+        grid.split_down(contexts.remove(0));
+        let rects = split_rects(&grid);
+
+        // The focus follows the swapped context, splits keep their size.
+        assert!(grid.swap_split_in_direction(SplitDirection::Right));
+        assert_eq!(rich_text_ids(&grid), [0, 1, 3, 2]);
+        assert_eq!(grid.current_index(), 2);
+        assert_eq!(grid.current().rich_text_id, 3);
+        assert_eq!(split_rects(&grid), rects);
+        assert!(!grid.swap_split_in_direction(SplitDirection::Right));
+
+        // From left to right and top to bottom: 0, 1, 3 and 2.
+        grid.rotate_splits();
+        assert_eq!(rich_text_ids(&grid), [3, 0, 2, 1]);
+        assert_eq!(grid.current().rich_text_id, 3);
+        assert_eq!(split_rects(&grid), rects);
+    }
+
+    #[test]
+    fn test_toggle_zoom() {
+        let (mut grid, mut contexts) = create_split_grid(2);
+        grid.toggle_zoom();
+        assert!(grid.zoomed.is_none());
+
+        grid.split_right(contexts.remove(0));
+        grid.toggle_zoom();
+        assert_eq!(
+            grid.objects(),
+            vec![Object::RichText(RichText {
+                id: 1,
+                position: [0., 0.],
+            })]
+        );
+        assert_eq!(grid.current().dimension.width, 1200.);
+        assert_eq!(grid.current().dimension.columns, 85);
+        assert_eq!(grid.border_at_mouse(&Mouse::default()), None);
+
+        // Resizing the window keeps the zoom.
+        grid.resize(600., 800.);
+        assert_eq!(grid.current().dimension.width, 600.);
+        grid.resize(1200., 800.);
+
+        grid.toggle_zoom();
+        assert_eq!(grid.objects().len(), 6);
+        assert_eq!(
+            split_rects(&grid),
+            [[0., 0., 598., 800.], [600., 0., 600., 800.]]
+        );
+
+        // Selecting another split restores the layout.
+        grid.toggle_zoom();
+        grid.select_next_split();
+        assert!(grid.zoomed.is_none());
+        assert_eq!(grid.contexts()[1].val.dimension.width, 600.);
+    }
+
+    #[test]
+    fn test_border_at_mouse() {
+        let mut mouse = Mouse::default();
+        let (mut grid, mut contexts) = create_split_grid(3);
+        assert_eq!(grid.border_at_mouse(&mouse), None);
+
+        grid.split_right(contexts.remove(0));
+        grid.split_down(contexts.remove(0));
+
+        mouse.x = 599;
+        mouse.y = 100;
+        assert_eq!(
+            grid.border_at_mouse(&mouse),
+            Some(SplitBorder {
+                index: 0,
+                direction: SplitDirection::Right,
+            })
+        );
+
+        mouse.x = 900;
+        mouse.y = 399;
+        assert_eq!(
+            grid.border_at_mouse(&mouse),
+            Some(SplitBorder {
+                index: 1,
+                direction: SplitDirection::Down,
+            })
+        );
 
-        // TODO: Finish test
+        // Inside a split or on the borders of the grid.
+        mouse.y = 200;
+        assert_eq!(grid.border_at_mouse(&mouse), None);
+        mouse.x = 1199;
+        assert_eq!(grid.border_at_mouse(&mouse), None);
     }
 
     #[test]
@@ -2,6 +2,7 @@ pub mod grid;
 pub mod renderable;
 
 use crate::ansi::CursorShape;
+use crate::bindings::parse_program;
 use crate::context::grid::ContextDimension;
 use crate::context::grid::ContextGrid;
 use crate::context::grid::Delta;
@@ -9,15 +10,17 @@ use crate::event::sync::FairMutex;
 use crate::event::RioEvent;
 use crate::ime::Ime;
 use crate::messenger::Messenger;
+use crate::performer::handler::{ParserProcessor, SyncLimits};
 use crate::performer::Machine;
 use renderable::Cursor;
 use renderable::RenderableContent;
+use rio_backend::config::layouts::LayoutPane;
 use rio_backend::config::Shell;
 use rio_backend::crosswords::{Crosswords, MIN_COLUMNS, MIN_LINES};
 use rio_backend::error::{RioError, RioErrorLevel, RioErrorType};
 use rio_backend::event::EventListener;
 use rio_backend::event::WindowId;
-use rio_backend::selection::SelectionRange;
+use rio_backend::selection::{SelectionConfig, SelectionRange};
 use rio_backend::sugarloaf::{font::SugarloafFont, Object, SugarloafErrors};
 use std::borrow::Cow;
 use std::collections::HashMap;
@@ -44,6 +47,8 @@ pub struct Context<T: EventListener> {
     pub rich_text_id: usize,
     pub dimension: ContextDimension,
     pub ime: Ime,
+    /// Program spawned in the terminal.
+    pub shell: Shell,
 }
 
 impl<T: rio_backend::event::EventListener> Drop for Context<T> {
@@ -107,6 +112,27 @@ impl<T: EventListener> Context<T> {
         &self.renderable_content
     }
 
+    /// Working directory reported by the shell, falling back to the path of
+    /// the foreground process when it is unknown or doesn't exist, since new
+    /// tabs and splits fail to spawn in a missing directory.
+    pub fn current_directory(&self) -> Option<String> {
+        let terminal = self.terminal.lock();
+        if let Some(path) = terminal.current_directory.as_ref().filter(|p| p.is_dir()) {
+            return Some(path.to_string_lossy().to_string());
+        }
+        drop(terminal);
+
+        #[cfg(not(target_os = "windows"))]
+        {
+            teletypewriter::foreground_process_path(*self.main_fd, self.shell_pid)
+                .map(|path| path.to_string_lossy().to_string())
+                .ok()
+        }
+
+        #[cfg(target_os = "windows")]
+        None
+    }
+
     #[inline]
     pub fn cursor_from_ref(&self) -> Cursor {
         Cursor {
@@ -129,6 +155,96 @@ pub struct ContextManagerConfig {
     pub is_native: bool,
     pub should_update_titles: bool,
     pub split_color: [f32; 4],
+    pub scrollback_lines: usize,
+    pub sync_limits: SyncLimits,
+    pub selection: SelectionConfig,
+    /// Variables set for the programs spawned, on top of the environment.
+    pub env_vars: Vec<(String, String)>,
+}
+
+impl ContextManagerConfig {
+    /// Configuration of a pane of a layout or a session, which always spawns
+    /// its program to give it the working directory and variables of the
+    /// pane.
+    pub fn for_pane(&self, pane: &PaneConfig) -> ContextManagerConfig {
+        let mut config = self.clone();
+
+        if let Some(shell) = &pane.shell {
+            config.shell = shell.clone();
+        }
+
+        if pane.working_dir.is_some() {
+            config.working_dir = pane.working_dir.clone();
+        }
+
+        config.env_vars = pane.env_vars.clone();
+        #[cfg(not(target_os = "windows"))]
+        {
+            config.use_fork = false;
+        }
+
+        config
+    }
+}
+
+/// Pane opened from a layout or restored from a session.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PaneConfig {
+    /// Program spawned instead of the configured shell.
+    pub shell: Option<Shell>,
+    pub working_dir: Option<String>,
+    pub env_vars: Vec<(String, String)>,
+    /// Title of the pane, until the program running in it sets its own.
+    pub title: Option<String>,
+    /// ANSI escape sequences written to the terminal before the program
+    /// output.
+    pub scrollback: String,
+}
+
+impl From<&LayoutPane> for PaneConfig {
+    fn from(pane: &LayoutPane) -> PaneConfig {
+        PaneConfig {
+            shell: pane
+                .command
+                .as_deref()
+                .and_then(parse_program)
+                .map(|program| Shell {
+                    program: program.program().to_owned(),
+                    args: program.args().to_vec(),
+                }),
+            working_dir: pane.working_dir.as_deref().map(expand_home),
+            env_vars: pane.env_vars(),
+            title: pane.title.clone(),
+            scrollback: String::new(),
+        }
+    }
+}
+
+impl PaneConfig {
+    /// Set the title of the terminal and replay the scrollback, before the
+    /// output of the program is read so it ends up below the scrollback.
+    fn apply<T: EventListener>(&self, terminal: &mut Crosswords<T>) {
+        if !self.scrollback.is_empty() {
+            let mut parser = ParserProcessor::new();
+            for byte in self.scrollback.as_bytes() {
+                parser.advance(terminal, *byte);
+            }
+        }
+
+        if let Some(title) = &self.title {
+            terminal.title = title.to_owned();
+        }
+    }
+}
+
+/// Replace a leading `~` of a path with the home directory.
+fn expand_home(path: &str) -> String {
+    match (path.strip_prefix('~'), dirs::home_dir()) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => {
+            format!("{}{rest}", home.display())
+        }
+        _ => path.to_owned(),
+    }
 }
 
 pub struct ContextManagerTitles {
@@ -210,11 +326,13 @@ pub fn create_mock_context<T: rio_backend::event::EventListener>(
         rich_text_id,
         dimension,
         ime: Ime::new(),
+        shell: Shell::default(),
     }
 }
 
 impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
     #[inline]
+    #[allow(clippy::too_many_arguments)]
     fn create_context(
         cursor_state: (&Cursor, bool),
         event_proxy: T,
@@ -223,6 +341,7 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
         rich_text_id: usize,
         dimension: ContextDimension,
         config: &ContextManagerConfig,
+        pane: Option<&PaneConfig>,
     ) -> Result<Context<T>, Box<dyn Error>> {
         let cols: u16 = dimension.columns.try_into().unwrap_or(MIN_COLUMNS as u16);
         let rows: u16 = dimension.lines.try_into().unwrap_or(MIN_LINES as u16);
@@ -235,6 +354,11 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
             route_id,
         );
         terminal.blinking_cursor = cursor_state.1;
+        terminal.update_history(config.scrollback_lines);
+        terminal.set_selection_config(config.selection.clone());
+        if let Some(pane) = pane {
+            pane.apply(&mut terminal);
+        }
         let terminal: Arc<FairMutex<Crosswords<T>>> = Arc::new(FairMutex::new(terminal));
 
         let pty;
@@ -259,6 +383,7 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
                     &Cow::Borrowed(&config.shell.program),
                     config.shell.args.clone(),
                     &config.working_dir,
+                    &config.env_vars,
                     cols,
                     rows,
                 ) {
@@ -282,6 +407,7 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
                 &Cow::Borrowed(&config.shell.program),
                 config.shell.args.clone(),
                 &config.working_dir,
+                &config.env_vars,
                 cols,
                 rows,
             ) {
@@ -299,6 +425,7 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
             event_proxy.clone(),
             window_id,
             route_id,
+            config.sync_limits,
         )?;
         let channel = machine.channel();
         if config.spawn_performer {
@@ -319,6 +446,7 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
             renderable_content: RenderableContent::new(cursor_state.0.clone()),
             dimension,
             ime: Ime::new(),
+            shell: config.shell.clone(),
         })
     }
 
@@ -334,7 +462,9 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
         size: ContextDimension,
         margin: Delta<f32>,
         sugarloaf_errors: Option<SugarloafErrors>,
+        initial_pane: Option<&PaneConfig>,
     ) -> Result<Self, Box<dyn Error>> {
+        let pane_config = initial_pane.map(|pane| ctx_config.for_pane(pane));
         let initial_context = match ContextManager::create_context(
             cursor_state,
             event_proxy.clone(),
@@ -342,7 +472,8 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
             route_id,
             rich_text_id,
             size,
-            &ctx_config,
+            pane_config.as_ref().unwrap_or(&ctx_config),
+            initial_pane,
         ) {
             Ok(context) => context,
             Err(err_message) => {
@@ -426,6 +557,10 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
             should_update_titles: false,
             use_current_path: false,
             split_color: [0., 0., 0., 0.],
+            scrollback_lines: 0,
+            sync_limits: SyncLimits::default(),
+            selection: SelectionConfig::default(),
+            env_vars: vec![],
         };
         let initial_context = ContextManager::create_context(
             (&Cursor::default(), false),
@@ -435,6 +570,7 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
             0,
             ContextDimension::default(),
             &config,
+            None,
         )?;
 
         let titles =
@@ -538,6 +674,34 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
         }
     }
 
+    #[inline]
+    pub fn report_key_binding_conflicts(&mut self, conflicts: Vec<String>) {
+        if !conflicts.is_empty() {
+            self.event_proxy.send_event(
+                RioEvent::ReportToAssistant({
+                    RioError {
+                        report: RioErrorType::KeyBindingConflicts(conflicts),
+                        level: RioErrorLevel::Warning,
+                    }
+                }),
+                self.window_id,
+            );
+        }
+    }
+
+    #[inline]
+    pub fn report_layout_not_found(&mut self, name: String) {
+        self.event_proxy.send_event(
+            RioEvent::ReportToAssistant({
+                RioError {
+                    report: RioErrorType::LayoutNotFound(name),
+                    level: RioErrorLevel::Warning,
+                }
+            }),
+            self.window_id,
+        );
+    }
+
     #[inline]
     pub fn create_new_window(&self) {
         self.event_proxy
@@ -604,6 +768,18 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
         self.event_proxy.send_event(RioEvent::Quit, self.window_id);
     }
 
+    #[inline]
+    pub fn save_session(&mut self) {
+        self.event_proxy
+            .send_event(RioEvent::SaveSession, self.window_id);
+    }
+
+    #[inline]
+    pub fn open_command_palette(&mut self) {
+        self.event_proxy
+            .send_event(RioEvent::CommandPalette, self.window_id);
+    }
+
     #[cfg(target_os = "macos")]
     #[inline]
     pub fn hide_other_apps(&mut self) {
@@ -665,12 +841,7 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
                         context.current().shell_pid,
                     );
 
-                    let path = teletypewriter::foreground_process_path(
-                        *context.current().main_fd,
-                        context.current().shell_pid,
-                    )
-                    .map(|p| p.to_string_lossy().to_string())
-                    .unwrap_or_default();
+                    let path = context.current().current_directory().unwrap_or_default();
 
                     let terminal_title = {
                         let terminal = context.current().terminal.lock();
@@ -693,7 +864,7 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
                             .send_event(RioEvent::Title(window_title), self.window_id);
                     }
 
-                    id.push_str(&format!("{}{}{};", i, program, terminal_title));
+                    id.push_str(&format!("{}{}{}{};", i, program, terminal_title, path));
                     self.titles.set_key_val(i, program, terminal_title, path);
                 }
                 self.titles.set_key(id);
@@ -702,21 +873,22 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
             #[cfg(not(unix))]
             {
                 let mut id = String::from("");
-                for (i, _context) in self.contexts.iter().enumerate() {
+                for (i, context) in self.contexts.iter().enumerate() {
                     let program = self.config.shell.program.to_owned();
-                    id.push_str(&format!("{}{}{};", i, program, String::default()));
-                    self.titles.set_key_val(
-                        i,
-                        program,
-                        String::default(),
-                        String::default(),
-                    );
+                    let path = context.current().current_directory().unwrap_or_default();
+                    id.push_str(&format!("{}{}{};", i, program, path));
+                    self.titles.set_key_val(i, program, String::default(), path);
                 }
                 self.titles.set_key(id);
             }
         }
     }
 
+    #[inline]
+    pub fn contexts(&self) -> &[ContextGrid<T>] {
+        &self.contexts
+    }
+
     #[inline]
     pub fn contexts_mut(&mut self) -> &mut Vec<ContextGrid<T>> {
         &mut self.contexts
@@ -753,6 +925,19 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
         if context_id < self.contexts.len() {
             self.current_index = context_id;
             self.current_route = self.current().route_id;
+            self.contexts[self.current_index].has_bell = false;
+        }
+    }
+
+    /// Select a tab and the split at the given index of its grid.
+    #[inline]
+    pub fn select_split(&mut self, tab_index: usize, split_index: usize) {
+        self.set_current(tab_index);
+        let grid = &mut self.contexts[self.current_index];
+        if self.current_index == tab_index && split_index < grid.len() {
+            grid.unzoom();
+            grid.current = split_index;
+            self.current_route = grid.current().route_id;
         }
     }
 
@@ -785,6 +970,45 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
         }
     }
 
+    /// Marks the tab owning the route as having rung the bell, returns
+    /// whether the route belongs to the current tab instead.
+    #[inline]
+    pub fn ring_bell(&mut self, route_id: usize) -> bool {
+        let current_index = self.current_index;
+        for (index, grid) in self.contexts.iter_mut().enumerate() {
+            if grid.contains_route(route_id) {
+                if index == current_index {
+                    return true;
+                }
+
+                grid.has_bell = true;
+                return false;
+            }
+        }
+
+        false
+    }
+
+    #[inline]
+    pub fn tabs_with_bell(&self) -> Vec<usize> {
+        self.contexts
+            .iter()
+            .enumerate()
+            .filter_map(|(index, grid)| grid.has_bell.then_some(index))
+            .collect()
+    }
+
+    /// Tab and split indexes of the pane of the route.
+    #[inline]
+    pub fn find_route(&self, route_id: usize) -> Option<(usize, usize)> {
+        self.contexts.iter().enumerate().find_map(|(tab, grid)| {
+            grid.contexts()
+                .iter()
+                .position(|item| item.context().route_id == route_id)
+                .map(|split| (tab, split))
+        })
+    }
+
     #[inline]
     pub fn current_index(&self) -> usize {
         self.current_index
@@ -820,6 +1044,7 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
         }
 
         self.current_route = self.current().route_id;
+        self.contexts[self.current_index].has_bell = false;
     }
 
     #[inline]
@@ -837,29 +1062,16 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
         }
 
         self.current_route = self.current().route_id;
+        self.contexts[self.current_index].has_bell = false;
     }
 
-    pub fn split(&mut self, rich_text_id: usize, split_down: bool) {
+    /// Split the current pane, a program given as shell always starts in the
+    /// working directory of the current pane.
+    pub fn split(&mut self, rich_text_id: usize, split_down: bool, shell: Option<Shell>) {
         let mut working_dir = self.config.working_dir.clone();
-        if self.config.use_current_path {
-            #[cfg(not(target_os = "windows"))]
-            {
-                let current_context = self.current();
-                if let Ok(path) = teletypewriter::foreground_process_path(
-                    *current_context.main_fd,
-                    current_context.shell_pid,
-                ) {
-                    working_dir = Some(path.to_string_lossy().to_string());
-                }
-            }
-
-            #[cfg(target_os = "windows")]
-            {
-                // if let Ok(path) = teletypewriter::foreground_process_path() {
-                //     working_dir =
-                //         Some(path.to_string_lossy().to_string());
-                // }
-                working_dir = None;
+        if self.config.use_current_path || shell.is_some() {
+            if let Some(path) = self.current().current_directory() {
+                working_dir = Some(path);
             }
         }
 
@@ -868,6 +1080,37 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
             cloned_config.working_dir = working_dir;
         }
 
+        if let Some(shell) = shell {
+            cloned_config.shell = shell;
+            // Forking only starts the shell, without arguments.
+            #[cfg(not(target_os = "windows"))]
+            {
+                cloned_config.use_fork = false;
+            }
+        }
+
+        self.split_with_config(rich_text_id, split_down, &cloned_config, None);
+    }
+
+    /// Split the current pane into a pane of a layout or a session, returns
+    /// whether the split was created.
+    pub fn split_pane(
+        &mut self,
+        rich_text_id: usize,
+        split_down: bool,
+        pane: &PaneConfig,
+    ) -> bool {
+        let config = self.config.for_pane(pane);
+        self.split_with_config(rich_text_id, split_down, &config, Some(pane))
+    }
+
+    fn split_with_config(
+        &mut self,
+        rich_text_id: usize,
+        split_down: bool,
+        config: &ContextManagerConfig,
+        pane: Option<&PaneConfig>,
+    ) -> bool {
         self.acc_current_route += 1;
         let current = self.current();
         let cursor = current.cursor_from_ref();
@@ -879,7 +1122,8 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
             self.acc_current_route,
             rich_text_id,
             self.current().dimension,
-            &cloned_config,
+            config,
+            pane,
         ) {
             Ok(new_context) => {
                 if split_down {
@@ -889,9 +1133,11 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
                 }
 
                 self.current_route = self.acc_current_route;
+                true
             }
             Err(..) => {
                 tracing::error!("not able to create a new context");
+                false
             }
         }
     }
@@ -922,58 +1168,21 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
             should_update_titles: !(config.navigation.is_collapsed_mode()
                 && config.navigation.color_automation.is_empty()),
             split_color: config.colors.split,
+            scrollback_lines: config.scrollback.lines,
+            sync_limits: SyncLimits::from(&config.renderer),
+            selection: SelectionConfig::from(&config.selection),
+            env_vars: vec![],
         };
 
-        self.acc_current_route += 1;
-        let current = self.current();
-        let cursor = current.cursor_from_ref();
-
-        match ContextManager::create_context(
-            (&cursor, current.renderable_content.has_blinking_enabled),
-            self.event_proxy.clone(),
-            self.window_id,
-            self.acc_current_route,
-            rich_text_id,
-            self.current().dimension,
-            &context_manager_config,
-        ) {
-            Ok(new_context) => {
-                if split_down {
-                    self.contexts[self.current_index].split_down(new_context);
-                } else {
-                    self.contexts[self.current_index].split_right(new_context);
-                }
-
-                self.current_route = self.acc_current_route;
-            }
-            Err(..) => {
-                tracing::error!("not able to create a new context");
-            }
-        }
+        self.split_with_config(rich_text_id, split_down, &context_manager_config, None);
     }
 
     #[inline]
     pub fn add_context(&mut self, redirect: bool, rich_text_id: usize) {
         let mut working_dir = self.config.working_dir.clone();
         if self.config.use_current_path {
-            #[cfg(not(target_os = "windows"))]
-            {
-                let current_context = self.current();
-                if let Ok(path) = teletypewriter::foreground_process_path(
-                    *current_context.main_fd,
-                    current_context.shell_pid,
-                ) {
-                    working_dir = Some(path.to_string_lossy().to_string());
-                }
-            }
-
-            #[cfg(target_os = "windows")]
-            {
-                // if let Ok(path) = teletypewriter::foreground_process_path() {
-                //     working_dir =
-                //         Some(path.to_string_lossy().to_string());
-                // }
-                working_dir = None;
+            if let Some(path) = self.current().current_directory() {
+                working_dir = Some(path);
             }
         }
 
@@ -983,42 +1192,68 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
             return;
         }
 
+        let mut cloned_config = self.config.clone();
+        if working_dir.is_some() {
+            cloned_config.working_dir = working_dir;
+        }
+
+        self.push_context(redirect, rich_text_id, &cloned_config, None);
+    }
+
+    /// Add a tab running the first pane of a layout or a session, returns
+    /// whether the tab was created. Native tabs are not supported.
+    pub fn add_tab_with_pane(&mut self, rich_text_id: usize, pane: &PaneConfig) -> bool {
+        if self.config.is_native {
+            return false;
+        }
+
+        let config = self.config.for_pane(pane);
+        self.push_context(true, rich_text_id, &config, Some(pane))
+    }
+
+    fn push_context(
+        &mut self,
+        redirect: bool,
+        rich_text_id: usize,
+        config: &ContextManagerConfig,
+        pane: Option<&PaneConfig>,
+    ) -> bool {
         let size = self.contexts.len();
-        if size < self.capacity {
-            let last_index = self.contexts.len();
+        if size >= self.capacity {
+            return false;
+        }
 
-            let mut cloned_config = self.config.clone();
-            if working_dir.is_some() {
-                cloned_config.working_dir = working_dir;
-            }
+        let last_index = self.contexts.len();
 
-            self.acc_current_route += 1;
-            let current = self.current();
-            let cursor = current.cursor_from_ref();
-            match ContextManager::create_context(
-                (&cursor, current.renderable_content.has_blinking_enabled),
-                self.event_proxy.clone(),
-                self.window_id,
-                self.acc_current_route,
-                rich_text_id,
-                self.current_grid().grid_dimension(),
-                &cloned_config,
-            ) {
-                Ok(new_context) => {
-                    let previous_margin = self.contexts[self.current_index].margin;
-                    self.contexts.push(ContextGrid::new(
-                        new_context,
-                        previous_margin,
-                        self.config.split_color,
-                    ));
-                    if redirect {
-                        self.current_index = last_index;
-                        self.current_route = self.current().route_id;
-                    }
-                }
-                Err(..) => {
-                    tracing::error!("not able to create a new context");
+        self.acc_current_route += 1;
+        let current = self.current();
+        let cursor = current.cursor_from_ref();
+        match ContextManager::create_context(
+            (&cursor, current.renderable_content.has_blinking_enabled),
+            self.event_proxy.clone(),
+            self.window_id,
+            self.acc_current_route,
+            rich_text_id,
+            self.current_grid().grid_dimension(),
+            config,
+            pane,
+        ) {
+            Ok(new_context) => {
+                let previous_margin = self.contexts[self.current_index].margin;
+                self.contexts.push(ContextGrid::new(
+                    new_context,
+                    previous_margin,
+                    self.config.split_color,
+                ));
+                if redirect {
+                    self.current_index = last_index;
+                    self.current_route = self.current().route_id;
                 }
+                true
+            }
+            Err(..) => {
+                tracing::error!("not able to create a new context");
+                false
             }
         }
     }
@@ -1093,6 +1328,40 @@ pub mod test {
         assert_eq!(context_manager.current_index, 2);
     }
 
+    #[test]
+    fn test_find_route() {
+        let window_id: WindowId = WindowId::from(0);
+
+        let mut context_manager =
+            ContextManager::start_with_capacity(5, VoidListener {}, window_id).unwrap();
+        context_manager.add_context(false, 0);
+        context_manager.add_context(false, 0);
+        assert_eq!(context_manager.find_route(0), Some((0, 0)));
+        assert_eq!(context_manager.find_route(2), Some((2, 0)));
+        assert_eq!(context_manager.find_route(3), None);
+    }
+
+    #[test]
+    fn test_add_tab_with_pane_scrollback() {
+        let window_id: WindowId = WindowId::from(0);
+
+        let mut context_manager =
+            ContextManager::start_with_capacity(5, VoidListener {}, window_id).unwrap();
+        // The terminal has a single line of two columns.
+        context_manager.config.scrollback_lines = 10;
+        let pane = PaneConfig {
+            title: Some(String::from("logs")),
+            scrollback: String::from("ab\r\ncd\r\n"),
+            ..PaneConfig::default()
+        };
+        assert!(context_manager.add_tab_with_pane(0, &pane));
+
+        // The scrollback is written before the program output is read.
+        let terminal = context_manager.current().terminal.lock();
+        assert_eq!(terminal.title, "logs");
+        assert_eq!(terminal.last_lines_to_string(Some(3)), "ab\ncd\n\n");
+    }
+
     #[test]
     fn test_add_context_start_with_capacity_limit() {
         let window_id: WindowId = WindowId::from(0);
@@ -1244,4 +1513,79 @@ pub mod test {
         context_manager.switch_to_next();
         assert_eq!(context_manager.current_index, 1);
     }
+
+    #[test]
+    fn test_ring_bell() {
+        let window_id: WindowId = WindowId::from(0);
+
+        let mut context_manager =
+            ContextManager::start_with_capacity(5, VoidListener {}, window_id).unwrap();
+        let should_redirect = false;
+
+        context_manager.add_context(should_redirect, 0);
+        context_manager.add_context(should_redirect, 0);
+        assert_eq!(context_manager.len(), 3);
+
+        assert!(context_manager.ring_bell(0));
+        assert!(!context_manager.ring_bell(2));
+        assert!(!context_manager.ring_bell(42));
+        assert_eq!(context_manager.tabs_with_bell(), vec![2]);
+
+        context_manager.switch_to_next();
+        assert!(!context_manager.ring_bell(0));
+        assert_eq!(context_manager.tabs_with_bell(), vec![0, 2]);
+
+        context_manager.set_current(2);
+        assert_eq!(context_manager.tabs_with_bell(), vec![0]);
+        context_manager.switch_to_prev();
+        context_manager.switch_to_prev();
+        assert!(context_manager.tabs_with_bell().is_empty());
+    }
+
+    #[test]
+    fn test_config_for_pane() {
+        let config = ContextManagerConfig {
+            shell: Shell {
+                program: String::from("zsh"),
+                args: vec![String::from("-l")],
+            },
+            working_dir: Some(String::from("/tmp")),
+            ..ContextManagerConfig::default()
+        };
+
+        let pane = LayoutPane {
+            command: Some(String::from("tail -f 'log file.txt'")),
+            working_dir: Some(String::from("/srv/app")),
+            env: vec![String::from("RUST_LOG=debug"), String::from("IGNORED")],
+            ..LayoutPane::default()
+        };
+        let pane_config = config.for_pane(&PaneConfig::from(&pane));
+        assert_eq!(
+            pane_config.shell,
+            Shell {
+                program: String::from("tail"),
+                args: vec![String::from("-f"), String::from("log file.txt")],
+            }
+        );
+        assert_eq!(pane_config.working_dir.as_deref(), Some("/srv/app"));
+        assert_eq!(
+            pane_config.env_vars,
+            [(String::from("RUST_LOG"), String::from("debug"))]
+        );
+        #[cfg(not(target_os = "windows"))]
+        assert!(!pane_config.use_fork);
+
+        // Panes without a command run the shell.
+        let pane_config = config.for_pane(&PaneConfig::default());
+        assert_eq!(pane_config.shell, config.shell);
+        assert_eq!(pane_config.working_dir.as_deref(), Some("/tmp"));
+        assert!(pane_config.env_vars.is_empty());
+
+        if let Some(home) = dirs::home_dir() {
+            assert_eq!(expand_home("~"), home.display().to_string());
+            assert_eq!(expand_home("~/app"), format!("{}/app", home.display()));
+        }
+        assert_eq!(expand_home("~user/app"), "~user/app");
+        assert_eq!(expand_home("/srv/~"), "/srv/~");
+    }
 }
@@ -0,0 +1,342 @@
+use crate::event::{EventListener, RioEvent};
+use corcovado::listener::UnixListener;
+use corcovado::{Events, Poll, PollOpt, Ready, Token};
+use rio_backend::ipc::{Request, Response, SOCKET_ENV};
+use std::error::Error;
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::os::unix::fs::{DirBuilderExt, MetadataExt};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Time given to Rio to answer a request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[inline]
+fn current_uid() -> u32 {
+    unsafe { libc::getuid() }
+}
+
+/// Directory of the sockets of the user, in `XDG_RUNTIME_DIR` or in the
+/// temporary directory when it is not available.
+fn socket_dir() -> PathBuf {
+    let parent = std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_dir())
+        .unwrap_or_else(std::env::temp_dir);
+    parent.join(format!("rio-{}", current_uid()))
+}
+
+/// Create the directory of the sockets, only accessible by the user. Since
+/// the temporary directory is shared, an existing directory is refused when
+/// it belongs to someone else or can be accessed by others.
+fn create_socket_dir(dir: &Path) -> std::io::Result<()> {
+    if let Err(err) = std::fs::DirBuilder::new().mode(0o700).create(dir) {
+        if err.kind() != ErrorKind::AlreadyExists {
+            return Err(err);
+        }
+    }
+
+    let metadata = std::fs::symlink_metadata(dir)?;
+    if !metadata.is_dir()
+        || metadata.uid() != current_uid()
+        || metadata.mode() & 0o077 != 0
+    {
+        return Err(std::io::Error::new(
+            ErrorKind::PermissionDenied,
+            format!("{} is not a private directory of the user", dir.display()),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Socket of the current Rio process.
+pub fn socket_path() -> PathBuf {
+    socket_dir().join(format!("rio-{}.sock", std::process::id()))
+}
+
+/// Listen for requests on the socket of the current process, each request
+/// is dispatched to the event loop which writes the answer back. The path
+/// of the socket is exported to the terminals through `RIO_SOCKET`.
+pub fn spawn_ipc_socket<T: EventListener + Clone + Send + 'static>(
+    event_proxy: T,
+) -> std::io::Result<PathBuf> {
+    create_socket_dir(&socket_dir())?;
+    let path = socket_path();
+    // Left behind by a process with the same id which didn't quit cleanly.
+    let _ = std::fs::remove_file(&path);
+    let listener = UnixListener::bind(&path)?;
+    std::env::set_var(SOCKET_ENV, &path);
+
+    let poll = Poll::new()?;
+    poll.register(&listener, Token(0), Ready::readable(), PollOpt::level())?;
+
+    std::thread::Builder::new()
+        .name(String::from("ipc"))
+        .spawn(move || {
+            let mut events = Events::with_capacity(16);
+            loop {
+                if let Err(err) = poll.poll(&mut events, None) {
+                    match err.kind() {
+                        ErrorKind::Interrupted => continue,
+                        _ => {
+                            tracing::error!("unable to poll the ipc socket: {err:?}");
+                            return;
+                        }
+                    }
+                }
+
+                loop {
+                    match listener.accept_std() {
+                        Ok(Some((stream, _))) => {
+                            let event_proxy = event_proxy.clone();
+                            std::thread::spawn(move || {
+                                if let Err(err) = handle_connection(stream, &event_proxy)
+                                {
+                                    tracing::warn!("ipc connection failed: {err:?}");
+                                }
+                            });
+                        }
+                        Ok(None) => break,
+                        Err(err) => {
+                            tracing::warn!("unable to accept ipc connection: {err:?}");
+                            break;
+                        }
+                    }
+                }
+            }
+        })?;
+
+    Ok(path)
+}
+
+/// Answer the requests of a client, one JSON object per line.
+fn handle_connection<T: EventListener>(
+    stream: UnixStream,
+    event_proxy: &T,
+) -> std::io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(request) => dispatch(request, event_proxy),
+            Err(err) => Response::Error {
+                message: format!("invalid request: {err}"),
+            },
+        };
+
+        let mut content = serde_json::to_string(&response)?;
+        content.push('\n');
+        writer.write_all(content.as_bytes())?;
+    }
+
+    Ok(())
+}
+
+fn dispatch<T: EventListener>(request: Request, event_proxy: &T) -> Response {
+    let (sender, receiver) = std::sync::mpsc::channel();
+    event_proxy.send_event(
+        RioEvent::Ipc(request, sender),
+        rio_backend::event::WindowId::from(0),
+    );
+
+    receiver
+        .recv_timeout(REQUEST_TIMEOUT)
+        .unwrap_or_else(|_| Response::Error {
+            message: String::from("no answer from Rio"),
+        })
+}
+
+/// Remove the socket of the current process when quitting.
+pub fn remove_socket() {
+    let _ = std::fs::remove_file(socket_path());
+}
+
+/// Socket to send requests to: the given one, the one of the Rio process
+/// running the current terminal, or the one of the last started Rio process.
+pub fn find_socket(socket: Option<PathBuf>) -> Option<PathBuf> {
+    if let Some(socket) = socket {
+        return Some(socket);
+    }
+
+    if let Some(socket) = std::env::var_os(SOCKET_ENV).filter(|s| !s.is_empty()) {
+        return Some(PathBuf::from(socket));
+    }
+
+    find_socket_in(&socket_dir())
+}
+
+/// Last started socket of the directory which is owned by the user.
+fn find_socket_in(dir: &Path) -> Option<PathBuf> {
+    let uid = current_uid();
+    let mut sockets: Vec<(SystemTime, PathBuf)> = std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            name.starts_with("rio-") && name.ends_with(".sock")
+        })
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            if metadata.uid() != uid {
+                return None;
+            }
+            Some((metadata.modified().ok()?, entry.path()))
+        })
+        .collect();
+    sockets.sort();
+
+    // Sockets of processes which didn't quit cleanly refuse connections.
+    sockets
+        .into_iter()
+        .rev()
+        .map(|(_, path)| path)
+        .find(|path| UnixStream::connect(path).is_ok())
+}
+
+/// Send a request to the socket and wait for the answer.
+pub fn send_request(path: &Path, request: &Request) -> Result<Response, Box<dyn Error>> {
+    let mut stream = UnixStream::connect(path)
+        .map_err(|err| format!("unable to connect to {}: {err}", path.display()))?;
+
+    let mut content = serde_json::to_string(request)?;
+    content.push('\n');
+    stream.write_all(content.as_bytes())?;
+
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line)?;
+    if line.is_empty() {
+        return Err("the connection was closed without an answer".into());
+    }
+
+    Ok(serde_json::from_str(&line)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rio_backend::config::layouts::LayoutSplit;
+    use rio_backend::ipc::Target;
+
+    /// Answers the requests like the event loop would.
+    struct Responder;
+
+    impl EventListener for Responder {
+        fn event(&self) -> (Option<RioEvent>, bool) {
+            (None, false)
+        }
+
+        fn send_event(&self, event: RioEvent, _id: rio_backend::event::WindowId) {
+            if let RioEvent::Ipc(request, sender) = event {
+                let response = match request {
+                    Request::GetText { lines, .. } => Response::Text {
+                        text: format!("{lines:?}"),
+                    },
+                    _ => Response::Ok,
+                };
+                let _ = sender.send(response);
+            }
+        }
+    }
+
+    #[test]
+    fn test_request_format() {
+        let request: Request = serde_json::from_str(
+            r#"{"type": "split", "pane": 2, "direction": "down", "command": ["htop"]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            request,
+            Request::Split {
+                target: Target {
+                    window: None,
+                    pane: Some(2),
+                },
+                direction: LayoutSplit::Down,
+                command: vec![String::from("htop")],
+                working_dir: None,
+            }
+        );
+
+        let request = Request::SetConfig {
+            values: vec![String::from("fonts.size = 18")],
+            reset: false,
+        };
+        let content = serde_json::to_string(&request).unwrap();
+        assert_eq!(
+            content,
+            r#"{"type":"set-config","values":["fonts.size = 18"],"reset":false}"#
+        );
+        assert_eq!(serde_json::from_str::<Request>(&content).unwrap(), request);
+
+        assert!(serde_json::from_str::<Request>(r#"{"type": "unknown"}"#).is_err());
+    }
+
+    #[test]
+    fn test_request_round_trip() {
+        let path = std::env::temp_dir()
+            .join(format!("test-rio-ipc-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = std::os::unix::net::UnixListener::bind(&path).unwrap();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().take(2) {
+                handle_connection(stream.unwrap(), &Responder).unwrap();
+            }
+        });
+
+        let request = Request::GetText {
+            target: Target::default(),
+            lines: Some(3),
+        };
+        assert_eq!(
+            send_request(&path, &request).unwrap(),
+            Response::Text {
+                text: String::from("Some(3)"),
+            }
+        );
+
+        let mut stream = UnixStream::connect(&path).unwrap();
+        stream
+            .write_all(b"{\"type\": \"list-panes\"}\nnot json\n")
+            .unwrap();
+        let mut lines = BufReader::new(stream).lines();
+        assert_eq!(lines.next().unwrap().unwrap(), r#"{"type":"ok"}"#);
+        assert!(lines
+            .next()
+            .unwrap()
+            .unwrap()
+            .starts_with(r#"{"type":"error","message":"invalid request: "#));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_socket_dir() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir =
+            std::env::temp_dir().join(format!("test-rio-ipc-dir-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        create_socket_dir(&dir).unwrap();
+        let mode = std::fs::metadata(&dir).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+        assert_eq!(find_socket_in(&dir), None);
+
+        let path = dir.join("rio-1.sock");
+        let _listener = std::os::unix::net::UnixListener::bind(&path).unwrap();
+        assert_eq!(find_socket_in(&dir), Some(path));
+
+        // A directory others can access is refused.
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o755)).unwrap();
+        assert_eq!(
+            create_socket_dir(&dir).unwrap_err().kind(),
+            ErrorKind::PermissionDenied
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -10,8 +10,11 @@ mod cli;
 mod constants;
 mod context;
 mod ime;
+#[cfg(unix)]
+mod ipc;
 mod messenger;
 mod mouse;
+mod notifications;
 #[cfg(windows)]
 mod panic;
 mod platform;
@@ -19,6 +22,7 @@ mod renderer;
 mod router;
 mod scheduler;
 mod screen;
+mod session;
 mod watcher;
 
 use clap::Parser;
@@ -124,6 +128,25 @@ fn setup_logs_by_filter_level(
     Ok(())
 }
 
+/// Send a request to a running Rio and print the answer: the text of a pane
+/// as is, nothing on success and JSON otherwise.
+#[cfg(unix)]
+fn send_message(options: cli::MessageOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let socket = ipc::find_socket(options.socket).ok_or("no running Rio was found")?;
+    match ipc::send_request(&socket, &options.message.into())? {
+        rio_backend::ipc::Response::Ok => {}
+        rio_backend::ipc::Response::Text { text } => print!("{text}"),
+        rio_backend::ipc::Response::Error { message } => return Err(message.into()),
+        response => println!("{}", serde_json::to_string_pretty(&response)?),
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn send_message(_options: cli::MessageOptions) -> Result<(), Box<dyn std::error::Error>> {
+    Err("messages are only supported on unix platforms".into())
+}
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     #[cfg(windows)]
     panic::attach_handler();
@@ -139,6 +162,14 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Load command line options.
     let args = cli::Cli::parse();
 
+    if let Some(cli::Subcommands::Msg(options)) = args.subcommands {
+        if let Err(err) = send_message(options) {
+            eprintln!("rio msg: {err}");
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     let write_config_path = args.window_options.terminal_options.write_config.clone();
     if let Some(config_path) = write_config_path {
         let _ = setup_logs_by_filter_level("TRACE", false);
@@ -183,6 +214,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
 
         config.window.initial_title = args.window_options.terminal_options.title;
+
+        if let Some(layout) = args.window_options.terminal_options.layout {
+            config.layout = Some(layout);
+        }
     }
 
     #[cfg(target_os = "linux")]
@@ -1,4 +1,5 @@
 use crate::event::Msg;
+use crate::performer::handler::SyncLimits;
 use std::borrow::Cow;
 use teletypewriter::WinsizeBuilder;
 
@@ -34,4 +35,9 @@ impl Messenger {
             Err(..) => Err("Error sending message".to_string()),
         }
     }
+
+    #[inline]
+    pub fn send_sync_limits(&self, limits: SyncLimits) {
+        let _ = self.channel.send(Msg::SyncLimits(limits));
+    }
 }
@@ -0,0 +1,201 @@
+use rio_backend::config::notifications::NotificationPolicy;
+use std::io;
+#[cfg(unix)]
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+const DEFAULT_TITLE: &str = "Rio";
+
+/// Shortest time between two notifications, a program printing notification
+/// sequences in a loop would spawn a process for each of them.
+const NOTIFY_INTERVAL: Duration = Duration::from_secs(1);
+
+pub trait NotificationBackend {
+    fn show(&mut self, title: &str, body: &str) -> io::Result<()>;
+}
+
+/// Shows notifications with the notification tool of the platform.
+pub struct SystemNotifications;
+
+impl NotificationBackend for SystemNotifications {
+    #[cfg(target_os = "macos")]
+    fn show(&mut self, title: &str, body: &str) -> io::Result<()> {
+        let quote = |text: &str| text.replace('\\', "\\\\").replace('"', "\\\"");
+        let script = format!(
+            "display notification \"{}\" with title \"{}\"",
+            quote(body),
+            quote(title)
+        );
+        spawn(Command::new("osascript").arg("-e").arg(script))
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn show(&mut self, title: &str, body: &str) -> io::Result<()> {
+        spawn(
+            Command::new("notify-send")
+                .arg("--app-name")
+                .arg(DEFAULT_TITLE)
+                .arg("--")
+                .arg(title)
+                .arg(body),
+        )
+    }
+
+    #[cfg(windows)]
+    fn show(&mut self, _title: &str, _body: &str) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "desktop notifications are not supported on Windows yet",
+        ))
+    }
+}
+
+#[cfg(unix)]
+fn spawn(command: &mut Command) -> io::Result<()> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    // Reap the process once the notification is delivered.
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
+pub struct Notifier<B: NotificationBackend> {
+    pub policy: NotificationPolicy,
+    backend: B,
+    last_shown: Option<Instant>,
+}
+
+impl<B: NotificationBackend> Notifier<B> {
+    pub fn new(policy: NotificationPolicy, backend: B) -> Self {
+        Self {
+            policy,
+            backend,
+            last_shown: None,
+        }
+    }
+
+    /// Show a notification if allowed by the policy, returns whether it was shown.
+    /// Notifications requested less than [`NOTIFY_INTERVAL`] after the last shown
+    /// one are dropped.
+    #[inline]
+    pub fn notify(&mut self, title: &str, body: &str, is_focused: bool) -> bool {
+        self.notify_at(title, body, is_focused, Instant::now())
+    }
+
+    fn notify_at(
+        &mut self,
+        title: &str,
+        body: &str,
+        is_focused: bool,
+        now: Instant,
+    ) -> bool {
+        let should_show = match self.policy {
+            NotificationPolicy::Always => true,
+            NotificationPolicy::Unfocused => !is_focused,
+            NotificationPolicy::Never => false,
+        };
+
+        if !should_show || (title.is_empty() && body.is_empty()) {
+            return false;
+        }
+
+        if self.last_shown.is_some_and(|last_shown| {
+            now.saturating_duration_since(last_shown) < NOTIFY_INTERVAL
+        }) {
+            return false;
+        }
+
+        let title = if title.is_empty() {
+            DEFAULT_TITLE
+        } else {
+            title
+        };
+        match self.backend.show(title, body) {
+            Ok(()) => {
+                self.last_shown = Some(now);
+                true
+            }
+            Err(err) => {
+                tracing::warn!("unable to show notification: {err}");
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct StubBackend {
+        shown: Vec<(String, String)>,
+    }
+
+    impl NotificationBackend for StubBackend {
+        fn show(&mut self, title: &str, body: &str) -> io::Result<()> {
+            self.shown.push((title.to_owned(), body.to_owned()));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn notify_always() {
+        let mut notifier =
+            Notifier::new(NotificationPolicy::Always, StubBackend::default());
+
+        let start = Instant::now();
+        assert!(notifier.notify_at("Build", "Finished", true, start));
+        assert!(notifier.notify_at("", "Finished", false, start + NOTIFY_INTERVAL));
+        assert!(!notifier.notify("", "", false));
+        assert_eq!(
+            notifier.backend.shown,
+            vec![
+                (String::from("Build"), String::from("Finished")),
+                (String::from(DEFAULT_TITLE), String::from("Finished")),
+            ]
+        );
+    }
+
+    #[test]
+    fn notify_unfocused() {
+        let mut notifier =
+            Notifier::new(NotificationPolicy::Unfocused, StubBackend::default());
+
+        assert!(!notifier.notify("Build", "Finished", true));
+        assert!(notifier.notify("Build", "Finished", false));
+        assert_eq!(notifier.backend.shown.len(), 1);
+    }
+
+    #[test]
+    fn notify_throttle() {
+        let mut notifier =
+            Notifier::new(NotificationPolicy::Always, StubBackend::default());
+        let start = Instant::now();
+
+        let shown = (0..1000)
+            .filter(|i| {
+                notifier.notify_at("", "x", false, start + Duration::from_micros(*i))
+            })
+            .count();
+        assert_eq!(shown, 1);
+        assert_eq!(notifier.backend.shown.len(), 1);
+
+        assert!(!notifier.notify_at("", "x", false, start + Duration::from_millis(999)));
+        assert!(notifier.notify_at("", "x", false, start + NOTIFY_INTERVAL));
+    }
+
+    #[test]
+    fn notify_never() {
+        let mut notifier =
+            Notifier::new(NotificationPolicy::Never, StubBackend::default());
+
+        assert!(!notifier.notify("Build", "Finished", true));
+        assert!(!notifier.notify("Build", "Finished", false));
+        assert!(notifier.backend.shown.is_empty());
+    }
+}
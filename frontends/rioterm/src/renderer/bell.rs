@@ -0,0 +1,141 @@
+use rio_backend::config::bell::Bell;
+use rio_backend::config::colors::ColorArray;
+use rio_backend::sugarloaf::{Object, Rect};
+use std::time::{Duration, Instant};
+
+pub struct VisualBell {
+    duration: Duration,
+    color: ColorArray,
+    start_time: Option<Instant>,
+}
+
+impl VisualBell {
+    pub fn new(config: &Bell) -> VisualBell {
+        VisualBell {
+            duration: Duration::from_millis(config.visual_duration),
+            color: config.visual_color,
+            start_time: None,
+        }
+    }
+
+    /// Start the flash animation, does nothing if the visual bell is disabled.
+    #[inline]
+    pub fn ring(&mut self) {
+        if !self.duration.is_zero() {
+            self.start_time = Some(Instant::now());
+        }
+    }
+
+    #[inline]
+    pub fn is_animating(&self) -> bool {
+        self.intensity_at(Instant::now()) > 0.
+    }
+
+    /// Opacity multiplier of the flash, fading linearly from 1 to 0.
+    fn intensity_at(&self, now: Instant) -> f32 {
+        let Some(start_time) = self.start_time else {
+            return 0.;
+        };
+
+        let elapsed = now.saturating_duration_since(start_time);
+        if elapsed >= self.duration {
+            return 0.;
+        }
+
+        1. - elapsed.as_secs_f32() / self.duration.as_secs_f32()
+    }
+
+    #[inline]
+    pub fn draw(&mut self, objects: &mut Vec<Object>, dimensions: (f32, f32, f32)) {
+        let intensity = self.intensity_at(Instant::now());
+        if intensity <= 0. {
+            self.start_time = None;
+            return;
+        }
+
+        let (width, height, scale) = dimensions;
+        let mut color = self.color;
+        color[3] *= intensity;
+
+        objects.push(Object::Rect(Rect {
+            position: [0.0, 0.0],
+            color,
+            size: [width / scale, height / scale],
+        }));
+    }
+}
+
+/// Shortest time between two runs of the bell command.
+const COMMAND_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Limits how often the bell command runs, a burst of bells like `yes $'\a'`
+/// or `cat` of a binary file would spawn a process for each of them.
+#[derive(Default)]
+pub struct BellCommandThrottle {
+    last_run: Option<Instant>,
+}
+
+impl BellCommandThrottle {
+    /// Whether the command should run for a bell ringing now.
+    #[inline]
+    pub fn should_run(&mut self) -> bool {
+        self.should_run_at(Instant::now())
+    }
+
+    fn should_run_at(&mut self, now: Instant) -> bool {
+        if self.last_run.is_some_and(|last_run| {
+            now.saturating_duration_since(last_run) < COMMAND_INTERVAL
+        }) {
+            return false;
+        }
+
+        self.last_run = Some(now);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bell_command_throttle() {
+        let mut throttle = BellCommandThrottle::default();
+        let start = Instant::now();
+
+        let runs = (0..1000)
+            .filter(|i| throttle.should_run_at(start + Duration::from_micros(*i)))
+            .count();
+        assert_eq!(runs, 1);
+
+        assert!(!throttle.should_run_at(start + Duration::from_millis(499)));
+        assert!(throttle.should_run_at(start + COMMAND_INTERVAL));
+    }
+
+    #[test]
+    fn test_visual_bell_intensity() {
+        let mut bell = VisualBell::new(&Bell {
+            visual_duration: 100,
+            ..Bell::default()
+        });
+        assert_eq!(bell.intensity_at(Instant::now()), 0.);
+
+        bell.ring();
+        let start_time = bell.start_time.unwrap();
+        assert_eq!(bell.intensity_at(start_time), 1.);
+        let intensity = bell.intensity_at(start_time + Duration::from_millis(25));
+        assert!((intensity - 0.75).abs() < 0.001);
+        assert_eq!(
+            bell.intensity_at(start_time + Duration::from_millis(100)),
+            0.
+        );
+    }
+
+    #[test]
+    fn test_visual_bell_disabled() {
+        let mut bell = VisualBell::new(&Bell::default());
+        bell.ring();
+        assert!(bell.start_time.is_none());
+        assert!(!bell.is_animating());
+    }
+}
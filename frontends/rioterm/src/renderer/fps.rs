@@ -0,0 +1,97 @@
+use rio_backend::config::colors::Colors;
+use rio_backend::crosswords::SyncMetrics;
+use rio_backend::sugarloaf::{Object, Rect, Text};
+use std::time::{Duration, Instant};
+
+const WIDTH: f32 = 210.;
+const HEIGHT: f32 = 40.;
+const MARGIN: f32 = 8.;
+const UPDATE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Developer overlay with the frames rendered in the last second and the
+/// synchronized updates that were aborted by the terminal.
+pub struct FpsCounter {
+    frames: usize,
+    fps: usize,
+    last_update: Instant,
+}
+
+impl FpsCounter {
+    pub fn new() -> FpsCounter {
+        FpsCounter {
+            frames: 0,
+            fps: 0,
+            last_update: Instant::now(),
+        }
+    }
+
+    /// Account a rendered frame, the rate is refreshed once per interval.
+    fn tick(&mut self, now: Instant) {
+        self.frames += 1;
+
+        let elapsed = now.saturating_duration_since(self.last_update);
+        if elapsed >= UPDATE_INTERVAL {
+            self.fps = (self.frames as f32 / elapsed.as_secs_f32()).round() as usize;
+            self.frames = 0;
+            self.last_update = now;
+        }
+    }
+
+    #[inline]
+    pub fn draw(
+        &mut self,
+        objects: &mut Vec<Object>,
+        colors: &Colors,
+        dimensions: (f32, f32, f32),
+        sync_metrics: SyncMetrics,
+    ) {
+        self.tick(Instant::now());
+
+        let (width, _, scale) = dimensions;
+        let position_x = (width / scale - WIDTH - MARGIN).max(0.);
+
+        objects.push(Object::Rect(Rect {
+            position: [position_x, MARGIN],
+            color: colors.bar,
+            size: [WIDTH, HEIGHT],
+        }));
+
+        objects.push(Object::Text(Text::single_line(
+            (position_x + 6., MARGIN + 4.),
+            format!("{} fps", self.fps),
+            14.,
+            colors.foreground,
+        )));
+
+        objects.push(Object::Text(Text::single_line(
+            (position_x + 6., MARGIN + 22.),
+            format!(
+                "sync timeouts: {} overflows: {}",
+                sync_metrics.timeouts, sync_metrics.overflows
+            ),
+            12.,
+            colors.foreground,
+        )));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fps_counter_tick() {
+        let mut counter = FpsCounter::new();
+        let start = counter.last_update;
+
+        for i in 1..60 {
+            counter.tick(start + Duration::from_millis(i * 10));
+        }
+        assert_eq!(counter.fps, 0);
+
+        counter.tick(start + UPDATE_INTERVAL);
+        assert_eq!(counter.fps, 60);
+        assert_eq!(counter.frames, 0);
+        assert_eq!(counter.last_update, start + UPDATE_INTERVAL);
+    }
+}
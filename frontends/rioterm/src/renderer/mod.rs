@@ -1,7 +1,12 @@
+pub mod bell;
+mod fps;
 pub mod navigation;
 mod search;
+mod sequence;
 pub mod utils;
 
+pub use search::SearchBar;
+
 use crate::ansi::CursorShape;
 use crate::context::renderable::{Cursor, RenderableContent, RenderableContentStrategy};
 use crate::context::ContextManager;
@@ -9,6 +14,8 @@ use crate::crosswords::grid::row::Row;
 use crate::crosswords::pos::{Column, Line, Pos};
 use crate::crosswords::square::{Flags, Square};
 use crate::screen::hint::HintMatches;
+use bell::{BellCommandThrottle, VisualBell};
+use fps::FpsCounter;
 use navigation::ScreenNavigation;
 use rio_backend::config::colors::{
     term::{List, TermColors},
@@ -47,7 +54,14 @@ pub struct Renderer {
         (char, rio_backend::sugarloaf::font_introspector::Attributes),
         (usize, f32),
     >,
-    active_search: Option<String>,
+    active_search: Option<SearchBar>,
+    /// Keys typed so far of a key binding sequence.
+    pending_keys: Option<String>,
+    /// Characters of the hint labels, drawn over the grid of the active context.
+    hint_labels: FxHashMap<(i32, usize), char>,
+    pub visual_bell: VisualBell,
+    pub bell_command: BellCommandThrottle,
+    fps_counter: Option<FpsCounter>,
 }
 
 impl Renderer {
@@ -96,16 +110,33 @@ impl Renderer {
             named_colors,
             dynamic_background,
             active_search: None,
+            pending_keys: None,
+            hint_labels: FxHashMap::default(),
+            visual_bell: VisualBell::new(&config.bell),
+            bell_command: BellCommandThrottle::default(),
+            fps_counter: config.developer.enable_fps_counter.then(FpsCounter::new),
             font_cache: FxHashMap::default(),
             font_context: font_context.clone(),
         }
     }
 
     #[inline]
-    pub fn set_active_search(&mut self, active_search: Option<String>) {
+    pub fn set_active_search(&mut self, active_search: Option<SearchBar>) {
         self.active_search = active_search;
     }
 
+    #[inline]
+    pub fn set_pending_keys(&mut self, pending_keys: Option<String>) {
+        self.pending_keys = pending_keys;
+    }
+
+    #[inline]
+    pub fn set_hint_labels(&mut self, labels: impl Iterator<Item = (Pos, char)>) {
+        self.hint_labels.clear();
+        self.hint_labels
+            .extend(labels.map(|(pos, c)| ((pos.row.0, pos.col.0), c)));
+    }
+
     #[inline]
     fn create_style(&mut self, square: &Square) -> (FragmentStyle, char) {
         let flags = square.flags;
@@ -244,14 +275,24 @@ impl Renderer {
                 continue;
             }
 
-            let (mut style, square_content) =
+            let (mut style, mut square_content) =
                 if has_cursor && column == cursor.state.pos.col {
                     self.create_cursor_style(square, cursor, is_active)
                 } else {
                     self.create_style(square)
                 };
 
-            if hyperlink_range.is_some()
+            let hint_label = if is_active && !self.hint_labels.is_empty() {
+                self.hint_labels.get(&(line.0, column)).copied()
+            } else {
+                None
+            };
+
+            if let Some(label) = hint_label {
+                square_content = label;
+                style.color = self.named_colors.hint_foreground;
+                style.background_color = Some(self.named_colors.hint_background);
+            } else if hyperlink_range.is_some()
                 && square.hyperlink().is_some()
                 && hyperlink_range
                     .unwrap()
@@ -672,11 +713,12 @@ impl Renderer {
             }
 
             let display_offset = renderable_content.display_offset;
-            let strategy = if is_active && hints.is_some() {
-                &RenderableContentStrategy::Full
-            } else {
-                &renderable_content.strategy
-            };
+            let strategy =
+                if is_active && (hints.is_some() || !self.hint_labels.is_empty()) {
+                    &RenderableContentStrategy::Full
+                } else {
+                    &renderable_content.strategy
+                };
 
             match strategy {
                 RenderableContentStrategy::Full => {
@@ -736,12 +778,12 @@ impl Renderer {
             &mut objects,
         );
 
-        if let Some(active_search_content) = &self.active_search {
+        if let Some(search_bar) = &self.active_search {
             search::draw_search_bar(
                 &mut objects,
                 &self.named_colors,
                 (window_size.width, window_size.height, scale_factor),
-                active_search_content,
+                search_bar,
             );
 
             self.active_search = None;
@@ -751,6 +793,30 @@ impl Renderer {
             objects.push(rte);
         }
 
+        if let Some(pending_keys) = self.pending_keys.take() {
+            sequence::draw_pending_keys(
+                &mut objects,
+                &self.named_colors,
+                (window_size.width, window_size.height, scale_factor),
+                &pending_keys,
+            );
+        }
+
+        self.visual_bell.draw(
+            &mut objects,
+            (window_size.width, window_size.height, scale_factor),
+        );
+
+        if let Some(fps_counter) = &mut self.fps_counter {
+            let sync_metrics = context_manager.current().terminal.lock().sync_metrics;
+            fps_counter.draw(
+                &mut objects,
+                &self.named_colors,
+                (window_size.width, window_size.height, scale_factor),
+                sync_metrics,
+            );
+        }
+
         sugarloaf.set_objects(objects);
     }
 }
@@ -8,6 +8,7 @@ pub struct ScreenNavigation {
     pub navigation: Navigation,
     pub objects: Vec<Object>,
     keys: String,
+    bells: Vec<usize>,
     current: usize,
     len: usize,
     width: f32,
@@ -27,6 +28,7 @@ impl ScreenNavigation {
             navigation,
             objects: Vec::with_capacity(26),
             keys: String::from(""),
+            bells: Vec::new(),
             color_automation,
             current: 0,
             len: 0,
@@ -77,6 +79,12 @@ impl ScreenNavigation {
             has_changes = true;
         }
 
+        let bells = context_manager.tabs_with_bell();
+        if bells != self.bells {
+            self.bells = bells;
+            has_changes = true;
+        }
+
         let current = context_manager.current_index();
         if current != self.current {
             self.current = current;
@@ -151,6 +159,8 @@ impl ScreenNavigation {
             if i == self.current {
                 color = colors.tabs_active_highlight;
                 size = ACTIVE_TAB_WIDTH_SIZE;
+            } else if self.bells.contains(&i) {
+                color = colors.yellow;
             }
 
             if let Some(title) = titles.get(&i) {
@@ -241,7 +251,8 @@ impl ScreenNavigation {
                 size: [250., PADDING_Y_BOTTOM_TABS],
             }));
 
-            if is_current {
+            let has_bell = !is_current && self.bells.contains(&i);
+            if is_current || has_bell {
                 // TopBar case should render on bottom
                 let position = if position_y == 0.0 {
                     PADDING_Y_BOTTOM_TABS - (PADDING_Y_BOTTOM_TABS / 10.)
@@ -249,9 +260,15 @@ impl ScreenNavigation {
                     position_y
                 };
 
+                let color = if has_bell {
+                    colors.yellow
+                } else {
+                    colors.tabs_active_highlight
+                };
+
                 self.objects.push(Object::Rect(Rect {
                     position: [initial_position_x, position],
-                    color: colors.tabs_active_highlight,
+                    color,
                     size: [250., PADDING_Y_BOTTOM_TABS / 10.],
                 }));
             }
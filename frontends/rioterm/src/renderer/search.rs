@@ -1,16 +1,71 @@
 use crate::constants::*;
 use rio_backend::config::colors::Colors;
+use rio_backend::crosswords::search::{SearchCase, SearchCount, SearchOptions};
 use rio_backend::sugarloaf::{Object, Rect, Text};
 
+/// Approximate width of a character of the search bar, relative to its size.
+const CHAR_WIDTH: f32 = 0.6;
+const STATUS_FONT_SIZE: f32 = 12.;
+
+/// Content of the search bar.
+pub struct SearchBar {
+    pub content: String,
+    pub count: Option<SearchCount>,
+    pub options: SearchOptions,
+}
+
+/// Match count and active modes, like `3 of 12 · smart case · regex`.
+fn status(count: Option<SearchCount>, options: SearchOptions) -> String {
+    let mut parts = Vec::with_capacity(4);
+
+    if let Some(count) = count {
+        let total = if count.limited {
+            format!("{}+", count.total)
+        } else {
+            count.total.to_string()
+        };
+
+        parts.push(match count.current {
+            _ if count.total == 0 => String::from("no matches"),
+            Some(current) => format!("{current} of {total}"),
+            None => format!("{total} matches"),
+        });
+    }
+
+    parts.push(String::from(match options.case {
+        SearchCase::Smart => "smart case",
+        SearchCase::Sensitive => "match case",
+        SearchCase::Insensitive => "ignore case",
+    }));
+
+    if options.whole_word {
+        parts.push(String::from("whole word"));
+    }
+
+    parts.push(String::from(if options.literal {
+        "literal"
+    } else {
+        "regex"
+    }));
+
+    parts.join(" · ")
+}
+
 #[inline]
 pub fn draw_search_bar(
     objects: &mut Vec<Object>,
     colors: &Colors,
     dimensions: (f32, f32, f32),
-    content: &String,
+    search_bar: &SearchBar,
 ) {
     let (width, height, scale) = dimensions;
     let position_y = (height / scale) - PADDING_Y_BOTTOM_TABS;
+    let dimmed_foreground = [
+        colors.foreground[0],
+        colors.foreground[1],
+        colors.foreground[2],
+        colors.foreground[3] - 0.3,
+    ];
 
     objects.push(Object::Rect(Rect {
         position: [0.0, position_y],
@@ -18,25 +73,73 @@ pub fn draw_search_bar(
         size: [width * 2., PADDING_Y_BOTTOM_TABS],
     }));
 
-    if content.is_empty() {
+    let status = status(search_bar.count, search_bar.options);
+    let status_width = status.chars().count() as f32 * STATUS_FONT_SIZE * CHAR_WIDTH;
+    objects.push(Object::Text(Text::single_line(
+        (
+            (width / scale - status_width - 8.).max(0.),
+            position_y + 11.,
+        ),
+        status,
+        STATUS_FONT_SIZE,
+        dimmed_foreground,
+    )));
+
+    if search_bar.content.is_empty() {
         objects.push(Object::Text(Text::single_line(
             (4., position_y + 10.),
             String::from("Search: type something..."),
             14.,
-            [
-                colors.foreground[0],
-                colors.foreground[1],
-                colors.foreground[2],
-                colors.foreground[3] - 0.3,
-            ],
+            dimmed_foreground,
         )));
         return;
     }
 
     objects.push(Object::Text(Text::single_line(
         (4., position_y + 10.),
-        format!("Search: {}", content),
+        format!("Search: {}", search_bar.content),
         14.,
         colors.foreground,
     )));
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_status() {
+        let options = SearchOptions::default();
+        assert_eq!(status(None, options), "smart case · regex");
+
+        let count = SearchCount {
+            total: 12,
+            current: Some(3),
+            limited: false,
+        };
+        let options = SearchOptions {
+            case: SearchCase::Sensitive,
+            whole_word: true,
+            literal: true,
+        };
+        assert_eq!(
+            status(Some(count), options),
+            "3 of 12 · match case · whole word · literal"
+        );
+
+        let count = SearchCount {
+            total: 1000,
+            current: None,
+            limited: true,
+        };
+        assert_eq!(
+            status(Some(count), SearchOptions::default()),
+            "1000+ matches · smart case · regex"
+        );
+
+        assert_eq!(
+            status(Some(SearchCount::default()), SearchOptions::default()),
+            "no matches · smart case · regex"
+        );
+    }
+}
@@ -0,0 +1,38 @@
+use rio_backend::config::colors::Colors;
+use rio_backend::sugarloaf::{Object, Rect, Text};
+
+/// Approximate width of a character of the indicator, relative to its size.
+const CHAR_WIDTH: f32 = 0.6;
+const FONT_SIZE: f32 = 14.;
+const HEIGHT: f32 = 24.;
+const MARGIN: f32 = 8.;
+const PADDING_X: f32 = 6.;
+
+/// Indicator of the keys typed so far of a key binding sequence, like
+/// `control+a …`, drawn at the bottom right of the window.
+#[inline]
+pub fn draw_pending_keys(
+    objects: &mut Vec<Object>,
+    colors: &Colors,
+    dimensions: (f32, f32, f32),
+    keys: &str,
+) {
+    let (width, height, scale) = dimensions;
+    let content = format!("{keys} …");
+    let content_width = content.chars().count() as f32 * FONT_SIZE * CHAR_WIDTH;
+    let position_x = (width / scale - content_width - PADDING_X * 2. - MARGIN).max(0.);
+    let position_y = (height / scale - HEIGHT - MARGIN).max(0.);
+
+    objects.push(Object::Rect(Rect {
+        position: [position_x, position_y],
+        color: colors.bar,
+        size: [content_width + PADDING_X * 2., HEIGHT],
+    }));
+
+    objects.push(Object::Text(Text::single_line(
+        (position_x + PADDING_X, position_y + 4.),
+        content,
+        FONT_SIZE,
+        colors.foreground,
+    )));
+}
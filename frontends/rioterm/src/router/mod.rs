@@ -3,13 +3,17 @@ mod window;
 use crate::event::EventProxy;
 use crate::router::window::{configure_window, create_window_builder};
 use crate::scheduler::{Scheduler, TimerId, Topic};
+use crate::screen::search_history::SearchHistory;
 use crate::screen::{Screen, ScreenWindowProperties};
+use crate::session::WindowSession;
 use assistant::Assistant;
 use raw_window_handle::{HasDisplayHandle, HasWindowHandle};
 use rio_backend::clipboard::Clipboard;
+use rio_backend::config::window::WindowMode;
 use rio_backend::config::Config as RioConfig;
 use rio_backend::error::{RioError, RioErrorLevel, RioErrorType};
 use rio_backend::event::{EventPayload, RioEvent, RioEventType};
+use rio_window::event::ElementState;
 use rio_window::event_loop::ActiveEventLoop;
 use rio_window::keyboard::{Key, NamedKey};
 #[cfg(not(any(target_os = "macos", windows)))]
@@ -17,6 +21,7 @@ use rio_window::platform::startup_notify::{
     self, EventLoopExtStartupNotify, WindowAttributesExtStartupNotify,
 };
 use rio_window::window::{Window, WindowId};
+use routes::palette::CommandPalette;
 use routes::{assistant, RoutePath};
 use rustc_hash::FxHashMap;
 use std::cell::RefCell;
@@ -32,6 +37,7 @@ const RIO_TITLE: &str = "▲";
 
 pub struct Route<'a> {
     pub assistant: assistant::Assistant,
+    pub palette: CommandPalette,
     pub path: RoutePath,
     pub window: RouteWindow<'a>,
 }
@@ -46,6 +52,7 @@ impl Route<'_> {
     ) -> Route {
         Route {
             assistant,
+            palette: CommandPalette::new(),
             path,
             window,
         }
@@ -123,15 +130,60 @@ impl Route<'_> {
 
     #[inline]
     pub fn quit(&mut self) {
+        #[cfg(unix)]
+        crate::ipc::remove_socket();
         std::process::exit(0);
     }
 
+    pub fn open_command_palette(&mut self) {
+        if self.path != RoutePath::Terminal {
+            return;
+        }
+
+        let entries = self.window.screen.command_palette_entries();
+        self.palette.open(entries);
+        self.path = RoutePath::CommandPalette;
+    }
+
+    fn process_command_palette_key(&mut self, key_event: &rio_window::event::KeyEvent) {
+        match &key_event.logical_key {
+            Key::Named(NamedKey::Escape) => {
+                self.palette.close();
+                self.path = RoutePath::Terminal;
+            }
+            Key::Named(NamedKey::Enter) => {
+                self.path = RoutePath::Terminal;
+                if let Some(item) = self.palette.confirm() {
+                    self.window.screen.run_palette_item(item);
+                }
+            }
+            Key::Named(NamedKey::ArrowDown) => self.palette.select_next(),
+            Key::Named(NamedKey::ArrowUp) => self.palette.select_prev(),
+            Key::Named(NamedKey::Backspace) => self.palette.pop(),
+            _ => {
+                if let Some(text) = &key_event.text {
+                    self.palette.push_str(text);
+                }
+            }
+        }
+
+        self.request_redraw();
+    }
+
     #[inline]
     pub fn has_key_wait(&mut self, key_event: &rio_window::event::KeyEvent) -> bool {
         if self.path == RoutePath::Terminal {
             return false;
         }
 
+        if self.path == RoutePath::CommandPalette {
+            if key_event.state == ElementState::Pressed {
+                self.process_command_palette_key(key_event);
+            }
+
+            return true;
+        }
+
         let is_enter = key_event.logical_key == Key::Named(NamedKey::Enter);
         if self.path == RoutePath::Assistant && is_enter {
             if self.assistant.is_warning() {
@@ -168,12 +220,14 @@ pub struct Router<'a> {
     pub font_library: Box<rio_backend::sugarloaf::font::FontLibrary>,
     pub config_route: Option<WindowId>,
     pub clipboard: Rc<RefCell<Clipboard>>,
+    pub search_history: Rc<RefCell<SearchHistory>>,
 }
 
 impl Router<'_> {
     pub fn new<'b>(
         fonts: rio_backend::sugarloaf::font::SugarloafFonts,
         clipboard: Clipboard,
+        search_history: SearchHistory,
     ) -> Router<'b> {
         let (font_library, fonts_not_found) =
             rio_backend::sugarloaf::font::FontLibrary::new(fonts);
@@ -188,6 +242,7 @@ impl Router<'_> {
         }
 
         let clipboard = Rc::new(RefCell::new(clipboard));
+        let search_history = Rc::new(RefCell::new(search_history));
 
         Router {
             routes: FxHashMap::default(),
@@ -195,6 +250,7 @@ impl Router<'_> {
             config_route: None,
             font_library: Box::new(font_library),
             clipboard,
+            search_history,
         }
     }
 
@@ -264,7 +320,9 @@ impl Router<'_> {
             "Rio Settings",
             None,
             None,
+            None,
             self.clipboard.clone(),
+            self.search_history.clone(),
         );
         let id = window.winit_window.id();
         let route = Route::new(Assistant::new(), RoutePath::Terminal, window);
@@ -309,7 +367,7 @@ impl Router<'_> {
         event_proxy: EventProxy,
         config: &'a rio_backend::config::Config,
         open_url: Option<String>,
-    ) {
+    ) -> WindowId {
         let tab_id = if config.navigation.is_native() {
             Some(self.routes.len().to_string())
         } else {
@@ -324,7 +382,9 @@ impl Router<'_> {
             RIO_TITLE,
             tab_id.as_deref(),
             open_url,
+            None,
             self.clipboard.clone(),
+            self.search_history.clone(),
         );
         let id = window.winit_window.id();
 
@@ -332,6 +392,7 @@ impl Router<'_> {
             window,
             path: RoutePath::Terminal,
             assistant: Assistant::new(),
+            palette: CommandPalette::new(),
         };
 
         if let Some(err) = &self.propagated_report {
@@ -340,6 +401,55 @@ impl Router<'_> {
         }
 
         self.routes.insert(id, route);
+        id
+    }
+
+    /// Create a window with the size, position, tabs and splits of a window
+    /// of a saved session.
+    pub fn restore_window<'a>(
+        &'a mut self,
+        event_loop: &'a ActiveEventLoop,
+        event_proxy: EventProxy,
+        config: &'a rio_backend::config::Config,
+        session: &WindowSession,
+    ) {
+        let mut config = config.clone();
+        config.window.width = session.width;
+        config.window.height = session.height;
+        if session.maximized {
+            config.window.mode = WindowMode::Maximized;
+        } else if config.window.mode == WindowMode::Maximized {
+            config.window.mode = WindowMode::Windowed;
+        }
+
+        let window = RouteWindow::from_target(
+            event_loop,
+            event_proxy,
+            &config,
+            &self.font_library,
+            RIO_TITLE,
+            None,
+            None,
+            Some(session),
+            self.clipboard.clone(),
+            self.search_history.clone(),
+        );
+        if let (Some(x), Some(y), false) = (session.x, session.y, session.maximized) {
+            window
+                .winit_window
+                .set_outer_position(rio_window::dpi::PhysicalPosition::new(x, y));
+        }
+
+        let id = window.winit_window.id();
+        self.routes.insert(
+            id,
+            Route {
+                window,
+                path: RoutePath::Terminal,
+                assistant: Assistant::new(),
+                palette: CommandPalette::new(),
+            },
+        );
     }
 
     #[cfg(target_os = "macos")]
@@ -360,7 +470,9 @@ impl Router<'_> {
             RIO_TITLE,
             tab_id,
             open_url,
+            None,
             self.clipboard.clone(),
+            self.search_history.clone(),
         );
         self.routes.insert(
             window.winit_window.id(),
@@ -368,6 +480,7 @@ impl Router<'_> {
                 window,
                 path: RoutePath::Terminal,
                 assistant: Assistant::new(),
+                palette: CommandPalette::new(),
             },
         );
     }
@@ -434,7 +547,9 @@ impl<'a> RouteWindow<'a> {
         window_name: &str,
         tab_id: Option<&str>,
         open_url: Option<String>,
+        session: Option<&WindowSession>,
         clipboard: Rc<RefCell<Clipboard>>,
+        search_history: Rc<RefCell<SearchHistory>>,
     ) -> RouteWindow<'a> {
         #[allow(unused_mut)]
         let mut window_builder = create_window_builder(window_name, config, tab_id);
@@ -465,7 +580,9 @@ impl<'a> RouteWindow<'a> {
             event_proxy,
             font_library,
             open_url,
+            session,
             clipboard,
+            search_history,
         )
         .expect("Screen not created");
 
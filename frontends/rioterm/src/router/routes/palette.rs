@@ -0,0 +1,348 @@
+use crate::bindings::Action;
+use crate::context::grid::ContextDimension;
+use rio_backend::sugarloaf::{Object, Rect, Sugarloaf, Text};
+
+/// Entries listed at once, the list scrolls to keep the selection visible.
+const VISIBLE_ENTRIES: usize = 14;
+const ENTRY_HEIGHT: f32 = 24.;
+/// Approximate width of a character, relative to its size.
+const CHAR_WIDTH: f32 = 0.6;
+
+/// What choosing an entry of the command palette does.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PaletteItem {
+    Action(Action),
+    Tab(usize),
+    Split { tab: usize, split: usize },
+}
+
+pub struct PaletteEntry {
+    pub title: String,
+    /// Key binding or state shown next to the title.
+    pub hint: String,
+    pub item: PaletteItem,
+}
+
+/// Fuzzy-filterable list of actions, tabs and splits.
+#[derive(Default)]
+pub struct CommandPalette {
+    entries: Vec<PaletteEntry>,
+    query: String,
+    /// Indexes of the entries matching the query, best match first.
+    matches: Vec<usize>,
+    selected: usize,
+}
+
+impl CommandPalette {
+    pub fn new() -> CommandPalette {
+        CommandPalette::default()
+    }
+
+    pub fn open(&mut self, entries: Vec<PaletteEntry>) {
+        self.entries = entries;
+        self.query.clear();
+        self.filter();
+    }
+
+    #[inline]
+    pub fn close(&mut self) {
+        self.entries.clear();
+        self.matches.clear();
+        self.query.clear();
+    }
+
+    #[inline]
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub fn push_str(&mut self, text: &str) {
+        self.query.extend(text.chars().filter(|c| !c.is_control()));
+        self.filter();
+    }
+
+    pub fn pop(&mut self) {
+        if self.query.pop().is_some() {
+            self.filter();
+        }
+    }
+
+    pub fn select_next(&mut self) {
+        if !self.matches.is_empty() {
+            self.selected = (self.selected + 1) % self.matches.len();
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        if !self.matches.is_empty() {
+            self.selected = self
+                .selected
+                .checked_sub(1)
+                .unwrap_or(self.matches.len() - 1);
+        }
+    }
+
+    /// Item of the selected entry, closing the palette.
+    pub fn confirm(&mut self) -> Option<PaletteItem> {
+        let item = self
+            .matches
+            .get(self.selected)
+            .map(|index| self.entries[*index].item.clone());
+        self.close();
+        item
+    }
+
+    /// Matching entries around the selection, with whether they are selected.
+    pub fn visible_entries(&self) -> impl Iterator<Item = (bool, &PaletteEntry)> {
+        let start = (self.selected + 1).saturating_sub(VISIBLE_ENTRIES);
+        self.matches
+            .iter()
+            .enumerate()
+            .skip(start)
+            .take(VISIBLE_ENTRIES)
+            .map(|(position, index)| (position == self.selected, &self.entries[*index]))
+    }
+
+    fn filter(&mut self) {
+        let mut scores: Vec<(usize, i32)> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(index, entry)| {
+                fuzzy_score(&self.query, &entry.title).map(|score| (index, score))
+            })
+            .collect();
+        // The sort is stable, entries matching equally keep their order.
+        scores.sort_by_key(|(_, score)| std::cmp::Reverse(*score));
+
+        self.matches = scores.into_iter().map(|(index, _)| index).collect();
+        self.selected = 0;
+    }
+}
+
+/// Score of a text containing the characters of the query in order, ignoring
+/// case and whitespace. Consecutive characters and word starts score higher.
+fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.chars().collect();
+    let query: Vec<char> = query.chars().filter(|c| !c.is_whitespace()).collect();
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    // Best score of the query so far when its last character matches the
+    // text at each index.
+    let mut scores: Vec<Option<i32>> = vec![None; text.len()];
+    for (query_index, query_char) in query.iter().enumerate() {
+        let mut next = vec![None; text.len()];
+        // Best score of the matches ending before the previous index.
+        let mut best = None;
+
+        for index in 0..text.len() {
+            let previous = if query_index == 0 {
+                Some(0)
+            } else if index == 0 {
+                None
+            } else {
+                let consecutive = scores[index - 1].map(|score| score + 4);
+                let previous = best.max(consecutive);
+                best = best.max(scores[index - 1]);
+                previous
+            };
+
+            if let Some(previous) = previous {
+                if text[index].to_lowercase().eq(query_char.to_lowercase()) {
+                    let is_word_start = index == 0 || !text[index - 1].is_alphanumeric();
+                    next[index] = Some(previous + if is_word_start { 4 } else { 1 });
+                }
+            }
+        }
+
+        scores = next;
+    }
+
+    scores.into_iter().max().flatten()
+}
+
+/// Title of a configuration action name, like `Create Tab` for `CreateTab`.
+pub fn action_title(name: &str) -> String {
+    let mut title = String::with_capacity(name.len() + 4);
+    for (index, c) in name.chars().enumerate() {
+        if index > 0 && c.is_uppercase() {
+            title.push(' ');
+        }
+        title.push(c);
+    }
+    title
+}
+
+#[inline]
+pub fn screen(
+    sugarloaf: &mut Sugarloaf,
+    context_dimension: &ContextDimension,
+    palette: &CommandPalette,
+) {
+    let blue = [0.1764706, 0.6039216, 1.0, 1.0];
+    let dimmed = [1., 1., 1., 0.5];
+    let white = [1., 1., 1., 1.];
+    let black = [0.0, 0.0, 0.0, 1.0];
+
+    let layout = sugarloaf.window_size();
+    let width = layout.width / sugarloaf.style().scale_factor;
+    let top_y = context_dimension.margin.top_y + 40.;
+
+    let mut objects = Vec::with_capacity(VISIBLE_ENTRIES * 3 + 4);
+
+    objects.push(Object::Rect(Rect {
+        position: [0., 0.0],
+        color: black,
+        size: [layout.width, layout.height],
+    }));
+    objects.push(Object::Rect(Rect {
+        position: [0., top_y],
+        color: blue,
+        size: [30., ENTRY_HEIGHT + 8.],
+    }));
+
+    let (query, query_color) = if palette.query().is_empty() {
+        (
+            String::from("Type to filter actions, tabs and splits"),
+            dimmed,
+        )
+    } else {
+        (palette.query().to_string(), white)
+    };
+    objects.push(Object::Text(Text::single_line(
+        (50., top_y + 6.),
+        query,
+        22.,
+        query_color,
+    )));
+
+    let mut position_y = top_y + 60.;
+    let mut has_entries = false;
+    for (is_selected, entry) in palette.visible_entries() {
+        has_entries = true;
+
+        if is_selected {
+            objects.push(Object::Rect(Rect {
+                position: [40., position_y - 4.],
+                color: blue,
+                size: [layout.width, ENTRY_HEIGHT],
+            }));
+        }
+
+        objects.push(Object::Text(Text::single_line(
+            (50., position_y),
+            entry.title.to_owned(),
+            16.,
+            white,
+        )));
+
+        if !entry.hint.is_empty() {
+            let hint_width = entry.hint.chars().count() as f32 * 14. * CHAR_WIDTH;
+            objects.push(Object::Text(Text::single_line(
+                ((width - hint_width - 20.).max(50.), position_y + 1.),
+                entry.hint.to_owned(),
+                14.,
+                if is_selected { white } else { dimmed },
+            )));
+        }
+
+        position_y += ENTRY_HEIGHT;
+    }
+
+    if !has_entries {
+        objects.push(Object::Text(Text::single_line(
+            (50., position_y),
+            String::from("No matches"),
+            16.,
+            dimmed,
+        )));
+    }
+
+    sugarloaf.set_objects(objects);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(title: &str) -> PaletteEntry {
+        PaletteEntry {
+            title: String::from(title),
+            hint: String::new(),
+            item: PaletteItem::Action(Action::None),
+        }
+    }
+
+    fn titles(palette: &CommandPalette) -> Vec<&str> {
+        palette
+            .matches
+            .iter()
+            .map(|index| palette.entries[*index].title.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert!(fuzzy_score("", "Create Tab").is_some());
+        assert!(fuzzy_score("ct", "Create Tab").is_some());
+        assert!(fuzzy_score("CREATE", "Create Tab").is_some());
+        assert_eq!(fuzzy_score("tb", "Create Tab"), Some(5));
+        assert_eq!(fuzzy_score("tc", "Create Tab"), None);
+        assert_eq!(fuzzy_score("x", "Create Tab"), None);
+
+        // Word starts and consecutive characters win.
+        assert!(fuzzy_score("ct", "Close Tab") > fuzzy_score("ct", "Select Next"));
+        assert!(fuzzy_score("tab", "Create Tab") > fuzzy_score("tab", "Toggle Alt Bar"));
+    }
+
+    #[test]
+    fn test_command_palette() {
+        let mut palette = CommandPalette::new();
+        palette.open(vec![
+            entry("Select Next Tab"),
+            entry("Create Tab"),
+            entry("Split Right"),
+            entry("Close Tab"),
+        ]);
+        assert_eq!(titles(&palette).len(), 4);
+
+        palette.push_str("tab");
+        assert_eq!(
+            titles(&palette),
+            ["Select Next Tab", "Create Tab", "Close Tab"]
+        );
+
+        palette.push_str("c");
+        assert!(titles(&palette).is_empty());
+        assert_eq!(palette.confirm(), None);
+
+        palette.open(vec![entry("Create Tab"), entry("Close Tab")]);
+        palette.push_str("ct");
+        palette.select_prev();
+        assert_eq!(
+            palette
+                .visible_entries()
+                .find(|(selected, _)| *selected)
+                .unwrap()
+                .1
+                .title,
+            "Close Tab"
+        );
+        palette.select_next();
+        palette.pop();
+        palette.pop();
+        palette.pop();
+        assert_eq!(palette.query(), "");
+        assert_eq!(palette.confirm(), Some(PaletteItem::Action(Action::None)));
+        assert!(palette.entries.is_empty());
+    }
+
+    #[test]
+    fn test_action_title() {
+        assert_eq!(action_title("CreateTab"), "Create Tab");
+        assert_eq!(action_title("ToggleViMode"), "Toggle Vi Mode");
+        assert_eq!(action_title("Quit"), "Quit");
+    }
+}
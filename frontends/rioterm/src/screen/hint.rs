@@ -1,6 +1,8 @@
+use rio_backend::config::hints::{Hint, Hints};
 use rio_backend::crosswords::pos::{Column, Direction, Line, Pos};
 use rio_backend::crosswords::search::Match;
 use rio_backend::crosswords::search::{RegexIter, RegexSearch};
+use rio_backend::crosswords::square::{Flags, Hyperlink};
 use rio_backend::crosswords::Crosswords;
 use std::borrow::Cow;
 use std::ops::Deref;
@@ -78,3 +80,439 @@ impl Deref for HintMatches<'_> {
         self.matches.deref()
     }
 }
+
+/// Match of a hint, either a regex match or a hyperlink.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HintMatch {
+    pub bounds: Match,
+    pub hyperlink: Option<Hyperlink>,
+}
+
+impl HintMatch {
+    /// Text the hint action is applied to.
+    pub fn text<T: rio_backend::event::EventListener>(
+        &self,
+        term: &Crosswords<T>,
+    ) -> String {
+        match &self.hyperlink {
+            Some(hyperlink) => hyperlink.uri().to_owned(),
+            None => term.bounds_to_string(*self.bounds.start(), *self.bounds.end()),
+        }
+    }
+}
+
+/// State of the keyboard hint mode.
+pub struct HintState {
+    rules: Vec<Hint>,
+    /// Regexes of the rules, compiled once the rule is first used.
+    regexes: Vec<Option<RegexSearch>>,
+    alphabet: Vec<char>,
+    /// Index of the rule being selected.
+    active: Option<usize>,
+    matches: Vec<HintMatch>,
+    labels: Vec<Vec<char>>,
+    /// Label characters typed so far.
+    keys: Vec<char>,
+}
+
+impl HintState {
+    pub fn new(config: &Hints) -> HintState {
+        let mut alphabet: Vec<char> = Vec::with_capacity(config.alphabet.len());
+        for c in config.alphabet.chars() {
+            if !alphabet.contains(&c) {
+                alphabet.push(c);
+            }
+        }
+
+        if alphabet.len() < 2 {
+            tracing::warn!("hints alphabet needs at least two characters, using default");
+            alphabet = Hints::default().alphabet.chars().collect();
+        }
+
+        HintState {
+            rules: config.rules.clone(),
+            regexes: config.rules.iter().map(|_| None).collect(),
+            alphabet,
+            active: None,
+            matches: Vec::new(),
+            labels: Vec::new(),
+            keys: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub fn active(&self) -> bool {
+        self.active.is_some()
+    }
+
+    /// Rule of the active hint mode.
+    #[inline]
+    pub fn rule(&self) -> Option<&Hint> {
+        self.rules.get(self.active?)
+    }
+
+    /// Start selecting a match of the rule, does nothing if there is no match.
+    pub fn start<T: rio_backend::event::EventListener>(
+        &mut self,
+        term: &Crosswords<T>,
+        index: usize,
+    ) -> bool {
+        let Some(rule) = self.rules.get(index) else {
+            return false;
+        };
+
+        if let (Some(regex), None) = (&rule.regex, &self.regexes[index]) {
+            match RegexSearch::new(regex) {
+                Ok(regex) => self.regexes[index] = Some(regex),
+                Err(err) => tracing::warn!("invalid hint regex {regex:?}: {err}"),
+            }
+        }
+
+        self.active = Some(index);
+        self.keys.clear();
+        self.update_matches(term);
+        self.active()
+    }
+
+    pub fn stop(&mut self) {
+        self.active = None;
+        self.matches.clear();
+        self.labels.clear();
+        self.keys.clear();
+    }
+
+    /// Find the visible matches of the active rule and label them.
+    pub fn update_matches<T: rio_backend::event::EventListener>(
+        &mut self,
+        term: &Crosswords<T>,
+    ) {
+        let Some(index) = self.active else {
+            return;
+        };
+
+        self.matches.clear();
+
+        let rule = &self.rules[index];
+        if let Some(regex) = self.regexes[index].as_mut() {
+            for bounds in visible_regex_match_iter(term, regex) {
+                let bounds = if rule.post_processing {
+                    match post_process(term, bounds) {
+                        Some(bounds) => bounds,
+                        None => continue,
+                    }
+                } else {
+                    bounds
+                };
+
+                self.matches.push(HintMatch {
+                    bounds,
+                    hyperlink: None,
+                });
+            }
+        }
+
+        if rule.hyperlinks {
+            self.matches.extend(visible_unique_hyperlinks(term));
+        }
+
+        // Overlapping matches keep the longest one.
+        self.matches.sort_by(|a, b| {
+            a.bounds
+                .start()
+                .partial_cmp(b.bounds.start())
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| {
+                    b.bounds
+                        .end()
+                        .partial_cmp(a.bounds.end())
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                })
+        });
+        let mut last_end: Option<Pos> = None;
+        self.matches.retain(|hint| {
+            if last_end.is_some_and(|end| *hint.bounds.start() <= end) {
+                return false;
+            }
+            last_end = Some(*hint.bounds.end());
+            true
+        });
+
+        if self.matches.is_empty() {
+            self.stop();
+            return;
+        }
+
+        self.labels = labels(&self.alphabet, self.matches.len());
+        if !self
+            .labels
+            .iter()
+            .any(|label| label.starts_with(&self.keys))
+        {
+            self.keys.clear();
+        }
+    }
+
+    /// Handle a typed character, returns the match once its label is complete.
+    pub fn keyboard_input(&mut self, c: char) -> Option<HintMatch> {
+        self.active?;
+
+        self.keys.push(c);
+        let Some(index) = self
+            .labels
+            .iter()
+            .position(|label| label.starts_with(&self.keys))
+        else {
+            self.keys.pop();
+            return None;
+        };
+
+        if self.labels[index].len() > self.keys.len() {
+            return None;
+        }
+
+        let hint = self.matches.get(index).cloned();
+        self.stop();
+        hint
+    }
+
+    /// Remove the last typed character of the label.
+    #[inline]
+    pub fn pop_key(&mut self) {
+        self.keys.pop();
+    }
+
+    /// Characters of the labels left to type with their position in the grid.
+    pub fn visible_labels(&self) -> impl Iterator<Item = (Pos, char)> + '_ {
+        self.matches
+            .iter()
+            .zip(&self.labels)
+            .filter(|(_, label)| label.starts_with(&self.keys))
+            .flat_map(|(hint, label)| {
+                let start = *hint.bounds.start();
+                label[self.keys.len()..]
+                    .iter()
+                    .enumerate()
+                    .map(move |(i, c)| (Pos::new(start.row, start.col + i), *c))
+            })
+    }
+}
+
+/// Labels of the same length for `count` matches, so no label is the prefix
+/// of another one.
+fn labels(alphabet: &[char], count: usize) -> Vec<Vec<char>> {
+    let mut length = 1;
+    let mut capacity = alphabet.len();
+    while capacity < count {
+        length += 1;
+        capacity = capacity.saturating_mul(alphabet.len());
+    }
+
+    (0..count)
+        .map(|mut index| {
+            let mut label = vec![alphabet[0]; length];
+            for c in label.iter_mut().rev() {
+                *c = alphabet[index % alphabet.len()];
+                index /= alphabet.len();
+            }
+            label
+        })
+        .collect()
+}
+
+/// Iterate over the hyperlinks of the viewport, each one is only returned
+/// for its first visible occurrence.
+fn visible_unique_hyperlinks<T: rio_backend::event::EventListener>(
+    term: &Crosswords<T>,
+) -> Vec<HintMatch> {
+    let mut hints: Vec<HintMatch> = Vec::new();
+    let mut current: Option<HintMatch> = None;
+
+    for indexed in term.grid.display_iter() {
+        let hyperlink = indexed.square.hyperlink();
+        if let Some(hint) = current.as_mut() {
+            if hyperlink.is_some() && hint.hyperlink == hyperlink {
+                hint.bounds = *hint.bounds.start()..=indexed.pos;
+                continue;
+            }
+
+            hints.extend(current.take());
+        }
+
+        if let Some(hyperlink) = hyperlink {
+            if hints
+                .iter()
+                .all(|hint| hint.hyperlink.as_ref() != Some(&hyperlink))
+            {
+                current = Some(HintMatch {
+                    bounds: indexed.pos..=indexed.pos,
+                    hyperlink: Some(hyperlink),
+                });
+            }
+        }
+    }
+
+    hints.extend(current);
+    hints
+}
+
+/// Remove trailing punctuation and unbalanced closing brackets from a match,
+/// like the `.` ending a sentence or the `)` around a URL.
+fn post_process<T: rio_backend::event::EventListener>(
+    term: &Crosswords<T>,
+    bounds: Match,
+) -> Option<Match> {
+    let start = *bounds.start();
+    let end = *bounds.end();
+    let mut squares = vec![(start, term.grid[start].c)];
+    squares.extend(
+        term.grid
+            .iter_from(start)
+            .take_while(|indexed| indexed.pos <= end)
+            .filter(|indexed| !indexed.square.flags.contains(Flags::WIDE_CHAR_SPACER))
+            .map(|indexed| (indexed.pos, indexed.square.c)),
+    );
+
+    let mut open_brackets: Vec<char> = Vec::new();
+    let mut len = squares.len();
+    for (i, (_, c)) in squares.iter().enumerate() {
+        match c {
+            '(' | '[' | '{' | '<' => open_brackets.push(*c),
+            ')' | ']' | '}' | '>' => {
+                let open = match c {
+                    ')' => '(',
+                    ']' => '[',
+                    '}' => '{',
+                    _ => '<',
+                };
+                if open_brackets.pop() != Some(open) {
+                    len = i;
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    squares.truncate(len);
+    while let Some((_, c)) = squares.last() {
+        if matches!(
+            c,
+            '.' | ',' | ':' | ';' | '?' | '!' | '\'' | '"' | '(' | '[' | '{' | '<'
+        ) {
+            squares.pop();
+        } else {
+            break;
+        }
+    }
+
+    let (new_end, _) = squares.last()?;
+    Some(start..=*new_end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rio_backend::ansi::CursorShape;
+    use rio_backend::config::hints::{HintAction, URL_REGEX};
+    use rio_backend::crosswords::CrosswordsSize;
+    use rio_backend::event::{VoidListener, WindowId};
+    use rio_backend::performer::handler::ParserProcessor;
+
+    fn term(input: &[u8]) -> Crosswords<VoidListener> {
+        let size = CrosswordsSize::new(40, 4);
+        let mut term = Crosswords::new(
+            size,
+            CursorShape::Block,
+            VoidListener {},
+            WindowId::from(0),
+            0,
+        );
+
+        let mut parser = ParserProcessor::new();
+        for byte in input {
+            parser.advance(&mut term, *byte);
+        }
+
+        term
+    }
+
+    fn hints(regex: Option<&str>, hyperlinks: bool) -> Hints {
+        Hints {
+            alphabet: String::from("ab"),
+            rules: vec![Hint {
+                regex: regex.map(String::from),
+                hyperlinks,
+                post_processing: true,
+                action: HintAction::Copy,
+                command: None,
+                binding: None,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_hint_labels() {
+        assert_eq!(labels(&['a', 'b', 'c'], 2), vec![vec!['a'], vec!['b']]);
+        assert_eq!(
+            labels(&['a', 'b'], 3),
+            vec![vec!['a', 'a'], vec!['a', 'b'], vec!['b', 'a']]
+        );
+        assert_eq!(labels(&['a', 'b'], 5)[4], vec!['b', 'a', 'a']);
+    }
+
+    #[test]
+    fn test_hint_selection() {
+        let term = term(b"abc1234 def5678 9012345");
+        let mut state = HintState::new(&hints(Some("[0-9]{4,}"), false));
+        assert!(state.start(&term, 0));
+
+        let labels: Vec<(Pos, char)> = state.visible_labels().collect();
+        assert_eq!(labels.len(), 6);
+        assert_eq!(labels[0], (Pos::new(Line(0), Column(3)), 'a'));
+        assert_eq!(labels[1], (Pos::new(Line(0), Column(4)), 'a'));
+
+        // Keys outside of the labels are ignored.
+        assert_eq!(state.keyboard_input('z'), None);
+        assert_eq!(state.keyboard_input('b'), None);
+        assert_eq!(state.visible_labels().count(), 1);
+
+        let hint = state.keyboard_input('a').unwrap();
+        assert_eq!(hint.text(&term), "9012345");
+        assert!(!state.active());
+    }
+
+    #[test]
+    fn test_hint_without_matches() {
+        let term = term(b"no numbers here");
+        let mut state = HintState::new(&hints(Some("[0-9]+"), false));
+        assert!(!state.start(&term, 0));
+        assert!(!state.start(&term, 1));
+    }
+
+    #[test]
+    fn test_hint_post_processing() {
+        let term = term(b"(see https://rio.dev/docs). or <https://a.b/c_(d)>");
+        let mut state = HintState::new(&hints(Some(URL_REGEX), false));
+        assert!(state.start(&term, 0));
+
+        let urls: Vec<String> = state.matches.iter().map(|m| m.text(&term)).collect();
+        assert_eq!(urls, vec!["https://rio.dev/docs", "https://a.b/c_(d)"]);
+    }
+
+    #[test]
+    fn test_hint_hyperlinks() {
+        let term = term(
+            b"\x1b]8;id=a;https://a.dev\x1b\\link\x1b]8;;\x1b\\ and \x1b]8;id=a;https://a.dev\x1b\\again\x1b]8;;\x1b\\",
+        );
+        let mut state = HintState::new(&hints(None, true));
+        assert!(state.start(&term, 0));
+
+        // Both parts share the same id, so only the first one is labeled.
+        assert_eq!(state.matches.len(), 1);
+        assert_eq!(
+            state.matches[0].bounds,
+            Pos::new(Line(0), Column(0))..=Pos::new(Line(0), Column(3))
+        );
+        assert_eq!(state.matches[0].text(&term), "https://a.dev");
+    }
+}
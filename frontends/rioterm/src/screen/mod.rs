@@ -7,42 +7,54 @@
 // which is licensed under Apache 2.0 license.
 
 pub mod hint;
+pub mod search_history;
 pub mod touch;
 
 use crate::bindings::{
-    Action as Act, BindingKey, BindingMode, FontSizeAction, MouseBinding, SearchAction,
-    ViAction,
+    sequence_name, Action as Act, BindingKey, BindingMode, FontSizeAction, MouseAction,
+    MouseBinding, PendingKeys, Program, SearchAction, SequenceMatch, ViAction,
+    NAMED_ACTIONS,
 };
 #[cfg(target_os = "macos")]
 use crate::constants::{DEADZONE_END_Y, DEADZONE_START_Y};
-use crate::context::grid::{ContextDimension, Delta};
+use crate::context::grid::{ContextDimension, Delta, SplitBorder, SplitDirection};
 use crate::context::renderable::{Cursor, RenderableContent};
-use crate::context::{self, process_open_url, ContextManager};
+use crate::context::{self, process_open_url, ContextManager, PaneConfig};
 use crate::crosswords::{
     grid::{Dimensions, Scroll},
     pos::{Column, Pos, Side},
     square::Hyperlink,
-    vi_mode::ViMotion,
+    vi_mode::{ViInput, ViMotion},
     Mode,
 };
 use crate::mouse::{calculate_mouse_position, Mouse};
 use crate::renderer::{
     utils::{padding_bottom_from_config, padding_top_from_config},
-    Renderer,
+    Renderer, SearchBar,
 };
-use crate::screen::hint::HintMatches;
-use crate::selection::{Selection, SelectionType};
+use crate::router::routes::palette::{
+    action_title, CommandPalette, PaletteEntry, PaletteItem,
+};
+use crate::screen::hint::{HintMatch, HintMatches, HintState};
+use crate::screen::search_history::SearchHistory;
+use crate::selection::{Selection, SelectionConfig, SelectionType};
+use crate::session::{TabSession, WindowSession};
 use core::fmt::Debug;
 use raw_window_handle::{RawDisplayHandle, RawWindowHandle};
 use rio_backend::clipboard::Clipboard;
 use rio_backend::clipboard::ClipboardType;
 use rio_backend::config::{
     colors::term::List,
+    hints::HintAction,
+    layouts::{Layout, LayoutPane, LayoutSplit},
     renderer::{Backend as RendererBackend, Performance as RendererPerformance},
+    Shell,
 };
 use rio_backend::crosswords::pos::{Boundary, CursorState, Direction, Line};
 use rio_backend::crosswords::search::RegexSearch;
+use rio_backend::crosswords::styled_text::StyledText;
 use rio_backend::event::{ClickState, EventProxy, SearchState};
+use rio_backend::performer::handler::SyncLimits;
 use rio_backend::sugarloaf::{
     layout::RootStyle, Sugarloaf, SugarloafErrors, SugarloafRenderer, SugarloafWindow,
     SugarloafWindowSize,
@@ -60,6 +72,7 @@ use std::cmp::{max, min};
 use std::error::Error;
 use std::ffi::OsStr;
 use std::rc::Rc;
+use std::time::{Duration, Instant};
 use touch::TouchPurpose;
 
 /// Minimum number of pixels at the bottom/top where selection scrolling is performed.
@@ -71,16 +84,29 @@ const SELECTION_SCROLLING_STEP: f32 = 10.;
 /// Maximum number of lines for the blocking search while still typing the search regex.
 const MAX_SEARCH_WHILE_TYPING: Option<usize> = Some(1000);
 
-/// Maximum number of search terms stored in the history.
-const MAX_SEARCH_HISTORY_SIZE: usize = 255;
+/// Maximum number of matches counted for the search bar.
+const MAX_SEARCH_COUNT: usize = 1000;
+
+/// Minimum time between two counts of the matches caused by new output.
+const SEARCH_COUNT_REFRESH_INTERVAL: Duration = Duration::from_millis(250);
 
 pub struct Screen<'screen> {
     bindings: crate::bindings::KeyBindings,
     mouse_bindings: Vec<MouseBinding>,
+    pending_keys: PendingKeys,
     pub modifiers: Modifiers,
     pub mouse: Mouse,
     pub touchpurpose: TouchPurpose,
     pub search_state: SearchState,
+    search_history: Rc<RefCell<SearchHistory>>,
+    /// Whether new output arrived since the matches of the search were counted.
+    search_count_outdated: bool,
+    search_count_updated: Instant,
+    /// Border grabbed with the mouse, with the position it was last moved to.
+    split_drag: Option<(SplitBorder, usize)>,
+    /// Store selections into clipboard as well as the selection buffer.
+    copy_on_select: bool,
+    pub hint_state: HintState,
     pub renderer: Renderer,
     pub sugarloaf: Sugarloaf<'screen>,
     pub context_manager: context::ContextManager<EventProxy>,
@@ -96,13 +122,16 @@ pub struct ScreenWindowProperties {
 }
 
 impl Screen<'_> {
+    #[allow(clippy::too_many_arguments)]
     pub fn new<'screen>(
         window_properties: ScreenWindowProperties,
         config: &rio_backend::config::Config,
         event_proxy: EventProxy,
         font_library: &rio_backend::sugarloaf::font::FontLibrary,
         open_url: Option<String>,
+        session: Option<&WindowSession>,
         clipboard: Rc<RefCell<Clipboard>>,
+        search_history: Rc<RefCell<SearchHistory>>,
     ) -> Result<Screen<'screen>, Box<dyn Error>> {
         let size = window_properties.size;
         let scale = window_properties.scale;
@@ -177,12 +206,13 @@ impl Screen<'_> {
 
         let renderer = Renderer::new(config, font_library);
 
-        let bindings = crate::bindings::default_key_bindings(
+        let mut bindings = crate::bindings::default_key_bindings(
             config.bindings.keys.to_owned(),
             config.navigation.has_navigation_key_bindings(),
             config.navigation.use_split,
             config.keyboard,
         );
+        bindings.extend(crate::bindings::hint_key_bindings(&config.hints));
 
         let is_collapsed = config.navigation.is_collapsed_mode();
         let is_native = config.navigation.is_native();
@@ -207,6 +237,25 @@ impl Screen<'_> {
             should_update_titles: !(is_collapsed
                 && config.navigation.color_automation.is_empty()),
             split_color: config.colors.split,
+            scrollback_lines: config.scrollback.lines,
+            sync_limits: SyncLimits::from(&config.renderer),
+            selection: SelectionConfig::from(&config.selection),
+            env_vars: vec![],
+        };
+
+        let layout = config
+            .layout
+            .as_ref()
+            .and_then(|name| config.layouts.iter().find(|layout| &layout.name == name));
+        let first_pane = match session {
+            Some(session) => session
+                .tabs
+                .first()
+                .and_then(|tab| tab.panes.first())
+                .map(PaneConfig::from),
+            None => layout
+                .and_then(|layout| layout.tabs.first())
+                .map(|tab| PaneConfig::from(&tab.first())),
         };
 
         let rich_text_id = sugarloaf.create_rich_text();
@@ -231,7 +280,7 @@ impl Screen<'_> {
             is_ime_enabled: false,
         };
 
-        let context_manager = context::ContextManager::start(
+        let mut context_manager = context::ContextManager::start(
             // config.cursor.blinking
             (&cursor, config.cursor.blinking),
             event_proxy,
@@ -242,7 +291,14 @@ impl Screen<'_> {
             context_dimension,
             margin,
             sugarloaf_errors,
+            first_pane.as_ref(),
         )?;
+        context_manager.report_key_binding_conflicts(
+            crate::bindings::key_binding_conflicts(&bindings),
+        );
+        if let (Some(name), None) = (&config.layout, layout) {
+            context_manager.report_layout_not_found(name.to_owned());
+        }
 
         if cfg!(target_os = "macos") {
             sugarloaf.set_background_color(None);
@@ -255,9 +311,20 @@ impl Screen<'_> {
         }
         sugarloaf.render();
 
-        Ok(Screen {
+        let mut screen = Screen {
             search_state: SearchState::default(),
-            mouse_bindings: crate::bindings::default_mouse_bindings(),
+            search_history,
+            search_count_outdated: false,
+            search_count_updated: Instant::now(),
+            split_drag: None,
+            copy_on_select: config.selection.copy_on_select,
+            hint_state: HintState::new(&config.hints),
+            mouse_bindings: crate::bindings::default_mouse_bindings(
+                config.bindings.mouse.to_owned(),
+            ),
+            pending_keys: PendingKeys::new(Duration::from_millis(
+                config.bindings.sequence_timeout,
+            )),
             modifiers: Modifiers::default(),
             context_manager,
             sugarloaf,
@@ -266,7 +333,15 @@ impl Screen<'_> {
             renderer,
             bindings,
             clipboard,
-        })
+        };
+
+        if let Some(session) = session {
+            screen.restore_session(session);
+        } else if let Some(layout) = layout {
+            screen.open_layout(layout);
+        }
+
+        Ok(screen)
     }
 
     #[inline]
@@ -372,7 +447,19 @@ impl Screen<'_> {
         self.sugarloaf
             .update_filters(config.renderer.filters.as_slice());
         self.renderer = Renderer::new(config, font_library);
-
+        self.hint_state = HintState::new(&config.hints);
+        self.copy_on_select = config.selection.copy_on_select;
+        self.search_history
+            .borrow_mut()
+            .set_limit(config.search.history_size);
+        self.pending_keys
+            .set_timeout(Duration::from_millis(config.bindings.sequence_timeout));
+
+        self.context_manager.config.scrollback_lines = config.scrollback.lines;
+        let sync_limits = SyncLimits::from(&config.renderer);
+        self.context_manager.config.sync_limits = sync_limits;
+        let selection_config = SelectionConfig::from(&config.selection);
+        self.context_manager.config.selection = selection_config.clone();
         for context_grid in self.context_manager.contexts_mut() {
             context_grid.update_margin((
                 config.padding_x,
@@ -384,6 +471,7 @@ impl Screen<'_> {
 
             for current_context in context_grid.contexts_mut() {
                 let current_context = current_context.context_mut();
+                current_context.messenger.send_sync_limits(sync_limits);
                 let mut terminal = current_context.terminal.lock();
                 current_context.renderable_content =
                     RenderableContent::from_cursor_config(&config.cursor);
@@ -391,6 +479,8 @@ impl Screen<'_> {
                 terminal.cursor_shape = shape;
                 terminal.default_cursor_shape = shape;
                 terminal.blinking_cursor = config.cursor.blinking;
+                terminal.update_history(config.scrollback.lines);
+                terminal.set_selection_config(selection_config.clone());
                 drop(terminal);
             }
         }
@@ -414,16 +504,20 @@ impl Screen<'_> {
 
     #[inline]
     pub fn change_font_size(&mut self, action: FontSizeAction) {
-        let action: u8 = match action {
-            FontSizeAction::Increase => 2,
-            FontSizeAction::Decrease => 1,
-            FontSizeAction::Reset => 0,
-        };
+        let rich_text_id = self.context_manager.current().rich_text_id;
+        if let FontSizeAction::Set(font_size) = action {
+            self.sugarloaf
+                .set_rich_text_font_size(&rich_text_id, font_size);
+        } else {
+            let action: u8 = match action {
+                FontSizeAction::Increase => 2,
+                FontSizeAction::Decrease => 1,
+                _ => 0,
+            };
 
-        self.sugarloaf.set_rich_text_font_size_based_on_action(
-            &self.context_manager.current().rich_text_id,
-            action,
-        );
+            self.sugarloaf
+                .set_rich_text_font_size_based_on_action(&rich_text_id, action);
+        }
 
         self.context_manager
             .current_grid_mut()
@@ -585,6 +679,35 @@ impl Screen<'_> {
             return;
         }
 
+        // Key bindings are disabled while a hint is being selected.
+        if self.hint_state.active() {
+            match key.logical_key.as_ref() {
+                Key::Named(NamedKey::Escape) => self.stop_hint(),
+                Key::Named(NamedKey::Backspace) => {
+                    self.hint_state.pop_key();
+                    self.render();
+                }
+                _ => {
+                    let text = key.text_with_all_modifiers().unwrap_or_default();
+                    for character in text.chars() {
+                        self.hint_input(character);
+                    }
+                }
+            }
+
+            return;
+        }
+
+        if self.process_key_sequence(key, &mode, mods) {
+            return;
+        }
+
+        // Counts and vi commands typed over several keys take precedence
+        // over the bindings.
+        if mode.contains(Mode::VI) && !self.search_active() && self.vi_input(key, mods) {
+            return;
+        }
+
         let ignore_chars = self.process_key_bindings(key, &mode, mods);
         if ignore_chars {
             return;
@@ -657,12 +780,15 @@ impl Screen<'_> {
         }
     }
 
+    /// Run the mouse bindings triggered by the button, returns `true` when
+    /// at least one binding was triggered.
     #[inline]
-    pub fn process_mouse_bindings(&mut self, button: MouseButton) {
+    pub fn process_mouse_bindings(&mut self, button: MouseButton) -> bool {
         let mode = self.get_mode();
         let binding_mode = BindingMode::new(&mode, self.search_active());
         let mouse_mode = self.mouse_mode();
         let mods = self.modifiers.state();
+        let mut triggered = false;
 
         for i in 0..self.mouse_bindings.len() {
             let mut binding = self.mouse_bindings[i].clone();
@@ -672,409 +798,943 @@ impl Screen<'_> {
                 binding.mods |= ModifiersState::SHIFT;
             }
 
-            if binding.is_triggered_by(binding_mode.to_owned(), mods, &button)
-                && binding.action == Act::PasteSelection
-            {
+            if binding.is_triggered_by(binding_mode.to_owned(), mods, &button) {
+                triggered = true;
+                self.process_action(&binding.action);
+            }
+        }
+
+        triggered
+    }
+
+    /// Feed a key to the key binding sequences, returns `true` when the key
+    /// was consumed by a sequence.
+    fn process_key_sequence(
+        &mut self,
+        key: &rio_window::event::KeyEvent,
+        mode: &Mode,
+        mods: ModifiersState,
+    ) -> bool {
+        // Modifiers are pressed on their own before the next key.
+        if let Key::Named(
+            NamedKey::Control
+            | NamedKey::Shift
+            | NamedKey::Alt
+            | NamedKey::Super
+            | NamedKey::Meta
+            | NamedKey::Hyper,
+        ) = key.logical_key
+        {
+            return !self.pending_keys.is_empty();
+        }
+
+        let now = Instant::now();
+        let was_pending =
+            self.pending_keys.remaining(now).is_some() && !self.pending_keys.is_empty();
+        if !was_pending {
+            self.pending_keys.clear();
+        }
+
+        let binding_mode = BindingMode::new(mode, self.search_active());
+        let trigger = BindingKey::Keycode {
+            key: binding_logical_key(key, mods),
+            location: key.location,
+        };
+        self.pending_keys.push(trigger, mods, now);
+
+        let mut is_prefix = false;
+        let mut action = None;
+        for binding in &self.bindings {
+            match binding.sequence_match(&binding_mode, self.pending_keys.keys()) {
+                SequenceMatch::Complete => {
+                    action = Some(binding.action.clone());
+                    break;
+                }
+                SequenceMatch::Prefix => is_prefix = true,
+                SequenceMatch::None => (),
+            }
+        }
+
+        if let Some(action) = action {
+            self.pending_keys.clear();
+            self.process_action(&action);
+        } else if !is_prefix {
+            self.pending_keys.clear();
+
+            // A key which doesn't continue the sequence only cancels it.
+            if !was_pending {
+                return false;
+            }
+        }
+
+        self.render();
+        true
+    }
+
+    /// Feed a key to the vi command being typed, returns `true` when the
+    /// key was consumed.
+    fn vi_input(
+        &mut self,
+        key: &rio_window::event::KeyEvent,
+        mods: ModifiersState,
+    ) -> bool {
+        if mods.intersects(
+            ModifiersState::CONTROL | ModifiersState::ALT | ModifiersState::SUPER,
+        ) {
+            return false;
+        }
+
+        let text = key.text_with_all_modifiers().unwrap_or_default();
+        let mut chars = text.chars();
+        let (Some(character), None) = (chars.next(), chars.next()) else {
+            return false;
+        };
+
+        let current = self.context_manager.current_mut();
+        let mut terminal = current.terminal.lock();
+        match terminal.vi_input(character) {
+            ViInput::Ignored => false,
+            ViInput::Pending => true,
+            ViInput::Done => {
+                current.renderable_content.selection_range = terminal
+                    .selection
+                    .as_ref()
+                    .and_then(|selection| selection.to_range(&terminal));
+                drop(terminal);
+                self.render();
+                true
+            }
+        }
+    }
+
+    pub fn process_key_bindings(
+        &mut self,
+        key: &rio_window::event::KeyEvent,
+        mode: &Mode,
+        mods: ModifiersState,
+    ) -> bool {
+        let search_active = self.search_active();
+        let binding_mode = BindingMode::new(mode, search_active);
+        let mut ignore_chars = None;
+
+        for i in 0..self.bindings.len() {
+            let binding = &self.bindings[i];
+
+            // We don't want the key without modifier, because it means something else most of
+            // the time. However what we want is to manually lowercase the character to account
+            // for both small and capital letters on regular characters at the same time.
+            let logical_key = binding_logical_key(key, mods);
+
+            let key_match = match (&binding.trigger, logical_key) {
+                (BindingKey::Scancode(_), _) => BindingKey::Scancode(key.physical_key),
+                (_, code) => BindingKey::Keycode {
+                    key: code,
+                    location: key.location,
+                },
+            };
+
+            if binding.is_triggered_by(binding_mode.to_owned(), mods, &key_match) {
+                *ignore_chars.get_or_insert(true) &= binding.action != Act::ReceiveChar;
+
+                let action = binding.action.clone();
+                self.process_action(&action);
+            }
+        }
+
+        ignore_chars.unwrap_or(false)
+    }
+
+    /// Run the action of a key or mouse binding.
+    fn process_action(&mut self, action: &Act) {
+        match action {
+            Act::Run(program) => {
+                let command = self.expand_program(program);
+                self.exec(&command.program, &command.args);
+            }
+            Act::Esc(s) => {
+                let current_context = self.context_manager.current_mut();
+                current_context.set_selection(None);
+                let mut terminal = current_context.terminal.lock();
+                terminal.selection.take();
+                terminal.scroll_display(Scroll::Bottom);
+                drop(terminal);
+                current_context
+                    .messenger
+                    .send_bytes(s.to_owned().into_bytes());
+            }
+            Act::Paste => {
+                let content = self.clipboard.borrow_mut().get(ClipboardType::Clipboard);
+                self.paste(&content, true);
+            }
+            Act::ClearSelection => {
+                self.clear_selection();
+            }
+            Act::PasteSelection => {
                 let content = self.clipboard.borrow_mut().get(ClipboardType::Selection);
                 self.paste(&content, true);
             }
+            Act::Mouse(MouseAction::ExpandSelection) => {
+                self.expand_selection();
+            }
+            Act::Mouse(MouseAction::OpenHyperlink) => {
+                self.open_hyperlink_at_mouse();
+            }
+            Act::Copy => {
+                self.copy_selection(ClipboardType::Clipboard);
+            }
+            Act::CopyAnsi => {
+                self.copy_styled_selection(|text, _| text.to_ansi());
+            }
+            Act::CopyHtml => {
+                self.copy_styled_selection(|text, colors| text.to_html(colors));
+            }
+            Act::SearchForward => {
+                self.start_search(Direction::Right);
+                self.resize_top_or_bottom_line(self.ctx().len());
+                self.render();
+            }
+            Act::SearchBackward => {
+                self.start_search(Direction::Left);
+                self.resize_top_or_bottom_line(self.ctx().len());
+                self.render();
+            }
+            Act::Search(SearchAction::SearchConfirm) => {
+                self.confirm_search();
+                self.resize_top_or_bottom_line(self.ctx().len());
+                self.render();
+            }
+            Act::Search(SearchAction::SearchCancel) => {
+                self.cancel_search();
+                self.resize_top_or_bottom_line(self.ctx().len());
+                self.render();
+            }
+            Act::Search(SearchAction::SearchClear) => {
+                let direction = self.search_state.direction;
+                self.cancel_search();
+                self.start_search(direction);
+                self.resize_top_or_bottom_line(self.ctx().len());
+                self.render();
+            }
+            Act::Search(SearchAction::SearchFocusNext) => {
+                self.advance_search_origin(self.search_state.direction);
+                self.resize_top_or_bottom_line(self.ctx().len());
+                self.render();
+            }
+            Act::Search(SearchAction::SearchFocusPrevious) => {
+                let direction = self.search_state.direction.opposite();
+                self.advance_search_origin(direction);
+                self.resize_top_or_bottom_line(self.ctx().len());
+                self.render();
+            }
+            Act::Search(SearchAction::SearchDeleteWord) => {
+                self.search_pop_word();
+                self.render();
+            }
+            Act::Search(SearchAction::SearchHistoryPrevious) => {
+                self.search_history_previous();
+                self.render();
+            }
+            Act::Search(SearchAction::SearchHistoryNext) => {
+                self.search_history_next();
+                self.render();
+            }
+            Act::Search(SearchAction::SearchToggleCase) => {
+                let options = &mut self.search_state.options;
+                options.case = options.case.next();
+                self.update_search();
+                self.render();
+            }
+            Act::Search(SearchAction::SearchToggleWholeWord) => {
+                let options = &mut self.search_state.options;
+                options.whole_word = !options.whole_word;
+                self.update_search();
+                self.render();
+            }
+            Act::Search(SearchAction::SearchToggleLiteral) => {
+                let options = &mut self.search_state.options;
+                options.literal = !options.literal;
+                self.update_search();
+                self.render();
+            }
+            Act::ToggleViMode => {
+                let mut terminal = self.context_manager.current_mut().terminal.lock();
+                terminal.toggle_vi_mode();
+                let has_vi_mode_enabled = terminal.mode().contains(Mode::VI);
+                drop(terminal);
+                self.renderer.set_vi_mode(has_vi_mode_enabled);
+                self.render();
+            }
+            Act::ViMotion(motion) => {
+                let current_context = self.context_manager.current_mut();
+                let mut terminal = current_context.terminal.lock();
+                if terminal.mode().contains(Mode::VI) {
+                    terminal.vi_motion(*motion);
+                }
+
+                if let Some(selection) = &terminal.selection {
+                    current_context.renderable_content.selection_range =
+                        selection.to_range(&terminal);
+                };
+                drop(terminal);
+                self.render();
+            }
+            Act::Vi(ViAction::CenterAroundViCursor) => {
+                let mut terminal = self.context_manager.current_mut().terminal.lock();
+                let display_offset = terminal.display_offset() as i32;
+                let target =
+                    -display_offset + terminal.grid.screen_lines() as i32 / 2 - 1;
+                let line = terminal.vi_mode_cursor.pos.row;
+                let scroll_lines = target - line.0;
+
+                terminal.scroll_display(Scroll::Delta(scroll_lines));
+                drop(terminal);
+            }
+            Act::Vi(ViAction::ToggleNormalSelection) => {
+                self.toggle_selection(SelectionType::Simple, Side::Left);
+                self.render();
+            }
+            Act::Vi(ViAction::ToggleLineSelection) => {
+                self.toggle_selection(SelectionType::Lines, Side::Left);
+                self.render();
+            }
+            Act::Vi(ViAction::ToggleBlockSelection) => {
+                self.toggle_selection(SelectionType::Block, Side::Left);
+                self.render();
+            }
+            Act::Vi(ViAction::ToggleSemanticSelection) => {
+                self.toggle_selection(SelectionType::Semantic, Side::Left);
+                self.render();
+            }
+            Act::SplitRight(program) => {
+                let shell = program.as_ref().map(|program| self.expand_program(program));
+                self.split(false, shell);
+            }
+            Act::SplitDown(program) => {
+                let shell = program.as_ref().map(|program| self.expand_program(program));
+                self.split(true, shell);
+            }
+            Act::ConfigEditor => {
+                self.context_manager.switch_to_settings();
+            }
+            Act::WindowCreateNew => {
+                self.context_manager.create_new_window();
+            }
+            Act::CloseCurrentSplitOrTab => {
+                self.close_split_or_tab();
+            }
+            Act::TabCreateNew => {
+                self.create_tab();
+            }
+            Act::TabCloseCurrent => {
+                self.close_tab();
+            }
+            Act::TabCloseUnfocused => {
+                self.clear_selection();
+                self.cancel_search();
+                if self.ctx().len() > 1 {
+                    self.context_manager.close_unfocused_tabs();
+                    self.resize_top_or_bottom_line(1);
+                    self.render();
+                }
+            }
+            Act::Quit => {
+                self.context_manager.quit();
+            }
+            Act::SaveSession => {
+                self.context_manager.save_session();
+            }
+            Act::CommandPalette => {
+                self.context_manager.open_command_palette();
+            }
+            Act::IncreaseFontSize => {
+                self.change_font_size(FontSizeAction::Increase);
+            }
+            Act::DecreaseFontSize => {
+                self.change_font_size(FontSizeAction::Decrease);
+            }
+            Act::ResetFontSize => {
+                self.change_font_size(FontSizeAction::Reset);
+            }
+            Act::SetFontSize(font_size) => {
+                self.change_font_size(FontSizeAction::Set(*font_size));
+            }
+            Act::ScrollPageUp => {
+                // Move vi mode cursor.
+                let mut terminal = self.context_manager.current_mut().terminal.lock();
+                let scroll_lines = terminal.grid.screen_lines() as i32;
+                terminal.vi_mode_cursor =
+                    terminal.vi_mode_cursor.scroll(&terminal, scroll_lines);
+                terminal.scroll_display(Scroll::PageUp);
+                drop(terminal);
+                self.render();
+            }
+            Act::ScrollPageDown => {
+                // Move vi mode cursor.
+                let mut terminal = self.context_manager.current_mut().terminal.lock();
+                let scroll_lines = -(terminal.grid.screen_lines() as i32);
+
+                terminal.vi_mode_cursor =
+                    terminal.vi_mode_cursor.scroll(&terminal, scroll_lines);
+
+                terminal.scroll_display(Scroll::PageDown);
+                drop(terminal);
+                self.render();
+            }
+            Act::ScrollHalfPageUp => {
+                // Move vi mode cursor.
+                let mut terminal = self.context_manager.current_mut().terminal.lock();
+                let scroll_lines = terminal.grid.screen_lines() as i32 / 2;
+
+                terminal.vi_mode_cursor =
+                    terminal.vi_mode_cursor.scroll(&terminal, scroll_lines);
+
+                terminal.scroll_display(Scroll::Delta(scroll_lines));
+                drop(terminal);
+                self.render();
+            }
+            Act::ScrollHalfPageDown => {
+                // Move vi mode cursor.
+                let mut terminal = self.context_manager.current_mut().terminal.lock();
+                let scroll_lines = -(terminal.grid.screen_lines() as i32 / 2);
+
+                terminal.vi_mode_cursor =
+                    terminal.vi_mode_cursor.scroll(&terminal, scroll_lines);
+
+                terminal.scroll_display(Scroll::Delta(scroll_lines));
+                drop(terminal);
+                self.render();
+            }
+            Act::ScrollToTop => {
+                let mut terminal = self.context_manager.current_mut().terminal.lock();
+                terminal.scroll_display(Scroll::Top);
+
+                let topmost_line = terminal.grid.topmost_line();
+                terminal.vi_mode_cursor.pos.row = topmost_line;
+                terminal.vi_motion(ViMotion::FirstOccupied);
+                drop(terminal);
+                self.render();
+            }
+            Act::ScrollToBottom => {
+                let mut terminal = self.context_manager.current_mut().terminal.lock();
+                terminal.scroll_display(Scroll::Bottom);
+
+                // Move vi mode cursor.
+                terminal.vi_mode_cursor.pos.row = terminal.grid.bottommost_line();
+
+                // Move to beginning twice, to always jump across linewraps.
+                terminal.vi_motion(ViMotion::FirstOccupied);
+                terminal.vi_motion(ViMotion::FirstOccupied);
+                drop(terminal);
+                self.render();
+            }
+            Act::ScrollToPrevPrompt => {
+                let mut terminal = self.context_manager.current_mut().terminal.lock();
+                terminal.scroll_to_prompt(Direction::Left);
+                drop(terminal);
+                self.render();
+            }
+            Act::ScrollToNextPrompt => {
+                let mut terminal = self.context_manager.current_mut().terminal.lock();
+                terminal.scroll_to_prompt(Direction::Right);
+                drop(terminal);
+                self.render();
+            }
+            Act::SelectLastCommandOutput => {
+                self.select_last_command_output();
+                self.render();
+            }
+            Act::CopyLastCommandOutput => {
+                self.copy_last_command_output();
+            }
+            Act::Scroll(delta) => {
+                let mut terminal = self.context_manager.current_mut().terminal.lock();
+                terminal.scroll_display(Scroll::Delta(*delta));
+                drop(terminal);
+                self.render();
+            }
+            Act::ClearHistory => {
+                let mut terminal = self.context_manager.current_mut().terminal.lock();
+                terminal.clear_saved_history();
+                drop(terminal);
+                self.render();
+            }
+            Act::ToggleFullscreen => self.context_manager.toggle_full_screen(),
+            Act::Hint(index) => {
+                self.start_hint(*index);
+            }
+            Act::Minimize => {
+                self.context_manager.minimize();
+            }
+            Act::Hide => {
+                self.context_manager.hide();
+            }
+            #[cfg(target_os = "macos")]
+            Act::HideOtherApplications => {
+                self.context_manager.hide_other_apps();
+            }
+            Act::SelectNextSplit => {
+                self.cancel_search();
+                self.context_manager.select_next_split();
+                self.render();
+            }
+            Act::SelectPrevSplit => {
+                self.cancel_search();
+                self.context_manager.select_prev_split();
+                self.render();
+            }
+            Act::FocusSplit(direction) => {
+                self.cancel_search();
+                if self
+                    .context_manager
+                    .current_grid_mut()
+                    .select_split_in_direction(*direction)
+                {
+                    self.context_manager.select_route_from_current_grid();
+                }
+                self.render();
+            }
+            Act::SwapSplit(direction) => {
+                self.cancel_search();
+                if self
+                    .context_manager
+                    .current_grid_mut()
+                    .swap_split_in_direction(*direction)
+                {
+                    self.render();
+                }
+            }
+            Act::RotateSplits => {
+                self.cancel_search();
+                self.context_manager.current_grid_mut().rotate_splits();
+                self.render();
+            }
+            Act::ToggleSplitZoom => {
+                self.cancel_search();
+                self.context_manager.current_grid_mut().toggle_zoom();
+                self.render();
+            }
+            Act::GrowSplit(direction) => {
+                self.resize_split(*direction, true);
+            }
+            Act::ShrinkSplit(direction) => {
+                self.resize_split(*direction, false);
+            }
+            Act::SelectTab(tab_index) => {
+                self.context_manager.select_tab(*tab_index);
+                self.cancel_search();
+                self.render();
+            }
+            Act::SelectLastTab => {
+                self.cancel_search();
+                self.context_manager.select_last_tab();
+                self.render();
+            }
+            Act::SelectNextTab => {
+                self.cancel_search();
+                self.clear_selection();
+                self.context_manager.switch_to_next();
+                self.render();
+            }
+            Act::SelectPrevTab => {
+                self.cancel_search();
+                self.clear_selection();
+                self.context_manager.switch_to_prev();
+                self.render();
+            }
+            Act::ReceiveChar | Act::None => (),
+            _ => (),
+        }
+    }
+
+    pub fn split_right_with_config(&mut self, config: rio_backend::config::Config) {
+        let rich_text_id = self.sugarloaf.create_rich_text();
+        self.context_manager
+            .split_from_config(rich_text_id, false, config);
+
+        self.render();
+    }
+
+    pub fn split_right(&mut self) {
+        self.split(false, None);
+    }
+
+    pub fn split_down(&mut self) {
+        self.split(true, None);
+    }
+
+    /// Split the current pane, running the given program instead of the
+    /// configured shell.
+    pub fn split(&mut self, split_down: bool, shell: Option<Shell>) {
+        let rich_text_id = self.sugarloaf.create_rich_text();
+        self.context_manager.split(rich_text_id, split_down, shell);
+
+        self.render();
+    }
+
+    pub fn resize_split(&mut self, direction: SplitDirection, grow: bool) {
+        if self
+            .context_manager
+            .current_grid_mut()
+            .resize_current(direction, grow)
+        {
+            self.render();
+        }
+    }
+
+    /// Border between splits under the mouse, if any.
+    #[inline]
+    pub fn split_border_at_mouse(&self) -> Option<SplitBorder> {
+        self.context_manager
+            .current_grid()
+            .border_at_mouse(&self.mouse)
+    }
+
+    /// Grab the border between splits under the mouse, returns whether there
+    /// is one.
+    pub fn start_split_drag(&mut self) -> bool {
+        self.split_drag = self.split_border_at_mouse().map(|border| {
+            let position = if border.direction == SplitDirection::Right {
+                self.mouse.x
+            } else {
+                self.mouse.y
+            };
+            (border, position)
+        });
+        self.split_drag.is_some()
+    }
+
+    /// Release the grabbed border, returns whether there was one.
+    #[inline]
+    pub fn stop_split_drag(&mut self) -> bool {
+        self.split_drag.take().is_some()
+    }
+
+    #[inline]
+    pub fn is_dragging_split(&self) -> bool {
+        self.split_drag.is_some()
+    }
+
+    /// Move the grabbed border to the mouse position.
+    pub fn drag_split(&mut self) {
+        let Some((border, position)) = self.split_drag else {
+            return;
+        };
+
+        let mouse_position = if border.direction == SplitDirection::Right {
+            self.mouse.x
+        } else {
+            self.mouse.y
+        };
+        let delta = mouse_position as f32 - position as f32;
+        let moved = self.context_manager.current_grid_mut().move_border(
+            border.index,
+            border.direction,
+            delta,
+        );
+
+        if moved != 0. {
+            // Keep the offset between the mouse and the border when it stops
+            // at the minimum size of a split.
+            let position = (position as f32 + moved).round().max(0.) as usize;
+            self.split_drag = Some((border, position));
+            self.render();
+        }
+    }
+
+    /// Actions with their key binding, tabs and splits listed by the command
+    /// palette.
+    pub fn command_palette_entries(&self) -> Vec<PaletteEntry> {
+        let mut entries = Vec::with_capacity(NAMED_ACTIONS.len());
+        for (name, action) in NAMED_ACTIONS {
+            // Actions which only make sense in search mode or at the mouse
+            // position are left out.
+            if matches!(
+                action,
+                Act::Search(_)
+                    | Act::Mouse(_)
+                    | Act::ReceiveChar
+                    | Act::None
+                    | Act::CommandPalette
+            ) {
+                continue;
+            }
+
+            let hint = self
+                .bindings
+                .iter()
+                .find(|binding| {
+                    binding.action == *action
+                        && !binding
+                            .mode
+                            .intersects(BindingMode::SEARCH | BindingMode::VI)
+                })
+                .map(|binding| sequence_name(&binding.keys()))
+                .unwrap_or_default();
+
+            entries.push(PaletteEntry {
+                title: action_title(name),
+                hint,
+                item: PaletteItem::Action(action.clone()),
+            });
+        }
+
+        let current_tab = self.context_manager.current_index();
+        for (tab, grid) in self.context_manager.contexts().iter().enumerate() {
+            let title = self
+                .context_manager
+                .titles
+                .titles
+                .get(&tab)
+                .map(|titles| titles[0].to_owned())
+                .filter(|program| !program.is_empty())
+                .unwrap_or_else(|| context_title(grid.current()));
+
+            entries.push(PaletteEntry {
+                title: format!("Tab {}: {title}", tab + 1),
+                hint: if tab == current_tab {
+                    String::from("current")
+                } else {
+                    String::new()
+                },
+                item: PaletteItem::Tab(tab),
+            });
+
+            if grid.len() == 1 {
+                continue;
+            }
+
+            for (split, item) in grid.contexts().iter().enumerate() {
+                entries.push(PaletteEntry {
+                    title: format!(
+                        "Tab {} Split {}: {}",
+                        tab + 1,
+                        split + 1,
+                        context_title(item.context())
+                    ),
+                    hint: if tab == current_tab && split == grid.current {
+                        String::from("current")
+                    } else {
+                        String::new()
+                    },
+                    item: PaletteItem::Split { tab, split },
+                });
+            }
+        }
+
+        entries
+    }
+
+    /// Run the entry chosen in the command palette.
+    pub fn run_palette_item(&mut self, item: PaletteItem) {
+        match item {
+            PaletteItem::Action(action) => self.process_action(&action),
+            PaletteItem::Tab(tab) => self.process_action(&Act::SelectTab(tab)),
+            PaletteItem::Split { tab, split } => {
+                self.cancel_search();
+                self.clear_selection();
+                self.context_manager.select_split(tab, split);
+                self.render();
+            }
+        }
+    }
+
+    /// Program of an action with its placeholders replaced by the working
+    /// directory and the selection of the focused pane.
+    fn expand_program(&self, program: &Program) -> Shell {
+        let current = self.context_manager.current();
+        let cwd = current.current_directory().unwrap_or_default();
+        let selection = current
+            .terminal
+            .lock()
+            .selection_to_string()
+            .unwrap_or_default();
+
+        program.expand(&cwd, &selection)
+    }
+
+    /// Open the tabs and splits of a layout, the current split already runs
+    /// the first pane of its first tab.
+    pub fn open_layout(&mut self, layout: &Layout) {
+        let first_tab = self.context_manager.current_index();
+        for (index, tab) in layout.tabs.iter().enumerate() {
+            if index > 0 {
+                let rich_text_id = self.sugarloaf.create_rich_text();
+                if !self
+                    .context_manager
+                    .add_tab_with_pane(rich_text_id, &PaneConfig::from(&tab.first()))
+                {
+                    break;
+                }
+                let num_tabs = self.ctx().len();
+                self.resize_top_or_bottom_line(num_tabs);
+            }
+
+            self.split_layout_pane(tab);
+        }
+
+        self.context_manager.select_split(first_tab, 0);
+    }
+
+    /// Split the current split, which runs the first pane of `pane`, into the
+    /// panes of `pane`.
+    fn split_layout_pane(&mut self, pane: &LayoutPane) {
+        let children = pane.children();
+        if children.is_empty() {
+            return;
+        }
+
+        let split_down = pane.split == LayoutSplit::Down;
+        let mut indexes = vec![self.context_manager.current_grid().current];
+        for child in &children[1..] {
+            let rich_text_id = self.sugarloaf.create_rich_text();
+            if !self.context_manager.split_pane(
+                rich_text_id,
+                split_down,
+                &PaneConfig::from(&child.first()),
+            ) {
+                break;
+            }
+            indexes.push(self.context_manager.current_grid().current);
+        }
+
+        let direction = if split_down {
+            SplitDirection::Down
+        } else {
+            SplitDirection::Right
+        };
+        let ratios: Vec<f32> = children.iter().map(|child| child.ratio).collect();
+        self.context_manager.current_grid_mut().set_split_ratios(
+            &indexes,
+            direction,
+            &ratios[..indexes.len()],
+        );
+
+        let tab = self.context_manager.current_index();
+        for (index, child) in indexes.into_iter().zip(&children) {
+            self.context_manager.select_split(tab, index);
+            self.split_layout_pane(child);
         }
     }
 
-    pub fn process_key_bindings(
-        &mut self,
-        key: &rio_window::event::KeyEvent,
-        mode: &Mode,
-        mods: ModifiersState,
-    ) -> bool {
-        let search_active = self.search_active();
-        let binding_mode = BindingMode::new(mode, search_active);
-        let mut ignore_chars = None;
-
-        for i in 0..self.bindings.len() {
-            let binding = &self.bindings[i];
-
-            // We don't want the key without modifier, because it means something else most of
-            // the time. However what we want is to manually lowercase the character to account
-            // for both small and capital letters on regular characters at the same time.
-            let logical_key = if let Key::Character(ch) = key.logical_key.as_ref() {
-                // Match `Alt` bindings without `Alt` being applied, otherwise they use the
-                // composed chars, which are not intuitive to bind.
-                //
-                // On Windows, the `Ctrl + Alt` mangles `logical_key` to unidentified values, thus
-                // preventing them from being used in bindings
-                //
-                // For more see https://github.com/rust-windowing/winit/issues/2945.
-                // if (cfg!(target_os = "macos") || (cfg!(windows) && mods.control_key()))
-                //     && mods.alt_key()
-                if (mods.shift_key() || mods.alt_key())
-                    || mods.alt_key() && (cfg!(windows) && mods.control_key())
+    /// Reopen the tabs and splits of a window of a saved session, the
+    /// current split already runs the first pane of its first tab.
+    pub fn restore_session(&mut self, session: &WindowSession) {
+        let mut current_splits = Vec::with_capacity(session.tabs.len());
+        for (index, tab) in session.tabs.iter().enumerate() {
+            let Some(first) = tab.panes.first() else {
+                continue;
+            };
+            if index > 0 {
+                let rich_text_id = self.sugarloaf.create_rich_text();
+                if !self
+                    .context_manager
+                    .add_tab_with_pane(rich_text_id, &PaneConfig::from(first))
                 {
-                    key.key_without_modifiers()
-                } else {
-                    Key::Character(ch.to_lowercase().into())
+                    break;
                 }
-            } else {
-                key.logical_key.clone()
-            };
+                let num_tabs = self.ctx().len();
+                self.resize_top_or_bottom_line(num_tabs);
+            }
 
-            let key_match = match (&binding.trigger, logical_key) {
-                (BindingKey::Scancode(_), _) => BindingKey::Scancode(key.physical_key),
-                (_, code) => BindingKey::Keycode {
-                    key: code,
-                    location: key.location,
-                },
-            };
+            let current_split = self.restore_splits(tab);
+            current_splits.push((self.context_manager.current_index(), current_split));
+        }
 
-            if binding.is_triggered_by(binding_mode.to_owned(), mods, &key_match) {
-                *ignore_chars.get_or_insert(true) &= binding.action != Act::ReceiveChar;
+        for (tab, split) in &current_splits {
+            self.context_manager.select_split(*tab, *split);
+        }
+        if let Some((tab, split)) = current_splits.get(session.current_tab) {
+            self.context_manager.select_split(*tab, *split);
+        }
+    }
 
-                match &binding.action {
-                    Act::Run(program) => self.exec(program.program(), program.args()),
-                    Act::Esc(s) => {
-                        let current_context = self.context_manager.current_mut();
-                        current_context.set_selection(None);
-                        let mut terminal = current_context.terminal.lock();
-                        terminal.selection.take();
-                        terminal.scroll_display(Scroll::Bottom);
-                        drop(terminal);
-                        current_context
-                            .messenger
-                            .send_bytes(s.to_owned().into_bytes());
-                    }
-                    Act::Paste => {
-                        let content =
-                            self.clipboard.borrow_mut().get(ClipboardType::Clipboard);
-                        self.paste(&content, true);
-                    }
-                    Act::ClearSelection => {
-                        self.clear_selection();
-                    }
-                    Act::PasteSelection => {
-                        let content =
-                            self.clipboard.borrow_mut().get(ClipboardType::Selection);
-                        self.paste(&content, true);
-                    }
-                    Act::Copy => {
-                        self.copy_selection(ClipboardType::Clipboard);
-                    }
-                    Act::SearchForward => {
-                        self.start_search(Direction::Right);
-                        self.resize_top_or_bottom_line(self.ctx().len());
-                        self.render();
-                    }
-                    Act::SearchBackward => {
-                        self.start_search(Direction::Left);
-                        self.resize_top_or_bottom_line(self.ctx().len());
-                        self.render();
-                    }
-                    Act::Search(SearchAction::SearchConfirm) => {
-                        self.confirm_search();
-                        self.resize_top_or_bottom_line(self.ctx().len());
-                        self.render();
-                    }
-                    Act::Search(SearchAction::SearchCancel) => {
-                        self.cancel_search();
-                        self.resize_top_or_bottom_line(self.ctx().len());
-                        self.render();
-                    }
-                    Act::Search(SearchAction::SearchClear) => {
-                        let direction = self.search_state.direction;
-                        self.cancel_search();
-                        self.start_search(direction);
-                        self.resize_top_or_bottom_line(self.ctx().len());
-                        self.render();
-                    }
-                    Act::Search(SearchAction::SearchFocusNext) => {
-                        self.advance_search_origin(self.search_state.direction);
-                        self.resize_top_or_bottom_line(self.ctx().len());
-                        self.render();
-                    }
-                    Act::Search(SearchAction::SearchFocusPrevious) => {
-                        let direction = self.search_state.direction.opposite();
-                        self.advance_search_origin(direction);
-                        self.resize_top_or_bottom_line(self.ctx().len());
-                        self.render();
-                    }
-                    Act::Search(SearchAction::SearchDeleteWord) => {
-                        self.search_pop_word();
-                        self.render();
-                    }
-                    Act::Search(SearchAction::SearchHistoryPrevious) => {
-                        self.search_history_previous();
-                        self.render();
-                    }
-                    Act::Search(SearchAction::SearchHistoryNext) => {
-                        self.search_history_next();
-                        self.render();
-                    }
-                    Act::ToggleViMode => {
-                        let mut terminal =
-                            self.context_manager.current_mut().terminal.lock();
-                        terminal.toggle_vi_mode();
-                        let has_vi_mode_enabled = terminal.mode().contains(Mode::VI);
-                        drop(terminal);
-                        self.renderer.set_vi_mode(has_vi_mode_enabled);
-                        self.render();
-                    }
-                    Act::ViMotion(motion) => {
-                        let current_context = self.context_manager.current_mut();
-                        let mut terminal = current_context.terminal.lock();
-                        if terminal.mode().contains(Mode::VI) {
-                            terminal.vi_motion(*motion);
-                        }
+    /// Split the current split, which runs the first pane of `tab`, into the
+    /// panes of `tab` following their links, then give them their saved
+    /// sizes. Returns the index of the saved current split.
+    fn restore_splits(&mut self, tab: &TabSession) -> usize {
+        let tab_index = self.context_manager.current_index();
+        let mut indexes = vec![None; tab.panes.len()];
+        indexes[0] = Some(self.context_manager.current_grid().current);
 
-                        if let Some(selection) = &terminal.selection {
-                            current_context.renderable_content.selection_range =
-                                selection.to_range(&terminal);
-                        };
-                        drop(terminal);
-                        self.render();
-                    }
-                    Act::Vi(ViAction::CenterAroundViCursor) => {
-                        let mut terminal =
-                            self.context_manager.current_mut().terminal.lock();
-                        let display_offset = terminal.display_offset() as i32;
-                        let target =
-                            -display_offset + terminal.grid.screen_lines() as i32 / 2 - 1;
-                        let line = terminal.vi_mode_cursor.pos.row;
-                        let scroll_lines = target - line.0;
-
-                        terminal.scroll_display(Scroll::Delta(scroll_lines));
-                        drop(terminal);
-                    }
-                    Act::Vi(ViAction::ToggleNormalSelection) => {
-                        self.toggle_selection(SelectionType::Simple, Side::Left);
-                        self.render();
-                    }
-                    Act::Vi(ViAction::ToggleLineSelection) => {
-                        self.toggle_selection(SelectionType::Lines, Side::Left);
-                        self.render();
-                    }
-                    Act::Vi(ViAction::ToggleBlockSelection) => {
-                        self.toggle_selection(SelectionType::Block, Side::Left);
-                        self.render();
-                    }
-                    Act::Vi(ViAction::ToggleSemanticSelection) => {
-                        self.toggle_selection(SelectionType::Semantic, Side::Left);
-                        self.render();
-                    }
-                    Act::SplitRight => {
-                        self.split_right();
-                    }
-                    Act::SplitDown => {
-                        self.split_down();
-                    }
-                    Act::ConfigEditor => {
-                        self.context_manager.switch_to_settings();
-                    }
-                    Act::WindowCreateNew => {
-                        self.context_manager.create_new_window();
-                    }
-                    Act::CloseCurrentSplitOrTab => {
-                        self.close_split_or_tab();
-                    }
-                    Act::TabCreateNew => {
-                        self.create_tab();
-                    }
-                    Act::TabCloseCurrent => {
-                        self.close_tab();
-                    }
-                    Act::TabCloseUnfocused => {
-                        self.clear_selection();
-                        self.cancel_search();
-                        if self.ctx().len() <= 1 {
-                            return true;
-                        }
-                        self.context_manager.close_unfocused_tabs();
-                        self.resize_top_or_bottom_line(1);
-                        self.render();
-                    }
-                    Act::Quit => {
-                        self.context_manager.quit();
-                    }
-                    Act::IncreaseFontSize => {
-                        self.change_font_size(FontSizeAction::Increase);
-                    }
-                    Act::DecreaseFontSize => {
-                        self.change_font_size(FontSizeAction::Decrease);
-                    }
-                    Act::ResetFontSize => {
-                        self.change_font_size(FontSizeAction::Reset);
-                    }
-                    Act::ScrollPageUp => {
-                        // Move vi mode cursor.
-                        let mut terminal =
-                            self.context_manager.current_mut().terminal.lock();
-                        let scroll_lines = terminal.grid.screen_lines() as i32;
-                        terminal.vi_mode_cursor =
-                            terminal.vi_mode_cursor.scroll(&terminal, scroll_lines);
-                        terminal.scroll_display(Scroll::PageUp);
-                        drop(terminal);
-                        self.render();
-                    }
-                    Act::ScrollPageDown => {
-                        // Move vi mode cursor.
-                        let mut terminal =
-                            self.context_manager.current_mut().terminal.lock();
-                        let scroll_lines = -(terminal.grid.screen_lines() as i32);
-
-                        terminal.vi_mode_cursor =
-                            terminal.vi_mode_cursor.scroll(&terminal, scroll_lines);
-
-                        terminal.scroll_display(Scroll::PageDown);
-                        drop(terminal);
-                        self.render();
-                    }
-                    Act::ScrollHalfPageUp => {
-                        // Move vi mode cursor.
-                        let mut terminal =
-                            self.context_manager.current_mut().terminal.lock();
-                        let scroll_lines = terminal.grid.screen_lines() as i32 / 2;
-
-                        terminal.vi_mode_cursor =
-                            terminal.vi_mode_cursor.scroll(&terminal, scroll_lines);
-
-                        terminal.scroll_display(Scroll::Delta(scroll_lines));
-                        drop(terminal);
-                        self.render();
-                    }
-                    Act::ScrollHalfPageDown => {
-                        // Move vi mode cursor.
-                        let mut terminal =
-                            self.context_manager.current_mut().terminal.lock();
-                        let scroll_lines = -(terminal.grid.screen_lines() as i32 / 2);
-
-                        terminal.vi_mode_cursor =
-                            terminal.vi_mode_cursor.scroll(&terminal, scroll_lines);
-
-                        terminal.scroll_display(Scroll::Delta(scroll_lines));
-                        drop(terminal);
-                        self.render();
-                    }
-                    Act::ScrollToTop => {
-                        let mut terminal =
-                            self.context_manager.current_mut().terminal.lock();
-                        terminal.scroll_display(Scroll::Top);
-
-                        let topmost_line = terminal.grid.topmost_line();
-                        terminal.vi_mode_cursor.pos.row = topmost_line;
-                        terminal.vi_motion(ViMotion::FirstOccupied);
-                        drop(terminal);
-                        self.render();
-                    }
-                    Act::ScrollToBottom => {
-                        let mut terminal =
-                            self.context_manager.current_mut().terminal.lock();
-                        terminal.scroll_display(Scroll::Bottom);
-
-                        // Move vi mode cursor.
-                        terminal.vi_mode_cursor.pos.row = terminal.grid.bottommost_line();
-
-                        // Move to beginning twice, to always jump across linewraps.
-                        terminal.vi_motion(ViMotion::FirstOccupied);
-                        terminal.vi_motion(ViMotion::FirstOccupied);
-                        drop(terminal);
-                        self.render();
-                    }
-                    Act::Scroll(delta) => {
-                        let mut terminal =
-                            self.context_manager.current_mut().terminal.lock();
-                        terminal.scroll_display(Scroll::Delta(*delta));
-                        drop(terminal);
-                        self.render();
-                    }
-                    Act::ClearHistory => {
-                        let mut terminal =
-                            self.context_manager.current_mut().terminal.lock();
-                        terminal.clear_saved_history();
-                        drop(terminal);
-                        self.render();
-                    }
-                    Act::ToggleFullscreen => self.context_manager.toggle_full_screen(),
-                    Act::Minimize => {
-                        self.context_manager.minimize();
-                    }
-                    Act::Hide => {
-                        self.context_manager.hide();
-                    }
-                    #[cfg(target_os = "macos")]
-                    Act::HideOtherApplications => {
-                        self.context_manager.hide_other_apps();
-                    }
-                    Act::SelectNextSplit => {
-                        self.cancel_search();
-                        self.context_manager.select_next_split();
-                        self.render();
-                    }
-                    Act::SelectPrevSplit => {
-                        self.cancel_search();
-                        self.context_manager.select_prev_split();
-                        self.render();
-                    }
-                    Act::SelectTab(tab_index) => {
-                        self.context_manager.select_tab(*tab_index);
-                        self.cancel_search();
-                        self.render();
-                    }
-                    Act::SelectLastTab => {
-                        self.cancel_search();
-                        self.context_manager.select_last_tab();
-                        self.render();
-                    }
-                    Act::SelectNextTab => {
-                        self.cancel_search();
-                        self.clear_selection();
-                        self.context_manager.switch_to_next();
-                        self.render();
-                    }
-                    Act::SelectPrevTab => {
-                        self.cancel_search();
-                        self.clear_selection();
-                        self.context_manager.switch_to_prev();
-                        self.render();
-                    }
-                    Act::ReceiveChar | Act::None => (),
-                    _ => (),
+        let mut stack = vec![0];
+        while let Some(saved) = stack.pop() {
+            let Some(index) = indexes[saved] else {
+                continue;
+            };
+
+            let pane = &tab.panes[saved];
+            for (child, split_down) in [(pane.right, false), (pane.down, true)] {
+                let Some(child) =
+                    child.filter(|child| indexes.get(*child) == Some(&None))
+                else {
+                    continue;
+                };
+
+                self.context_manager.select_split(tab_index, index);
+                let rich_text_id = self.sugarloaf.create_rich_text();
+                if self.context_manager.split_pane(
+                    rich_text_id,
+                    split_down,
+                    &PaneConfig::from(&tab.panes[child]),
+                ) {
+                    indexes[child] = Some(self.context_manager.current_grid().current);
+                    stack.push(child);
                 }
             }
         }
 
-        ignore_chars.unwrap_or(false)
-    }
-
-    pub fn split_right_with_config(&mut self, config: rio_backend::config::Config) {
-        let rich_text_id = self.sugarloaf.create_rich_text();
+        let sizes: Vec<(usize, f32, f32)> = indexes
+            .iter()
+            .zip(&tab.panes)
+            .filter_map(|(index, pane)| Some(((*index)?, pane.width, pane.height)))
+            .collect();
         self.context_manager
-            .split_from_config(rich_text_id, false, config);
+            .current_grid_mut()
+            .restore_sizes(tab.width, tab.height, &sizes);
 
-        self.render();
+        indexes
+            .get(tab.current)
+            .copied()
+            .flatten()
+            .unwrap_or_default()
     }
 
-    pub fn split_right(&mut self) {
+    pub fn create_tab(&mut self) {
+        let redirect = true;
+
         let rich_text_id = self.sugarloaf.create_rich_text();
-        self.context_manager.split(rich_text_id, false);
+        self.context_manager.add_context(redirect, rich_text_id);
 
+        let num_tabs = self.ctx().len();
+        self.cancel_search();
+        self.resize_top_or_bottom_line(num_tabs);
         self.render();
     }
 
-    pub fn split_down(&mut self) {
+    /// Add a tab running the given pane, returns whether it was created.
+    pub fn create_tab_with_pane(&mut self, pane: &PaneConfig) -> bool {
         let rich_text_id = self.sugarloaf.create_rich_text();
-        self.context_manager.split(rich_text_id, true);
+        if !self.context_manager.add_tab_with_pane(rich_text_id, pane) {
+            return false;
+        }
 
+        let num_tabs = self.ctx().len();
+        self.cancel_search();
+        self.resize_top_or_bottom_line(num_tabs);
         self.render();
+        true
     }
 
-    pub fn create_tab(&mut self) {
-        let redirect = true;
-
+    /// Split the current pane into the given pane, returns whether it was
+    /// created.
+    pub fn split_with_pane(&mut self, split_down: bool, pane: &PaneConfig) -> bool {
         let rich_text_id = self.sugarloaf.create_rich_text();
-        self.context_manager.add_context(redirect, rich_text_id);
+        let created = self
+            .context_manager
+            .split_pane(rich_text_id, split_down, pane);
 
-        let num_tabs = self.ctx().len();
-        self.cancel_search();
-        self.resize_top_or_bottom_line(num_tabs);
         self.render();
+        created
     }
 
     pub fn close_split_or_tab(&mut self) {
@@ -1220,6 +1880,8 @@ impl Screen<'_> {
         terminal.scroll_display(Scroll::Delta(-self.search_state.display_offset_delta));
         drop(terminal);
         self.search_state.origin = new_origin;
+
+        self.update_search_count();
     }
 
     /// Whether we should send `ESC` due to `Alt` being pressed.
@@ -1259,7 +1921,7 @@ impl Screen<'_> {
         };
         drop(terminal);
 
-        if ty == ClipboardType::Selection {
+        if ty == ClipboardType::Selection && self.copy_on_select {
             self.clipboard
                 .borrow_mut()
                 .set(ClipboardType::Clipboard, text.clone());
@@ -1267,6 +1929,58 @@ impl Screen<'_> {
         self.clipboard.borrow_mut().set(ty, text);
     }
 
+    /// Store the selection into clipboard, formatted with the colors of the
+    /// configuration.
+    fn copy_styled_selection(&mut self, format: impl Fn(&StyledText, &List) -> String) {
+        let terminal = self.context_manager.current().terminal.lock();
+        let text = match terminal
+            .selection_to_styled_text()
+            .filter(|t| !t.is_empty())
+        {
+            Some(text) => text,
+            None => return,
+        };
+        drop(terminal);
+
+        let colors = List::from(&self.renderer.named_colors);
+        self.clipboard
+            .borrow_mut()
+            .set(ClipboardType::Clipboard, format(&text, &colors));
+    }
+
+    pub fn copy_last_command_output(&mut self) {
+        let terminal = self.context_manager.current().terminal.lock();
+        let text = match terminal.last_command_output() {
+            Some(lines) => terminal.bounds_to_string(
+                Pos::new(*lines.start(), Column(0)),
+                Pos::new(*lines.end(), terminal.last_column()),
+            ),
+            None => return,
+        };
+        drop(terminal);
+
+        self.clipboard
+            .borrow_mut()
+            .set(ClipboardType::Clipboard, text);
+    }
+
+    pub fn select_last_command_output(&mut self) {
+        let current = self.context_manager.current_mut();
+        let mut terminal = current.terminal.lock();
+        let lines = match terminal.last_command_output() {
+            Some(lines) => lines,
+            None => return,
+        };
+
+        let start = Pos::new(*lines.start(), Column(0));
+        let mut selection = Selection::new(SelectionType::Lines, start, Side::Left);
+        selection.update(Pos::new(*lines.end(), terminal.last_column()), Side::Right);
+
+        current.renderable_content.selection_range = selection.to_range(&terminal);
+        terminal.selection = Some(selection);
+        drop(terminal);
+    }
+
     #[inline]
     pub fn clear_selection(&mut self) {
         // Clear the selection on the terminal.
@@ -1350,6 +2064,26 @@ impl Screen<'_> {
         drop(terminal);
     }
 
+    /// Extend the selection to the mouse cursor, or start a new one.
+    fn expand_selection(&mut self) {
+        let display_offset = self.display_offset();
+        let pos = self.mouse_position(display_offset);
+        let side = self.mouse.square_side;
+
+        let has_selection = self
+            .context_manager
+            .current()
+            .terminal
+            .lock()
+            .selection
+            .is_some();
+        if has_selection {
+            self.update_selection(pos, side);
+        } else {
+            self.start_selection(SelectionType::Simple, pos, side);
+        }
+    }
+
     #[inline]
     pub fn search_nearest_hyperlink_from_pos(&mut self) -> bool {
         #[cfg(target_os = "macos")]
@@ -1407,15 +2141,120 @@ impl Screen<'_> {
         false
     }
 
+    /// Open the hyperlink or the URL under the mouse cursor.
+    fn open_hyperlink_at_mouse(&self) {
+        let mut terminal = self.context_manager.current().terminal.lock();
+        let display_offset = terminal.display_offset();
+        let pos = self.mouse_position(display_offset);
+        terminal.search_nearest_hyperlink_from_pos(pos);
+        let pos_hyperlink = terminal.grid[pos].hyperlink();
+        drop(terminal);
+
+        if let Some(hyperlink) = pos_hyperlink {
+            self.open_hyperlink(hyperlink);
+        }
+    }
+
     fn open_hyperlink(&self, hyperlink: Hyperlink) {
+        self.open_uri(hyperlink.uri());
+    }
+
+    fn open_uri(&self, uri: &str) {
         #[cfg(not(any(target_os = "macos", windows)))]
-        self.exec("xdg-open", [hyperlink.uri()]);
+        self.exec("xdg-open", [uri]);
 
         #[cfg(target_os = "macos")]
-        self.exec("open", [hyperlink.uri()]);
+        self.exec("open", [uri]);
 
         #[cfg(windows)]
-        self.exec("cmd", ["/c", "start", "", hyperlink.uri()]);
+        self.exec("cmd", ["/c", "start", "", uri]);
+    }
+
+    /// Start the keyboard hint mode of the rule at `index`.
+    fn start_hint(&mut self, index: usize) {
+        let terminal = self.context_manager.current().terminal.lock();
+        let is_active = self.hint_state.start(&terminal, index);
+        drop(terminal);
+
+        if is_active {
+            self.context_manager
+                .current_mut()
+                .renderable_content
+                .mark_pending_updates();
+            self.render();
+        }
+    }
+
+    fn stop_hint(&mut self) {
+        self.hint_state.stop();
+        self.context_manager
+            .current_mut()
+            .renderable_content
+            .mark_pending_updates();
+        self.render();
+    }
+
+    fn hint_input(&mut self, c: char) {
+        let rule = self.hint_state.rule().cloned();
+        match (self.hint_state.keyboard_input(c), rule) {
+            (Some(hint), Some(rule)) => {
+                self.context_manager
+                    .current_mut()
+                    .renderable_content
+                    .mark_pending_updates();
+                self.trigger_hint(&hint, rule.action, rule.command.as_ref());
+                self.render();
+            }
+            _ => self.render(),
+        }
+    }
+
+    /// Run the action of a hint rule on the selected match.
+    fn trigger_hint(
+        &mut self,
+        hint: &HintMatch,
+        action: HintAction,
+        command: Option<&Shell>,
+    ) {
+        let terminal = self.context_manager.current().terminal.lock();
+        let text = hint.text(&terminal);
+        drop(terminal);
+
+        if let Some(command) = command {
+            let mut args = command.args.clone();
+            args.push(text);
+            self.exec(&command.program, &args);
+            return;
+        }
+
+        match action {
+            HintAction::Copy => {
+                self.clipboard
+                    .borrow_mut()
+                    .set(ClipboardType::Clipboard, text);
+            }
+            HintAction::Paste => self.paste(&text, true),
+            HintAction::Open => match &hint.hyperlink {
+                Some(hyperlink) => self.open_hyperlink(hyperlink.clone()),
+                None => self.open_uri(&text),
+            },
+            HintAction::Select => {
+                let current = self.context_manager.current_mut();
+                let mut terminal = current.terminal.lock();
+                let mut selection = Selection::new(
+                    SelectionType::Simple,
+                    *hint.bounds.start(),
+                    Side::Left,
+                );
+                selection.update(*hint.bounds.end(), Side::Right);
+
+                current.renderable_content.selection_range =
+                    selection.to_range(&terminal);
+                terminal.selection = Some(selection);
+                drop(terminal);
+                self.copy_selection(ClipboardType::Selection);
+            }
+        }
     }
 
     pub fn exec<I, S>(&self, program: &str, args: I)
@@ -1557,16 +2396,11 @@ impl Screen<'_> {
 
     #[inline]
     fn start_search(&mut self, direction: Direction) {
-        // Only create new history entry if the previous regex wasn't empty.
-        if self
-            .search_state
-            .history
-            .front()
-            .map_or(true, |regex| !regex.is_empty())
-        {
-            self.search_state.history.push_front(String::new());
-            self.search_state.history.truncate(MAX_SEARCH_HISTORY_SIZE);
-        }
+        // The history is shared with the other windows, the first entry is
+        // the new search.
+        self.search_state.history =
+            self.search_history.borrow().entries().cloned().collect();
+        self.search_state.history.push_front(String::new());
 
         self.search_state.history_index = Some(0);
         self.search_state.direction = direction;
@@ -1642,7 +2476,12 @@ impl Screen<'_> {
         // let vi_mode = self.get_mode().contains(Mode::VI);
         // self.window().set_ime_allowed(!vi_mode);
 
+        if let Some(regex) = self.search_state.regex() {
+            self.search_history.borrow_mut().push(regex);
+        }
+
         self.search_state.history_index = None;
+        self.search_state.count = None;
 
         // Clear focused match.
         self.search_state.focused_match = None;
@@ -1696,11 +2535,55 @@ impl Screen<'_> {
             self.search_state.dfas = None;
         } else {
             // Create search dfas for the new regex string.
-            self.search_state.dfas = RegexSearch::new(regex).ok();
+            self.search_state.dfas =
+                RegexSearch::with_options(regex, self.search_state.options).ok();
 
             // Update search highlighting.
             self.goto_match(MAX_SEARCH_WHILE_TYPING);
         }
+
+        self.update_search_count();
+    }
+
+    /// Count the matches of the search, including the position of the
+    /// focused match.
+    fn update_search_count(&mut self) {
+        self.search_state.count = self.search_state.dfas.as_mut().map(|dfas| {
+            let mut terminal = self.context_manager.current().terminal.lock();
+            let count = terminal.search_count(
+                dfas,
+                self.search_state.focused_match.as_ref(),
+                MAX_SEARCH_COUNT,
+            );
+            terminal.grid.release_expanded_rows();
+            count
+        });
+        self.search_count_outdated = false;
+        self.search_count_updated = Instant::now();
+    }
+
+    /// Count the matches of an active search again after new output.
+    pub fn outdate_search_count(&mut self) {
+        if self.search_active() {
+            self.search_count_outdated = true;
+        }
+    }
+
+    /// Count the matches again if new output arrived, at most once every
+    /// [`SEARCH_COUNT_REFRESH_INTERVAL`].
+    fn refresh_search_count(&mut self) {
+        if !self.search_count_outdated {
+            return;
+        }
+
+        let elapsed = self.search_count_updated.elapsed();
+        if elapsed >= SEARCH_COUNT_REFRESH_INTERVAL {
+            self.update_search_count();
+        } else {
+            let remaining = SEARCH_COUNT_REFRESH_INTERVAL - elapsed;
+            self.context_manager
+                .schedule_render(remaining.as_millis() as u64 + 1);
+        }
     }
 
     /// Reset terminal to the state before search was started.
@@ -1791,6 +2674,7 @@ impl Screen<'_> {
                     self.search_state.focused_match = None;
                 }
             }
+            terminal.grid.release_expanded_rows();
             drop(terminal);
         }
 
@@ -2047,6 +2931,16 @@ impl Screen<'_> {
         self.sugarloaf.render();
     }
 
+    pub fn render_command_palette(&mut self, palette: &CommandPalette) {
+        self.sugarloaf.clear();
+        crate::router::routes::palette::screen(
+            &mut self.sugarloaf,
+            &self.context_manager.current().dimension,
+            palette,
+        );
+        self.sugarloaf.render();
+    }
+
     pub fn render_welcome(&mut self) {
         self.sugarloaf.clear();
         crate::router::routes::welcome::screen(
@@ -2071,13 +2965,36 @@ impl Screen<'_> {
         // println!("_____________________________\nrender time elapsed");
         let is_search_active = self.search_active();
         if is_search_active {
+            self.refresh_search_count();
             if let Some(history_index) = self.search_state.history_index {
                 self.renderer.set_active_search(
-                    self.search_state.history.get(history_index).cloned(),
+                    self.search_state.history.get(history_index).map(|content| {
+                        SearchBar {
+                            content: content.clone(),
+                            count: self.search_state.count,
+                            options: self.search_state.options,
+                        }
+                    }),
                 );
             }
         }
 
+        if !self.pending_keys.is_empty() {
+            match self.pending_keys.remaining(Instant::now()) {
+                Some(remaining) => {
+                    self.renderer
+                        .set_pending_keys(Some(crate::bindings::sequence_name(
+                            self.pending_keys.keys(),
+                        )));
+
+                    // Render again to hide the indicator once the sequence expires.
+                    self.context_manager
+                        .schedule_render(remaining.as_millis() as u64 + 1);
+                }
+                None => self.pending_keys.clear(),
+            }
+        }
+
         let mut search_hints = if is_search_active {
             let terminal = self.context_manager.current().terminal.lock();
             let hints = self
@@ -2090,6 +3007,22 @@ impl Screen<'_> {
             None
         };
 
+        if self.hint_state.active() {
+            let terminal = self.context_manager.current().terminal.lock();
+            self.hint_state.update_matches(&terminal);
+            drop(terminal);
+
+            // Matches are gone, the labels still need to be cleared.
+            if !self.hint_state.active() {
+                self.context_manager
+                    .current_mut()
+                    .renderable_content
+                    .mark_pending_updates();
+            }
+        }
+        self.renderer
+            .set_hint_labels(self.hint_state.visible_labels());
+
         self.renderer.prepare_term(
             &mut self.sugarloaf,
             &mut self.context_manager,
@@ -2116,3 +3049,39 @@ impl Screen<'_> {
         // println!("Total whole render function is: {:?}\n", duration);
     }
 }
+
+/// Key matched against the key bindings.
+fn binding_logical_key(key: &rio_window::event::KeyEvent, mods: ModifiersState) -> Key {
+    if let Key::Character(ch) = key.logical_key.as_ref() {
+        // Match `Alt` bindings without `Alt` being applied, otherwise they use the
+        // composed chars, which are not intuitive to bind.
+        //
+        // On Windows, the `Ctrl + Alt` mangles `logical_key` to unidentified values, thus
+        // preventing them from being used in bindings
+        //
+        // For more see https://github.com/rust-windowing/winit/issues/2945.
+        // if (cfg!(target_os = "macos") || (cfg!(windows) && mods.control_key()))
+        //     && mods.alt_key()
+        if (mods.shift_key() || mods.alt_key())
+            || mods.alt_key() && (cfg!(windows) && mods.control_key())
+        {
+            key.key_without_modifiers()
+        } else {
+            Key::Character(ch.to_lowercase().into())
+        }
+    } else {
+        key.logical_key.clone()
+    }
+}
+
+/// Title of a terminal listed by the command palette.
+fn context_title(context: &context::Context<EventProxy>) -> String {
+    let title = context.terminal.lock().title.to_owned();
+    if !title.is_empty() {
+        return title;
+    }
+
+    context
+        .current_directory()
+        .unwrap_or_else(|| String::from("Terminal"))
+}
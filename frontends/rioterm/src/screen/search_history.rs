@@ -0,0 +1,130 @@
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+
+/// Name of the file storing the search history in the configuration directory.
+pub const SEARCH_HISTORY_FILE_NAME: &str = "search_history";
+
+/// Search terms shared by every window, stored in a file so they survive
+/// restarts. The most recent term comes first.
+pub struct SearchHistory {
+    entries: VecDeque<String>,
+    limit: usize,
+    path: PathBuf,
+}
+
+impl SearchHistory {
+    pub fn new(limit: usize) -> Self {
+        let path = rio_backend::config::config_dir_path().join(SEARCH_HISTORY_FILE_NAME);
+        Self::load(path, limit)
+    }
+
+    fn load(path: PathBuf, limit: usize) -> Self {
+        let mut history = Self {
+            entries: read_entries(&path),
+            limit,
+            path,
+        };
+        history.entries.truncate(limit);
+        history
+    }
+
+    #[inline]
+    pub fn entries(&self) -> impl Iterator<Item = &String> {
+        self.entries.iter()
+    }
+
+    /// Store a search term in front of the history, removing its older copy.
+    pub fn push(&mut self, entry: &str) {
+        if entry.is_empty() || entry.contains('\n') || self.limit == 0 {
+            return;
+        }
+
+        // Keep the terms stored by other Rio processes since the last change.
+        self.entries = read_entries(&self.path);
+        self.entries.retain(|existing| existing != entry);
+        self.entries.push_front(entry.to_owned());
+        self.entries.truncate(self.limit);
+        self.save();
+    }
+
+    /// Change the number of terms kept, like on configuration updates.
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+        if self.entries.len() > limit {
+            self.entries.truncate(limit);
+            self.save();
+        }
+    }
+
+    fn save(&self) {
+        let mut content = String::new();
+        for entry in &self.entries {
+            content.push_str(entry);
+            content.push('\n');
+        }
+
+        // Search terms may contain secrets.
+        if let Err(err) = crate::session::write_private(&self.path, content.as_bytes()) {
+            tracing::warn!("unable to store search history {:?}: {err}", self.path);
+        }
+    }
+}
+
+fn read_entries(path: &Path) -> VecDeque<String> {
+    let mut entries = VecDeque::new();
+    for line in std::fs::read_to_string(path).unwrap_or_default().lines() {
+        if !line.is_empty() && !entries.iter().any(|entry| entry == line) {
+            entries.push_back(line.to_owned());
+        }
+    }
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_history() {
+        let path = std::env::temp_dir()
+            .join(format!("test-rio-search-history-{}", std::process::id()));
+        std::fs::write(&path, "error\nrequest-id\nerror\n\nwarn\n").unwrap();
+
+        let mut history = SearchHistory::load(path.clone(), 3);
+        assert_eq!(
+            history.entries().collect::<Vec<_>>(),
+            ["error", "request-id", "warn"]
+        );
+
+        // Recurring terms move to the front instead of being duplicated.
+        history.push("warn");
+        history.push("panic");
+        history.push("");
+        assert_eq!(
+            history.entries().collect::<Vec<_>>(),
+            ["panic", "warn", "error"]
+        );
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "panic\nwarn\nerror\n"
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        // Another window sees the stored terms.
+        let other = SearchHistory::load(path.clone(), 3);
+        assert_eq!(
+            other.entries().collect::<Vec<_>>(),
+            ["panic", "warn", "error"]
+        );
+
+        history.set_limit(1);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "panic\n");
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
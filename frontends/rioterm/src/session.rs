@@ -0,0 +1,282 @@
+use crate::context::grid::ContextGrid;
+use crate::context::{Context, PaneConfig};
+use rio_backend::config::Shell;
+use rio_backend::event::EventListener;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Name of the file storing the session in the configuration directory.
+pub const SESSION_FILE_NAME: &str = "session.toml";
+
+/// Windows, tabs and splits saved when Rio quits, reopened at startup when
+/// `session.restore` is enabled.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Session {
+    #[serde(default = "Vec::default")]
+    pub windows: Vec<WindowSession>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WindowSession {
+    /// Logical size of the inside of the window.
+    pub width: i32,
+    pub height: i32,
+    /// Position of the window on the screen, when the platform reports it.
+    #[serde(default = "Option::default")]
+    pub x: Option<i32>,
+    #[serde(default = "Option::default")]
+    pub y: Option<i32>,
+    #[serde(default = "bool::default")]
+    pub maximized: bool,
+    #[serde(default = "usize::default", rename = "current-tab")]
+    pub current_tab: usize,
+    #[serde(default = "Vec::default")]
+    pub tabs: Vec<TabSession>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TabSession {
+    /// Size of the tab when it was saved, the splits are scaled from it.
+    pub width: f32,
+    pub height: f32,
+    /// Index of the selected pane.
+    #[serde(default = "usize::default")]
+    pub current: usize,
+    #[serde(default = "Vec::default")]
+    pub panes: Vec<PaneSession>,
+}
+
+/// Split of a tab, the first one is on the top left corner and the other
+/// ones are reached from it by their `right` and `down` links.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PaneSession {
+    #[serde(default = "Option::default", rename = "working-dir")]
+    pub working_dir: Option<String>,
+    #[serde(default = "Option::default")]
+    pub title: Option<String>,
+    pub width: f32,
+    pub height: f32,
+    /// Index of the pane on the right side.
+    #[serde(default = "Option::default")]
+    pub right: Option<usize>,
+    /// Index of the pane on the bottom side.
+    #[serde(default = "Option::default")]
+    pub down: Option<usize>,
+    /// Last lines of the pane as ANSI escape sequences.
+    #[serde(default = "String::default", skip_serializing_if = "String::is_empty")]
+    pub scrollback: String,
+    /// Program running instead of the shell.
+    #[serde(default = "Option::default")]
+    pub command: Option<Shell>,
+}
+
+impl Session {
+    #[inline]
+    pub fn path() -> PathBuf {
+        rio_backend::config::config_dir_path().join(SESSION_FILE_NAME)
+    }
+
+    /// Session stored in the configuration directory, if any.
+    pub fn load() -> Option<Session> {
+        Self::load_from(&Self::path())
+    }
+
+    fn load_from(path: &Path) -> Option<Session> {
+        let content = std::fs::read_to_string(path).ok()?;
+        match toml::from_str(&content) {
+            Ok(session) => Some(session),
+            Err(err) => {
+                tracing::warn!("unable to read session {path:?}: {err}");
+                None
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        self.save_to(&Self::path());
+    }
+
+    fn save_to(&self, path: &Path) {
+        let content = match toml::to_string(self) {
+            Ok(content) => content,
+            Err(err) => {
+                tracing::warn!("unable to serialize session: {err}");
+                return;
+            }
+        };
+
+        if let Err(err) = write_private(path, content.as_bytes()) {
+            tracing::warn!("unable to store session {path:?}: {err}");
+        }
+    }
+}
+
+/// Write a file only readable by the user, for files which often contain
+/// secrets like the scrollback of the panes or the search history.
+pub fn write_private(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+        options.mode(0o600);
+        let mut file = options.open(path)?;
+        // Files created by previous versions keep their permissions.
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+        file.write_all(content)
+    }
+
+    #[cfg(not(unix))]
+    options.open(path)?.write_all(content)
+}
+
+impl TabSession {
+    /// Splits of a tab, keeping the command of the panes which do not run
+    /// the `shell` and up to `scrollback_lines` of their last lines.
+    pub fn new<T: EventListener>(
+        grid: &ContextGrid<T>,
+        shell: &Shell,
+        scrollback_lines: usize,
+    ) -> TabSession {
+        let sizes = grid.split_sizes();
+        let panes = grid
+            .contexts()
+            .iter()
+            .zip(sizes)
+            .map(|(item, (width, height))| PaneSession {
+                right: item.right(),
+                down: item.down(),
+                width,
+                height,
+                ..PaneSession::new(item.context(), shell, scrollback_lines)
+            })
+            .collect();
+
+        TabSession {
+            width: grid.width,
+            height: grid.height,
+            current: grid.current,
+            panes,
+        }
+    }
+}
+
+impl PaneSession {
+    fn new<T: EventListener>(
+        context: &Context<T>,
+        shell: &Shell,
+        scrollback_lines: usize,
+    ) -> PaneSession {
+        let working_dir = context.current_directory();
+        let terminal = context.terminal.lock();
+
+        PaneSession {
+            working_dir,
+            title: Some(terminal.title.to_owned()).filter(|title| !title.is_empty()),
+            scrollback: terminal.last_lines_to_ansi(scrollback_lines),
+            command: Some(context.shell.clone()).filter(|command| command != shell),
+            ..PaneSession::default()
+        }
+    }
+}
+
+impl From<&PaneSession> for PaneConfig {
+    fn from(pane: &PaneSession) -> PaneConfig {
+        PaneConfig {
+            shell: pane.command.clone(),
+            // Directories removed since the session was saved can't be used.
+            working_dir: pane
+                .working_dir
+                .clone()
+                .filter(|dir| Path::new(dir).is_dir()),
+            env_vars: vec![],
+            title: pane.title.clone(),
+            scrollback: pane.scrollback.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_file() {
+        let path = std::env::temp_dir()
+            .join(format!("test-rio-session-{}.toml", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(Session::load_from(&path), None);
+
+        let session = Session {
+            windows: vec![WindowSession {
+                width: 800,
+                height: 600,
+                x: Some(10),
+                y: None,
+                maximized: false,
+                current_tab: 1,
+                tabs: vec![
+                    TabSession {
+                        width: 1600.,
+                        height: 1200.,
+                        current: 1,
+                        panes: vec![
+                            PaneSession {
+                                working_dir: Some(String::from("/tmp")),
+                                title: Some(String::from("logs")),
+                                width: 798.,
+                                height: 1200.,
+                                right: Some(1),
+                                scrollback: String::from("\x1b[0;31merror\x1b[0m\r\n"),
+                                ..PaneSession::default()
+                            },
+                            PaneSession {
+                                width: 800.,
+                                height: 1200.,
+                                command: Some(Shell {
+                                    program: String::from("tail"),
+                                    args: vec![String::from("-f"), String::from("a b")],
+                                }),
+                                ..PaneSession::default()
+                            },
+                        ],
+                    },
+                    TabSession {
+                        width: 1600.,
+                        height: 1200.,
+                        panes: vec![PaneSession::default()],
+                        ..TabSession::default()
+                    },
+                ],
+            }],
+        };
+
+        session.save_to(&path);
+        assert_eq!(Session::load_from(&path), Some(session.clone()));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        // Missing directories are not used when the pane is restored.
+        let pane = &session.windows[0].tabs[0].panes[0];
+        let config = PaneConfig::from(pane);
+        assert_eq!(config.working_dir.as_deref(), Some("/tmp"));
+        assert_eq!(config.title.as_deref(), Some("logs"));
+        assert_eq!(config.scrollback, pane.scrollback);
+        let pane = PaneSession {
+            working_dir: Some(String::from("/rio/missing/directory")),
+            ..PaneSession::default()
+        };
+        assert_eq!(PaneConfig::from(&pane).working_dir, None);
+
+        std::fs::write(&path, "windows = 1").unwrap();
+        assert_eq!(Session::load_from(&path), None);
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -1,4 +1,5 @@
 use crate::event::{EventListener, RioEvent};
+use crate::screen::search_history::SEARCH_HISTORY_FILE_NAME;
 use notify::{Config, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use std::path::Path;
 use std::time::Duration;
@@ -32,6 +33,13 @@ pub fn configuration_file_updates<
 
         for res in rx {
             match res {
+                // The search history is stored next to the configuration.
+                Ok(event)
+                    if !event.paths.is_empty()
+                        && event.paths.iter().all(|path| {
+                            path.file_name()
+                                .is_some_and(|name| name == SEARCH_HISTORY_FILE_NAME)
+                        }) => {}
                 Ok(event) => match event.kind {
                     EventKind::Any
                     | EventKind::Create(_)
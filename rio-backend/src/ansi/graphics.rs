@@ -2,6 +2,7 @@
 // Alacritty is licensed under Apache 2.0 license.
 // https://github.com/alacritty/alacritty/pull/4763/files
 
+use crate::ansi::kitty_graphics_protocol::KittyGraphicsState;
 use crate::ansi::sixel;
 use crate::config::colors::ColorRgb;
 use crate::crosswords::grid::Dimensions;
@@ -147,6 +148,9 @@ pub struct Graphics {
 
     /// Current Sixel parser.
     pub sixel_parser: Option<Box<sixel::Parser>>,
+
+    /// Images and placements of the kitty graphics protocol.
+    pub kitty_graphics: KittyGraphicsState,
 }
 
 impl Graphics {
@@ -186,7 +190,10 @@ impl Graphics {
 
         for operation in texture_operations {
             match operation {
-                TextureOperation::Remove(id) => remove_queue.push(id),
+                TextureOperation::Remove(id) => {
+                    self.kitty_graphics.placements.remove(&id);
+                    remove_queue.push(id);
+                }
                 TextureOperation::ClearSubregion(cs) => clear_subregions.push(cs),
             }
         }
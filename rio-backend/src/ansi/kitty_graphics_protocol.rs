@@ -0,0 +1,1106 @@
+//! This module implements the [kitty graphics protocol].
+//!
+//! Commands are sent as APC strings, with a comma-separated list of
+//! `key=value` pairs as control data and a base64-encoded payload:
+//!
+//! ```notrust
+//! ESC _ G <control data> ; <payload> ESC \
+//! ```
+//!
+//! # Limitations
+//!
+//! * Animation commands (`a=f`, `a=a` and `a=c`) are not supported.
+//!
+//! * Shared memory transmission (`t=s`) is not supported.
+//!
+//! * The z-index is stored, so placements can be deleted by it, but every
+//!   graphic is rendered over the text.
+//!
+//! [kitty graphics protocol]: https://sw.kovidgoyal.net/kitty/graphics-protocol/
+
+use std::fmt::{self, Write};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::str;
+
+use base64::engine::general_purpose::STANDARD as Base64;
+use base64::Engine;
+use image_rs::{DynamicImage, ImageFormat, RgbImage, RgbaImage};
+use rustc_hash::FxHashMap;
+use sugarloaf::{
+    GraphicData, GraphicId, ResizeCommand, ResizeParameter, MAX_GRAPHIC_DIMENSIONS,
+};
+
+/// Maximum number of bytes accepted for a single image, before decoding.
+const MAX_PAYLOAD_SIZE: usize = MAX_GRAPHIC_DIMENSIONS[0] * MAX_GRAPHIC_DIMENSIONS[1] * 4;
+
+/// Maximum number of bytes used by the stored images (320MiB, as kitty).
+const MAX_STORAGE_SIZE: usize = 320 * 1024 * 1024;
+
+/// Temporary files must contain this string in their path to be deleted.
+const TEMP_FILE_MARKER: &str = "tty-graphics-protocol";
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Transmit image data (`a=t`).
+    #[default]
+    Transmit,
+
+    /// Transmit image data and display it (`a=T`).
+    TransmitAndDisplay,
+
+    /// Check if the image data could be loaded, without storing it (`a=q`).
+    Query,
+
+    /// Display an image previously transmitted (`a=p`).
+    Put,
+
+    /// Delete placements and, optionally, image data (`a=d`).
+    Delete,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// 3 bytes per pixel (`f=24`).
+    Rgb,
+
+    /// 4 bytes per pixel (`f=32`).
+    #[default]
+    Rgba,
+
+    /// PNG encoded data (`f=100`).
+    Png,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Transmission {
+    /// Data is sent in the payload (`t=d`).
+    #[default]
+    Direct,
+
+    /// Payload contains the path of a file with the data (`t=f`).
+    File,
+
+    /// Like `File`, but the file is deleted after reading it (`t=t`).
+    TempFile,
+
+    /// Payload contains the name of a shared memory object (`t=s`).
+    SharedMemory,
+}
+
+/// Placements affected by a delete command (`d` key).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DeleteTarget {
+    /// All placements visible on the screen (`d=a`).
+    #[default]
+    Visible,
+
+    /// Placements of an image, optionally filtered by placement id (`d=i`).
+    ImageId,
+
+    /// Placements of the newest image with an image number (`d=n`).
+    ImageNumber,
+
+    /// Placements intersecting the cursor (`d=c`).
+    Cursor,
+
+    /// Placements intersecting the cell at `x` and `y` (`d=p`).
+    Cell,
+
+    /// Placements intersecting the column `x` (`d=x`).
+    Column,
+
+    /// Placements intersecting the row `y` (`d=y`).
+    Row,
+
+    /// Placements with the z-index `z` (`d=z`).
+    ZIndex,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Delete {
+    pub target: DeleteTarget,
+
+    /// Free the image data when it has no placements left. Uppercase values
+    /// of the `d` key set this flag.
+    pub free: bool,
+}
+
+/// A command parsed from an APC string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KittyGraphicsCommand {
+    pub action: Action,
+
+    /// Suppress `OK` responses with `1`, and all responses with `2`.
+    pub quiet: u8,
+
+    pub format: Format,
+
+    pub transmission: Transmission,
+
+    /// Data is compressed with zlib (`o=z`).
+    pub compressed: bool,
+
+    /// More chunks of the payload will follow (`m=1`).
+    pub more_chunks: bool,
+
+    pub image_id: u32,
+
+    pub image_number: u32,
+
+    pub placement_id: u32,
+
+    /// Width, in pixels, of the raw image data.
+    pub width: u32,
+
+    /// Height, in pixels, of the raw image data.
+    pub height: u32,
+
+    /// Number of bytes to read from a file.
+    pub data_size: usize,
+
+    /// Offset to start reading from a file.
+    pub data_offset: usize,
+
+    /// Left edge of the source rectangle, or the column for deletions.
+    pub x: u32,
+
+    /// Top edge of the source rectangle, or the row for deletions.
+    pub y: u32,
+
+    /// Width of the source rectangle.
+    pub source_width: u32,
+
+    /// Height of the source rectangle.
+    pub source_height: u32,
+
+    /// Number of columns to display the image over.
+    pub columns: u32,
+
+    /// Number of rows to display the image over.
+    pub rows: u32,
+
+    /// Move the cursor after the image is displayed (`C=0`).
+    pub move_cursor: bool,
+
+    pub z_index: i32,
+
+    pub delete: Delete,
+
+    /// Base64-encoded payload.
+    pub payload: Vec<u8>,
+}
+
+impl Default for KittyGraphicsCommand {
+    fn default() -> Self {
+        Self {
+            action: Action::default(),
+            quiet: 0,
+            format: Format::default(),
+            transmission: Transmission::default(),
+            compressed: false,
+            more_chunks: false,
+            image_id: 0,
+            image_number: 0,
+            placement_id: 0,
+            width: 0,
+            height: 0,
+            data_size: 0,
+            data_offset: 0,
+            x: 0,
+            y: 0,
+            source_width: 0,
+            source_height: 0,
+            columns: 0,
+            rows: 0,
+            move_cursor: true,
+            z_index: 0,
+            delete: Delete::default(),
+            payload: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The command or its data is not valid.
+    InvalidData(String),
+
+    /// The payload is empty.
+    NoData,
+
+    /// The image, or the file with its data, does not exist.
+    NotFound(String),
+
+    /// The file with the image data can't be read.
+    BadFile(String),
+
+    /// Image dimensions are too big.
+    TooBigImage { width: usize, height: usize },
+
+    /// The command is valid, but Rio does not implement it.
+    Unsupported(&'static str),
+}
+
+impl Error {
+    /// Error code sent to the application.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::InvalidData(_) | Error::Unsupported(_) => "EINVAL",
+            Error::NoData => "ENODATA",
+            Error::NotFound(_) => "ENOENT",
+            Error::BadFile(_) => "EBADF",
+            Error::TooBigImage { .. } => "EFBIG",
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidData(reason) => write!(fmt, "Invalid data: {}", reason),
+            Error::NoData => write!(fmt, "No image data"),
+            Error::NotFound(what) => write!(fmt, "Not found: {}", what),
+            Error::BadFile(reason) => write!(fmt, "Can't read file: {}", reason),
+            Error::TooBigImage { width, height } => {
+                write!(
+                    fmt,
+                    "The image dimensions are too big ({}, {})",
+                    width, height
+                )
+            }
+            Error::Unsupported(what) => write!(fmt, "Unsupported: {}", what),
+        }
+    }
+}
+
+/// Parse the content of an APC string, without the leading `G`.
+pub fn parse(bytes: &[u8]) -> Option<KittyGraphicsCommand> {
+    let (control, payload) = match bytes.iter().position(|&b| b == b';') {
+        Some(separator) => (&bytes[..separator], &bytes[separator + 1..]),
+        None => (bytes, &[][..]),
+    };
+
+    let mut command = KittyGraphicsCommand {
+        payload: payload.to_vec(),
+        ..Default::default()
+    };
+
+    for pair in control.split(|&b| b == b',') {
+        if pair.is_empty() {
+            continue;
+        }
+
+        let (key, value) = match pair {
+            [key, b'=', value @ ..] => (*key, value),
+            _ => {
+                tracing::warn!("Invalid kitty graphics key: {:?}", pair);
+                return None;
+            }
+        };
+
+        match key {
+            b'a' => {
+                command.action = match value {
+                    b"t" => Action::Transmit,
+                    b"T" => Action::TransmitAndDisplay,
+                    b"q" => Action::Query,
+                    b"p" => Action::Put,
+                    b"d" => Action::Delete,
+                    _ => {
+                        tracing::warn!("Unsupported kitty graphics action: {:?}", value);
+                        return None;
+                    }
+                }
+            }
+            b'f' => {
+                command.format = match value {
+                    b"24" => Format::Rgb,
+                    b"32" => Format::Rgba,
+                    b"100" => Format::Png,
+                    _ => return None,
+                }
+            }
+            b't' => {
+                command.transmission = match value {
+                    b"d" => Transmission::Direct,
+                    b"f" => Transmission::File,
+                    b"t" => Transmission::TempFile,
+                    b"s" => Transmission::SharedMemory,
+                    _ => return None,
+                }
+            }
+            b'd' => {
+                let target = match value.to_ascii_lowercase().as_slice() {
+                    b"a" => DeleteTarget::Visible,
+                    b"i" => DeleteTarget::ImageId,
+                    b"n" => DeleteTarget::ImageNumber,
+                    b"c" => DeleteTarget::Cursor,
+                    b"p" => DeleteTarget::Cell,
+                    b"x" => DeleteTarget::Column,
+                    b"y" => DeleteTarget::Row,
+                    b"z" => DeleteTarget::ZIndex,
+                    _ => return None,
+                };
+
+                command.delete = Delete {
+                    target,
+                    free: value.iter().all(u8::is_ascii_uppercase),
+                };
+            }
+            b'o' => command.compressed = value == b"z",
+            b'm' => command.more_chunks = parse_number::<u8>(value)? == 1,
+            b'q' => command.quiet = parse_number(value)?,
+            b'i' => command.image_id = parse_number(value)?,
+            b'I' => command.image_number = parse_number(value)?,
+            b'p' => command.placement_id = parse_number(value)?,
+            b's' => command.width = parse_number(value)?,
+            b'v' => command.height = parse_number(value)?,
+            b'S' => command.data_size = parse_number(value)?,
+            b'O' => command.data_offset = parse_number(value)?,
+            b'x' => command.x = parse_number(value)?,
+            b'y' => command.y = parse_number(value)?,
+            b'w' => command.source_width = parse_number(value)?,
+            b'h' => command.source_height = parse_number(value)?,
+            b'c' => command.columns = parse_number(value)?,
+            b'r' => command.rows = parse_number(value)?,
+            b'C' => command.move_cursor = parse_number::<u8>(value)? == 0,
+            b'z' => command.z_index = parse_number(value)?,
+            // Keys for features not supported by Rio (like animations or
+            // relative placements) are ignored.
+            _ => (),
+        }
+    }
+
+    Some(command)
+}
+
+fn parse_number<T: str::FromStr>(value: &[u8]) -> Option<T> {
+    str::from_utf8(value).ok()?.parse().ok()
+}
+
+/// Build the response for a command, if the application expects one.
+///
+/// No response is sent when the command has no image id or number, or when
+/// the `q` key suppresses it.
+pub fn response(
+    command: &KittyGraphicsCommand,
+    image_id: u32,
+    result: &Result<(), Error>,
+) -> Option<String> {
+    if command.image_id == 0 && command.image_number == 0 {
+        return None;
+    }
+
+    match (result, command.quiet) {
+        (Ok(()), 1 | 2) | (Err(_), 2) => return None,
+        _ => (),
+    }
+
+    let mut text = String::from("\x1b_G");
+    let _ = write!(text, "i={}", image_id);
+    if command.image_number != 0 {
+        let _ = write!(text, ",I={}", command.image_number);
+    }
+    if command.placement_id != 0 {
+        let _ = write!(text, ",p={}", command.placement_id);
+    }
+
+    match result {
+        Ok(()) => text.push_str(";OK"),
+        Err(err) => {
+            let _ = write!(text, ";{}:{}", err.code(), err);
+        }
+    }
+
+    text.push_str("\x1b\\");
+    Some(text)
+}
+
+/// Image data transmitted by the application.
+#[derive(Debug)]
+pub struct KittyImage {
+    /// Image number given by the application, or `0`.
+    pub number: u32,
+
+    /// Decoded image.
+    pub data: GraphicData,
+
+    /// Used to find the newest image with a number, and to evict the oldest
+    /// images when the storage is full.
+    generation: u64,
+}
+
+/// A placement of an image in the grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KittyPlacement {
+    pub image_id: u32,
+
+    pub placement_id: u32,
+
+    pub z_index: i32,
+}
+
+/// State of the kitty graphics protocol for a terminal.
+#[derive(Debug, Default)]
+pub struct KittyGraphicsState {
+    /// Command waiting for the rest of its chunks.
+    chunked: Option<KittyGraphicsCommand>,
+
+    /// Images stored by their id.
+    pub images: FxHashMap<u32, KittyImage>,
+
+    /// Placements in the grid, indexed by the identifier of their graphic.
+    pub placements: FxHashMap<GraphicId, KittyPlacement>,
+
+    /// Last image id assigned by the terminal.
+    last_image_id: u32,
+
+    /// Last generation assigned to a stored image.
+    last_generation: u64,
+
+    /// Number of bytes used by the stored images.
+    storage_size: usize,
+}
+
+impl KittyGraphicsState {
+    /// Join chunked transmissions.
+    ///
+    /// Returns the full command when its last chunk has been received, or
+    /// when the command was not chunked.
+    pub fn join_chunks(
+        &mut self,
+        command: KittyGraphicsCommand,
+    ) -> Option<KittyGraphicsCommand> {
+        let mut pending = match self.chunked.take() {
+            Some(pending) => pending,
+            None if command.more_chunks => {
+                self.chunked = Some(command);
+                return None;
+            }
+            None => return Some(command),
+        };
+
+        // Following chunks only carry the `m` and `q` keys, so everything
+        // else is taken from the first one.
+        if pending.payload.len() + command.payload.len() > MAX_PAYLOAD_SIZE {
+            tracing::warn!("Kitty graphics payload is too big, discarding it");
+            return None;
+        }
+
+        pending.payload.extend_from_slice(&command.payload);
+        if command.more_chunks {
+            self.chunked = Some(pending);
+            None
+        } else {
+            pending.more_chunks = false;
+            Some(pending)
+        }
+    }
+
+    /// Load and store the image of a transmit command.
+    ///
+    /// Returns the id of the stored image. The image is not stored for
+    /// query commands.
+    pub fn transmit(&mut self, command: &KittyGraphicsCommand) -> Result<u32, Error> {
+        let data = load(command)?;
+
+        if command.action == Action::Query {
+            return Ok(command.image_id);
+        }
+
+        let image_id = if command.image_id != 0 {
+            command.image_id
+        } else {
+            self.next_image_id()
+        };
+
+        self.last_generation += 1;
+        self.storage_size += data.pixels.len();
+        let image = KittyImage {
+            number: command.image_number,
+            data,
+            generation: self.last_generation,
+        };
+
+        if let Some(old) = self.images.insert(image_id, image) {
+            self.storage_size -= old.data.pixels.len();
+        }
+
+        self.evict(image_id);
+
+        Ok(image_id)
+    }
+
+    /// Find the id of the image referenced by a command.
+    pub fn image_id(&self, command: &KittyGraphicsCommand) -> Option<u32> {
+        if command.image_id != 0 {
+            return self
+                .images
+                .contains_key(&command.image_id)
+                .then_some(command.image_id);
+        }
+
+        if command.image_number != 0 {
+            return self
+                .images
+                .iter()
+                .filter(|(_, image)| image.number == command.image_number)
+                .max_by_key(|(_, image)| image.generation)
+                .map(|(id, _)| *id);
+        }
+
+        None
+    }
+
+    /// Build the graphic to display a stored image, according to the source
+    /// rectangle and the cells requested in the command.
+    pub fn placement_graphic(
+        &self,
+        image_id: u32,
+        command: &KittyGraphicsCommand,
+    ) -> Result<GraphicData, Error> {
+        let image = self
+            .images
+            .get(&image_id)
+            .ok_or_else(|| Error::NotFound(format!("image {}", image_id)))?;
+
+        let mut graphic = crop(&image.data, command)?;
+
+        graphic.resize = match (command.columns, command.rows) {
+            (0, 0) => None,
+            (columns, rows) => {
+                let parameter = |cells| match cells {
+                    0 => ResizeParameter::Auto,
+                    cells => ResizeParameter::Cells(cells),
+                };
+
+                Some(ResizeCommand {
+                    width: parameter(columns),
+                    height: parameter(rows),
+                    preserve_aspect_ratio: false,
+                })
+            }
+        };
+
+        Ok(graphic)
+    }
+
+    /// Remove the data of an image.
+    pub fn remove_image(&mut self, image_id: u32) {
+        if let Some(image) = self.images.remove(&image_id) {
+            self.storage_size -= image.data.pixels.len();
+        }
+    }
+
+    fn next_image_id(&mut self) -> u32 {
+        // Ids assigned by the terminal start from the top of the range, so
+        // they are unlikely to collide with ids chosen by applications.
+        loop {
+            self.last_image_id = self.last_image_id.wrapping_add(1);
+            let image_id = u32::MAX - self.last_image_id;
+            if image_id != 0 && !self.images.contains_key(&image_id) {
+                return image_id;
+            }
+        }
+    }
+
+    /// Remove the oldest images until the storage has room again. The image
+    /// with `keep_id` is never removed.
+    fn evict(&mut self, keep_id: u32) {
+        while self.storage_size > MAX_STORAGE_SIZE {
+            let oldest = self
+                .images
+                .iter()
+                .filter(|(id, _)| **id != keep_id)
+                .min_by_key(|(_, image)| image.generation)
+                .map(|(id, _)| *id);
+
+            match oldest {
+                Some(image_id) => self.remove_image(image_id),
+                None => break,
+            }
+        }
+    }
+}
+
+/// Decode the image data of a transmit command.
+pub fn load(command: &KittyGraphicsCommand) -> Result<GraphicData, Error> {
+    if command.payload.is_empty() {
+        return Err(Error::NoData);
+    }
+
+    let payload = Base64
+        .decode(&command.payload)
+        .map_err(|err| Error::InvalidData(err.to_string()))?;
+
+    let data = match command.transmission {
+        Transmission::Direct => payload,
+        Transmission::File | Transmission::TempFile => {
+            let path = str::from_utf8(&payload)
+                .map_err(|_| Error::InvalidData(String::from("file path")))?;
+            read_file(command, Path::new(path))?
+        }
+        Transmission::SharedMemory => {
+            return Err(Error::Unsupported("shared memory transmission"))
+        }
+    };
+
+    let data = if command.compressed {
+        let mut decoder =
+            flate2::read::ZlibDecoder::new(&data[..]).take(MAX_PAYLOAD_SIZE as u64 + 1);
+        let mut decompressed = Vec::new();
+        decoder
+            .read_to_end(&mut decompressed)
+            .map_err(|err| Error::InvalidData(err.to_string()))?;
+        decompressed
+    } else {
+        data
+    };
+
+    if data.len() > MAX_PAYLOAD_SIZE {
+        return Err(Error::InvalidData(String::from("payload is too big")));
+    }
+
+    let image = match command.format {
+        Format::Png => image_rs::load_from_memory_with_format(&data, ImageFormat::Png)
+            .map_err(|err| Error::InvalidData(err.to_string()))?,
+        Format::Rgb | Format::Rgba => raw_image(command, data)?,
+    };
+
+    let (width, height) = (image.width() as usize, image.height() as usize);
+    if width > MAX_GRAPHIC_DIMENSIONS[0] || height > MAX_GRAPHIC_DIMENSIONS[1] {
+        return Err(Error::TooBigImage { width, height });
+    }
+
+    Ok(GraphicData::from_dynamic_image(GraphicId(0), image))
+}
+
+fn raw_image(
+    command: &KittyGraphicsCommand,
+    mut data: Vec<u8>,
+) -> Result<DynamicImage, Error> {
+    let (width, height) = (command.width as usize, command.height as usize);
+    if width == 0 || height == 0 {
+        return Err(Error::InvalidData(String::from("missing image dimensions")));
+    }
+
+    if width > MAX_GRAPHIC_DIMENSIONS[0] || height > MAX_GRAPHIC_DIMENSIONS[1] {
+        return Err(Error::TooBigImage { width, height });
+    }
+
+    let bytes_per_pixel = if command.format == Format::Rgb { 3 } else { 4 };
+    let expected = width * height * bytes_per_pixel;
+    if data.len() < expected {
+        return Err(Error::InvalidData(format!(
+            "expected {} bytes, got {}",
+            expected,
+            data.len()
+        )));
+    }
+    data.truncate(expected);
+
+    let image = if command.format == Format::Rgb {
+        RgbImage::from_raw(command.width, command.height, data)
+            .map(DynamicImage::ImageRgb8)
+    } else {
+        RgbaImage::from_raw(command.width, command.height, data)
+            .map(DynamicImage::ImageRgba8)
+    };
+
+    image.ok_or_else(|| Error::InvalidData(String::from("image dimensions")))
+}
+
+fn read_file(command: &KittyGraphicsCommand, path: &Path) -> Result<Vec<u8>, Error> {
+    // Errors are answered without the path or the system error, since the
+    // answer is written to the terminal.
+    let path = std::fs::canonicalize(path).map_err(|err| {
+        tracing::debug!("Can't open {}: {}", path.display(), err);
+        Error::NotFound(String::from("file"))
+    })?;
+
+    // Files of /proc and /sys claim to be regular, but are generated on read.
+    if is_pseudo_file(&path) {
+        return Err(Error::BadFile(String::from("not a regular file")));
+    }
+
+    let mut file = File::open(&path).map_err(|err| {
+        tracing::debug!("Can't open {}: {}", path.display(), err);
+        Error::NotFound(String::from("file"))
+    })?;
+
+    // Only regular files are accepted, to avoid blocking on devices or pipes.
+    let metadata = file.metadata().map_err(|err| read_error(&path, err))?;
+    if !metadata.is_file() {
+        return Err(Error::BadFile(String::from("not a regular file")));
+    }
+
+    if command.data_offset != 0 {
+        file.seek(SeekFrom::Start(command.data_offset as u64))
+            .map_err(|err| read_error(&path, err))?;
+    }
+
+    let limit = match command.data_size {
+        0 => MAX_PAYLOAD_SIZE,
+        size => size.min(MAX_PAYLOAD_SIZE),
+    };
+
+    let mut data = Vec::new();
+    file.take(limit as u64)
+        .read_to_end(&mut data)
+        .map_err(|err| read_error(&path, err))?;
+
+    if command.transmission == Transmission::TempFile && is_temp_file(&path) {
+        if let Err(err) = std::fs::remove_file(&path) {
+            tracing::warn!("Can't remove {}: {}", path.display(), err);
+        }
+    }
+
+    Ok(data)
+}
+
+fn read_error(path: &Path, err: std::io::Error) -> Error {
+    tracing::debug!("Can't read {}: {}", path.display(), err);
+    Error::BadFile(String::from("read error"))
+}
+
+/// Check if a canonical path is in the pseudo filesystems of Linux.
+fn is_pseudo_file(path: &Path) -> bool {
+    path.starts_with("/proc") || path.starts_with("/sys")
+}
+
+/// Check if a file can be deleted after a `t=t` transmission: the canonical
+/// path must be directly in a temporary directory, with the marker in its name.
+fn is_temp_file(path: &Path) -> bool {
+    let has_marker = path
+        .file_name()
+        .is_some_and(|name| name.to_string_lossy().contains(TEMP_FILE_MARKER));
+    let Some(parent) = path.parent() else {
+        return false;
+    };
+
+    let temp_dirs: [PathBuf; 3] = [
+        std::env::temp_dir(),
+        PathBuf::from("/tmp"),
+        PathBuf::from("/dev/shm"),
+    ];
+
+    has_marker
+        && temp_dirs.iter().any(|dir| {
+            std::fs::canonicalize(dir).is_ok_and(|dir| dir.as_path() == parent)
+        })
+}
+
+/// Extract the source rectangle of a command from an image.
+fn crop(
+    image: &GraphicData,
+    command: &KittyGraphicsCommand,
+) -> Result<GraphicData, Error> {
+    let x = command.x as usize;
+    let y = command.y as usize;
+
+    if x == 0 && y == 0 && command.source_width == 0 && command.source_height == 0 {
+        return Ok(image.clone());
+    }
+
+    if x >= image.width || y >= image.height {
+        return Err(Error::InvalidData(String::from("source rectangle")));
+    }
+
+    let width = match command.source_width as usize {
+        0 => image.width - x,
+        width => width.min(image.width - x),
+    };
+    let height = match command.source_height as usize {
+        0 => image.height - y,
+        height => height.min(image.height - y),
+    };
+
+    let bytes_per_pixel = image.pixels.len() / (image.width * image.height);
+    let mut pixels = Vec::with_capacity(width * height * bytes_per_pixel);
+    for row in y..y + height {
+        let start = (row * image.width + x) * bytes_per_pixel;
+        pixels.extend_from_slice(&image.pixels[start..start + width * bytes_per_pixel]);
+    }
+
+    Ok(GraphicData {
+        width,
+        height,
+        pixels,
+        ..image.clone()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rgba_command(width: u32, height: u32, pixels: &[u8]) -> KittyGraphicsCommand {
+        KittyGraphicsCommand {
+            action: Action::TransmitAndDisplay,
+            format: Format::Rgba,
+            width,
+            height,
+            payload: Base64.encode(pixels).into_bytes(),
+            ..Default::default()
+        }
+    }
+
+    fn code_of<T>(result: Result<T, Error>) -> &'static str {
+        result.err().map(|err| err.code()).unwrap_or("OK")
+    }
+
+    #[test]
+    fn parse_control_data() {
+        let command = parse(b"a=T,f=24,s=10,v=20,i=31,p=7,c=4,r=2,C=1,q=2;AAAA").unwrap();
+
+        assert_eq!(command.action, Action::TransmitAndDisplay);
+        assert_eq!(command.format, Format::Rgb);
+        assert_eq!(command.width, 10);
+        assert_eq!(command.height, 20);
+        assert_eq!(command.image_id, 31);
+        assert_eq!(command.placement_id, 7);
+        assert_eq!(command.columns, 4);
+        assert_eq!(command.rows, 2);
+        assert!(!command.move_cursor);
+        assert_eq!(command.quiet, 2);
+        assert_eq!(command.payload, b"AAAA");
+    }
+
+    #[test]
+    fn parse_defaults() {
+        let command = parse(b"i=1").unwrap();
+
+        assert_eq!(command.action, Action::Transmit);
+        assert_eq!(command.format, Format::Rgba);
+        assert_eq!(command.transmission, Transmission::Direct);
+        assert!(command.move_cursor);
+        assert!(command.payload.is_empty());
+    }
+
+    #[test]
+    fn parse_delete() {
+        let command = parse(b"a=d,d=I,i=3").unwrap();
+        assert_eq!(command.action, Action::Delete);
+        assert_eq!(
+            command.delete,
+            Delete {
+                target: DeleteTarget::ImageId,
+                free: true
+            }
+        );
+
+        let command = parse(b"a=d,d=p,x=3,y=4").unwrap();
+        assert_eq!(
+            command.delete,
+            Delete {
+                target: DeleteTarget::Cell,
+                free: false
+            }
+        );
+        assert_eq!((command.x, command.y), (3, 4));
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert!(parse(b"a=t,i=abc").is_none());
+        assert!(parse(b"a=x").is_none());
+        assert!(parse(b"invalid").is_none());
+    }
+
+    #[test]
+    fn join_chunked_payload() {
+        let mut state = KittyGraphicsState::default();
+
+        let first = parse(b"a=T,f=100,i=1,m=1;AAAA").unwrap();
+        assert!(state.join_chunks(first).is_none());
+
+        let middle = parse(b"m=1;BBBB").unwrap();
+        assert!(state.join_chunks(middle).is_none());
+
+        let last = parse(b"m=0;CC==").unwrap();
+        let command = state.join_chunks(last).unwrap();
+
+        assert_eq!(command.action, Action::TransmitAndDisplay);
+        assert_eq!(command.format, Format::Png);
+        assert_eq!(command.image_id, 1);
+        assert!(!command.more_chunks);
+        assert_eq!(command.payload, b"AAAABBBBCC==");
+    }
+
+    #[test]
+    fn load_raw_pixels() {
+        let pixels = [1, 2, 3, 4, 5, 6];
+        let command = KittyGraphicsCommand {
+            format: Format::Rgb,
+            ..rgba_command(2, 1, &pixels)
+        };
+
+        let graphic = load(&command).unwrap();
+        assert_eq!(graphic.width, 2);
+        assert_eq!(graphic.height, 1);
+        assert_eq!(graphic.pixels, vec![1, 2, 3, 255, 4, 5, 6, 255]);
+    }
+
+    #[test]
+    fn load_compressed_pixels() {
+        use flate2::write::ZlibEncoder;
+        use std::io::Write;
+
+        let pixels = [10; 2 * 2 * 4];
+        let mut encoder = ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&pixels).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let command = KittyGraphicsCommand {
+            compressed: true,
+            ..rgba_command(2, 2, &compressed)
+        };
+
+        let graphic = load(&command).unwrap();
+        assert_eq!(graphic.pixels, pixels.to_vec());
+    }
+
+    #[test]
+    fn load_errors() {
+        let command = rgba_command(2, 2, &[0; 4]);
+        assert!(matches!(load(&command), Err(Error::InvalidData(_))));
+
+        let command = KittyGraphicsCommand::default();
+        assert_eq!(load(&command), Err(Error::NoData));
+
+        let command = KittyGraphicsCommand {
+            transmission: Transmission::SharedMemory,
+            ..rgba_command(1, 1, b"name")
+        };
+        assert_eq!(code_of(load(&command)), "EINVAL");
+    }
+
+    #[test]
+    fn load_from_temp_file() {
+        let path = std::env::temp_dir().join(format!(
+            "{}-rio-test-{}",
+            TEMP_FILE_MARKER,
+            std::process::id()
+        ));
+        std::fs::write(&path, [0, 0, 0, 9, 9, 9, 9, 9]).unwrap();
+
+        let command = KittyGraphicsCommand {
+            transmission: Transmission::TempFile,
+            data_offset: 4,
+            ..rgba_command(1, 1, path.to_str().unwrap().as_bytes())
+        };
+
+        let graphic = load(&command).unwrap();
+        assert_eq!(graphic.pixels, vec![9, 9, 9, 9]);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn keep_files_outside_of_temp_dir() {
+        let id = std::process::id();
+        let dir = std::env::temp_dir().join(format!("{TEMP_FILE_MARKER}-rio-dir-{id}"));
+        std::fs::create_dir_all(&dir).unwrap();
+        let pixels = [9, 9, 9, 9];
+
+        // The file name of the canonical path has no marker.
+        let victim = std::env::temp_dir().join(format!("rio-kitty-victim-{id}"));
+        std::fs::write(&victim, pixels).unwrap();
+        let traversal = dir.join("..").join(victim.file_name().unwrap());
+
+        // The file is in a sub-directory of the temporary directory.
+        let nested = dir.join(format!("{TEMP_FILE_MARKER}-{id}"));
+        std::fs::write(&nested, pixels).unwrap();
+
+        for path in [&traversal, &nested] {
+            let command = KittyGraphicsCommand {
+                transmission: Transmission::TempFile,
+                ..rgba_command(1, 1, path.to_str().unwrap().as_bytes())
+            };
+            assert_eq!(load(&command).unwrap().pixels, pixels);
+            assert!(path.exists());
+        }
+
+        let _ = std::fs::remove_file(&victim);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn refuse_unreadable_files() {
+        let command = KittyGraphicsCommand {
+            transmission: Transmission::File,
+            ..rgba_command(1, 1, b"/nonexistent/rio/secret")
+        };
+        let error = load(&command).unwrap_err();
+        assert_eq!(error, Error::NotFound(String::from("file")));
+        assert!(!error.to_string().contains("secret"));
+
+        #[cfg(target_os = "linux")]
+        {
+            let command = KittyGraphicsCommand {
+                transmission: Transmission::File,
+                ..rgba_command(1, 1, b"/proc/self/environ")
+            };
+            assert_eq!(code_of(load(&command)), "EBADF");
+        }
+    }
+
+    #[test]
+    fn store_and_crop_images() {
+        let mut state = KittyGraphicsState::default();
+
+        // 2x2 image, each pixel with a different value.
+        let pixels: Vec<u8> = (0..4).flat_map(|p| [p; 4]).collect();
+        let command = KittyGraphicsCommand {
+            image_number: 5,
+            ..rgba_command(2, 2, &pixels)
+        };
+
+        let image_id = state.transmit(&command).unwrap();
+        assert_ne!(image_id, 0);
+        assert_eq!(state.image_id(&command), Some(image_id));
+
+        let put = KittyGraphicsCommand {
+            x: 1,
+            y: 1,
+            columns: 3,
+            ..Default::default()
+        };
+        let graphic = state.placement_graphic(image_id, &put).unwrap();
+        assert_eq!((graphic.width, graphic.height), (1, 1));
+        assert_eq!(graphic.pixels, vec![3; 4]);
+        assert_eq!(
+            graphic.resize.map(|resize| (resize.width, resize.height)),
+            Some((ResizeParameter::Cells(3), ResizeParameter::Auto))
+        );
+
+        state.remove_image(image_id);
+        assert_eq!(state.image_id(&command), None);
+        assert_eq!(state.storage_size, 0);
+    }
+
+    #[test]
+    fn build_responses() {
+        let command = parse(b"a=t,i=7").unwrap();
+        assert_eq!(
+            response(&command, 7, &Ok(())).as_deref(),
+            Some("\x1b_Gi=7;OK\x1b\\")
+        );
+
+        let command = parse(b"a=t,I=2").unwrap();
+        assert_eq!(
+            response(&command, 99, &Err(Error::NoData)).as_deref(),
+            Some("\x1b_Gi=99,I=2;ENODATA:No image data\x1b\\")
+        );
+
+        // No ids, or quiet mode.
+        let command = parse(b"a=t").unwrap();
+        assert_eq!(response(&command, 1, &Ok(())), None);
+        let command = parse(b"a=t,i=1,q=1").unwrap();
+        assert_eq!(response(&command, 1, &Ok(())), None);
+        assert!(response(&command, 1, &Err(Error::NoData)).is_some());
+        let command = parse(b"a=t,i=1,q=2").unwrap();
+        assert_eq!(response(&command, 1, &Err(Error::NoData)), None);
+    }
+}
@@ -5,8 +5,10 @@ pub mod charset;
 pub mod control;
 pub mod graphics;
 pub mod iterm2_image_protocol;
+pub mod kitty_graphics_protocol;
 pub mod mode;
 pub mod sixel;
+pub mod terminfo;
 
 #[derive(Default, Clone, Serialize, Deserialize, Copy, Debug, Eq, PartialEq)]
 pub enum CursorShape {
@@ -75,6 +77,31 @@ pub enum LineClearMode {
     All,
 }
 
+/// Rectangular area of the VT400 rectangle editing sequences, in 1-based
+/// coordinates relative to the origin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RectangularArea {
+    pub top: usize,
+    pub left: usize,
+    /// Bottom line, defaults to the last line.
+    pub bottom: Option<usize>,
+    /// Right column, defaults to the last column.
+    pub right: Option<usize>,
+}
+
+/// Shell integration marks reported through OSC 133.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptMark {
+    /// Start of a prompt (`A`).
+    PromptStart,
+    /// End of the prompt and start of the command input (`B`).
+    CommandStart,
+    /// End of the command input and start of its output (`C`).
+    OutputStart,
+    /// End of the command output (`D`).
+    CommandEnd,
+}
+
 bitflags! {
     /// A set of [`kitty keyboard protocol'] modes.
     ///
@@ -58,6 +58,7 @@ impl PrivateMode {
             7 => Self::Named(NamedPrivateMode::LineWrap),
             12 => Self::Named(NamedPrivateMode::BlinkingCursor),
             25 => Self::Named(NamedPrivateMode::ShowCursor),
+            69 => Self::Named(NamedPrivateMode::LeftRightMargin),
             1000 => Self::Named(NamedPrivateMode::ReportMouseClicks),
             1002 => Self::Named(NamedPrivateMode::ReportCellMouseMotion),
             1003 => Self::Named(NamedPrivateMode::ReportAllMouseMotion),
@@ -108,6 +109,11 @@ pub enum NamedPrivateMode {
     LineWrap = 7,
     BlinkingCursor = 12,
     ShowCursor = 25,
+    /// Enable left and right margins (DECLRMM).
+    ///
+    /// CSI ? 69 h -> allow setting margins with `CSI Pl ; Pr s` (DECSLRM).
+    /// CSI ? 69 l -> reset margins to the full width of the screen.
+    LeftRightMargin = 69,
     ReportMouseClicks = 1000,
     ReportCellMouseMotion = 1002,
     ReportAllMouseMotion = 1003,
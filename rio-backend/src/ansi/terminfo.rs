@@ -0,0 +1,281 @@
+// Capabilities of the terminfo entry shipped with Rio, used to answer
+// XTGETTCAP requests without depending on the terminfo database of the host.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+const TERMINFO_SOURCE: &str = include_str!("../../../misc/rio.terminfo");
+
+/// Name of the terminfo entry describing Rio.
+pub const TERMINFO_NAME: &str = "rio";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Capability {
+    Boolean,
+    Number(u32),
+    String(Vec<u8>),
+}
+
+/// Look up a capability of Rio's terminfo entry by its terminfo name.
+pub fn capability(name: &str) -> Option<&'static Capability> {
+    static CAPABILITIES: OnceLock<HashMap<String, Capability>> = OnceLock::new();
+    CAPABILITIES
+        .get_or_init(|| {
+            let entries = parse_entries(TERMINFO_SOURCE);
+            resolve(&entries, TERMINFO_NAME)
+        })
+        .get(name)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Field {
+    Capability(String, Capability),
+    Cancel(String),
+    Use(String),
+}
+
+/// Split a terminfo source into its entries, keyed by every name of the entry.
+fn parse_entries(source: &str) -> HashMap<String, Vec<Field>> {
+    let mut entries = HashMap::new();
+    let mut current: Option<(Vec<String>, String)> = None;
+
+    for line in source.lines() {
+        if line.trim_start().starts_with('#') || line.trim().is_empty() {
+            continue;
+        }
+
+        // Entries start at the first column and continue on indented lines,
+        // the indentation of continuation lines is not part of the values.
+        if !line.starts_with(char::is_whitespace) {
+            if let Some((names, body)) = current.take() {
+                insert_entry(&mut entries, names, &body);
+            }
+
+            let (names, body) = line.split_once(',').unwrap_or((line, ""));
+            let names = names.split('|').map(String::from).collect();
+            current = Some((names, body.trim().to_string()));
+        } else if let Some((_, body)) = current.as_mut() {
+            body.push_str(line.trim());
+        }
+    }
+
+    if let Some((names, body)) = current.take() {
+        insert_entry(&mut entries, names, &body);
+    }
+
+    entries
+}
+
+fn insert_entry(
+    entries: &mut HashMap<String, Vec<Field>>,
+    names: Vec<String>,
+    body: &str,
+) {
+    let fields: Vec<Field> = split_fields(body)
+        .iter()
+        .filter_map(|field| parse_field(field))
+        .collect();
+
+    // The last name is the description of the entry.
+    let aliases = names.len().saturating_sub(1).max(1);
+    for name in names.into_iter().take(aliases) {
+        entries.insert(name, fields.clone());
+    }
+}
+
+/// Split capabilities on commas, keeping escaped commas inside of values.
+fn split_fields(body: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut chars = body.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                field.push(c);
+                if let Some(next) = chars.next() {
+                    field.push(next);
+                }
+            }
+            ',' => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+
+    fields
+        .into_iter()
+        .map(|field| field.trim().to_string())
+        .filter(|field| !field.is_empty())
+        .collect()
+}
+
+fn parse_field(field: &str) -> Option<Field> {
+    if let Some((name, value)) = field.split_once('=') {
+        if name == "use" {
+            return Some(Field::Use(value.to_string()));
+        }
+
+        return Some(Field::Capability(
+            name.to_string(),
+            Capability::String(unescape(value)),
+        ));
+    }
+
+    if let Some((name, value)) = field.split_once('#') {
+        let number = match value.strip_prefix("0x") {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None if value.len() > 1 && value.starts_with('0') => {
+                u32::from_str_radix(&value[1..], 8).ok()?
+            }
+            None => value.parse().ok()?,
+        };
+        return Some(Field::Capability(
+            name.to_string(),
+            Capability::Number(number),
+        ));
+    }
+
+    if let Some(name) = field.strip_suffix('@') {
+        return Some(Field::Cancel(name.to_string()));
+    }
+
+    Some(Field::Capability(field.to_string(), Capability::Boolean))
+}
+
+/// Decode the escapes of a terminfo string capability.
+fn unescape(value: &str) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('E' | 'e') => bytes.push(0x1b),
+                Some('n' | 'l') => bytes.push(b'\n'),
+                Some('r') => bytes.push(b'\r'),
+                Some('t') => bytes.push(b'\t'),
+                Some('b') => bytes.push(0x08),
+                Some('f') => bytes.push(0x0c),
+                Some('s') => bytes.push(b' '),
+                Some(digit @ '0'..='7') => {
+                    let mut octal = digit.to_digit(8).unwrap_or_default();
+                    for _ in 0..2 {
+                        match chars.peek().and_then(|c| c.to_digit(8)) {
+                            Some(next) => {
+                                octal = octal * 8 + next;
+                                chars.next();
+                            }
+                            None => break,
+                        }
+                    }
+                    // A null byte is encoded as \200 by terminfo.
+                    bytes.push(if octal == 0 { 0x80 } else { octal as u8 });
+                }
+                Some(other) => push_char(&mut bytes, other),
+                None => bytes.push(b'\\'),
+            },
+            '^' => match chars.next() {
+                Some('?') => bytes.push(0x7f),
+                Some(control) => bytes.push((control as u8) & 0x1f),
+                None => bytes.push(b'^'),
+            },
+            _ => push_char(&mut bytes, c),
+        }
+    }
+
+    bytes
+}
+
+#[inline]
+fn push_char(bytes: &mut Vec<u8>, c: char) {
+    let mut buf = [0; 4];
+    bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+}
+
+/// Collect the capabilities of an entry, including the ones inherited with
+/// `use`. Capabilities of the entry itself take precedence over inherited
+/// ones and cancelled capabilities are never inherited.
+fn resolve(
+    entries: &HashMap<String, Vec<Field>>,
+    name: &str,
+) -> HashMap<String, Capability> {
+    let mut capabilities = HashMap::new();
+    let Some(fields) = entries.get(name) else {
+        return capabilities;
+    };
+
+    let mut cancelled = Vec::new();
+    for field in fields {
+        match field {
+            Field::Capability(name, value) => {
+                capabilities
+                    .entry(name.clone())
+                    .or_insert_with(|| value.clone());
+            }
+            Field::Cancel(name) => cancelled.push(name.clone()),
+            Field::Use(_) => {}
+        }
+    }
+
+    for field in fields {
+        if let Field::Use(parent) = field {
+            if parent == name {
+                continue;
+            }
+
+            for (name, value) in resolve(entries, parent) {
+                if !cancelled.contains(&name) {
+                    capabilities.entry(name).or_insert(value);
+                }
+            }
+        }
+    }
+
+    capabilities
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_terminfo_capabilities() {
+        assert_eq!(capability("am"), Some(&Capability::Boolean));
+        assert_eq!(capability("colors"), Some(&Capability::Number(256)));
+        assert_eq!(capability("pairs"), Some(&Capability::Number(0x7FFF)));
+        assert_eq!(capability("cols"), Some(&Capability::Number(80)));
+        assert_eq!(
+            capability("bold"),
+            Some(&Capability::String(b"\x1b[1m".to_vec()))
+        );
+        assert_eq!(
+            capability("bel"),
+            Some(&Capability::String(b"\x07".to_vec()))
+        );
+        assert_eq!(
+            capability("rs1"),
+            Some(&Capability::String(b"\x1bc\x1b]104\x07".to_vec()))
+        );
+        // Continuation lines are joined without their indentation.
+        assert_eq!(
+            capability("setaf"),
+            Some(&Capability::String(
+                b"\x1b[%?%p1%{8}%<%t3%p1%d%e%p1%{16}%<%t9%p1%{8}%-%d%e38;5;%p1%d%;m"
+                    .to_vec()
+            ))
+        );
+        // Cancelled in `rio`, so not inherited from `rio+base`.
+        assert_eq!(capability("setf"), None);
+        assert_eq!(capability("unknown"), None);
+    }
+
+    #[test]
+    fn test_terminfo_unescape() {
+        assert_eq!(unescape(r"\E[%i%p1%dG"), b"\x1b[%i%p1%dG");
+        assert_eq!(unescape(r"\Ec\E]104\007"), b"\x1bc\x1b]104\x07");
+        assert_eq!(unescape(r"rgb\:\,\\"), b"rgb:,\\");
+        assert_eq!(unescape("^G^?"), b"\x07\x7f");
+        assert_eq!(unescape(r"\0"), b"\x80");
+    }
+}
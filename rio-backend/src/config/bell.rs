@@ -0,0 +1,35 @@
+use crate::config::colors::{deserialize_to_arr, ColorArray, ColorBuilder, Format};
+use crate::config::Shell;
+use serde::{Deserialize, Serialize};
+
+#[inline]
+fn default_visual_color() -> ColorArray {
+    ColorBuilder::from_hex(String::from("#FFFFFF4D"), Format::SRGB0_1)
+        .unwrap()
+        .to_arr()
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct Bell {
+    /// Duration of the visual bell flash in milliseconds, zero disables it.
+    #[serde(default = "u64::default", rename = "visual-duration")]
+    pub visual_duration: u64,
+    #[serde(
+        deserialize_with = "deserialize_to_arr",
+        default = "default_visual_color",
+        rename = "visual-color"
+    )]
+    pub visual_color: ColorArray,
+    #[serde(default = "Option::default")]
+    pub command: Option<Shell>,
+}
+
+impl Default for Bell {
+    fn default() -> Bell {
+        Bell {
+            visual_duration: 0,
+            visual_color: default_visual_color(),
+            command: None,
+        }
+    }
+}
@@ -1,9 +1,20 @@
+use crate::config::defaults::default_sequence_timeout;
 use serde::{Deserialize, Serialize};
 
 // Examples:
 // { key = "w", mods: "super", action = "quit" }
 // Bytes[27, 91, 53, 126] is equivalent to "\x1b[5~"
 // { key = "Home", mods: "super | shift", bytes = [27, 91, 53, 126] }
+// Sequence of control + a then c
+// { key = "a", with = "control", then = [{ key = "c" }], action = "createtab" }
+
+/// Key of a key binding sequence, pressed after the previous ones.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct KeyStroke {
+    pub key: String,
+    #[serde(default = "String::default")]
+    pub with: String,
+}
 
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct KeyBinding {
@@ -18,13 +29,48 @@ pub struct KeyBinding {
     pub bytes: Vec<u8>,
     #[serde(default = "String::default")]
     pub mode: String,
+    #[serde(default = "Vec::default")]
+    pub then: Vec<KeyStroke>,
 }
 
 pub type KeyBindings = Vec<KeyBinding>;
 
-#[derive(Default, Debug, PartialEq, Clone, Serialize, Deserialize)]
+// Examples:
+// { button = "middle", action = "paste" }
+// { button = "left", with = "control", action = "openhyperlink" }
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct MouseBinding {
+    pub button: String,
+    #[serde(default = "String::default")]
+    pub with: String,
+    #[serde(default = "String::default")]
+    pub action: String,
+    #[serde(default = "String::default")]
+    pub mode: String,
+}
+
+pub type MouseBindings = Vec<MouseBinding>;
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Bindings {
+    #[serde(default = "Vec::default")]
     pub keys: KeyBindings,
+    #[serde(default = "Vec::default")]
+    pub mouse: MouseBindings,
+    /// Milliseconds to wait for the next key of a sequence.
+    #[serde(default = "default_sequence_timeout", rename = "sequence-timeout")]
+    pub sequence_timeout: u64,
+}
+
+impl Default for Bindings {
+    fn default() -> Bindings {
+        Bindings {
+            keys: Vec::default(),
+            mouse: Vec::default(),
+            sequence_timeout: default_sequence_timeout(),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -157,4 +203,57 @@ mod tests {
         assert_eq!(decoded.bindings.keys[5].action.to_owned(), "selectprevtab");
         assert!(decoded.bindings.keys[5].text.to_owned().is_empty());
     }
+
+    #[test]
+    fn test_mouse_bindings() {
+        let content = r#"
+            [bindings]
+            mouse = [
+                { button = 'middle', action = 'paste' },
+                { button = 'left', with = 'control', action = 'openhyperlink' },
+                { button = 'back', mode = '~vi', action = 'selectprevtab' },
+            ]
+        "#;
+
+        let decoded = toml::from_str::<Root>(content).unwrap();
+        assert!(decoded.bindings.keys.is_empty());
+        assert_eq!(decoded.bindings.mouse.len(), 3);
+        assert_eq!(decoded.bindings.mouse[0].button, "middle");
+        assert!(decoded.bindings.mouse[0].with.is_empty());
+        assert_eq!(decoded.bindings.mouse[0].action, "paste");
+        assert_eq!(decoded.bindings.mouse[1].button, "left");
+        assert_eq!(decoded.bindings.mouse[1].with, "control");
+        assert_eq!(decoded.bindings.mouse[1].action, "openhyperlink");
+        assert_eq!(decoded.bindings.mouse[2].mode, "~vi");
+    }
+
+    #[test]
+    fn test_key_sequences() {
+        let content = r#"
+            [bindings]
+            sequence-timeout = 500
+            keys = [
+                { key = 'a', with = 'control', then = [{ key = 'c' }], action = 'createtab' },
+                { key = 'a', with = 'control', then = [{ key = 'w' }, { key = 'n', with = 'shift' }], action = 'splitright' },
+            ]
+        "#;
+
+        let decoded = toml::from_str::<Root>(content).unwrap();
+        assert_eq!(decoded.bindings.sequence_timeout, 500);
+        assert_eq!(decoded.bindings.keys[0].key, "a");
+        assert_eq!(decoded.bindings.keys[0].with, "control");
+        assert_eq!(
+            decoded.bindings.keys[0].then,
+            vec![super::KeyStroke {
+                key: String::from("c"),
+                with: String::default(),
+            }]
+        );
+        assert_eq!(decoded.bindings.keys[1].then.len(), 2);
+        assert_eq!(decoded.bindings.keys[1].then[1].with, "shift");
+
+        let decoded = toml::from_str::<Root>("").unwrap();
+        assert_eq!(decoded.bindings.sequence_timeout, 1000);
+        assert!(decoded.bindings.keys.is_empty());
+    }
 }
@@ -300,3 +300,15 @@ pub fn search_focused_match_background() -> ColorArray {
 pub fn search_focused_match_foreground() -> ColorArray {
     [1., 1., 1., 1.]
 }
+#[inline]
+pub fn hint_background() -> ColorArray {
+    ColorBuilder::from_hex(String::from("#F4BF75"), Format::SRGB0_1)
+        .unwrap()
+        .to_arr()
+}
+#[inline]
+pub fn hint_foreground() -> ColorArray {
+    ColorBuilder::from_hex(String::from("#181818"), Format::SRGB0_1)
+        .unwrap()
+        .to_arr()
+}
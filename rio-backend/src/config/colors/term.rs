@@ -1,4 +1,4 @@
-use crate::config::colors::{ColorArray, ColorBuilder, ColorRgb, Format};
+use crate::config::colors::{ColorArray, ColorBuilder, ColorRgb, Colors, Format};
 use std::ops::{Index, IndexMut};
 
 use crate::config::colors::defaults;
@@ -93,6 +93,45 @@ impl From<&TermColors> for List {
     }
 }
 
+/// Terminal colors using the named colors of the configuration.
+impl From<&Colors> for List {
+    fn from(colors: &Colors) -> List {
+        let mut list = List::from(&TermColors::default());
+
+        list[NamedColor::Black] = colors.black;
+        list[NamedColor::Red] = colors.red;
+        list[NamedColor::Green] = colors.green;
+        list[NamedColor::Yellow] = colors.yellow;
+        list[NamedColor::Blue] = colors.blue;
+        list[NamedColor::Magenta] = colors.magenta;
+        list[NamedColor::Cyan] = colors.cyan;
+        list[NamedColor::White] = colors.white;
+        list[NamedColor::LightBlack] = colors.light_black;
+        list[NamedColor::LightRed] = colors.light_red;
+        list[NamedColor::LightGreen] = colors.light_green;
+        list[NamedColor::LightYellow] = colors.light_yellow;
+        list[NamedColor::LightBlue] = colors.light_blue;
+        list[NamedColor::LightMagenta] = colors.light_magenta;
+        list[NamedColor::LightCyan] = colors.light_cyan;
+        list[NamedColor::LightWhite] = colors.light_white;
+        list[NamedColor::LightForeground] = colors.light_foreground;
+        list[NamedColor::Foreground] = colors.foreground;
+        list[NamedColor::Background] = colors.background.0;
+        list[NamedColor::Cursor] = colors.cursor;
+        list[NamedColor::DimForeground] = colors.dim_foreground;
+        list[NamedColor::DimBlack] = colors.dim_black;
+        list[NamedColor::DimRed] = colors.dim_red;
+        list[NamedColor::DimGreen] = colors.dim_green;
+        list[NamedColor::DimYellow] = colors.dim_yellow;
+        list[NamedColor::DimBlue] = colors.dim_blue;
+        list[NamedColor::DimMagenta] = colors.dim_magenta;
+        list[NamedColor::DimCyan] = colors.dim_cyan;
+        list[NamedColor::DimWhite] = colors.dim_white;
+
+        list
+    }
+}
+
 impl List {
     pub fn fill_named(&mut self) {
         self[NamedColor::Black] = defaults::black();
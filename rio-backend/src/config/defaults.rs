@@ -15,6 +15,34 @@ pub fn default_cursor_interval() -> u64 {
     800
 }
 
+#[inline]
+pub fn default_scrollback_lines() -> usize {
+    10_000
+}
+
+#[inline]
+pub fn default_search_history_size() -> usize {
+    255
+}
+
+/// Maximum time in milliseconds before a synchronized update is aborted.
+#[inline]
+pub fn default_sync_timeout() -> u64 {
+    150
+}
+
+/// Time in milliseconds to wait for the next key of a key binding sequence.
+#[inline]
+pub fn default_sequence_timeout() -> u64 {
+    1000
+}
+
+/// Maximum number of bytes read in one synchronized update (2MiB).
+#[inline]
+pub fn default_sync_max_bytes() -> usize {
+    0x20_0000
+}
+
 #[inline]
 pub fn default_padding_y() -> [f32; 2] {
     [0., 0.]
@@ -208,6 +236,14 @@ pub fn default_config_file_content() -> String {
 #
 # confirm-before-quit = false
 
+# IPC socket
+#
+# Listen to the requests of "rio msg" on a Unix socket, whose path is
+# set in the RIO_SOCKET variable of the terminals. Not available on
+# Windows. Default is `false`
+#
+# ipc-socket = true
+
 # Cursor
 #
 # shape - Default cursor shape is 'block'
@@ -284,6 +320,12 @@ pub fn default_config_file_content() -> String {
 #
 # • filters: A list of paths to RetroArch slang shaders. Might not work with OpenGL.
 #
+# • sync-timeout: Maximum time in milliseconds to wait for the end of a
+#   synchronized update (mode 2026) before drawing. Default is 150.
+#
+# • sync-max-bytes: Maximum number of bytes buffered during a synchronized
+#   update before drawing. Default is 2097152 (2MiB).
+#
 # Example:
 # [renderer]
 # performance = "high"
@@ -291,6 +333,8 @@ pub fn default_config_file_content() -> String {
 # disable-unfocused-render = false
 # level = 1
 # filters = []
+# sync-timeout = 150
+# sync-max-bytes = 2097152
 
 # Keyboard
 #
@@ -304,6 +348,147 @@ pub fn default_config_file_content() -> String {
 # use-kitty-keyboard-protocol = false
 # disable-ctlseqs-alt = false
 
+# Notifications
+#
+# "policy" - When desktop notifications requested through OSC 9, OSC 777
+#   or OSC 99 are shown.
+#   • always
+#   • unfocused (default) - Only when the window or the tab is not focused.
+#   • never
+#
+# Example:
+# [notifications]
+# policy = "unfocused"
+
+# Scrollback
+#
+# "lines" - Maximum number of lines kept in the scrollback history,
+#   zero disables it. Default is 10000.
+#
+# Example:
+# [scrollback]
+# lines = 10000
+
+# Search
+#
+# "history-size" - Maximum number of search terms kept in the history,
+#   which is shared by all windows and stored in the "search_history"
+#   file of the configuration directory. Zero disables it. Default is 255.
+#
+# Example:
+# [search]
+# history-size = 255
+
+# Selection
+#
+# "semantic-escape-chars" - Characters separating the words selected
+#   with a double-click.
+# "smart-rules" - Regexes selected as a whole when double-clicking inside
+#   a match, like URLs, file paths and quoted strings. When several rules
+#   match, the one with the highest "priority" wins. Defining rules
+#   replaces the default ones.
+# "trim-block-whitespace" - Remove the trailing whitespace of each line
+#   copied from a block selection. Default is true.
+# "copy-on-select" - Store selections into the clipboard as well as the
+#   selection buffer. Default is true.
+#
+# Example:
+# [selection]
+# semantic-escape-chars = ",│`|:\"' ()[]{}<>\t"
+# trim-block-whitespace = true
+# copy-on-select = true
+#
+# [[selection.smart-rules]]
+# regex = "[0-9a-f]{7,40}"
+# priority = 40
+
+# Bell
+#
+# "visual-duration" - Duration in milliseconds of the flash shown when
+#   the bell rings, zero (default) disables it.
+# "visual-color" - Color of the flash, alpha is supported.
+# "command" - Program to run when the bell rings.
+#
+# Example:
+# [bell]
+# visual-duration = 150
+# visual-color = '#FFFFFF4D'
+# command = { program = "paplay", args = ["/usr/share/sounds/freedesktop/stereo/bell.oga"] }
+
+# Hints
+#
+# Hint mode labels the visible text matching a rule, typing a label
+# runs the action of the rule on the matched text.
+#
+# "alphabet" - Characters used for the labels.
+# "rules" - List of hints, each one with:
+#   "regex" - Regex matched against the visible text.
+#   "hyperlinks" - Also match hyperlinks set by applications (OSC 8).
+#   "post-processing" - Strip trailing delimiters from regex matches.
+#   "action" - One of "copy", "paste", "open" or "select".
+#   "command" - Program to run with the match, replaces "action".
+#   "binding" - Key binding to start the hint mode.
+#
+# By default URLs are opened with control + shift + o (super + shift + o on macOS).
+#
+# Example:
+# [hints]
+# alphabet = "jfkdls;ahgurieowpq"
+#
+# [[hints.rules]]
+# regex = "[0-9a-f]{7,40}"
+# action = "copy"
+# binding = { key = "h", with = "control | shift" }
+
+# Layouts
+#
+# Tabs and splits opened together with "rio --layout NAME", or in the
+# first window by setting "layout = NAME" at the top of this file.
+#
+# "name" - Name of the layout.
+# "tabs" - Tabs of the layout, each one is a pane.
+#
+# Each pane accepts:
+#   "command" - Program and arguments to run, the pane closes when it
+#     exits. The shell runs when unset.
+#   "working-dir" - Directory the program starts in.
+#   "env" - Variables set for the program, like ["RUST_LOG=debug"].
+#   "title" - Title shown until the program sets its own.
+#   "panes" - Panes the pane is split into, they inherit its
+#     working-dir, env and title.
+#   "split" - Direction of the panes, "right" (default) or "down".
+#   "ratio" - Size of the pane relative to its siblings. Default is 1.
+#
+# Example:
+# [[layouts]]
+# name = "dev"
+#
+# [[layouts.tabs]]
+# working-dir = "/home/user/app"
+# split = "down"
+# panes = [
+#   { command = "cargo run", ratio = 2 },
+#   { command = "tail -f log/app.log" },
+# ]
+#
+# [[layouts.tabs]]
+# command = "psql app"
+
+# Session
+#
+# "restore" - Reopen the windows, tabs and splits of the last session at
+#   startup, with the working directory, title and command of each pane.
+#   The session is saved in the "session.toml" file of the configuration
+#   directory when Rio quits or on the "SaveSession" action, and is not
+#   restored when a layout is opened. Default is false.
+# "scrollback-lines" - Lines of each pane kept in the session, replayed
+#   when it is restored. Default is 0.
+#
+# Example:
+# [session]
+# restore = true
+# scrollback-lines = 200
+
 # Fonts
 #
 # Configure fonts used by the terminal
@@ -453,7 +638,14 @@ pub fn default_config_file_content() -> String {
 # keys = [
 #   { key = "q", with = "super", action = "Quit" },
 #   # Bytes[27, 91, 53, 126] is equivalent to "\x1b[5~"
-#   { key = "home", with = "super | shift", bytes = [27, 91, 53, 126] }
+#   { key = "home", with = "super | shift", bytes = [27, 91, 53, 126] },
+#   # Sequence of control + a then c
+#   { key = "a", with = "control", then = [{ key = "c" }], action = "CreateTab" }
+# ]
+# sequence-timeout = 1000
+# mouse = [
+#   { button = "middle", action = "Paste" },
+#   { button = "left", with = "control", action = "OpenHyperlink" }
 # ]
 
 # Platform
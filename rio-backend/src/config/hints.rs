@@ -0,0 +1,90 @@
+use crate::config::defaults::default_bool_true;
+use crate::config::Shell;
+use serde::{Deserialize, Serialize};
+
+/// Characters used to label hint matches, the home row comes first.
+#[inline]
+fn default_alphabet() -> String {
+    String::from("jfkdls;ahgurieowpq")
+}
+
+/// Regex matching URLs, used by the default hint.
+pub const URL_REGEX: &str = "(ipfs:|ipns:|magnet:|mailto:|gemini://|gopher://|https://|http://|news:|file:|git://|ssh:|ftp://)[^\u{0000}-\u{001F}\u{007F}-\u{009F}<>\"\\s{-}\\^⟨⟩`]+";
+
+#[inline]
+fn default_rules() -> Vec<Hint> {
+    #[cfg(target_os = "macos")]
+    let with = String::from("super | shift");
+    #[cfg(not(target_os = "macos"))]
+    let with = String::from("control | shift");
+
+    vec![Hint {
+        regex: Some(String::from(URL_REGEX)),
+        hyperlinks: true,
+        post_processing: true,
+        action: HintAction::Open,
+        command: None,
+        binding: Some(HintBinding {
+            key: String::from("o"),
+            with,
+        }),
+    }]
+}
+
+/// What to do with the text of the selected hint.
+#[derive(Default, Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
+pub enum HintAction {
+    #[default]
+    #[serde(alias = "copy")]
+    Copy,
+    #[serde(alias = "paste")]
+    Paste,
+    #[serde(alias = "open")]
+    Open,
+    #[serde(alias = "select")]
+    Select,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct HintBinding {
+    pub key: String,
+    #[serde(default = "String::default")]
+    pub with: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct Hint {
+    /// Regex matched against the visible text.
+    #[serde(default = "Option::default")]
+    pub regex: Option<String>,
+    /// Also match the hyperlinks set with OSC 8.
+    #[serde(default = "bool::default")]
+    pub hyperlinks: bool,
+    /// Strip trailing delimiters and unbalanced brackets from regex matches.
+    #[serde(default = "default_bool_true", rename = "post-processing")]
+    pub post_processing: bool,
+    #[serde(default = "HintAction::default")]
+    pub action: HintAction,
+    /// Program called with the hint text as last argument, replaces `action`.
+    #[serde(default = "Option::default")]
+    pub command: Option<Shell>,
+    #[serde(default = "Option::default")]
+    pub binding: Option<HintBinding>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct Hints {
+    #[serde(default = "default_alphabet")]
+    pub alphabet: String,
+    #[serde(default = "default_rules")]
+    pub rules: Vec<Hint>,
+}
+
+impl Default for Hints {
+    fn default() -> Hints {
+        Hints {
+            alphabet: default_alphabet(),
+            rules: default_rules(),
+        }
+    }
+}
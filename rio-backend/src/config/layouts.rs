@@ -0,0 +1,158 @@
+use serde::{Deserialize, Serialize};
+
+#[inline]
+fn default_ratio() -> f32 {
+    1.0
+}
+
+/// Direction in which a pane is split into its panes.
+#[derive(Default, Clone, Serialize, Deserialize, Copy, Debug, PartialEq, Eq)]
+pub enum LayoutSplit {
+    #[default]
+    #[serde(alias = "right")]
+    Right,
+    #[serde(alias = "down")]
+    Down,
+}
+
+/// Named set of tabs, opened with `--layout NAME` or the `layout` property.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Layout {
+    pub name: String,
+    #[serde(default = "Vec::default")]
+    pub tabs: Vec<LayoutPane>,
+}
+
+/// Pane of a layout, either running a command or split into more panes.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LayoutPane {
+    /// Title of the pane, until the program running in it sets its own.
+    #[serde(default = "Option::default")]
+    pub title: Option<String>,
+    /// Program and arguments, the configured shell runs when it is unset.
+    #[serde(default = "Option::default")]
+    pub command: Option<String>,
+    #[serde(default = "Option::default", rename = "working-dir")]
+    pub working_dir: Option<String>,
+    /// Variables set for the program, written as `KEY=VALUE`.
+    #[serde(default = "Vec::default")]
+    pub env: Vec<String>,
+    /// Size of the pane relative to the other panes of its parent.
+    #[serde(default = "default_ratio")]
+    pub ratio: f32,
+    #[serde(default = "LayoutSplit::default")]
+    pub split: LayoutSplit,
+    #[serde(default = "Vec::default")]
+    pub panes: Vec<LayoutPane>,
+}
+
+impl Default for LayoutPane {
+    fn default() -> LayoutPane {
+        LayoutPane {
+            title: None,
+            command: None,
+            working_dir: None,
+            env: vec![],
+            ratio: default_ratio(),
+            split: LayoutSplit::default(),
+            panes: vec![],
+        }
+    }
+}
+
+impl LayoutPane {
+    /// Panes this one is split into, inheriting its title, working directory
+    /// and environment.
+    pub fn children(&self) -> Vec<LayoutPane> {
+        self.panes
+            .iter()
+            .map(|pane| {
+                let mut env = self.env.clone();
+                env.extend(pane.env.iter().cloned());
+
+                LayoutPane {
+                    title: pane.title.clone().or_else(|| self.title.clone()),
+                    working_dir: pane
+                        .working_dir
+                        .clone()
+                        .or_else(|| self.working_dir.clone()),
+                    env,
+                    ..pane.clone()
+                }
+            })
+            .collect()
+    }
+
+    /// Pane running in the top left split of this one.
+    pub fn first(&self) -> LayoutPane {
+        match self.children().first() {
+            Some(pane) => pane.first(),
+            None => self.clone(),
+        }
+    }
+
+    /// Variables of `env` split into their key and value, entries without a
+    /// `=` are ignored.
+    pub fn env_vars(&self) -> Vec<(String, String)> {
+        self.env
+            .iter()
+            .filter_map(|var| var.split_once('='))
+            .map(|(key, value)| (key.to_owned(), value.to_owned()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_pane_inheritance() {
+        let tab = LayoutPane {
+            title: Some(String::from("dev")),
+            working_dir: Some(String::from("/srv/app")),
+            env: vec![String::from("RUST_LOG=info"), String::from("PORT")],
+            panes: vec![
+                LayoutPane {
+                    split: LayoutSplit::Down,
+                    panes: vec![
+                        LayoutPane {
+                            command: Some(String::from("cargo run")),
+                            env: vec![String::from("RUST_LOG=debug")],
+                            ..LayoutPane::default()
+                        },
+                        LayoutPane::default(),
+                    ],
+                    ..LayoutPane::default()
+                },
+                LayoutPane {
+                    title: Some(String::from("db")),
+                    working_dir: Some(String::from("/tmp")),
+                    ..LayoutPane::default()
+                },
+            ],
+            ..LayoutPane::default()
+        };
+
+        let first = tab.first();
+        assert_eq!(first.command.as_deref(), Some("cargo run"));
+        assert_eq!(first.title.as_deref(), Some("dev"));
+        assert_eq!(first.working_dir.as_deref(), Some("/srv/app"));
+        // Later variables win when the pane is spawned.
+        assert_eq!(
+            first.env_vars(),
+            [
+                (String::from("RUST_LOG"), String::from("info")),
+                (String::from("RUST_LOG"), String::from("debug")),
+            ]
+        );
+
+        let children = tab.children();
+        assert_eq!(children.len(), 2);
+        assert_eq!(children[0].split, LayoutSplit::Down);
+        assert_eq!(children[0].children().len(), 2);
+        assert_eq!(children[1].title.as_deref(), Some("db"));
+        assert_eq!(children[1].working_dir.as_deref(), Some("/tmp"));
+        assert_eq!(children[1].command, None);
+    }
+}
@@ -1,18 +1,28 @@
+pub mod bell;
 pub mod bindings;
 pub mod colors;
 pub mod defaults;
+pub mod hints;
 pub mod keyboard;
+pub mod layouts;
 pub mod navigation;
+pub mod notifications;
 pub mod renderer;
+pub mod selection;
 pub mod theme;
 pub mod window;
 
 use crate::ansi::CursorShape;
+use crate::config::bell::Bell;
 use crate::config::bindings::Bindings;
 use crate::config::defaults::*;
+use crate::config::hints::Hints;
 use crate::config::keyboard::Keyboard;
+use crate::config::layouts::Layout;
 use crate::config::navigation::Navigation;
+use crate::config::notifications::Notifications;
 use crate::config::renderer::Renderer;
+use crate::config::selection::Selection;
 use crate::config::window::Window;
 use colors::Colors;
 use serde::{Deserialize, Serialize};
@@ -68,6 +78,44 @@ impl Default for Scroll {
     }
 }
 
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct Scrollback {
+    #[serde(default = "default_scrollback_lines")]
+    pub lines: usize,
+}
+
+impl Default for Scrollback {
+    fn default() -> Scrollback {
+        Scrollback {
+            lines: default_scrollback_lines(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct Search {
+    #[serde(default = "default_search_history_size", rename = "history-size")]
+    pub history_size: usize,
+}
+
+impl Default for Search {
+    fn default() -> Search {
+        Search {
+            history_size: default_search_history_size(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Clone)]
+pub struct Session {
+    /// Restore the windows, tabs and splits of the last session at startup.
+    #[serde(default = "bool::default")]
+    pub restore: bool,
+    /// Lines of each pane kept in the session.
+    #[serde(default = "usize::default", rename = "scrollback-lines")]
+    pub scrollback_lines: usize,
+}
+
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Developer {
     #[serde(default = "bool::default", rename = "enable-fps-counter")]
@@ -112,6 +160,8 @@ pub struct Config {
     pub theme: String,
     #[serde(default = "Scroll::default")]
     pub scroll: Scroll,
+    #[serde(default = "Scrollback::default")]
+    pub scrollback: Scrollback,
     #[serde(
         default = "Option::default",
         skip_serializing,
@@ -145,6 +195,9 @@ pub struct Config {
     pub ignore_selection_fg_color: bool,
     #[serde(default = "default_bool_true", rename = "confirm-before-quit")]
     pub confirm_before_quit: bool,
+    /// Listen to the requests of `rio msg` on a Unix socket.
+    #[serde(default = "bool::default", rename = "ipc-socket")]
+    pub ipc_socket: bool,
     #[serde(
         default = "bool::default",
         rename = "hide-mouse-cursor-when-typing",
@@ -153,6 +206,23 @@ pub struct Config {
     pub hide_cursor_when_typing: bool,
     #[serde(default = "Renderer::default")]
     pub renderer: Renderer,
+    #[serde(default = "Notifications::default")]
+    pub notifications: Notifications,
+    #[serde(default = "Bell::default")]
+    pub bell: Bell,
+    #[serde(default = "Hints::default")]
+    pub hints: Hints,
+    #[serde(default = "Search::default")]
+    pub search: Search,
+    #[serde(default = "Selection::default")]
+    pub selection: Selection,
+    #[serde(default = "Vec::default")]
+    pub layouts: Vec<Layout>,
+    #[serde(default = "Session::default")]
+    pub session: Session,
+    /// Name of the layout opened in the first window.
+    #[serde(default = "Option::default", skip_serializing)]
+    pub layout: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -189,6 +259,21 @@ pub fn config_file_content() -> String {
     default_config_file_content()
 }
 
+/// Merge the tables of `overrides` into the ones of `table`, other values
+/// are replaced.
+fn merge_tables(table: &mut toml::Table, overrides: toml::Table) {
+    for (key, value) in overrides {
+        match (table.get_mut(&key), value) {
+            (Some(toml::Value::Table(existing)), toml::Value::Table(value)) => {
+                merge_tables(existing, value);
+            }
+            (_, value) => {
+                table.insert(key, value);
+            }
+        }
+    }
+}
+
 #[inline]
 pub fn create_config_file(path: Option<PathBuf>) {
     let default_file_path = path.clone().unwrap_or(config_file_path());
@@ -355,85 +440,98 @@ impl Config {
     }
 
     pub fn try_load() -> Result<Self, ConfigError> {
+        Self::try_load_with_overrides(&[])
+    }
+
+    /// Load the configuration file with values written in TOML on top of
+    /// it, like `fonts.size = 18`.
+    pub fn try_load_with_overrides(overrides: &[String]) -> Result<Self, ConfigError> {
         let path = config_file_path();
-        if path.exists() {
-            match std::fs::read_to_string(path) {
-                Ok(content) => match toml::from_str::<Config>(&content) {
-                    Ok(mut decoded) => {
-                        let theme = &decoded.theme;
-                        let theme_path = config_dir_path().join("themes");
-                        if !theme.is_empty() {
-                            let path = theme_path.join(theme).with_extension("toml");
-                            match Config::load_theme(&path) {
-                                Ok(loaded_theme) => {
-                                    decoded.colors = loaded_theme.colors;
-                                }
-                                Err(err_message) => {
-                                    return Err(ConfigError::ErrLoadingTheme(
-                                        err_message,
-                                    ));
-                                }
-                            }
+        let content = if path.exists() {
+            std::fs::read_to_string(path)
+                .map_err(|err| ConfigError::ErrLoadingConfig(err.to_string()))?
+        } else if overrides.is_empty() {
+            return Err(ConfigError::PathNotFound);
+        } else {
+            String::new()
+        };
+
+        let decoded = if overrides.is_empty() {
+            toml::from_str::<Config>(&content)
+        } else {
+            Config::from_str_with_overrides(&content, overrides)
+        };
+
+        match decoded {
+            Ok(mut decoded) => {
+                let theme = &decoded.theme;
+                let theme_path = config_dir_path().join("themes");
+                if !theme.is_empty() {
+                    let path = theme_path.join(theme).with_extension("toml");
+                    match Config::load_theme(&path) {
+                        Ok(loaded_theme) => {
+                            decoded.colors = loaded_theme.colors;
+                        }
+                        Err(err_message) => {
+                            return Err(ConfigError::ErrLoadingTheme(err_message));
                         }
+                    }
+                }
 
-                        if let Some(adaptive_theme) = &decoded.adaptive_theme {
-                            let mut adaptive_colors = AdaptiveColors {
-                                dark: None,
-                                light: None,
-                            };
-
-                            let light_theme = &adaptive_theme.light;
-                            let path =
-                                theme_path.join(light_theme).with_extension("toml");
-                            match Config::load_theme(&path) {
-                                Ok(light_loaded_theme) => {
-                                    adaptive_colors.light =
-                                        Some(light_loaded_theme.colors)
-                                }
-                                Err(err_message) => {
-                                    warn!("failed to load light theme: {}", light_theme);
-                                    return Err(ConfigError::ErrLoadingTheme(
-                                        err_message,
-                                    ));
-                                }
-                            }
-
-                            let dark_theme = &adaptive_theme.dark;
-                            let path = theme_path.join(dark_theme).with_extension("toml");
-                            match Config::load_theme(&path) {
-                                Ok(dark_loaded_theme) => {
-                                    adaptive_colors.dark = Some(dark_loaded_theme.colors)
-                                }
-                                Err(err_message) => {
-                                    warn!("failed to load dark theme: {}", dark_theme);
-                                    return Err(ConfigError::ErrLoadingTheme(
-                                        err_message,
-                                    ));
-                                }
-                            }
-
-                            if adaptive_colors.light.is_some()
-                                && adaptive_colors.dark.is_some()
-                            {
-                                decoded.adaptive_colors = Some(adaptive_colors);
-                            }
+                if let Some(adaptive_theme) = &decoded.adaptive_theme {
+                    let mut adaptive_colors = AdaptiveColors {
+                        dark: None,
+                        light: None,
+                    };
+
+                    let light_theme = &adaptive_theme.light;
+                    let path = theme_path.join(light_theme).with_extension("toml");
+                    match Config::load_theme(&path) {
+                        Ok(light_loaded_theme) => {
+                            adaptive_colors.light = Some(light_loaded_theme.colors)
                         }
+                        Err(err_message) => {
+                            warn!("failed to load light theme: {}", light_theme);
+                            return Err(ConfigError::ErrLoadingTheme(err_message));
+                        }
+                    }
 
-                        Ok(decoded)
+                    let dark_theme = &adaptive_theme.dark;
+                    let path = theme_path.join(dark_theme).with_extension("toml");
+                    match Config::load_theme(&path) {
+                        Ok(dark_loaded_theme) => {
+                            adaptive_colors.dark = Some(dark_loaded_theme.colors)
+                        }
+                        Err(err_message) => {
+                            warn!("failed to load dark theme: {}", dark_theme);
+                            return Err(ConfigError::ErrLoadingTheme(err_message));
+                        }
                     }
-                    Err(err_message) => {
-                        Err(ConfigError::ErrLoadingConfig(err_message.to_string()))
+
+                    if adaptive_colors.light.is_some() && adaptive_colors.dark.is_some() {
+                        decoded.adaptive_colors = Some(adaptive_colors);
                     }
-                },
-                Err(err_message) => {
-                    Err(ConfigError::ErrLoadingConfig(err_message.to_string()))
                 }
+
+                Ok(decoded)
+            }
+            Err(err_message) => {
+                Err(ConfigError::ErrLoadingConfig(err_message.to_string()))
             }
-        } else {
-            Err(ConfigError::PathNotFound)
         }
     }
 
+    fn from_str_with_overrides(
+        content: &str,
+        overrides: &[String],
+    ) -> Result<Config, toml::de::Error> {
+        let mut table: toml::Table = toml::from_str(content)?;
+        for value in overrides {
+            merge_tables(&mut table, toml::from_str(value)?);
+        }
+        Config::deserialize(toml::Value::Table(table))
+    }
+
     pub fn overwrite_based_on_platform(&mut self) {
         #[cfg(windows)]
         if let Some(windows) = &self.platform.windows {
@@ -480,6 +578,7 @@ impl Default for Config {
             bindings: Bindings::default(),
             colors: Colors::default(),
             scroll: Scroll::default(),
+            scrollback: Scrollback::default(),
             keyboard: Keyboard::default(),
             developer: Developer::default(),
             env_vars: vec![],
@@ -498,7 +597,16 @@ impl Default for Config {
             working_dir: default_working_dir(),
             ignore_selection_fg_color: false,
             confirm_before_quit: true,
+            ipc_socket: false,
             hide_cursor_when_typing: false,
+            notifications: Notifications::default(),
+            bell: Bell::default(),
+            hints: Hints::default(),
+            search: Search::default(),
+            session: Session::default(),
+            selection: Selection::default(),
+            layouts: vec![],
+            layout: None,
         }
     }
 }
@@ -658,6 +766,26 @@ mod tests {
         assert_eq!(result.colors.cursor, colors::defaults::cursor());
     }
 
+    #[test]
+    fn test_change_config_renderer_sync() {
+        let result = create_temporary_config(
+            "change-renderer-sync",
+            r#"
+            [renderer]
+            sync-timeout = 500
+            sync-max-bytes = 1024
+        "#,
+        );
+
+        assert_eq!(result.renderer.sync_timeout, 500);
+        assert_eq!(result.renderer.sync_max_bytes, 1024);
+        assert_eq!(result.renderer.performance, renderer::Performance::High);
+        assert_eq!(
+            renderer::Renderer::default().sync_timeout,
+            default_sync_timeout()
+        );
+    }
+
     #[test]
     fn test_change_config_environment_variables() {
         let result = create_temporary_config(
@@ -973,6 +1101,289 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_notifications() {
+        let result = create_temporary_config(
+            "change-notifications",
+            r#"
+            [notifications]
+            policy = "always"
+        "#,
+        );
+
+        assert_eq!(
+            result.notifications.policy,
+            notifications::NotificationPolicy::Always
+        );
+    }
+
+    #[test]
+    fn test_scrollback() {
+        let result = create_temporary_config(
+            "change-scrollback",
+            r#"
+            [scrollback]
+            lines = 1000000000
+        "#,
+        );
+        assert_eq!(result.scrollback.lines, 1_000_000_000);
+
+        let result = create_temporary_config(
+            "disable-scrollback",
+            r#"
+            [scrollback]
+            lines = 0
+        "#,
+        );
+        assert_eq!(result.scrollback.lines, 0);
+    }
+
+    #[test]
+    fn test_search() {
+        let result = create_temporary_config("default-search", "");
+        assert_eq!(result.search.history_size, 255);
+
+        let result = create_temporary_config(
+            "change-search",
+            r#"
+            [search]
+            history-size = 20
+        "#,
+        );
+        assert_eq!(result.search.history_size, 20);
+    }
+
+    #[test]
+    fn test_config_overrides() {
+        let content = r#"
+            confirm-before-quit = false
+
+            [fonts]
+            size = 14
+            family = "cascadiacode"
+        "#;
+
+        let config = Config::from_str_with_overrides(
+            content,
+            &[
+                String::from("fonts.size = 18"),
+                String::from("[scrollback]\nlines = 20"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(config.fonts.size, 18.0);
+        assert_eq!(config.fonts.family.as_deref(), Some("cascadiacode"));
+        assert!(!config.confirm_before_quit);
+        assert_eq!(config.scrollback.lines, 20);
+        assert!(!config.ipc_socket);
+
+        // Later values win.
+        let config = Config::from_str_with_overrides(
+            content,
+            &[
+                String::from("confirm-before-quit = true"),
+                String::from("confirm-before-quit = false"),
+            ],
+        )
+        .unwrap();
+        assert!(!config.confirm_before_quit);
+
+        assert!(
+            Config::from_str_with_overrides(content, &[String::from("fonts.size")])
+                .is_err()
+        );
+        assert!(Config::from_str_with_overrides(
+            content,
+            &[String::from("fonts.size = \"large\"")]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_session() {
+        let result = create_temporary_config("default-session", "");
+        assert!(!result.session.restore);
+        assert_eq!(result.session.scrollback_lines, 0);
+
+        let result = create_temporary_config(
+            "change-session",
+            r#"
+            [session]
+            restore = true
+            scrollback-lines = 200
+        "#,
+        );
+        assert!(result.session.restore);
+        assert_eq!(result.session.scrollback_lines, 200);
+    }
+
+    #[test]
+    fn test_hints() {
+        let result = create_temporary_config(
+            "change-hints",
+            r#"
+            [hints]
+            alphabet = "asdf"
+
+            [[hints.rules]]
+            regex = "[0-9a-f]{7,40}"
+            action = "copy"
+            binding = { key = "h", with = "control | shift" }
+
+            [[hints.rules]]
+            hyperlinks = true
+            command = { program = "xdg-open" }
+        "#,
+        );
+
+        assert_eq!(result.hints.alphabet, "asdf");
+        assert_eq!(result.hints.rules.len(), 2);
+        let sha = &result.hints.rules[0];
+        assert_eq!(sha.regex.as_deref(), Some("[0-9a-f]{7,40}"));
+        assert_eq!(sha.action, hints::HintAction::Copy);
+        assert!(sha.post_processing);
+        assert!(!sha.hyperlinks);
+        assert_eq!(
+            sha.binding,
+            Some(hints::HintBinding {
+                key: String::from("h"),
+                with: String::from("control | shift"),
+            })
+        );
+        let link = &result.hints.rules[1];
+        assert!(link.regex.is_none());
+        assert!(link.hyperlinks);
+        assert_eq!(
+            link.command.as_ref().map(|c| c.program.as_str()),
+            Some("xdg-open")
+        );
+
+        let result = create_temporary_config("default-hints", "");
+        assert_eq!(result.hints, Hints::default());
+        assert_eq!(result.hints.rules[0].action, hints::HintAction::Open);
+    }
+
+    #[test]
+    fn test_selection() {
+        let result = create_temporary_config(
+            "change-selection",
+            r#"
+            [selection]
+            semantic-escape-chars = " ,;"
+            trim-block-whitespace = false
+            copy-on-select = false
+
+            [[selection.smart-rules]]
+            regex = "[0-9a-f]{7,40}"
+            priority = 5
+
+            [[selection.smart-rules]]
+            regex = "[A-Z]+-[0-9]+"
+        "#,
+        );
+
+        assert_eq!(result.selection.semantic_escape_chars, " ,;");
+        assert!(!result.selection.trim_block_whitespace);
+        assert!(!result.selection.copy_on_select);
+        assert_eq!(
+            result.selection.smart_rules,
+            vec![
+                selection::SmartRule {
+                    regex: String::from("[0-9a-f]{7,40}"),
+                    priority: 5,
+                },
+                selection::SmartRule {
+                    regex: String::from("[A-Z]+-[0-9]+"),
+                    priority: 0,
+                },
+            ]
+        );
+
+        let result = create_temporary_config("default-selection", "");
+        assert_eq!(result.selection, Selection::default());
+        assert!(result.selection.trim_block_whitespace);
+        assert!(result.selection.copy_on_select);
+        assert_eq!(result.selection.smart_rules.len(), 3);
+    }
+
+    #[test]
+    fn test_layouts() {
+        let result = create_temporary_config(
+            "change-layouts",
+            r#"
+            layout = "dev"
+
+            [[layouts]]
+            name = "dev"
+
+            [[layouts.tabs]]
+            title = "app"
+            working-dir = "/srv/app"
+            split = "down"
+
+            [[layouts.tabs.panes]]
+            ratio = 3
+            panes = [
+              { command = "cargo run", env = ["RUST_LOG=debug"] },
+              { command = "tail -f log/app.log", ratio = 0.5 },
+            ]
+
+            [[layouts.tabs.panes]]
+            command = "psql app"
+
+            [[layouts.tabs]]
+            command = "nvim"
+        "#,
+        );
+
+        assert_eq!(result.layout.as_deref(), Some("dev"));
+        assert_eq!(result.layouts.len(), 1);
+        let layout = &result.layouts[0];
+        assert_eq!(layout.name, "dev");
+        assert_eq!(layout.tabs.len(), 2);
+
+        let tab = &layout.tabs[0];
+        assert_eq!(tab.split, layouts::LayoutSplit::Down);
+        assert_eq!(tab.panes[0].ratio, 3.0);
+        assert_eq!(tab.panes[0].split, layouts::LayoutSplit::Right);
+        assert_eq!(tab.panes[0].panes[1].ratio, 0.5);
+        assert_eq!(tab.panes[1].ratio, 1.0);
+
+        let first = tab.first();
+        assert_eq!(first.command.as_deref(), Some("cargo run"));
+        assert_eq!(first.title.as_deref(), Some("app"));
+        assert_eq!(first.working_dir.as_deref(), Some("/srv/app"));
+        assert_eq!(first.env, [String::from("RUST_LOG=debug")]);
+        assert_eq!(layout.tabs[1].first().command.as_deref(), Some("nvim"));
+
+        let result = create_temporary_config("default-layouts", "");
+        assert!(result.layouts.is_empty());
+        assert_eq!(result.layout, None);
+    }
+
+    #[test]
+    fn test_bell() {
+        let result = create_temporary_config(
+            "change-bell",
+            r#"
+            [bell]
+            visual-duration = 150
+            visual-color = '#FF0000'
+            command = { program = "paplay", args = ["bell.oga"] }
+        "#,
+        );
+
+        assert_eq!(result.bell.visual_duration, 150);
+        assert_eq!(result.bell.visual_color, hex_to_color_arr("#FF0000"));
+        assert_eq!(
+            result.bell.command,
+            Some(Shell {
+                program: String::from("paplay"),
+                args: vec![String::from("bell.oga")],
+            })
+        );
+    }
+
     #[test]
     fn test_use_fork() {
         let result = create_temporary_config(
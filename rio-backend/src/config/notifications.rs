@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+/// When desktop notifications requested by the terminal should be shown.
+#[derive(Default, Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
+pub enum NotificationPolicy {
+    #[serde(alias = "always")]
+    Always,
+    #[default]
+    #[serde(alias = "unfocused")]
+    Unfocused,
+    #[serde(alias = "never")]
+    Never,
+}
+
+#[derive(Default, Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
+pub struct Notifications {
+    #[serde(default = "NotificationPolicy::default")]
+    pub policy: NotificationPolicy,
+}
@@ -1,3 +1,4 @@
+use crate::config::defaults::{default_sync_max_bytes, default_sync_timeout};
 use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 
@@ -15,6 +16,12 @@ pub struct Renderer {
     pub filters: Vec<String>,
     #[serde(default = "RendererStategy::default")]
     pub strategy: RendererStategy,
+    /// Milliseconds to wait for the end of a synchronized update.
+    #[serde(default = "default_sync_timeout", rename = "sync-timeout")]
+    pub sync_timeout: u64,
+    /// Bytes buffered during a synchronized update before drawing.
+    #[serde(default = "default_sync_max_bytes", rename = "sync-max-bytes")]
+    pub sync_max_bytes: usize,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Deserialize, Serialize)]
@@ -48,6 +55,8 @@ impl Default for Renderer {
             target_fps: None,
             filters: Vec::default(),
             strategy: RendererStategy::Events,
+            sync_timeout: default_sync_timeout(),
+            sync_max_bytes: default_sync_max_bytes(),
         }
     }
 }
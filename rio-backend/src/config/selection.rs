@@ -0,0 +1,70 @@
+use crate::config::defaults::default_bool_true;
+use crate::config::hints::URL_REGEX;
+use serde::{Deserialize, Serialize};
+
+/// Characters separating the words selected with a double-click.
+#[inline]
+pub fn default_semantic_escape_chars() -> String {
+    String::from(",│`|:\"' ()[]{}<>\t")
+}
+
+/// Regex matching absolute and relative file paths, like `~/.config/rio`.
+pub const PATH_REGEX: &str = r"[\w.~-]*(?:/[\w.@+~-]+)+/?";
+
+/// Regex matching single and double quoted strings.
+pub const QUOTED_REGEX: &str = r#""[^"]*"|'[^']*'"#;
+
+#[inline]
+fn default_smart_rules() -> Vec<SmartRule> {
+    vec![
+        SmartRule {
+            regex: String::from(URL_REGEX),
+            priority: 30,
+        },
+        SmartRule {
+            regex: String::from(PATH_REGEX),
+            priority: 20,
+        },
+        SmartRule {
+            regex: String::from(QUOTED_REGEX),
+            priority: 10,
+        },
+    ]
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct SmartRule {
+    /// Regex selected as a whole when double-clicking inside a match.
+    pub regex: String,
+    /// Rules with a higher priority win when several of them match.
+    #[serde(default = "i32::default")]
+    pub priority: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct Selection {
+    #[serde(
+        default = "default_semantic_escape_chars",
+        rename = "semantic-escape-chars"
+    )]
+    pub semantic_escape_chars: String,
+    #[serde(default = "default_smart_rules", rename = "smart-rules")]
+    pub smart_rules: Vec<SmartRule>,
+    /// Remove the whitespace at the end of the lines copied from a block selection.
+    #[serde(default = "default_bool_true", rename = "trim-block-whitespace")]
+    pub trim_block_whitespace: bool,
+    /// Store selections into the clipboard as well as the selection buffer.
+    #[serde(default = "default_bool_true", rename = "copy-on-select")]
+    pub copy_on_select: bool,
+}
+
+impl Default for Selection {
+    fn default() -> Selection {
+        Selection {
+            semantic_escape_chars: default_semantic_escape_chars(),
+            smart_rules: default_smart_rules(),
+            trim_block_whitespace: true,
+            copy_on_select: true,
+        }
+    }
+}
@@ -33,6 +33,10 @@ pub trait GridSquare: Sized {
     fn reset(&mut self, template: &Self);
     fn flags(&self) -> &Flags;
     fn flags_mut(&mut self) -> &mut Flags;
+
+    /// Character held by the square, stored apart when compressing rows.
+    fn character(&self) -> char;
+    fn set_character(&mut self, character: char);
 }
 
 #[derive(Debug, Clone)]
@@ -222,6 +226,9 @@ impl<T: GridSquare + Default + PartialEq + Clone> Grid<T> {
             // Rotate the entire line buffer upward.
             self.raw.rotate(-(positions as isize));
 
+            // Compress the lines which moved deep enough into history.
+            self.raw.compress_cold_rows(positions);
+
             // Swap the fixed lines at the bottom back into position.
             let screen_lines = self.screen_lines() as i32;
             for i in (region.end.0..screen_lines).rev().map(Line::from) {
@@ -322,6 +329,13 @@ impl<T> Grid<T> {
         self.display_offset = 0;
     }
 
+    /// Drop the squares expanded by reads of the compressed history, once a
+    /// search or any other read over the history is done.
+    #[inline]
+    pub fn release_expanded_rows(&mut self) {
+        self.raw.release_expanded_rows();
+    }
+
     /// This is used only for initializing after loading ref-tests.
     #[inline]
     #[allow(unused)]
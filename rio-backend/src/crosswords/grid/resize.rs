@@ -32,6 +32,9 @@ impl<T: GridSquare + Default + PartialEq + Clone> Grid<T> {
             Ordering::Equal => (),
         }
 
+        // Reflow expands every row, compress the history again.
+        self.raw.compress_cold_rows(self.history_size());
+
         // Restore template cell.
         self.cursor.template = template;
     }
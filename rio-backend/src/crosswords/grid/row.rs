@@ -7,12 +7,110 @@ use crate::crosswords::square::Flags;
 use crate::crosswords::square::ResetDiscriminant;
 use crate::crosswords::Column;
 use core::cmp::min;
+use std::cell::OnceCell;
 use std::cmp::max;
 use std::ops::{Index, IndexMut, Range, RangeFrom, RangeFull, RangeTo, RangeToInclusive};
-use std::{ptr, slice};
+use std::{mem, ptr, slice};
+
+/// Shell integration zone of a row, as reported through OSC 133.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SemanticZone {
+    #[default]
+    Unknown,
+    /// Row starts a prompt, the command is usually typed on the same row.
+    Prompt,
+    /// Row continues a multi-line prompt.
+    PromptContinuation,
+    /// Row is a continuation of the command input.
+    Command,
+    /// Row holds output of a command.
+    Output,
+}
+
+/// Squares of a row which moved far enough into history to be compressed.
+///
+/// Characters are stored apart from the rest of the squares, which are run-length
+/// encoded since consecutive squares usually share the same attributes.
+#[derive(Debug)]
+pub struct CompressedRow<T> {
+    len: usize,
+    chars: Box<str>,
+    runs: Box<[(T, u32)]>,
+
+    /// Expands the squares, captured on compression so reading a row does not
+    /// require any bound on `T`.
+    expand: fn(&CompressedRow<T>) -> Vec<T>,
+
+    /// Squares expanded by read accesses, dropped once compressed again.
+    expanded: OnceCell<Vec<T>>,
+}
+
+impl<T: GridSquare + Clone + PartialEq> CompressedRow<T> {
+    fn new(squares: Vec<T>) -> CompressedRow<T> {
+        let len = squares.len();
+        let mut chars = String::with_capacity(len);
+        let mut runs: Vec<(T, u32)> = Vec::new();
+
+        for mut square in squares {
+            chars.push(square.character());
+            square.set_character(' ');
+
+            match runs.last_mut() {
+                Some((attributes, count)) if *attributes == square => *count += 1,
+                _ => runs.push((square, 1)),
+            }
+        }
+
+        CompressedRow {
+            len,
+            chars: chars.into_boxed_str(),
+            runs: runs.into_boxed_slice(),
+            expand: Self::expand,
+            expanded: OnceCell::new(),
+        }
+    }
+
+    fn expand(&self) -> Vec<T> {
+        let mut squares = Vec::with_capacity(self.len);
+        let mut chars = self.chars.chars();
+
+        for (attributes, count) in self.runs.iter() {
+            for _ in 0..*count {
+                let mut square = attributes.clone();
+                if let Some(character) = chars.next() {
+                    square.set_character(character);
+                }
+                squares.push(square);
+            }
+        }
+
+        squares
+    }
+}
+
+impl<T> CompressedRow<T> {
+    #[inline]
+    fn squares(&self) -> &[T] {
+        self.expanded.get_or_init(|| (self.expand)(self))
+    }
+
+    #[inline]
+    fn into_squares(mut self) -> Vec<T> {
+        match self.expanded.take() {
+            Some(squares) => squares,
+            None => (self.expand)(&self),
+        }
+    }
+}
 
 /// A row in the grid.
-#[derive(Default, Clone, Debug)]
+///
+/// Rows can be compressed once they are deep in history. Reading a compressed row is
+/// transparent, however it must be decompressed before `inner` is used or any mutable
+/// access, which [`Storage`] takes care of.
+///
+/// [`Storage`]: super::storage::Storage
+#[derive(Default, Debug)]
 pub struct Row<T> {
     pub inner: Vec<T>,
 
@@ -21,11 +119,35 @@ pub struct Row<T> {
     /// This is the upper bound on the number of elements in the row, which have been modified
     /// since the last reset. All cells after this point are guaranteed to be equal.
     pub(crate) occ: usize,
+
+    /// Shell integration zone this row belongs to.
+    pub zone: SemanticZone,
+
+    /// Squares of the row while compressed, `inner` is empty in the meantime.
+    compressed: Option<Box<CompressedRow<T>>>,
 }
 
 impl<T: PartialEq> PartialEq for Row<T> {
     fn eq(&self, other: &Self) -> bool {
-        self.inner == other.inner
+        self.squares() == other.squares()
+    }
+}
+
+/// Cloned rows are always decompressed.
+impl<T: Clone> Clone for Row<T> {
+    fn clone(&self) -> Self {
+        let occ = if self.compressed.is_some() {
+            self.len()
+        } else {
+            self.occ
+        };
+
+        Row {
+            inner: self.squares().to_vec(),
+            occ,
+            zone: self.zone,
+            compressed: None,
+        }
     }
 }
 
@@ -51,7 +173,12 @@ impl<T: Clone + Default> Row<T> {
             inner.set_len(columns);
         }
 
-        Row { inner, occ: 0 }
+        Row {
+            inner,
+            occ: 0,
+            zone: SemanticZone::Unknown,
+            compressed: None,
+        }
     }
 
     /// Increase the number of columns in the row.
@@ -102,6 +229,8 @@ impl<T: Clone + Default> Row<T> {
     {
         debug_assert!(!self.inner.is_empty());
 
+        self.zone = SemanticZone::Unknown;
+
         // Mark all cells as dirty if template cell changed.
         let len = self.inner.len();
         if self.inner[len - 1].discriminant() != template.discriminant() {
@@ -121,17 +250,81 @@ impl<T: Clone + Default> Row<T> {
 impl<T> Row<T> {
     #[inline]
     pub fn from_vec(vec: Vec<T>, occ: usize) -> Row<T> {
-        Row { inner: vec, occ }
+        Row {
+            inner: vec,
+            occ,
+            zone: SemanticZone::Unknown,
+            compressed: None,
+        }
     }
 
     #[inline]
     pub fn len(&self) -> usize {
-        self.inner.len()
+        match &self.compressed {
+            Some(compressed) => compressed.len,
+            None => self.inner.len(),
+        }
+    }
+
+    /// Squares of the row, expanding them if the row is compressed.
+    #[inline]
+    pub fn squares(&self) -> &[T] {
+        match &self.compressed {
+            Some(compressed) => compressed.squares(),
+            None => &self.inner,
+        }
+    }
+
+    #[inline]
+    pub fn is_compressed(&self) -> bool {
+        self.compressed.is_some()
+    }
+
+    /// Whether the row is compressed and its squares were expanded by a read.
+    #[inline]
+    pub fn is_expanded(&self) -> bool {
+        self.compressed
+            .as_ref()
+            .is_some_and(|compressed| compressed.expanded.get().is_some())
+    }
+
+    /// Drop the squares expanded by reads of a compressed row.
+    #[inline]
+    pub fn release_expanded(&mut self) {
+        if let Some(compressed) = &mut self.compressed {
+            compressed.expanded.take();
+        }
+    }
+
+    /// Restore the squares of a compressed row into `inner`.
+    #[inline]
+    pub fn decompress(&mut self) {
+        if let Some(compressed) = self.compressed.take() {
+            self.inner = compressed.into_squares();
+            self.occ = self.inner.len();
+        }
+    }
+
+    /// Compress the squares of the row, or drop the squares expanded by reads if
+    /// it is already compressed.
+    pub fn compress(&mut self)
+    where
+        T: GridSquare + Clone + PartialEq,
+    {
+        match &mut self.compressed {
+            Some(compressed) => {
+                compressed.expanded.take();
+            }
+            None => {
+                let squares = mem::take(&mut self.inner);
+                self.compressed = Some(Box::new(CompressedRow::new(squares)));
+            }
+        }
     }
 
     #[inline]
     pub fn last(&self) -> Option<&T> {
-        self.inner.last()
+        self.squares().last()
     }
 
     #[inline]
@@ -171,7 +364,7 @@ impl<T> Row<T> {
     where
         T: GridSquare,
     {
-        self.inner.iter().all(GridSquare::is_empty)
+        self.squares().iter().all(GridSquare::is_empty)
     }
 }
 
@@ -181,7 +374,7 @@ impl<'a, T> IntoIterator for &'a Row<T> {
 
     #[inline]
     fn into_iter(self) -> slice::Iter<'a, T> {
-        self.inner.iter()
+        self.squares().iter()
     }
 }
 
@@ -201,7 +394,7 @@ impl<T> Index<Column> for Row<T> {
 
     #[inline]
     fn index(&self, index: Column) -> &T {
-        &self.inner[index.0]
+        &self.squares()[index.0]
     }
 }
 
@@ -218,7 +411,7 @@ impl<T> Index<Range<Column>> for Row<T> {
 
     #[inline]
     fn index(&self, index: Range<Column>) -> &[T] {
-        &self.inner[(index.start.0)..(index.end.0)]
+        &self.squares()[(index.start.0)..(index.end.0)]
     }
 }
 
@@ -235,7 +428,7 @@ impl<T> Index<RangeTo<Column>> for Row<T> {
 
     #[inline]
     fn index(&self, index: RangeTo<Column>) -> &[T] {
-        &self.inner[..(index.end.0)]
+        &self.squares()[..(index.end.0)]
     }
 }
 
@@ -252,7 +445,7 @@ impl<T> Index<RangeFrom<Column>> for Row<T> {
 
     #[inline]
     fn index(&self, index: RangeFrom<Column>) -> &[T] {
-        &self.inner[(index.start.0)..]
+        &self.squares()[(index.start.0)..]
     }
 }
 
@@ -269,7 +462,7 @@ impl<T> Index<RangeFull> for Row<T> {
 
     #[inline]
     fn index(&self, _: RangeFull) -> &[T] {
-        &self.inner[..]
+        self.squares()
     }
 }
 
@@ -286,7 +479,7 @@ impl<T> Index<RangeToInclusive<Column>> for Row<T> {
 
     #[inline]
     fn index(&self, index: RangeToInclusive<Column>) -> &[T] {
-        &self.inner[..=(index.end.0)]
+        &self.squares()[..=(index.end.0)]
     }
 }
 
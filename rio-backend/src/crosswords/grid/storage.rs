@@ -2,17 +2,24 @@
 // https://github.com/alacritty/alacritty/blob/e35e5ad14fce8456afdd89f2b392b9924bb27471/alacritty_terminal/src/grid/storage.rs
 // which is licensed under Apache 2.0 license.
 
-use std::cmp::{max, PartialEq};
+use std::cell::RefCell;
+use std::cmp::{max, min, PartialEq};
 use std::mem;
 use std::mem::MaybeUninit;
 use std::ops::{Index, IndexMut};
 
-use super::Row;
+use super::{GridSquare, Row};
 use crate::crosswords::Line;
 
 /// Maximum number of buffered lines outside of the grid for performance optimization.
 const MAX_CACHE_SIZE: usize = 1_000;
 
+/// Number of history lines above the visible ones which are never compressed.
+const HOT_HISTORY_SIZE: usize = 1_000;
+
+/// Number of rows visited by the compression sweep for each line moved into history.
+const COMPRESSION_SWEEP_FACTOR: usize = 8;
+
 /// A ring buffer for optimizing indexing and rotation.
 ///
 /// The [`Storage::rotate`] and [`Storage::rotate_down`] functions are fast modular additions on
@@ -50,6 +57,16 @@ pub struct Storage<T> {
     /// As long as `len` is bigger than `inner`, it is also possible to grow the scrollback buffer
     /// without any additional insertions.
     len: usize,
+
+    /// Index in the raw buffer where the compression sweep continues.
+    ///
+    /// The sweep compresses again cold rows which were expanded by a read or a
+    /// modification.
+    sweep: usize,
+
+    /// Raw indices of the compressed rows which were accessed for a read, and
+    /// likely expanded by it.
+    expanded: RefCell<Vec<usize>>,
 }
 
 impl<T: PartialEq> PartialEq for Storage<T> {
@@ -77,6 +94,8 @@ impl<T> Storage<T> {
             zero: 0,
             visible_lines,
             len: visible_lines,
+            sweep: 0,
+            expanded: RefCell::default(),
         }
     }
 
@@ -158,10 +177,11 @@ impl<T> Storage<T> {
     /// swap than going through slice::swap.
     ///
     /// The default implementation from swap generates 8 movups and 4 movaps
-    /// instructions. This implementation achieves the swap in only 8 movups
-    /// instructions.
+    /// instructions for a row of 4 qwords. This implementation achieves the
+    /// swap in only 8 movups instructions.
     pub fn swap(&mut self, a: Line, b: Line) {
-        debug_assert_eq!(mem::size_of::<Row<T>>(), mem::size_of::<usize>() * 4);
+        const QWORDS: usize = mem::size_of::<Row<()>>() / mem::size_of::<usize>();
+        debug_assert_eq!(mem::size_of::<Row<T>>(), mem::size_of::<usize>() * QWORDS);
 
         let a = self.compute_index(a);
         let b = self.compute_index(b);
@@ -177,7 +197,7 @@ impl<T> Storage<T> {
             //
             // The optimizer unrolls this loop and vectorizes it.
             let mut tmp: MaybeUninit<usize>;
-            for i in 0..4 {
+            for i in 0..QWORDS as isize {
                 tmp = *a_ptr.offset(i);
                 *a_ptr.offset(i) = *b_ptr.offset(i);
                 *b_ptr.offset(i) = tmp;
@@ -185,6 +205,48 @@ impl<T> Storage<T> {
         }
     }
 
+    /// Compress the history rows which are far from the visible lines.
+    ///
+    /// Should be called with the number of lines just moved into history, the rows
+    /// crossing into the cold part of the history are compressed right away while
+    /// the others are handled incrementally by a sweep over the buffer.
+    pub fn compress_cold_rows(&mut self, count: usize)
+    where
+        T: GridSquare + Clone + PartialEq,
+    {
+        let cold_start = self.visible_lines + HOT_HISTORY_SIZE;
+        if self.len <= cold_start {
+            return;
+        }
+
+        let inner_len = self.inner.len();
+        for offset in cold_start..min(cold_start + count, self.len) {
+            self.inner[(self.zero + offset) % inner_len].compress();
+        }
+
+        let sweep_len = min(count.saturating_mul(COMPRESSION_SWEEP_FACTOR), inner_len);
+        for _ in 0..sweep_len {
+            self.sweep = (self.sweep + 1) % inner_len;
+            let offset = (self.sweep + inner_len - self.zero) % inner_len;
+            if offset >= cold_start && offset < self.len {
+                self.inner[self.sweep].compress();
+            }
+        }
+    }
+
+    /// Drop the squares expanded by reads of the compressed rows.
+    ///
+    /// Reading a compressed row keeps its squares expanded until the sweep reaches
+    /// it, this should be called once a read over the history is done. Only the
+    /// rows accessed since the last call are visited.
+    pub fn release_expanded_rows(&mut self) {
+        for index in self.expanded.get_mut().drain(..) {
+            if let Some(row) = self.inner.get_mut(index) {
+                row.release_expanded();
+            }
+        }
+    }
+
     /// Rotate the grid, moving all lines up/down in history.
     #[inline]
     pub fn rotate(&mut self, count: isize) {
@@ -207,6 +269,7 @@ impl<T> Storage<T> {
     /// Update the raw storage buffer.
     #[inline]
     pub fn replace_inner(&mut self, vec: Vec<Row<T>>) {
+        self.expanded.get_mut().clear();
         self.len = vec.len();
         self.inner = vec;
         self.zero = 0;
@@ -221,6 +284,11 @@ impl<T> Storage<T> {
 
         mem::swap(&mut buffer, &mut self.inner);
         self.len = 0;
+        self.expanded.get_mut().clear();
+
+        for row in &mut buffer {
+            row.decompress();
+        }
 
         buffer
     }
@@ -254,6 +322,9 @@ impl<T> Storage<T> {
             return;
         }
 
+        // Raw indices of the expanded rows are about to change.
+        self.release_expanded_rows();
+
         self.inner.rotate_left(self.zero);
         self.zero = 0;
     }
@@ -265,7 +336,11 @@ impl<T> Index<Line> for Storage<T> {
     #[inline]
     fn index(&self, index: Line) -> &Self::Output {
         let index = self.compute_index(index);
-        &self.inner[index]
+        let row = &self.inner[index];
+        if row.is_compressed() && !row.is_expanded() {
+            self.expanded.borrow_mut().push(index);
+        }
+        row
     }
 }
 
@@ -273,7 +348,9 @@ impl<T> IndexMut<Line> for Storage<T> {
     #[inline]
     fn index_mut(&mut self, index: Line) -> &mut Self::Output {
         let index = self.compute_index(index);
-        &mut self.inner[index]
+        let row = &mut self.inner[index];
+        row.decompress();
+        row
     }
 }
 
@@ -282,6 +359,7 @@ mod tests {
     use crate::crosswords::grid::row::Row;
     use crate::crosswords::grid::storage::{Storage, MAX_CACHE_SIZE};
     use crate::crosswords::{Column, Line};
+    use std::cell::RefCell;
 
     #[test]
     fn with_capacity() {
@@ -348,6 +426,8 @@ mod tests {
             zero: 0,
             visible_lines: 3,
             len: 3,
+            sweep: 0,
+            expanded: RefCell::default(),
         };
 
         // Grow buffer.
@@ -359,6 +439,8 @@ mod tests {
             zero: 0,
             visible_lines: 4,
             len: 4,
+            sweep: 0,
+            expanded: RefCell::default(),
         };
         expected
             .inner
@@ -391,6 +473,8 @@ mod tests {
             zero: 1,
             visible_lines: 3,
             len: 3,
+            sweep: 0,
+            expanded: RefCell::default(),
         };
 
         // Grow buffer.
@@ -402,6 +486,8 @@ mod tests {
             zero: 0,
             visible_lines: 4,
             len: 4,
+            sweep: 0,
+            expanded: RefCell::default(),
         };
         expected
             .inner
@@ -431,6 +517,8 @@ mod tests {
             zero: 1,
             visible_lines: 3,
             len: 3,
+            sweep: 0,
+            expanded: RefCell::default(),
         };
 
         // Shrink buffer.
@@ -442,6 +530,8 @@ mod tests {
             zero: 1,
             visible_lines: 2,
             len: 2,
+            sweep: 0,
+            expanded: RefCell::default(),
         };
         assert_eq!(storage.visible_lines, expected.visible_lines);
         assert_eq!(storage.inner, expected.inner);
@@ -467,6 +557,8 @@ mod tests {
             zero: 0,
             visible_lines: 3,
             len: 3,
+            sweep: 0,
+            expanded: RefCell::default(),
         };
 
         // Shrink buffer.
@@ -478,6 +570,8 @@ mod tests {
             zero: 0,
             visible_lines: 2,
             len: 2,
+            sweep: 0,
+            expanded: RefCell::default(),
         };
         assert_eq!(storage.visible_lines, expected.visible_lines);
         assert_eq!(storage.inner, expected.inner);
@@ -516,6 +610,8 @@ mod tests {
             zero: 2,
             visible_lines: 6,
             len: 6,
+            sweep: 0,
+            expanded: RefCell::default(),
         };
 
         // Shrink buffer.
@@ -534,6 +630,8 @@ mod tests {
             zero: 2,
             visible_lines: 2,
             len: 2,
+            sweep: 0,
+            expanded: RefCell::default(),
         };
         assert_eq!(storage.visible_lines, expected.visible_lines);
         assert_eq!(storage.inner, expected.inner);
@@ -568,6 +666,8 @@ mod tests {
             zero: 2,
             visible_lines: 1,
             len: 2,
+            sweep: 0,
+            expanded: RefCell::default(),
         };
 
         // Truncate buffer.
@@ -579,6 +679,8 @@ mod tests {
             zero: 0,
             visible_lines: 1,
             len: 2,
+            sweep: 0,
+            expanded: RefCell::default(),
         };
         assert_eq!(storage.visible_lines, expected.visible_lines);
         assert_eq!(storage.inner, expected.inner);
@@ -603,6 +705,8 @@ mod tests {
             zero: 2,
             visible_lines: 1,
             len: 2,
+            sweep: 0,
+            expanded: RefCell::default(),
         };
 
         // Truncate buffer.
@@ -614,6 +718,8 @@ mod tests {
             zero: 0,
             visible_lines: 1,
             len: 2,
+            sweep: 0,
+            expanded: RefCell::default(),
         };
         assert_eq!(storage.visible_lines, expected.visible_lines);
         assert_eq!(storage.inner, expected.inner);
@@ -660,6 +766,8 @@ mod tests {
             zero: 2,
             visible_lines: 0,
             len: 6,
+            sweep: 0,
+            expanded: RefCell::default(),
         };
 
         // Shrink buffer.
@@ -678,6 +786,8 @@ mod tests {
             zero: 2,
             visible_lines: 0,
             len: 3,
+            sweep: 0,
+            expanded: RefCell::default(),
         };
         assert_eq!(storage.inner, shrinking_expected.inner);
         assert_eq!(storage.zero, shrinking_expected.zero);
@@ -699,6 +809,8 @@ mod tests {
             zero: 2,
             visible_lines: 0,
             len: 4,
+            sweep: 0,
+            expanded: RefCell::default(),
         };
 
         assert_eq!(storage.inner, growing_expected.inner);
@@ -721,6 +833,8 @@ mod tests {
             zero: 2,
             visible_lines: 0,
             len: 6,
+            sweep: 0,
+            expanded: RefCell::default(),
         };
 
         // Initialize additional lines.
@@ -743,6 +857,8 @@ mod tests {
             zero: 0,
             visible_lines: 0,
             len: 9,
+            sweep: 0,
+            expanded: RefCell::default(),
         };
 
         assert_eq!(storage.len, expected_storage.len);
@@ -767,6 +883,8 @@ mod tests {
             zero: 2,
             visible_lines: 25,
             len: 6,
+            sweep: 0,
+            expanded: RefCell::default(),
         };
 
         // Initialize additional lines.
@@ -787,6 +905,8 @@ mod tests {
             zero: 0,
             visible_lines: 25,
             len: 11,
+            sweep: 0,
+            expanded: RefCell::default(),
         };
 
         assert_eq!(storage.len, expected_storage.len);
@@ -801,6 +921,8 @@ mod tests {
             zero: 2,
             visible_lines: 0,
             len: 3,
+            sweep: 0,
+            expanded: RefCell::default(),
         };
 
         storage.rotate(2);
@@ -22,6 +22,14 @@ impl GridSquare for usize {
     fn flags_mut(&mut self) -> &mut Flags {
         unimplemented!();
     }
+
+    fn character(&self) -> char {
+        char::from_u32(*self as u32).unwrap_or_default()
+    }
+
+    fn set_character(&mut self, character: char) {
+        *self = character as usize;
+    }
 }
 
 // Scroll up moves lines upward.
@@ -353,6 +361,58 @@ fn shrink_reflow_disabled() {
     assert_eq!(grid[Line(0)][Column(1)], cell('2'));
 }
 
+#[test]
+fn compress_cold_history() {
+    let history = 1_500;
+    let mut grid = Grid::<Square>::new(2, 3, history);
+    let region = Line(0)..Line(2);
+
+    let row = |i: usize| {
+        let mut bold = cell(char::from_u32('a' as u32 + (i % 26) as u32).unwrap());
+        bold.flags.insert(Flags::BOLD);
+        [
+            bold.clone(),
+            bold,
+            cell(char::from_digit((i % 10) as u32, 10).unwrap()),
+        ]
+    };
+
+    let total = history + 2;
+    for i in 0..total {
+        for (column, square) in row(i).into_iter().enumerate() {
+            grid[Line(1)][Column(column)] = square;
+        }
+        grid.scroll_up(&region, 1);
+    }
+
+    assert_eq!(grid.history_size(), history);
+    let topmost = grid.topmost_line();
+    assert!(grid.raw[topmost].is_compressed());
+    assert!(!grid.raw[Line(-1)].is_compressed());
+
+    // Compressed rows read the same as before and are cloned decompressed.
+    for (i, line) in (topmost.0..=0).enumerate() {
+        let expected = row(total - 1 - history + i);
+        assert_eq!(grid[Line(line)][..], expected);
+        assert_eq!(grid[Line(line)].clone().inner, expected);
+    }
+
+    // Reads expand the compressed rows until they are released.
+    assert!(grid.raw[topmost].is_expanded());
+    grid.release_expanded_rows();
+    assert!(!grid.raw[topmost].is_expanded());
+    assert!(grid.raw[topmost].is_compressed());
+
+    // Mutable access decompresses the row.
+    grid[topmost][Column(2)] = cell('!');
+    assert!(!grid.raw[topmost].is_compressed());
+    assert_eq!(grid[topmost][Column(2)], cell('!'));
+
+    grid.update_history(0);
+    assert_eq!(grid.history_size(), 0);
+    assert_eq!(grid[Line(0)][..], row(total - 1));
+}
+
 // https://github.com/rust-lang/rust-clippy/pull/6375
 #[allow(clippy::all)]
 fn cell(c: char) -> Square {
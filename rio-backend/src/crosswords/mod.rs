@@ -19,19 +19,25 @@ pub mod grid;
 pub mod pos;
 pub mod search;
 pub mod square;
+pub mod styled_text;
 pub mod vi_mode;
 
 use crate::ansi::graphics::GraphicCell;
 use crate::ansi::graphics::Graphics;
 use crate::ansi::graphics::TextureRef;
 use crate::ansi::graphics::UpdateQueues;
+use crate::ansi::kitty_graphics_protocol::{
+    self, Action as KittyAction, DeleteTarget, KittyGraphicsCommand, KittyPlacement,
+};
 use crate::ansi::mode::NamedMode;
 use crate::ansi::mode::NamedPrivateMode;
 use crate::ansi::mode::PrivateMode;
 use crate::ansi::sixel;
+use crate::ansi::terminfo;
 use crate::ansi::{
     mode::Mode as AnsiMode, ClearMode, CursorShape, KeyboardModes,
-    KeyboardModesApplyBehavior, LineClearMode, TabulationClearMode,
+    KeyboardModesApplyBehavior, LineClearMode, PromptMark, RectangularArea,
+    TabulationClearMode,
 };
 use crate::clipboard::ClipboardType;
 use crate::config::colors::{
@@ -39,30 +45,33 @@ use crate::config::colors::{
     term::{List, TermColors},
     AnsiColor, ColorRgb,
 };
+use crate::config::defaults::default_scrollback_lines;
 use crate::crosswords::grid::{BidirectionalIterator, Dimensions, Grid, Scroll};
 use crate::event::WindowId;
 use crate::event::{EventListener, RioEvent};
-use crate::performer::handler::Handler;
-use crate::selection::{Selection, SelectionRange, SelectionType};
+use crate::performer::handler::{Handler, SyncAbort};
+use crate::selection::{Selection, SelectionConfig, SelectionRange, SelectionType};
 use attr::*;
 use base64::{engine::general_purpose, Engine as _};
 use bitflags::bitflags;
 use copa::Params;
-use grid::row::Row;
+use grid::row::{Row, SemanticZone};
 use pos::{
     Boundary, CharsetIndex, Column, Cursor, CursorState, Direction, Line, Pos, Side,
 };
 use square::{Hyperlink, LineLength, Square};
 use std::collections::HashSet;
 use std::mem;
-use std::ops::{Index, IndexMut, Range};
+use std::ops::{Index, IndexMut, Range, RangeInclusive};
 use std::option::Option;
+use std::path::PathBuf;
 use std::ptr;
 use std::sync::Arc;
-use sugarloaf::{GraphicData, MAX_GRAPHIC_DIMENSIONS};
+use styled_text::{StyledText, TextStyle};
+use sugarloaf::{GraphicData, GraphicId, MAX_GRAPHIC_DIMENSIONS};
 use tracing::{debug, info, trace, warn};
 use unicode_width::UnicodeWidthChar;
-use vi_mode::{ViModeCursor, ViMotion};
+use vi_mode::{ViInput, ViModeCursor, ViModeState, ViMotion, ViOperator};
 
 pub type NamedColor = colors::NamedColor;
 
@@ -99,6 +108,9 @@ bitflags! {
         const REPORT_ALTERNATE_KEYS   = 1 << 20;
         const REPORT_ALL_KEYS_AS_ESC  = 1 << 21;
         const REPORT_ASSOCIATED_TEXT  = 1 << 22;
+        const LEFT_RIGHT_MARGIN       = 1 << 23;
+        const RECTANGULAR_EXTENT      = 1 << 24;
+        const SYNC_UPDATE             = 1 << 25;
         const MOUSE_MODE = Self::MOUSE_REPORT_CLICK.bits() | Self::MOUSE_MOTION.bits() | Self::MOUSE_DRAG.bits();
         const KITTY_KEYBOARD_PROTOCOL = Self::DISAMBIGUATE_ESC_CODES.bits()
                                       | Self::REPORT_EVENT_TYPES.bits()
@@ -374,6 +386,78 @@ fn version_number(mut version: &str) -> usize {
     version_number
 }
 
+/// Hex encode bytes, as used by XTGETTCAP replies.
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut hex, byte| {
+        hex.push_str(&format!("{byte:02X}"));
+        hex
+    })
+}
+
+/// SGR parameter selecting a color, `base` is 30 for the foreground, 40 for the
+/// background and 50 for the underline color.
+fn sgr_color(color: AnsiColor, base: u16) -> Option<String> {
+    let extended = base + 8;
+    match color {
+        AnsiColor::Named(NamedColor::Foreground | NamedColor::Background) => None,
+        AnsiColor::Named(named) if (named as usize) < 8 && base != 50 => {
+            Some((base + named as u16).to_string())
+        }
+        AnsiColor::Named(named) if (named as usize) < 16 && base != 50 => {
+            Some((base + 60 + named as u16 - 8).to_string())
+        }
+        AnsiColor::Named(named) if (named as usize) < 16 => {
+            Some(format!("{extended}:5:{}", named as usize))
+        }
+        AnsiColor::Named(_) => None,
+        AnsiColor::Indexed(index) => Some(format!("{extended}:5:{index}")),
+        AnsiColor::Spec(rgb) => {
+            Some(format!("{extended}:2::{}:{}:{}", rgb.r, rgb.g, rgb.b))
+        }
+    }
+}
+
+/// SGR parameters reproducing the graphic rendition of a template.
+fn sgr_parameters(template: &Square) -> String {
+    let mut parameters = vec![String::from("0")];
+
+    let flags = [
+        (square::Flags::BOLD, "1"),
+        (square::Flags::DIM, "2"),
+        (square::Flags::ITALIC, "3"),
+        (square::Flags::UNDERLINE, "4"),
+        (square::Flags::DOUBLE_UNDERLINE, "4:2"),
+        (square::Flags::UNDERCURL, "4:3"),
+        (square::Flags::DOTTED_UNDERLINE, "4:4"),
+        (square::Flags::DASHED_UNDERLINE, "4:5"),
+        (square::Flags::INVERSE, "7"),
+        (square::Flags::HIDDEN, "8"),
+        (square::Flags::STRIKEOUT, "9"),
+    ];
+    for (flag, parameter) in flags {
+        if template.flags.contains(flag) {
+            parameters.push(String::from(parameter));
+        }
+    }
+
+    parameters.extend(sgr_color(template.fg, 30));
+    parameters.extend(sgr_color(template.bg, 40));
+    if let Some(color) = template.underline_color() {
+        parameters.extend(sgr_color(color, 50));
+    }
+
+    parameters.join(";")
+}
+
+/// Synchronized updates aborted before their end was received.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SyncMetrics {
+    /// Updates aborted because their end was not received in time.
+    pub timeouts: usize,
+    /// Updates aborted because they exceeded the buffer size.
+    pub overflows: usize,
+}
+
 // Max size of the window title stack.
 const TITLE_STACK_MAX_DEPTH: usize = 4096;
 
@@ -388,10 +472,12 @@ where
     active_charset: CharsetIndex,
     mode: Mode,
     pub vi_mode_cursor: ViModeCursor,
-    semantic_escape_chars: String,
+    vi_mode_state: ViModeState,
+    selection_config: SelectionConfig,
     pub grid: Grid<Square>,
     inactive_grid: Grid<Square>,
     scroll_region: Range<Line>,
+    left_right_margin: Range<Column>,
     tabs: TabStops,
     event_proxy: U,
     pub selection: Option<Selection>,
@@ -408,6 +494,15 @@ where
     title_stack: Vec<String>,
     hyperlink_re: regex::Regex,
 
+    // Shell integration zone new rows are marked with.
+    semantic_zone: SemanticZone,
+
+    /// Working directory reported by the shell through OSC 7.
+    pub current_directory: Option<PathBuf>,
+
+    /// Aborted synchronized updates, for the FPS counter.
+    pub sync_metrics: SyncMetrics,
+
     // The stack for the keyboard modes.
     keyboard_mode_stack: Vec<KeyboardModes>,
 
@@ -425,11 +520,11 @@ impl<U: EventListener> Crosswords<U> {
     ) -> Crosswords<U> {
         let cols = dimensions.columns();
         let rows = dimensions.screen_lines();
-        let grid = Grid::new(rows, cols, 10_000);
+        let grid = Grid::new(rows, cols, default_scrollback_lines());
         let alt = Grid::new(rows, cols, 0);
 
         let scroll_region = Line(0)..Line(rows as i32);
-        let semantic_escape_chars = String::from(",│`|:\"' ()[]{}<>\t");
+        let left_right_margin = Column(0)..Column(cols);
         let term_colors = TermColors::default();
         let colors = List::from(&term_colors);
         // Regex used for the default URL hint.
@@ -438,12 +533,14 @@ impl<U: EventListener> Crosswords<U> {
 
         Crosswords {
             vi_mode_cursor: ViModeCursor::new(grid.cursor.pos),
-            semantic_escape_chars,
+            vi_mode_state: ViModeState::default(),
+            selection_config: SelectionConfig::default(),
             selection: None,
             grid,
             inactive_grid: alt,
             active_charset: CharsetIndex::default(),
             scroll_region,
+            left_right_margin,
             event_proxy,
             colors,
             hyperlink_re: regex::Regex::new(url_regex).unwrap(),
@@ -461,6 +558,9 @@ impl<U: EventListener> Crosswords<U> {
             window_id,
             route_id,
             title_stack: Default::default(),
+            semantic_zone: SemanticZone::Unknown,
+            current_directory: None,
+            sync_metrics: SyncMetrics::default(),
             keyboard_mode_stack: Default::default(),
             inactive_keyboard_mode_stack: Default::default(),
         }
@@ -509,6 +609,79 @@ impl<U: EventListener> Crosswords<U> {
         }
     }
 
+    /// Scroll the display to the closest prompt from the top of the viewport.
+    pub fn scroll_to_prompt(&mut self, direction: Direction) {
+        let display_offset = self.grid.display_offset() as i32;
+        match self.prompt_line(Line(-display_offset), direction) {
+            Some(line) => {
+                let delta = std::cmp::max(-line.0, 0) - display_offset;
+                self.scroll_display(Scroll::Delta(delta));
+            }
+            None if direction == Direction::Right => {
+                self.scroll_display(Scroll::Bottom);
+            }
+            None => (),
+        }
+    }
+
+    /// Find the closest line starting a prompt before or after `line`.
+    pub fn prompt_line(&self, line: Line, direction: Direction) -> Option<Line> {
+        let is_prompt = |line: &Line| self.grid[*line].zone == SemanticZone::Prompt;
+        match direction {
+            Direction::Left => (self.grid.topmost_line().0..line.0)
+                .rev()
+                .map(Line::from)
+                .find(is_prompt),
+            Direction::Right => (line.0 + 1..=self.grid.bottommost_line().0)
+                .map(Line::from)
+                .find(is_prompt),
+        }
+    }
+
+    /// Lines holding the output of the last command.
+    pub fn last_command_output(&self) -> Option<RangeInclusive<Line>> {
+        let topmost_line = self.grid.topmost_line();
+        let mut line = self.grid.cursor.pos.row;
+
+        // Look behind the prompt at the cursor, reaching another prompt first
+        // means the last command had no output.
+        let mut prompts = 0;
+        let end = loop {
+            match self.grid[line].zone {
+                SemanticZone::Output => break line,
+                SemanticZone::Prompt if prompts > 0 => return None,
+                SemanticZone::Prompt => prompts += 1,
+                _ => (),
+            }
+
+            if line <= topmost_line {
+                return None;
+            }
+            line -= 1;
+        };
+
+        let mut start = end;
+        while start > topmost_line
+            && self.grid[start - 1usize].zone == SemanticZone::Output
+        {
+            start -= 1;
+        }
+
+        Some(start..=end)
+    }
+
+    /// Carry the current shell integration zone over to the cursor row.
+    #[inline]
+    fn mark_semantic_zone(&mut self) {
+        let zone = match self.semantic_zone {
+            SemanticZone::Unknown => return,
+            SemanticZone::Prompt => SemanticZone::PromptContinuation,
+            zone => zone,
+        };
+        let line = self.grid.cursor.pos.row;
+        self.grid[line].zone = zone;
+    }
+
     #[inline]
     pub fn bottommost_line(&self) -> Line {
         self.grid.bottommost_line()
@@ -561,6 +734,7 @@ impl<U: EventListener> Crosswords<U> {
         // Invalidate selection and tabs only when necessary.
         if old_cols != num_cols {
             self.selection = None;
+            self.vi_mode_state.clear_marks();
 
             // Recreate tabs list.
             self.tabs.resize(num_cols);
@@ -570,6 +744,10 @@ impl<U: EventListener> Crosswords<U> {
             self.selection = selection.rotate(&self.grid, &range, -delta);
         }
 
+        if old_cols == num_cols {
+            self.vi_mode_state.shift_marks(delta);
+        }
+
         // Clamp vi cursor to viewport.
         let vi_pos = self.vi_mode_cursor;
         let viewport_top = Line(-(self.grid.display_offset() as i32));
@@ -579,8 +757,9 @@ impl<U: EventListener> Crosswords<U> {
         self.vi_mode_cursor.pos.col =
             std::cmp::min(vi_pos.pos.col, self.grid.last_column());
 
-        // Reset scrolling region.
+        // Reset scrolling region and margins.
         self.scroll_region = Line(0)..Line(self.grid.screen_lines() as i32);
+        self.left_right_margin = Column(0)..Column(self.grid.columns());
 
         // Resize damage information.
         self.damage.resize(num_cols, num_lines);
@@ -596,6 +775,7 @@ impl<U: EventListener> Crosswords<U> {
         U: EventListener,
     {
         self.mode ^= Mode::VI;
+        self.vi_mode_state.cancel();
 
         if self.mode.contains(Mode::VI) {
             let display_offset = self.grid.display_offset() as i32;
@@ -639,9 +819,104 @@ impl<U: EventListener> Crosswords<U> {
             return;
         }
 
-        // Move cursor.
-        self.vi_mode_cursor = self.vi_mode_cursor.motion(self, motion);
-        self.vi_mode_recompute_selection();
+        let count = self.vi_mode_state.take_count();
+        let operator = self.vi_mode_state.take_operator();
+
+        // Move cursor, a count repeats the motion or picks the line to go to.
+        let start = self.vi_mode_cursor.pos;
+        let mut cursor = self.vi_mode_cursor;
+        match (motion, count) {
+            (ViMotion::Top | ViMotion::Bottom, Some(line)) => {
+                cursor = cursor.motion(self, ViMotion::GotoLine(line));
+            }
+            (_, count) => {
+                for _ in 0..count.unwrap_or(1) {
+                    let next = cursor.motion(self, motion);
+                    if next == cursor {
+                        break;
+                    }
+                    cursor = next;
+                }
+            }
+        }
+
+        match operator {
+            // Like in vi, the operator is dropped when the motion fails.
+            Some(ViOperator::Yank) if cursor.pos == start => (),
+            Some(ViOperator::Yank) => self.vi_yank(start, cursor.pos, motion),
+            None => {
+                self.vi_mode_cursor = cursor;
+                self.vi_mode_recompute_selection();
+            }
+        }
+
+        // Motions like searches may read the compressed history.
+        self.grid.release_expanded_rows();
+    }
+
+    /// Handle a key typed in vi mode that is part of a count or of a command
+    /// spanning several keys, like `5j`, `fa`, `gg`, `ma` or `yy`.
+    #[inline]
+    pub fn vi_input(&mut self, c: char) -> ViInput
+    where
+        U: EventListener,
+    {
+        if !self.mode.contains(Mode::VI) {
+            return ViInput::Ignored;
+        }
+
+        ViModeState::input(self, c)
+    }
+
+    /// Whether a vi command is partially typed.
+    #[inline]
+    pub fn vi_input_pending(&self) -> bool {
+        self.vi_mode_state.is_pending()
+    }
+
+    /// Copy the text covered by a motion to the clipboard, the cursor moves
+    /// to the start of that text.
+    fn vi_yank(&mut self, start: Pos, end: Pos, motion: ViMotion)
+    where
+        U: EventListener,
+    {
+        let (first, mut last) = if end < start {
+            (end, start)
+        } else {
+            (start, end)
+        };
+
+        let text = if motion.is_linewise() {
+            self.vi_mode_cursor.pos.row = first.row;
+            let start = Pos::new(first.row, Column(0));
+            let end = Pos::new(last.row, self.grid.last_column());
+            self.bounds_to_string(start, end) + "\n"
+        } else {
+            if motion.is_exclusive() {
+                last = last.sub(&self.grid, Boundary::Grid, 1);
+            }
+            self.vi_mode_cursor.pos = first;
+            self.bounds_to_string(first, last)
+        };
+
+        self.scroll_to_pos(self.vi_mode_cursor.pos);
+        self.event_proxy.send_event(
+            RioEvent::ClipboardStore(ClipboardType::Clipboard, text),
+            self.window_id,
+        );
+    }
+
+    /// Copy the lines starting at the vi cursor to the clipboard, like `yy`.
+    fn vi_yank_lines(&mut self)
+    where
+        U: EventListener,
+    {
+        let count = self.vi_mode_state.take_count().unwrap_or(1);
+        self.vi_mode_state.take_operator();
+
+        let start = self.vi_mode_cursor.pos;
+        let line = std::cmp::min(start.row + (count - 1), self.grid.bottommost_line());
+        self.vi_yank(start, Pos::new(line, start.col), ViMotion::Down);
     }
 
     /// Move vi cursor to a point in the grid.
@@ -716,7 +991,17 @@ impl<U: EventListener> Crosswords<U> {
 
     #[inline]
     pub fn semantic_escape_chars(&self) -> &str {
-        &self.semantic_escape_chars
+        &self.selection_config.semantic_escape_chars
+    }
+
+    #[inline]
+    pub fn selection_config(&self) -> &SelectionConfig {
+        &self.selection_config
+    }
+
+    /// Update the word separators, smart selection rules and block selection copy.
+    pub fn set_selection_config(&mut self, selection_config: SelectionConfig) {
+        self.selection_config = selection_config;
     }
 
     #[inline]
@@ -735,9 +1020,10 @@ impl<U: EventListener> Crosswords<U> {
         } else {
             self.damage_cursor();
             self.grid.cursor.pos.row += 1;
+            self.mark_semantic_zone();
         }
 
-        self.grid.cursor.pos.col = Column(0);
+        self.grid.cursor.pos.col = self.line_start();
         self.grid.cursor.should_wrap = false;
         self.damage_cursor();
     }
@@ -755,6 +1041,141 @@ impl<U: EventListener> Crosswords<U> {
         self.damage.damage_point(point);
     }
 
+    /// Column where lines start for the cursor, the left margin when the
+    /// cursor is inside of the margins.
+    #[inline]
+    fn line_start(&self) -> Column {
+        if self.left_right_margin.contains(&self.grid.cursor.pos.col) {
+            self.left_right_margin.start
+        } else {
+            Column(0)
+        }
+    }
+
+    /// Column after the last one the cursor writes to before wrapping, the right
+    /// margin unless the cursor is already past it.
+    #[inline]
+    fn line_end(&self) -> Column {
+        if self.grid.cursor.pos.col < self.left_right_margin.end {
+            self.left_right_margin.end
+        } else {
+            Column(self.grid.columns())
+        }
+    }
+
+    #[inline]
+    fn has_left_right_margins(&self) -> bool {
+        self.left_right_margin != (Column(0)..Column(self.grid.columns()))
+    }
+
+    /// Scroll the lines of `region` between the left and right margins, cells
+    /// outside of the margins and the scrollback history are left untouched.
+    fn scroll_margins(&mut self, region: Range<Line>, lines: usize, up: bool) {
+        let margin = self.left_right_margin.clone();
+        let lines = std::cmp::min(lines, (region.end - region.start).0 as usize);
+        let kept = (region.end - region.start).0 - lines as i32;
+
+        for offset in 0..kept {
+            let (destination, source) = if up {
+                let destination = region.start + offset;
+                (destination, destination + lines)
+            } else {
+                let destination = region.end - 1 - offset;
+                (destination, destination - lines)
+            };
+
+            let squares = self.grid[source][margin.clone()].to_vec();
+            self.grid[destination][margin.clone()].clone_from_slice(&squares);
+        }
+
+        let cleared = if up {
+            region.end - lines..region.end
+        } else {
+            region.start..region.start + lines
+        };
+        let bg = self.grid.cursor.template.bg;
+        for line in cleared.start.0..cleared.end.0 {
+            for square in &mut self.grid[Line(line)][margin.clone()] {
+                *square = bg.into();
+            }
+        }
+
+        self.mark_fully_damaged();
+    }
+
+    /// Resolve a rectangular area to inclusive grid positions, coordinates are
+    /// relative to the scrolling region and margins in origin mode. Returns
+    /// `None` for empty areas.
+    fn rectangular_area(&self, area: RectangularArea) -> Option<(Pos, Pos)> {
+        let (start, end) = self.area_corners(area);
+        (start.row <= end.row && start.col <= end.col).then_some((start, end))
+    }
+
+    /// Top-left and bottom-right corners of an area, clamped to the screen.
+    fn area_corners(&self, area: RectangularArea) -> (Pos, Pos) {
+        let (lines, columns) = if self.mode.contains(Mode::ORIGIN) {
+            (self.scroll_region.clone(), self.left_right_margin.clone())
+        } else {
+            (
+                Line(0)..Line(self.grid.screen_lines() as i32),
+                Column(0)..Column(self.grid.columns()),
+            )
+        };
+
+        let line =
+            |value: usize| std::cmp::min(lines.start + (value as i32 - 1), lines.end - 1);
+        let column =
+            |value: usize| std::cmp::min(columns.start + (value - 1), columns.end - 1);
+
+        let start = Pos::new(line(area.top), column(area.left));
+        let end = Pos::new(
+            area.bottom.map_or(lines.end - 1, line),
+            area.right.map_or(columns.end - 1, column),
+        );
+
+        (start, end)
+    }
+
+    /// Update the squares of a rectangular area, or of the stream of squares
+    /// between its corners unless the rectangular extent is selected (DECSACE).
+    fn update_area_squares<F>(&mut self, area: RectangularArea, mut update: F)
+    where
+        F: FnMut(&mut Square),
+    {
+        let (start, end) = self.area_corners(area);
+        let rectangle = self.mode.contains(Mode::RECTANGULAR_EXTENT);
+
+        // In a stream only the columns of a single line have to be ordered.
+        if start.row > end.row
+            || ((rectangle || start.row == end.row) && start.col > end.col)
+        {
+            return;
+        }
+
+        for line in start.row.0..=end.row.0 {
+            let (left, right) = if rectangle {
+                (start.col, end.col)
+            } else {
+                let left = if line == start.row.0 {
+                    start.col
+                } else {
+                    Column(0)
+                };
+                let right = if line == end.row.0 {
+                    end.col
+                } else {
+                    self.grid.last_column()
+                };
+                (left, right)
+            };
+
+            for square in &mut self.grid[Line(line)][left..right + 1] {
+                update(square);
+            }
+            self.damage.damage_line(line as usize, left.0, right.0);
+        }
+    }
+
     #[inline]
     fn scroll_down_relative(&mut self, origin: Line, mut lines: usize) {
         debug!(
@@ -770,6 +1191,11 @@ impl<U: EventListener> Crosswords<U> {
 
         let region = origin..self.scroll_region.end;
 
+        if self.has_left_right_margins() {
+            self.scroll_margins(region, lines, false);
+            return;
+        }
+
         // Scroll selection.
         self.selection = self
             .selection
@@ -781,6 +1207,7 @@ impl<U: EventListener> Crosswords<U> {
         if region.start <= *line && region.end > *line {
             *line = std::cmp::min(*line + lines, region.end - 1);
         }
+        self.vi_mode_state.scroll_marks(&region, -(lines as i32));
 
         // Scroll between origin and bottom
         self.grid.scroll_down(&region, lines);
@@ -798,6 +1225,11 @@ impl<U: EventListener> Crosswords<U> {
 
         let region = origin..self.scroll_region.end;
 
+        if self.has_left_right_margins() {
+            self.scroll_margins(region, lines, true);
+            return;
+        }
+
         // Scroll selection.
         self.selection = self
             .selection
@@ -817,6 +1249,7 @@ impl<U: EventListener> Crosswords<U> {
         if (top <= *line) && region.end > *line {
             *line = std::cmp::max(*line - lines, top);
         }
+        self.vi_mode_state.scroll_marks(&region, lines as i32);
         self.mark_fully_damaged();
     }
 
@@ -996,8 +1429,10 @@ impl<U: EventListener> Crosswords<U> {
         U: EventListener,
     {
         // Setting 132 column font makes no sense, but run the other side effects.
-        // Clear scrolling region.
+        // Clear scrolling region and margins.
         self.set_scrolling_region(1, None);
+        self.mode.remove(Mode::LEFT_RIGHT_MARGIN);
+        self.left_right_margin = Column(0)..Column(self.grid.columns());
 
         // Clear grid.
         self.grid.reset_region(..);
@@ -1045,6 +1480,17 @@ impl<U: EventListener> Crosswords<U> {
         CursorState { pos, content }
     }
 
+    /// Update the size of the scrollback history of the primary screen.
+    pub fn update_history(&mut self, history_size: usize) {
+        if self.mode.contains(Mode::ALT_SCREEN) {
+            self.inactive_grid.update_history(history_size);
+        } else {
+            self.grid.update_history(history_size);
+        }
+
+        self.mark_fully_damaged();
+    }
+
     pub fn swap_alt(&mut self) {
         if !self.mode.contains(Mode::ALT_SCREEN) {
             // Set alt screen cursor to the current primary screen cursor.
@@ -1073,6 +1519,7 @@ impl<U: EventListener> Crosswords<U> {
         mem::swap(&mut self.grid, &mut self.inactive_grid);
         self.mode ^= Mode::ALT_SCREEN;
         self.selection = None;
+        self.vi_mode_state.clear_marks();
         self.mark_fully_damaged();
     }
 
@@ -1083,10 +1530,16 @@ impl<U: EventListener> Crosswords<U> {
     }
 
     pub fn selection_to_string(&self) -> Option<String> {
+        self.selection_to_styled_text().map(|text| text.to_string())
+    }
+
+    /// Selected text keeping the style of each character, to copy it as ANSI
+    /// or HTML.
+    pub fn selection_to_styled_text(&self) -> Option<StyledText> {
         let selection_range = self.selection.as_ref().and_then(|s| s.to_range(self))?;
         let SelectionRange { start, end, .. } = selection_range;
 
-        let mut res = String::new();
+        let mut res = StyledText::default();
 
         match self.selection.as_ref() {
             Some(Selection {
@@ -1094,24 +1547,29 @@ impl<U: EventListener> Crosswords<U> {
                 ..
             }) => {
                 for line in (start.row.0..end.row.0).map(Line::from) {
-                    res += self
-                        .line_to_string(line, start.col..end.col, start.col.0 != 0)
-                        .trim_end();
-                    res += "\n";
+                    res.append(self.block_line_to_styled_text(
+                        line,
+                        start.col..end.col,
+                        start.col.0 != 0,
+                    ));
+                    res.push_str("\n");
                 }
 
-                res += self
-                    .line_to_string(end.row, start.col..end.col, true)
-                    .trim_end();
+                res.append(self.block_line_to_styled_text(
+                    end.row,
+                    start.col..end.col,
+                    true,
+                ));
             }
             Some(Selection {
                 ty: SelectionType::Lines,
                 ..
             }) => {
-                res = self.bounds_to_string(start, end) + "\n";
+                res = self.bounds_to_styled_text(start, end);
+                res.push_str("\n");
             }
             _ => {
-                res = self.bounds_to_string(start, end);
+                res = self.bounds_to_styled_text(start, end);
             }
         }
 
@@ -1119,7 +1577,68 @@ impl<U: EventListener> Crosswords<U> {
     }
 
     pub fn bounds_to_string(&self, start: Pos, end: Pos) -> String {
-        let mut res = String::new();
+        self.bounds_to_styled_text(start, end).to_string()
+    }
+
+    /// Up to `max_lines` lines above the cursor as ANSI escape sequences,
+    /// ending with a new line, to be replayed by another terminal. Programs
+    /// using the alternate screen have nothing to keep.
+    pub fn last_lines_to_ansi(&self, max_lines: usize) -> String {
+        let cursor_row = self.grid.cursor.pos.row;
+        if max_lines == 0
+            || self.mode.contains(Mode::ALT_SCREEN)
+            || cursor_row <= self.grid.topmost_line()
+        {
+            return String::new();
+        }
+
+        let end_row = Line(cursor_row.0 - 1);
+        let lines = i32::try_from(max_lines).unwrap_or(i32::MAX);
+        let start_row = std::cmp::max(
+            self.grid.topmost_line(),
+            Line(end_row.0.saturating_sub(lines - 1)),
+        );
+        let text = self.bounds_to_styled_text(
+            Pos::new(start_row, Column(0)),
+            Pos::new(end_row, self.grid.last_column()),
+        );
+
+        let mut ansi = text.to_ansi().replace('\n', "\r\n");
+        ansi.push_str("\r\n");
+        ansi
+    }
+
+    /// Text of the screen or, when `max_lines` is set, of up to `max_lines`
+    /// lines including the ones scrolled into the history. Empty lines below
+    /// the cursor are left out.
+    pub fn last_lines_to_string(&self, max_lines: Option<usize>) -> String {
+        let mut end_row = self.grid.bottommost_line();
+        while end_row > self.grid.cursor.pos.row && self.grid[end_row].is_clear() {
+            end_row = Line(end_row.0 - 1);
+        }
+
+        let start_row = match max_lines {
+            Some(0) => return String::new(),
+            Some(lines) => {
+                let lines = i32::try_from(lines).unwrap_or(i32::MAX);
+                std::cmp::max(
+                    self.grid.topmost_line(),
+                    Line(end_row.0.saturating_sub(lines - 1)),
+                )
+            }
+            None => Line(0),
+        };
+
+        let mut text = self.bounds_to_string(
+            Pos::new(start_row, Column(0)),
+            Pos::new(end_row, self.grid.last_column()),
+        );
+        text.push('\n');
+        text
+    }
+
+    fn bounds_to_styled_text(&self, start: Pos, end: Pos) -> StyledText {
+        let mut res = StyledText::default();
 
         for line in (start.row.0..=end.row.0).map(Line::from) {
             let start_col = if line == start.row {
@@ -1133,20 +1652,45 @@ impl<U: EventListener> Crosswords<U> {
                 self.grid.last_column()
             };
 
-            res += &self.line_to_string(line, start_col..end_col, line == end.row);
+            res.append(self.line_to_styled_text(
+                line,
+                start_col..end_col,
+                line == end.row,
+            ));
+        }
+
+        res.strip_suffix('\n');
+        res
+    }
+
+    /// Convert a line of a block selection to text, the trailing whitespace
+    /// is either removed or padded to the width of the block.
+    fn block_line_to_styled_text(
+        &self,
+        line: Line,
+        cols: Range<Column>,
+        include_wrapped_wide: bool,
+    ) -> StyledText {
+        let mut text = self.line_to_styled_text(line, cols.clone(), include_wrapped_wide);
+        if self.selection_config.trim_block_whitespace {
+            text.trim_end();
+            return text;
         }
 
-        res.strip_suffix('\n').map(str::to_owned).unwrap_or(res)
+        text.trim_end_matches('\n');
+        let block_width = cols.end.0 - cols.start.0 + 1;
+        text.push_str(&" ".repeat(block_width.saturating_sub(text.width())));
+        text
     }
 
-    /// Convert a single line in the grid to a String.
-    fn line_to_string(
+    /// Convert a single line in the grid to text.
+    fn line_to_styled_text(
         &self,
         line: Line,
         mut cols: Range<Column>,
         include_wrapped_wide: bool,
-    ) -> String {
-        let mut text = String::new();
+    ) -> StyledText {
+        let mut text = StyledText::default();
 
         let grid_line = &self.grid[line];
         let line_length = std::cmp::min(grid_line.line_length(), cols.end + 1);
@@ -1179,12 +1723,14 @@ impl<U: EventListener> Crosswords<U> {
             if !cell.flags.intersects(
                 square::Flags::WIDE_CHAR_SPACER | square::Flags::LEADING_WIDE_CHAR_SPACER,
             ) {
+                let style = TextStyle::from(cell);
+
                 // Push cells primary character.
-                text.push(cell.c);
+                text.push(cell.c, style);
 
                 // Push zero-width characters.
                 for c in cell.zerowidth().into_iter().flatten() {
-                    text.push(*c);
+                    text.push(*c, style);
                 }
             }
         }
@@ -1195,7 +1741,7 @@ impl<U: EventListener> Crosswords<U> {
                     .flags
                     .contains(square::Flags::WRAPLINE))
         {
-            text.push('\n');
+            text.push_str("\n");
         }
 
         // If wide char is not part of the selection, but leading spacer is, include it.
@@ -1206,7 +1752,8 @@ impl<U: EventListener> Crosswords<U> {
                 .contains(square::Flags::LEADING_WIDE_CHAR_SPACER)
             && include_wrapped_wide
         {
-            text.push(self.grid[line - 1i32][Column(0)].c);
+            let cell = &self.grid[line - 1i32][Column(0)];
+            text.push(cell.c, TextStyle::from(cell));
         }
 
         text
@@ -1386,13 +1933,16 @@ impl<U: EventListener> Handler for Crosswords<U> {
             NamedPrivateMode::AlternateScroll => self.mode.insert(Mode::ALTERNATE_SCROLL),
             NamedPrivateMode::LineWrap => self.mode.insert(Mode::LINE_WRAP),
             NamedPrivateMode::Origin => self.mode.insert(Mode::ORIGIN),
+            NamedPrivateMode::LeftRightMargin => {
+                self.mode.insert(Mode::LEFT_RIGHT_MARGIN)
+            }
             NamedPrivateMode::ColumnMode => self.deccolm(),
             NamedPrivateMode::BlinkingCursor => {
                 self.blinking_cursor = true;
                 self.event_proxy
                     .send_event(RioEvent::CursorBlinkingChange, self.window_id);
             }
-            NamedPrivateMode::SyncUpdate => (),
+            NamedPrivateMode::SyncUpdate => self.mode.insert(Mode::SYNC_UPDATE),
         }
     }
 
@@ -1458,6 +2008,10 @@ impl<U: EventListener> Handler for Crosswords<U> {
             NamedPrivateMode::AlternateScroll => self.mode.remove(Mode::ALTERNATE_SCROLL),
             NamedPrivateMode::LineWrap => self.mode.remove(Mode::LINE_WRAP),
             NamedPrivateMode::Origin => self.mode.remove(Mode::ORIGIN),
+            NamedPrivateMode::LeftRightMargin => {
+                self.mode.remove(Mode::LEFT_RIGHT_MARGIN);
+                self.left_right_margin = Column(0)..Column(self.grid.columns());
+            }
             NamedPrivateMode::ColumnMode => self.deccolm(),
             NamedPrivateMode::BlinkingCursor => {
                 // TODO: Update it
@@ -1465,7 +2019,7 @@ impl<U: EventListener> Handler for Crosswords<U> {
                 // self.event_proxy
                 // .send_event(RioEvent::CursorBlinkingChange, self.window_id);
             }
-            NamedPrivateMode::SyncUpdate => (),
+            NamedPrivateMode::SyncUpdate => self.mode.remove(Mode::SYNC_UPDATE),
         }
     }
 
@@ -1479,6 +2033,9 @@ impl<U: EventListener> Handler for Crosswords<U> {
                 }
                 NamedPrivateMode::Origin => self.mode.contains(Mode::ORIGIN).into(),
                 NamedPrivateMode::LineWrap => self.mode.contains(Mode::LINE_WRAP).into(),
+                NamedPrivateMode::LeftRightMargin => {
+                    self.mode.contains(Mode::LEFT_RIGHT_MARGIN).into()
+                }
                 NamedPrivateMode::BlinkingCursor => self.blinking_cursor.into(),
                 NamedPrivateMode::ShowCursor => {
                     self.mode.contains(Mode::SHOW_CURSOR).into()
@@ -1511,7 +2068,9 @@ impl<U: EventListener> Handler for Crosswords<U> {
                 NamedPrivateMode::BracketedPaste => {
                     self.mode.contains(Mode::BRACKETED_PASTE).into()
                 }
-                NamedPrivateMode::SyncUpdate => ModeState::Reset,
+                NamedPrivateMode::SyncUpdate => {
+                    self.mode.contains(Mode::SYNC_UPDATE).into()
+                }
                 NamedPrivateMode::ColumnMode => ModeState::NotSupported,
             },
             PrivateMode::Unknown(_) => ModeState::NotSupported,
@@ -1554,10 +2113,16 @@ impl<U: EventListener> Handler for Crosswords<U> {
             (Line(0), self.grid.bottommost_line())
         };
 
+        let (x_offset, max_x) = if self.mode.contains(Mode::ORIGIN) {
+            (self.left_right_margin.start, self.left_right_margin.end - 1)
+        } else {
+            (Column(0), self.grid.last_column())
+        };
+
         self.damage_cursor();
         self.grid.cursor.pos.row =
             std::cmp::max(std::cmp::min(line + y_offset, max_y), Line(0));
-        self.grid.cursor.pos.col = std::cmp::min(col, self.grid.last_column());
+        self.grid.cursor.pos.col = std::cmp::min(col + x_offset, max_x);
         self.damage_cursor();
         self.grid.cursor.should_wrap = false;
     }
@@ -1716,16 +2281,21 @@ impl<U: EventListener> Handler for Crosswords<U> {
 
     #[inline]
     fn delete_chars(&mut self, count: usize) {
-        let columns = self.grid.columns();
         let cursor = &self.grid.cursor;
         let bg = cursor.template.bg;
 
-        // Ensure deleting within terminal bounds.
-        let count = std::cmp::min(count, columns);
+        // Characters outside of the margins can't be deleted.
+        if !self.left_right_margin.contains(&cursor.pos.col) {
+            return;
+        }
 
+        // Ensure deleting within margins.
         let start = cursor.pos.col.0;
-        let end = std::cmp::min(start + count, columns - 1);
-        let num_cells = columns - end;
+        let right = self.left_right_margin.end.0;
+        let count = std::cmp::min(count, right - start);
+
+        let end = start + count;
+        let num_cells = right - end;
 
         let line = cursor.pos.row;
         self.damage
@@ -1736,10 +2306,8 @@ impl<U: EventListener> Handler for Crosswords<U> {
             row.swap(start + offset, end + offset);
         }
 
-        // Clear last `count` cells in the row. If deleting 1 char, need to delete
-        // 1 cell.
-        let end = columns - count;
-        for cell in &mut row[end..] {
+        // Clear last `count` cells before the right margin.
+        for cell in &mut row[right - count..right] {
             *cell = bg.into();
         }
     }
@@ -1763,12 +2331,18 @@ impl<U: EventListener> Handler for Crosswords<U> {
         let cursor = &self.grid.cursor;
         let bg = cursor.template.bg;
 
-        // Ensure inserting within terminal bounds
-        let count = std::cmp::min(count, self.grid.columns() - cursor.pos.col.0);
+        // Blanks can't be inserted outside of the margins.
+        if !self.left_right_margin.contains(&cursor.pos.col) {
+            return;
+        }
+
+        // Ensure inserting within margins.
+        let right = self.left_right_margin.end.0;
+        let count = std::cmp::min(count, right - cursor.pos.col.0);
 
         let source = cursor.pos.col;
         let destination = cursor.pos.col.0 + count;
-        let num_cells = self.grid.columns() - destination;
+        let num_cells = right - destination;
 
         let line = cursor.pos.row;
         self.damage
@@ -1780,7 +2354,7 @@ impl<U: EventListener> Handler for Crosswords<U> {
             row.swap(destination + offset, source.0 + offset);
         }
 
-        // Squares were just moved out toward the end of the line;
+        // Squares were just moved out toward the right margin;
         // fill in between source and dest with blanks.
         for cell in &mut row[source.0..destination] {
             *cell = bg.into();
@@ -1810,12 +2384,15 @@ impl<U: EventListener> Handler for Crosswords<U> {
         self.grid.reset();
         self.inactive_grid.reset();
         self.scroll_region = Line(0)..Line(self.grid.screen_lines() as i32);
+        self.left_right_margin = Column(0)..Column(self.grid.columns());
         self.tabs = TabStops::new(self.grid.columns());
         self.title_stack = Vec::new();
+        self.semantic_zone = SemanticZone::Unknown;
         self.keyboard_mode_stack = Vec::new();
         self.title = String::from("");
         self.selection = None;
         self.vi_mode_cursor = Default::default();
+        self.vi_mode_state = Default::default();
         self.keyboard_mode_stack = Default::default();
         self.inactive_keyboard_mode_stack = Default::default();
 
@@ -1831,67 +2408,8 @@ impl<U: EventListener> Handler for Crosswords<U> {
     #[inline]
     fn terminal_attribute(&mut self, attr: Attr) {
         trace!("Setting attribute: {:?}", attr);
-        let cursor = &mut self.grid.cursor;
-        match attr {
-            Attr::Foreground(color) => cursor.template.fg = color,
-            Attr::Background(color) => cursor.template.bg = color,
-            Attr::UnderlineColor(color) => cursor.template.set_underline_color(color),
-            Attr::Reset => {
-                cursor.template.fg = AnsiColor::Named(NamedColor::Foreground);
-                cursor.template.bg = AnsiColor::Named(NamedColor::Background);
-                cursor.template.flags = square::Flags::empty();
-                cursor.template.set_underline_color(None);
-            }
-            Attr::Reverse => cursor.template.flags.insert(square::Flags::INVERSE),
-            Attr::CancelReverse => cursor.template.flags.remove(square::Flags::INVERSE),
-            Attr::Bold => cursor.template.flags.insert(square::Flags::BOLD),
-            Attr::CancelBold => cursor.template.flags.remove(square::Flags::BOLD),
-            Attr::Dim => cursor.template.flags.insert(square::Flags::DIM),
-            Attr::CancelBoldDim => cursor
-                .template
-                .flags
-                .remove(square::Flags::BOLD | square::Flags::DIM),
-            Attr::Italic => cursor.template.flags.insert(square::Flags::ITALIC),
-            Attr::CancelItalic => cursor.template.flags.remove(square::Flags::ITALIC),
-            Attr::Underline => {
-                cursor.template.flags.remove(square::Flags::ALL_UNDERLINES);
-                cursor.template.flags.insert(square::Flags::UNDERLINE);
-            }
-            Attr::DoubleUnderline => {
-                cursor.template.flags.remove(square::Flags::ALL_UNDERLINES);
-                cursor
-                    .template
-                    .flags
-                    .insert(square::Flags::DOUBLE_UNDERLINE);
-            }
-            Attr::Undercurl => {
-                cursor.template.flags.remove(square::Flags::ALL_UNDERLINES);
-                cursor.template.flags.insert(square::Flags::UNDERCURL);
-            }
-            Attr::DottedUnderline => {
-                cursor.template.flags.remove(square::Flags::ALL_UNDERLINES);
-                cursor
-                    .template
-                    .flags
-                    .insert(square::Flags::DOTTED_UNDERLINE);
-            }
-            Attr::DashedUnderline => {
-                cursor.template.flags.remove(square::Flags::ALL_UNDERLINES);
-                cursor
-                    .template
-                    .flags
-                    .insert(square::Flags::DASHED_UNDERLINE);
-            }
-            Attr::CancelUnderline => {
-                cursor.template.flags.remove(square::Flags::ALL_UNDERLINES)
-            }
-            Attr::Hidden => cursor.template.flags.insert(square::Flags::HIDDEN),
-            Attr::CancelHidden => cursor.template.flags.remove(square::Flags::HIDDEN),
-            Attr::Strike => cursor.template.flags.insert(square::Flags::STRIKEOUT),
-            Attr::CancelStrike => cursor.template.flags.remove(square::Flags::STRIKEOUT),
-            _ => {
-                warn!("Term got unhandled attr: {:?}", attr);
-            }
+        if !self.grid.cursor.template.apply_attribute(attr) {
+            warn!("Term got unhandled attr: {:?}", attr);
         }
     }
 
@@ -1990,7 +2508,8 @@ impl<U: EventListener> Handler for Crosswords<U> {
             self.wrapline();
         }
 
-        let columns = self.grid.columns();
+        // Lines wrap at the right margin when the cursor is inside of the margins.
+        let columns = self.line_end().0;
         if self.mode.contains(Mode::INSERT) && self.grid.cursor.pos.col + width < columns
         {
             let line = self.grid.cursor.pos.row;
@@ -2081,6 +2600,79 @@ impl<U: EventListener> Handler for Crosswords<U> {
         }
     }
 
+    fn report_terminfo_capabilities(&mut self, names: Vec<String>) {
+        for name in names {
+            trace!("Reporting terminfo capability {name:?}");
+            let hex_name = encode_hex(name.as_bytes());
+            let capability = match name.as_str() {
+                "TN" | "name" => Some(terminfo::Capability::String(
+                    terminfo::TERMINFO_NAME.as_bytes().to_vec(),
+                )),
+                "Co" => terminfo::capability("colors").cloned(),
+                _ => terminfo::capability(&name).cloned(),
+            };
+
+            let text = match capability {
+                Some(terminfo::Capability::Boolean) => {
+                    format!("\x1bP1+r{hex_name}\x1b\\")
+                }
+                Some(terminfo::Capability::Number(number)) => {
+                    let value = encode_hex(number.to_string().as_bytes());
+                    format!("\x1bP1+r{hex_name}={value}\x1b\\")
+                }
+                Some(terminfo::Capability::String(value)) => {
+                    format!("\x1bP1+r{hex_name}={}\x1b\\", encode_hex(&value))
+                }
+                None => format!("\x1bP0+r{hex_name}\x1b\\"),
+            };
+            self.event_proxy
+                .send_event(RioEvent::PtyWrite(text), self.window_id);
+        }
+    }
+
+    fn report_status_string(&mut self, setting: &[u8]) {
+        trace!(
+            "Reporting status string {:?}",
+            String::from_utf8_lossy(setting)
+        );
+        let value = match setting {
+            b"m" => Some(format!("{}m", sgr_parameters(&self.grid.cursor.template))),
+            b"r" => Some(format!(
+                "{};{}r",
+                self.scroll_region.start.0 + 1,
+                self.scroll_region.end.0
+            )),
+            b"s" => Some(format!(
+                "{};{}s",
+                self.left_right_margin.start.0 + 1,
+                self.left_right_margin.end.0
+            )),
+            b" q" => {
+                let style = match self.cursor_shape {
+                    CursorShape::Block | CursorShape::Hidden => 1,
+                    CursorShape::Underline => 3,
+                    CursorShape::Beam => 5,
+                };
+                let style = if self.blinking_cursor {
+                    style
+                } else {
+                    style + 1
+                };
+                Some(format!("{style} q"))
+            }
+            // Conformance level matching the primary device attributes.
+            b"\"p" => Some(String::from("62;1\"p")),
+            _ => None,
+        };
+
+        let text = match value {
+            Some(value) => format!("\x1bP1$r{value}\x1b\\"),
+            None => String::from("\x1bP0$r\x1b\\"),
+        };
+        self.event_proxy
+            .send_event(RioEvent::PtyWrite(text), self.window_id);
+    }
+
     #[inline]
     fn report_keyboard_mode(&mut self) {
         let current_mode = self
@@ -2238,6 +2830,7 @@ impl<U: EventListener> Handler for Crosswords<U> {
                     .selection
                     .take()
                     .filter(|s| !s.intersects_range(..Line(0)));
+                self.vi_mode_state.truncate_marks(Line(0));
             }
             // We have no history to clear.
             ClearMode::Saved => (),
@@ -2268,6 +2861,8 @@ impl<U: EventListener> Handler for Crosswords<U> {
             self.grid.cursor.pos.row += 1;
             self.damage_cursor();
         }
+
+        self.mark_semantic_zone();
     }
 
     #[inline]
@@ -2280,19 +2875,58 @@ impl<U: EventListener> Handler for Crosswords<U> {
         self.grid.cursor.template.set_hyperlink(hyperlink);
     }
 
-    /// Set the indexed color value.
     #[inline]
-    fn set_color(&mut self, _index: usize, _color: ColorRgb) {
-        // Damage terminal if the color changed and it's not the cursor.
-        // if index != NamedColor::Cursor as usize && self.colors[index] != Some(color) {
-        // self.mark_fully_damaged();
-        // }
-
-        // self.colors[index] = Some(color);
+    fn set_current_directory(&mut self, path: Option<PathBuf>) {
+        debug!("Setting current directory: {path:?}");
+        self.current_directory = path;
     }
 
     #[inline]
-    fn reset_color(&mut self, _index: usize) {
+    fn desktop_notification(&mut self, title: String, body: String) {
+        self.event_proxy.send_event(
+            RioEvent::Notification {
+                title,
+                body,
+                route_id: self.route_id,
+            },
+            self.window_id,
+        );
+    }
+
+    #[inline]
+    fn prompt_mark(&mut self, mark: PromptMark) {
+        self.semantic_zone = match mark {
+            PromptMark::PromptStart => SemanticZone::Prompt,
+            PromptMark::CommandStart => SemanticZone::Command,
+            PromptMark::OutputStart => SemanticZone::Output,
+            PromptMark::CommandEnd => SemanticZone::Unknown,
+        };
+
+        // The command is usually typed on the prompt row, keep the prompt zone
+        // so it can still be found.
+        let line = self.grid.cursor.pos.row;
+        let row = &mut self.grid[line];
+        match (self.semantic_zone, row.zone) {
+            (SemanticZone::Unknown, _) => (),
+            (SemanticZone::Prompt, _) => row.zone = SemanticZone::Prompt,
+            (_, SemanticZone::Prompt | SemanticZone::PromptContinuation) => (),
+            (zone, _) => row.zone = zone,
+        }
+    }
+
+    /// Set the indexed color value.
+    #[inline]
+    fn set_color(&mut self, _index: usize, _color: ColorRgb) {
+        // Damage terminal if the color changed and it's not the cursor.
+        // if index != NamedColor::Cursor as usize && self.colors[index] != Some(color) {
+        // self.mark_fully_damaged();
+        // }
+
+        // self.colors[index] = Some(color);
+    }
+
+    #[inline]
+    fn reset_color(&mut self, _index: usize) {
         // Damage terminal if the color changed and it's not the cursor.
         // if index != NamedColor::Cursor as usize && self.colors[index].is_some() {
         // self.mark_fully_damaged();
@@ -2303,7 +2937,13 @@ impl<U: EventListener> Handler for Crosswords<U> {
 
     #[inline]
     fn bell(&mut self) {
-        warn!("[unimplemented] Bell");
+        self.event_proxy.send_event(
+            RioEvent::Bell {
+                route_id: self.route_id,
+                urgency_hint: self.mode.contains(Mode::URGENCY_HINTS),
+            },
+            self.window_id,
+        );
     }
 
     #[inline]
@@ -2367,7 +3007,7 @@ impl<U: EventListener> Handler for Crosswords<U> {
     #[inline]
     fn carriage_return(&mut self) {
         trace!("Carriage return");
-        let new_col = 0;
+        let new_col = self.line_start().0;
         let row = self.grid.cursor.pos.row.0 as usize;
         self.damage
             .damage_line(row, new_col, self.grid.cursor.pos.col.0);
@@ -2444,6 +3084,139 @@ impl<U: EventListener> Handler for Crosswords<U> {
         self.goto(Line(0), Column(0));
     }
 
+    #[inline]
+    fn set_left_right_margins(&mut self, left: usize, right: Option<usize>) {
+        if !self.mode.contains(Mode::LEFT_RIGHT_MARGIN) {
+            return;
+        }
+
+        // Fallback to the last column as default.
+        let columns = self.grid.columns();
+        let right = std::cmp::min(right.unwrap_or(columns), columns);
+
+        if left >= right {
+            warn!("Invalid left and right margins: ({};{})", left, right);
+            return;
+        }
+
+        debug!("Setting left and right margins: ({};{})", left, right);
+
+        self.left_right_margin = Column(left - 1)..Column(right);
+        self.goto(Line(0), Column(0));
+    }
+
+    #[inline]
+    fn is_left_right_margin_mode(&self) -> bool {
+        self.mode.contains(Mode::LEFT_RIGHT_MARGIN)
+    }
+
+    fn fill_rectangular_area(&mut self, c: char, area: RectangularArea) {
+        trace!("Filling rectangular area {area:?} with {c:?}");
+        let Some((start, end)) = self.rectangular_area(area) else {
+            return;
+        };
+
+        let mut template = self.grid.cursor.template.clone();
+        template.c = c;
+        for line in start.row.0..=end.row.0 {
+            for square in &mut self.grid[Line(line)][start.col..end.col + 1] {
+                *square = template.clone();
+            }
+            self.damage
+                .damage_line(line as usize, start.col.0, end.col.0);
+        }
+    }
+
+    fn erase_rectangular_area(&mut self, area: RectangularArea) {
+        trace!("Erasing rectangular area {area:?}");
+        let Some((start, end)) = self.rectangular_area(area) else {
+            return;
+        };
+
+        let bg = self.grid.cursor.template.bg;
+        for line in start.row.0..=end.row.0 {
+            for square in &mut self.grid[Line(line)][start.col..end.col + 1] {
+                *square = bg.into();
+            }
+            self.damage
+                .damage_line(line as usize, start.col.0, end.col.0);
+        }
+    }
+
+    fn copy_rectangular_area(
+        &mut self,
+        source: RectangularArea,
+        top: usize,
+        left: usize,
+    ) {
+        trace!("Copying rectangular area {source:?} to ({top};{left})");
+        let Some((start, end)) = self.rectangular_area(source) else {
+            return;
+        };
+        let destination = RectangularArea {
+            top,
+            left,
+            bottom: None,
+            right: None,
+        };
+        let Some((destination, limit)) = self.rectangular_area(destination) else {
+            return;
+        };
+
+        // Copy the source first, since both areas may overlap.
+        let rows: Vec<Vec<Square>> = (start.row.0..=end.row.0)
+            .map(|line| self.grid[Line(line)][start.col..end.col + 1].to_vec())
+            .collect();
+
+        let width =
+            std::cmp::min(end.col - start.col + 1, limit.col - destination.col + 1);
+        for (offset, squares) in rows.iter().enumerate() {
+            let line = destination.row + offset;
+            if line > limit.row {
+                break;
+            }
+
+            let columns = destination.col..destination.col + width;
+            self.grid[line][columns].clone_from_slice(&squares[..width.0]);
+            self.damage.damage_line(
+                line.0 as usize,
+                destination.col.0,
+                (destination.col + width - 1).0,
+            );
+        }
+    }
+
+    fn change_area_attributes(&mut self, area: RectangularArea, attrs: Vec<Attr>) {
+        trace!("Changing attributes of rectangular area {area:?}: {attrs:?}");
+        self.update_area_squares(area, |square| {
+            for attr in &attrs {
+                square.apply_attribute(*attr);
+            }
+        });
+    }
+
+    fn reverse_area_attributes(&mut self, area: RectangularArea, attrs: Vec<Attr>) {
+        trace!("Reversing attributes of rectangular area {area:?}: {attrs:?}");
+        self.update_area_squares(area, |square| {
+            for attr in &attrs {
+                square.reverse_attribute(*attr);
+            }
+        });
+    }
+
+    #[inline]
+    fn sync_update_aborted(&mut self, reason: SyncAbort) {
+        match reason {
+            SyncAbort::Timeout => self.sync_metrics.timeouts += 1,
+            SyncAbort::Overflow => self.sync_metrics.overflows += 1,
+        }
+    }
+
+    #[inline]
+    fn set_attribute_change_extent(&mut self, rectangle: bool) {
+        self.mode.set(Mode::RECTANGULAR_EXTENT, rectangle);
+    }
+
     #[inline]
     fn text_area_size_pixels(&mut self) {
         debug!("text_area_size_pixels");
@@ -2622,9 +3395,6 @@ impl<U: EventListener> Handler for Crosswords<U> {
 
     #[inline]
     fn insert_graphic(&mut self, graphic: GraphicData, palette: Option<Vec<ColorRgb>>) {
-        let cell_width = self.graphics.cell_width as usize;
-        let cell_height = self.graphics.cell_height as usize;
-
         // Store last palette if we receive a new one, and it is shared.
         if let Some(palette) = palette {
             if !self.mode.contains(Mode::SIXEL_PRIV_PALETTE) {
@@ -2632,40 +3402,128 @@ impl<U: EventListener> Handler for Crosswords<U> {
             }
         }
 
-        let graphic = match graphic.resized(
+        // If SIXEL_DISPLAY is disabled, the start of the graphic is the
+        // cursor position, and the grid can be scrolled if the graphic is
+        // larger than the screen. The cursor is moved to the next line
+        // after the graphic.
+        //
+        // If it is disabled, the graphic starts at (0, 0), the grid is never
+        // scrolled, and the cursor position is unmodified.
+        let scrolling = !self.mode.contains(Mode::SIXEL_DISPLAY);
+
+        let cursor = if self.mode.contains(Mode::SIXEL_CURSOR_TO_THE_RIGHT) {
+            GraphicCursor::Right
+        } else if scrolling {
+            GraphicCursor::NextLine
+        } else {
+            GraphicCursor::Unchanged
+        };
+
+        self.place_graphic(graphic, scrolling, cursor);
+    }
+
+    fn kitty_graphics_command(&mut self, command: KittyGraphicsCommand) {
+        let command = match self.graphics.kitty_graphics.join_chunks(command) {
+            Some(command) => command,
+            None => return,
+        };
+
+        let mut image_id = command.image_id;
+        let result = match command.action {
+            KittyAction::Transmit | KittyAction::Query => self
+                .graphics
+                .kitty_graphics
+                .transmit(&command)
+                .map(|id| image_id = id),
+            KittyAction::TransmitAndDisplay => {
+                match self.graphics.kitty_graphics.transmit(&command) {
+                    Ok(id) => {
+                        image_id = id;
+                        self.kitty_graphics_put(id, &command)
+                    }
+                    Err(err) => Err(err),
+                }
+            }
+            KittyAction::Put => match self.graphics.kitty_graphics.image_id(&command) {
+                Some(id) => {
+                    image_id = id;
+                    self.kitty_graphics_put(id, &command)
+                }
+                None => Err(kitty_graphics_protocol::Error::NotFound(String::from(
+                    "image",
+                ))),
+            },
+            KittyAction::Delete => {
+                self.kitty_graphics_delete(&command);
+                return;
+            }
+        };
+
+        if let Err(err) = &result {
+            warn!("Failed to run kitty graphics command: {}", err);
+        }
+
+        if let Some(text) = kitty_graphics_protocol::response(&command, image_id, &result)
+        {
+            self.event_proxy
+                .send_event(RioEvent::PtyWrite(text), self.window_id);
+        }
+    }
+}
+
+/// Cursor position after a graphic is added to the grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GraphicCursor {
+    /// First column of the line below the graphic.
+    NextLine,
+
+    /// Last line of the graphic, after its last column.
+    Right,
+
+    /// Same position as before adding the graphic.
+    Unchanged,
+}
+
+impl<U: EventListener> Crosswords<U> {
+    /// Add a graphic to the grid, and return its identifier.
+    ///
+    /// When `scrolling` is true, the graphic starts at the cursor position,
+    /// otherwise it starts at (0, 0) and the grid is never scrolled.
+    fn place_graphic(
+        &mut self,
+        graphic: GraphicData,
+        scrolling: bool,
+        cursor: GraphicCursor,
+    ) -> Option<GraphicId> {
+        let cell_width = self.graphics.cell_width as usize;
+        let cell_height = self.graphics.cell_height as usize;
+
+        if cell_width == 0 || cell_height == 0 {
+            return None;
+        }
+
+        let graphic = graphic.resized(
             cell_width,
             cell_height,
             cell_width * self.grid.columns(),
             cell_height * self.grid.screen_lines(),
-        ) {
-            Some(graphic) => graphic,
-            None => return,
-        };
+        )?;
 
         if graphic.width > MAX_GRAPHIC_DIMENSIONS[0]
             || graphic.height > MAX_GRAPHIC_DIMENSIONS[1]
         {
-            return;
+            return None;
         }
 
         let width = graphic.width as u16;
         let height = graphic.height as u16;
 
         if width == 0 || height == 0 {
-            return;
+            return None;
         }
 
         let graphic_id = self.graphics.next_id();
-
-        // If SIXEL_DISPLAY is disabled, the start of the graphic is the
-        // cursor position, and the grid can be scrolled if the graphic is
-        // larger than the screen. The cursor is moved to the next line
-        // after the graphic.
-        //
-        // If it is disabled, the graphic starts at (0, 0), the grid is never
-        // scrolled, and the cursor position is unmodified.
-
-        let scrolling = !self.mode.contains(Mode::SIXEL_DISPLAY);
+        let mut linefeeds = 0;
 
         let leftmost = if scrolling {
             self.grid.cursor.pos.col.0
@@ -2792,15 +3650,27 @@ impl<U: EventListener> Handler for Crosswords<U> {
 
             if scrolling && offset_y < height.saturating_sub(cell_height as u16) {
                 self.linefeed();
+                linefeeds += 1;
             }
         }
 
-        if self.mode.contains(Mode::SIXEL_CURSOR_TO_THE_RIGHT) {
-            let graphic_columns = graphic.width.div_ceil(cell_width);
-            self.move_forward(Column(graphic_columns));
-        } else if scrolling {
-            self.linefeed();
-            self.carriage_return();
+        match cursor {
+            GraphicCursor::Right => {
+                let graphic_columns = graphic.width.div_ceil(cell_width);
+                self.move_forward(Column(graphic_columns));
+            }
+            GraphicCursor::NextLine if scrolling => {
+                self.linefeed();
+                self.carriage_return();
+            }
+            GraphicCursor::Unchanged if scrolling => {
+                // The grid may be scrolled while the graphic is added, so
+                // the cursor goes back to the first line of the graphic.
+                let row = std::cmp::max(self.grid.cursor.pos.row.0 - linefeeds, 0);
+                self.grid.cursor.pos.row = Line(row);
+                self.grid.cursor.pos.col = Column(leftmost);
+            }
+            _ => (),
         }
 
         // Add the graphic data to the pending queue.
@@ -2811,6 +3681,188 @@ impl<U: EventListener> Handler for Crosswords<U> {
 
         self.event_proxy
             .send_event(RioEvent::UpdateGraphicLibrary, self.window_id);
+
+        Some(graphic_id)
+    }
+
+    /// Display a stored image of the kitty graphics protocol at the cursor.
+    fn kitty_graphics_put(
+        &mut self,
+        image_id: u32,
+        command: &KittyGraphicsCommand,
+    ) -> Result<(), kitty_graphics_protocol::Error> {
+        let graphic = self
+            .graphics
+            .kitty_graphics
+            .placement_graphic(image_id, command)?;
+
+        // A placement with the same id replaces the previous one.
+        if command.placement_id != 0 {
+            let replaced = self.kitty_graphics_ids(|_, placement| {
+                placement.image_id == image_id
+                    && placement.placement_id == command.placement_id
+            });
+            self.remove_graphics(&replaced);
+        }
+
+        let cursor = if command.move_cursor {
+            GraphicCursor::Right
+        } else {
+            GraphicCursor::Unchanged
+        };
+
+        if let Some(graphic_id) = self.place_graphic(graphic, true, cursor) {
+            self.graphics.kitty_graphics.placements.insert(
+                graphic_id,
+                KittyPlacement {
+                    image_id,
+                    placement_id: command.placement_id,
+                    z_index: command.z_index,
+                },
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Delete placements, and optionally images, of the kitty graphics
+    /// protocol.
+    fn kitty_graphics_delete(&mut self, command: &KittyGraphicsCommand) {
+        let screen_lines = self.grid.screen_lines() as i32;
+        let last_column = self.grid.columns().saturating_sub(1);
+        let column = Column((command.x as usize).saturating_sub(1).min(last_column));
+        let row = Line((command.y as i32 - 1).clamp(0, screen_lines - 1));
+
+        let targets = match command.delete.target {
+            DeleteTarget::Visible => self.kitty_graphics_ids_in(
+                Line(0)..Line(screen_lines),
+                Column(0)..Column(last_column + 1),
+            ),
+            DeleteTarget::ImageId | DeleteTarget::ImageNumber => {
+                let image_id = match self.graphics.kitty_graphics.image_id(command) {
+                    Some(image_id) => image_id,
+                    None => return,
+                };
+
+                let placement_id = match command.delete.target {
+                    DeleteTarget::ImageId => command.placement_id,
+                    _ => 0,
+                };
+
+                self.kitty_graphics_ids(|_, placement| {
+                    placement.image_id == image_id
+                        && (placement_id == 0 || placement.placement_id == placement_id)
+                })
+            }
+            DeleteTarget::Cursor => {
+                let pos = self.grid.cursor.pos;
+                self.kitty_graphics_ids_in(pos.row..pos.row + 1, pos.col..pos.col + 1)
+            }
+            DeleteTarget::Cell => {
+                self.kitty_graphics_ids_in(row..row + 1, column..column + 1)
+            }
+            DeleteTarget::Column => self
+                .kitty_graphics_ids_in(Line(0)..Line(screen_lines), column..column + 1),
+            DeleteTarget::Row => self
+                .kitty_graphics_ids_in(row..row + 1, Column(0)..Column(last_column + 1)),
+            DeleteTarget::ZIndex => self
+                .kitty_graphics_ids(|_, placement| placement.z_index == command.z_index),
+        };
+
+        let images: HashSet<u32> = targets
+            .iter()
+            .filter_map(|id| self.graphics.kitty_graphics.placements.get(id))
+            .map(|placement| placement.image_id)
+            .collect();
+
+        self.remove_graphics(&targets);
+
+        for id in &targets {
+            self.graphics.kitty_graphics.placements.remove(id);
+        }
+
+        // Uppercase values also free the images without placements.
+        if command.delete.free {
+            let state = &mut self.graphics.kitty_graphics;
+            for image_id in images {
+                if !state
+                    .placements
+                    .values()
+                    .any(|placement| placement.image_id == image_id)
+                {
+                    state.remove_image(image_id);
+                }
+            }
+        }
+    }
+
+    /// Identifiers of the kitty placements that match a predicate.
+    fn kitty_graphics_ids<F>(&self, predicate: F) -> HashSet<GraphicId>
+    where
+        F: Fn(&GraphicId, &KittyPlacement) -> bool,
+    {
+        self.graphics
+            .kitty_graphics
+            .placements
+            .iter()
+            .filter(|(id, placement)| predicate(id, placement))
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    /// Identifiers of the kitty placements intersecting a region of the
+    /// screen.
+    fn kitty_graphics_ids_in(
+        &self,
+        lines: Range<Line>,
+        columns: Range<Column>,
+    ) -> HashSet<GraphicId> {
+        let placements = &self.graphics.kitty_graphics.placements;
+        let mut ids = HashSet::new();
+
+        for line in (lines.start.0..lines.end.0).map(Line) {
+            let row = &self.grid[line];
+            for column in (columns.start.0..columns.end.0.min(row.len())).map(Column) {
+                if let Some(graphics) = row[column].graphics() {
+                    ids.extend(
+                        graphics
+                            .iter()
+                            .map(|graphic| graphic.texture.id)
+                            .filter(|id| placements.contains_key(id)),
+                    );
+                }
+            }
+        }
+
+        ids
+    }
+
+    /// Remove graphics from every line of the grid, including the history.
+    fn remove_graphics(&mut self, ids: &HashSet<GraphicId>) {
+        if ids.is_empty() {
+            return;
+        }
+
+        let topmost = self.grid.topmost_line().0;
+        let bottommost = self.grid.bottommost_line().0;
+        for line in (topmost..=bottommost).map(Line) {
+            for square in self.grid[line].inner.iter_mut() {
+                if square.graphics().is_none() {
+                    continue;
+                }
+
+                if let Some(mut graphics) = square.take_graphics() {
+                    graphics.retain(|graphic| !ids.contains(&graphic.texture.id));
+                    if !graphics.is_empty() {
+                        square.set_graphics(graphics);
+                    }
+                }
+            }
+        }
+
+        self.mark_fully_damaged();
+        self.event_proxy
+            .send_event(RioEvent::UpdateGraphicLibrary, self.window_id);
     }
 }
 
@@ -3106,6 +4158,49 @@ mod tests {
         assert_eq!(term.selection_to_string(), Some(String::from("\"aa\"a\n")));
     }
 
+    #[test]
+    fn styled_selection_works() {
+        let size = CrosswordsSize::new(5, 2);
+        let window_id = crate::event::WindowId::from(0);
+
+        let mut term =
+            Crosswords::new(size, CursorShape::Block, VoidListener {}, window_id, 0);
+        let grid = &mut term.grid;
+        for (j, c) in "ok: ".chars().enumerate() {
+            grid[Line(0)][Column(j)].c = c;
+        }
+        grid[Line(0)][Column(0)].fg = AnsiColor::Named(NamedColor::Green);
+        grid[Line(0)][Column(1)].fg = AnsiColor::Named(NamedColor::Green);
+        grid[Line(1)][Column(0)].c = 'x';
+        grid[Line(1)][Column(0)].flags.insert(square::Flags::BOLD);
+        grid[Line(1)][Column(0)]
+            .flags
+            .insert(square::Flags::WRAPLINE);
+
+        term.selection = Some(Selection::new(
+            SelectionType::Simple,
+            Pos {
+                row: Line(0),
+                col: Column(0),
+            },
+            Side::Left,
+        ));
+        if let Some(s) = term.selection.as_mut() {
+            s.update(
+                Pos {
+                    row: Line(1),
+                    col: Column(0),
+                },
+                Side::Right,
+            );
+        }
+
+        let text = term.selection_to_styled_text().unwrap();
+        assert_eq!(text.to_string(), "ok:\nx");
+        assert_eq!(text.to_string(), term.selection_to_string().unwrap());
+        assert_eq!(text.to_ansi(), "\x1b[0;32mok\x1b[0m:\n\x1b[0;1mx\x1b[0m");
+    }
+
     #[test]
     fn block_selection_works() {
         let size = CrosswordsSize::new(5, 5);
@@ -3480,6 +4575,141 @@ mod tests {
         );
     }
 
+    #[test]
+    fn kitty_graphics_place_and_delete() {
+        use crate::performer::handler::ParserProcessor;
+
+        let size = CrosswordsSize::new(10, 5);
+        let window_id = crate::event::WindowId::from(0);
+        let mut cw =
+            Crosswords::new(size, CursorShape::Block, VoidListener {}, window_id, 0);
+        cw.graphics.cell_width = 2.;
+        cw.graphics.cell_height = 2.;
+
+        let mut parser = ParserProcessor::new();
+        let mut advance = |cw: &mut Crosswords<VoidListener>, bytes: &[u8]| {
+            for byte in bytes {
+                parser.advance(cw, *byte);
+            }
+        };
+
+        // A 4x4 image covers 2x2 cells.
+        let pixels = general_purpose::STANDARD.encode([255; 4 * 4 * 4]);
+        let transmit = format!("\x1b_Ga=T,f=32,s=4,v=4,i=1;{pixels}\x1b\\");
+        advance(&mut cw, transmit.as_bytes());
+
+        for (row, col) in [(0, 0), (0, 1), (1, 0), (1, 1)] {
+            assert!(cw.grid[Line(row)][Column(col)].graphics().is_some());
+        }
+        assert!(cw.grid[Line(0)][Column(2)].graphics().is_none());
+        assert_eq!(cw.grid.cursor.pos, Pos::new(Line(1), Column(2)));
+
+        // Placing it again without moving the cursor.
+        advance(&mut cw, b"\x1b_Ga=p,i=1,p=3,C=1\x1b\\");
+        assert_eq!(cw.grid.cursor.pos, Pos::new(Line(1), Column(2)));
+        assert!(cw.grid[Line(2)][Column(3)].graphics().is_some());
+        assert_eq!(cw.graphics.kitty_graphics.placements.len(), 2);
+
+        // Delete the placement under a cell, keeping the image data.
+        advance(&mut cw, b"\x1b_Ga=d,d=p,x=4,y=3\x1b\\");
+        assert!(cw.grid[Line(2)][Column(3)].graphics().is_none());
+        assert!(cw.grid[Line(0)][Column(0)].graphics().is_some());
+        assert_eq!(cw.graphics.kitty_graphics.placements.len(), 1);
+        assert_eq!(cw.graphics.kitty_graphics.images.len(), 1);
+
+        // Delete all placements of the image, and its data.
+        advance(&mut cw, b"\x1b_Ga=d,d=I,i=1\x1b\\");
+        assert!(cw.grid[Line(0)][Column(0)].graphics().is_none());
+        assert!(cw.graphics.kitty_graphics.placements.is_empty());
+        assert!(cw.graphics.kitty_graphics.images.is_empty());
+    }
+
+    #[test]
+    fn last_lines_to_string() {
+        use crate::performer::handler::ParserProcessor;
+
+        let size = CrosswordsSize::new(10, 5);
+        let window_id = crate::event::WindowId::from(0);
+        let mut cw =
+            Crosswords::new(size, CursorShape::Block, VoidListener {}, window_id, 0);
+        cw.update_history(10);
+
+        let mut parser = ParserProcessor::new();
+        for byte in b"one\r\ntwo\r\n\x1b[31mthree\x1b[0m\r\nfour\r\nfive\r\nsix\r\n$ " {
+            parser.advance(&mut cw, *byte);
+        }
+
+        assert_eq!(cw.last_lines_to_string(None), "three\nfour\nfive\nsix\n$\n");
+        assert_eq!(cw.last_lines_to_string(Some(0)), "");
+        assert_eq!(cw.last_lines_to_string(Some(2)), "six\n$\n");
+        // Lines scrolled into the history are included.
+        assert_eq!(
+            cw.last_lines_to_string(Some(100)),
+            "one\ntwo\nthree\nfour\nfive\nsix\n$\n"
+        );
+
+        // Empty lines below the cursor are left out.
+        for byte in b"\x1b[2J\x1b[Hseven" {
+            parser.advance(&mut cw, *byte);
+        }
+        assert_eq!(cw.last_lines_to_string(None), "seven\n");
+    }
+
+    #[test]
+    fn last_lines_to_ansi() {
+        use crate::performer::handler::ParserProcessor;
+
+        let size = CrosswordsSize::new(10, 5);
+        let window_id = crate::event::WindowId::from(0);
+        let mut cw =
+            Crosswords::new(size, CursorShape::Block, VoidListener {}, window_id, 0);
+        cw.update_history(10);
+
+        let mut parser = ParserProcessor::new();
+        let mut advance = |cw: &mut Crosswords<VoidListener>, bytes: &[u8]| {
+            for byte in bytes {
+                parser.advance(cw, *byte);
+            }
+        };
+
+        assert_eq!(cw.last_lines_to_ansi(10), "");
+
+        advance(&mut cw, b"one\r\n\x1b[31mtwo\x1b[0m\r\nthree\r\nfour\r\n");
+        advance(&mut cw, b"five\r\nsix\r\n$ ");
+
+        assert_eq!(cw.last_lines_to_ansi(0), "");
+        assert_eq!(cw.last_lines_to_ansi(2), "five\r\nsix\r\n");
+        // Lines scrolled into the history are included.
+        assert_eq!(
+            cw.last_lines_to_ansi(100),
+            "one\r\n\x1b[0;31mtwo\x1b[0m\r\nthree\r\nfour\r\nfive\r\nsix\r\n"
+        );
+
+        // Replaying the lines in another terminal restores them.
+        let mut restored = Crosswords::new(
+            CrosswordsSize::new(10, 5),
+            CursorShape::Block,
+            VoidListener {},
+            window_id,
+            0,
+        );
+        let mut parser = ParserProcessor::new();
+        for byte in cw.last_lines_to_ansi(2).as_bytes() {
+            parser.advance(&mut restored, *byte);
+        }
+        assert_eq!(restored.grid.cursor.pos, Pos::new(Line(2), Column(0)));
+        assert_eq!(
+            restored.bounds_to_string(
+                Pos::new(Line(0), Column(0)),
+                Pos::new(Line(1), Column(9))
+            ),
+            "five\nsix"
+        );
+
+        advance(&mut cw, b"\x1b[?1049h");
+        assert_eq!(cw.last_lines_to_ansi(10), "");
+    }
+
     #[test]
     fn parse_cargo_version() {
         assert_eq!(version_number("0.0.1-nightly"), 1);
@@ -3487,4 +4717,532 @@ mod tests {
         assert_eq!(version_number("1.2.3-nightly"), 1_02_03);
         assert_eq!(version_number("999.99.99"), 9_99_99_99);
     }
+
+    #[test]
+    fn shell_integration_zones() {
+        use crate::performer::handler::ParserProcessor;
+
+        let size = CrosswordsSize::new(10, 4);
+        let window_id = crate::event::WindowId::from(0);
+        let mut cw =
+            Crosswords::new(size, CursorShape::Block, VoidListener {}, window_id, 0);
+
+        let mut parser = ParserProcessor::new();
+        let mut advance = |cw: &mut Crosswords<VoidListener>, bytes: &[u8]| {
+            for byte in bytes {
+                parser.advance(cw, *byte);
+            }
+        };
+
+        let command = |name: &str, output: &str| {
+            format!(
+                "\x1b]133;A\x07$ \x1b]133;B\x07{name}\r\n\x1b]133;C\x07{output}\x1b]133;D;0\x07"
+            )
+        };
+        advance(&mut cw, command("ls", "a\r\nb\r\n").as_bytes());
+        advance(&mut cw, command("true", "").as_bytes());
+        advance(&mut cw, command("pwd", "/\r\n").as_bytes());
+        advance(&mut cw, b"\x1b]133;A\x07$ ");
+
+        // Rows were scrolled into history.
+        let zones: Vec<SemanticZone> =
+            (-3..4).map(|line| cw.grid[Line(line)].zone).collect();
+        assert_eq!(
+            zones,
+            vec![
+                SemanticZone::Prompt,
+                SemanticZone::Output,
+                SemanticZone::Output,
+                SemanticZone::Prompt,
+                SemanticZone::Prompt,
+                SemanticZone::Output,
+                SemanticZone::Prompt,
+            ]
+        );
+
+        assert_eq!(cw.last_command_output(), Some(Line(2)..=Line(2)));
+        assert_eq!(cw.prompt_line(Line(0), Direction::Left), Some(Line(-3)));
+        assert_eq!(cw.prompt_line(Line(-3), Direction::Left), None);
+        assert_eq!(cw.prompt_line(Line(0), Direction::Right), Some(Line(1)));
+
+        cw.scroll_to_prompt(Direction::Left);
+        assert_eq!(cw.display_offset(), 3);
+        cw.scroll_to_prompt(Direction::Left);
+        assert_eq!(cw.display_offset(), 3);
+        cw.scroll_to_prompt(Direction::Right);
+        assert_eq!(cw.display_offset(), 0);
+
+        // The last command had no output.
+        advance(&mut cw, command("true", "").as_bytes());
+        advance(&mut cw, b"\x1b]133;A\x07$ ");
+        assert_eq!(cw.last_command_output(), None);
+    }
+
+    #[test]
+    fn osc7_current_directory() {
+        use crate::performer::handler::ParserProcessor;
+
+        let size = CrosswordsSize::new(10, 4);
+        let window_id = crate::event::WindowId::from(0);
+        let mut cw =
+            Crosswords::new(size, CursorShape::Block, VoidListener {}, window_id, 0);
+
+        let mut parser = ParserProcessor::new();
+        let mut advance = |cw: &mut Crosswords<VoidListener>, bytes: &[u8]| {
+            for byte in bytes {
+                parser.advance(cw, *byte);
+            }
+        };
+
+        assert_eq!(cw.current_directory, None);
+
+        advance(&mut cw, b"\x1b]7;file://localhost/home/rio\x07");
+        assert_eq!(cw.current_directory, Some(PathBuf::from("/home/rio")));
+
+        advance(&mut cw, b"\x1b]7;file:///tmp/a%20b;c\x1b\\");
+        assert_eq!(cw.current_directory, Some(PathBuf::from("/tmp/a b;c")));
+
+        // Invalid URLs keep the previous directory.
+        advance(&mut cw, b"\x1b]7;/home\x07");
+        assert_eq!(cw.current_directory, Some(PathBuf::from("/tmp/a b;c")));
+
+        // Directories of another machine, like through ssh, are not used.
+        advance(&mut cw, b"\x1b]7;file://remote.example.com/home/rio\x07");
+        assert_eq!(cw.current_directory, None);
+    }
+
+    #[test]
+    fn desktop_notifications() {
+        use crate::performer::handler::ParserProcessor;
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        #[derive(Clone, Default)]
+        struct NotificationListener(Rc<RefCell<Vec<(String, String)>>>);
+
+        impl EventListener for NotificationListener {
+            fn event(&self) -> (Option<RioEvent>, bool) {
+                (None, false)
+            }
+
+            fn send_event(&self, event: RioEvent, _id: WindowId) {
+                if let RioEvent::Notification { title, body, .. } = event {
+                    self.0.borrow_mut().push((title, body));
+                }
+            }
+        }
+
+        let size = CrosswordsSize::new(10, 4);
+        let window_id = crate::event::WindowId::from(0);
+        let listener = NotificationListener::default();
+        let mut cw =
+            Crosswords::new(size, CursorShape::Block, listener.clone(), window_id, 0);
+
+        let mut parser = ParserProcessor::new();
+        let mut advance = |cw: &mut Crosswords<NotificationListener>, bytes: &[u8]| {
+            for byte in bytes {
+                parser.advance(cw, *byte);
+            }
+        };
+
+        advance(&mut cw, b"\x1b]9;Build finished; 0 errors\x07");
+        // ConEmu progress report.
+        advance(&mut cw, b"\x1b]9;4;1;50\x07");
+        advance(&mut cw, b"\x1b]777;notify;Build;Finished\x1b\\");
+        advance(&mut cw, b"\x1b]99;;Hello\x1b\\");
+        advance(&mut cw, b"\x1b]99;i=1:d=0;Hello\x1b\\");
+        advance(&mut cw, b"\x1b]99;i=1:d=0:p=body:e=1;V29y\x1b\\");
+        advance(&mut cw, b"\x1b]99;i=1:p=body;ld\x1b\\");
+
+        // Notifications sent in too many chunks are dropped.
+        let chunk = format!("\x1b]99;i=2:d=0;{}\x1b\\", "x".repeat(1000));
+        for _ in 0..10 {
+            advance(&mut cw, chunk.as_bytes());
+        }
+        advance(&mut cw, b"\x1b]99;i=2;end\x1b\\");
+        advance(&mut cw, b"\x1b]99;i=3;Done\x1b\\");
+
+        let to_owned = |(title, body): (&str, &str)| (title.to_owned(), body.to_owned());
+        assert_eq!(
+            *listener.0.borrow(),
+            [
+                ("", "Build finished; 0 errors"),
+                ("Build", "Finished"),
+                ("Hello", ""),
+                ("Hello", "World"),
+                ("Done", ""),
+            ]
+            .map(to_owned)
+        );
+    }
+
+    #[test]
+    fn bell_event() {
+        use crate::performer::handler::ParserProcessor;
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        #[derive(Clone, Default)]
+        struct BellListener(Rc<Cell<usize>>, Rc<Cell<bool>>);
+
+        impl EventListener for BellListener {
+            fn event(&self) -> (Option<RioEvent>, bool) {
+                (None, false)
+            }
+
+            fn send_event(&self, event: RioEvent, _id: WindowId) {
+                if let RioEvent::Bell {
+                    route_id,
+                    urgency_hint,
+                } = event
+                {
+                    assert_eq!(route_id, 3);
+                    self.0.set(self.0.get() + 1);
+                    self.1.set(urgency_hint);
+                }
+            }
+        }
+
+        let size = CrosswordsSize::new(10, 4);
+        let window_id = crate::event::WindowId::from(0);
+        let listener = BellListener::default();
+        let mut cw =
+            Crosswords::new(size, CursorShape::Block, listener.clone(), window_id, 3);
+
+        let mut parser = ParserProcessor::new();
+        for byte in b"ding\x07\x1b]2;title\x07dong\x07" {
+            parser.advance(&mut cw, *byte);
+        }
+
+        // BEL terminating an OSC sequence does not ring the bell.
+        assert_eq!(listener.0.get(), 2);
+        assert!(listener.1.get());
+
+        for byte in b"\x1b[?1042l\x07" {
+            parser.advance(&mut cw, *byte);
+        }
+        assert_eq!(listener.0.get(), 3);
+        assert!(!listener.1.get());
+    }
+
+    #[derive(Clone, Default)]
+    struct PtyWriteListener(std::rc::Rc<std::cell::RefCell<Vec<String>>>);
+
+    impl EventListener for PtyWriteListener {
+        fn event(&self) -> (Option<RioEvent>, bool) {
+            (None, false)
+        }
+
+        fn send_event(&self, event: RioEvent, _id: WindowId) {
+            if let RioEvent::PtyWrite(text) = event {
+                self.0.borrow_mut().push(text);
+            }
+        }
+    }
+
+    fn query(input: &[u8]) -> Vec<String> {
+        use crate::performer::handler::ParserProcessor;
+
+        let size = CrosswordsSize::new(10, 5);
+        let window_id = crate::event::WindowId::from(0);
+        let listener = PtyWriteListener::default();
+        let mut cw =
+            Crosswords::new(size, CursorShape::Block, listener.clone(), window_id, 0);
+
+        let mut parser = ParserProcessor::new();
+        for byte in input {
+            parser.advance(&mut cw, *byte);
+        }
+
+        listener.0.take()
+    }
+
+    #[test]
+    fn xtgettcap() {
+        // Query `am`, `colors`, `bold` and `nope`.
+        let replies = query(b"\x1bP+q616d;636f6c6f7273;626F6C64;6e6f7065\x1b\\");
+        assert_eq!(
+            replies,
+            vec![
+                String::from("\x1bP1+r616D\x1b\\"),
+                String::from("\x1bP1+r636F6C6F7273=323536\x1b\\"),
+                String::from("\x1bP1+r626F6C64=1B5B316D\x1b\\"),
+                String::from("\x1bP0+r6E6F7065\x1b\\"),
+            ]
+        );
+
+        // Query `TN` and an invalid name.
+        let replies = query(b"\x1bP+q544E;zz\x1b\\");
+        assert_eq!(
+            replies,
+            vec![
+                String::from("\x1bP1+r544E=72696F\x1b\\"),
+                String::from("\x1bP0+r\x1b\\"),
+            ]
+        );
+    }
+
+    #[test]
+    fn decrqss() {
+        assert_eq!(query(b"\x1bP$qm\x1b\\"), vec!["\x1bP1$r0m\x1b\\"]);
+        assert_eq!(
+            query(b"\x1b[1;4;31;48;2;1;2;3m\x1bP$qm\x1b\\"),
+            vec!["\x1bP1$r0;1;4;31;48:2::1:2:3m\x1b\\"]
+        );
+        assert_eq!(query(b"\x1bP$qr\x1b\\"), vec!["\x1bP1$r1;5r\x1b\\"]);
+        assert_eq!(
+            query(b"\x1b[2;4r\x1bP$qr\x1b\\"),
+            vec!["\x1bP1$r2;4r\x1b\\"]
+        );
+        assert_eq!(query(b"\x1b[6 q\x1bP$q q\x1b\\"), vec!["\x1bP1$r6 q\x1b\\"]);
+        assert_eq!(query(b"\x1bP$q\"p\x1b\\"), vec!["\x1bP1$r62;1\"p\x1b\\"]);
+        assert_eq!(query(b"\x1bP$qx\x1b\\"), vec!["\x1bP0$r\x1b\\"]);
+    }
+
+    #[test]
+    fn sync_update_mode_report() {
+        assert_eq!(query(b"\x1b[?2026$p"), vec!["\x1b[?2026;2$y"]);
+        // Queries inside of the update are answered when it is applied.
+        assert_eq!(
+            query(b"\x1b[?2026h\x1b[?2026$p\x1b[?2026l\x1b[?2026$p"),
+            vec!["\x1b[?2026;1$y", "\x1b[?2026;2$y"]
+        );
+    }
+
+    #[test]
+    fn sync_update_metrics() {
+        use crate::performer::handler::{ParserProcessor, SyncAbort, SyncLimits};
+
+        let size = CrosswordsSize::new(10, 3);
+        let window_id = crate::event::WindowId::from(0);
+        let mut cw =
+            Crosswords::new(size, CursorShape::Block, VoidListener {}, window_id, 0);
+        let mut parser = ParserProcessor::with_sync_limits(SyncLimits {
+            max_bytes: 16,
+            ..SyncLimits::default()
+        });
+
+        for byte in b"\x1b[?2026h0123456789abcdef" {
+            parser.advance(&mut cw, *byte);
+        }
+        assert!(parser.sync_timeout().is_none());
+        assert!(!cw.mode().contains(Mode::SYNC_UPDATE));
+        assert_eq!(cw.grid[Line(0)][Column(0)].c, '0');
+
+        for byte in b"\x1b[?2026h012" {
+            parser.advance(&mut cw, *byte);
+        }
+        assert!(parser.sync_timeout().is_some());
+        parser.abort_sync(&mut cw, SyncAbort::Timeout);
+        assert_eq!(
+            cw.sync_metrics,
+            SyncMetrics {
+                timeouts: 1,
+                overflows: 1,
+            }
+        );
+
+        // Updated limits apply to the next bytes.
+        parser.set_sync_limits(SyncLimits {
+            max_bytes: 8,
+            ..SyncLimits::default()
+        });
+        for byte in b"\x1b[?2026h01234567" {
+            parser.advance(&mut cw, *byte);
+        }
+        assert!(parser.sync_timeout().is_none());
+        assert_eq!(
+            cw.sync_metrics,
+            SyncMetrics {
+                timeouts: 1,
+                overflows: 2,
+            }
+        );
+    }
+
+    fn filled_term(input: &[u8]) -> Crosswords<VoidListener> {
+        use crate::performer::handler::ParserProcessor;
+
+        let size = CrosswordsSize::new(10, 3);
+        let window_id = crate::event::WindowId::from(0);
+        let mut cw =
+            Crosswords::new(size, CursorShape::Block, VoidListener {}, window_id, 0);
+
+        let mut parser = ParserProcessor::new();
+        for byte in b"0123456789\r\nabcdefghij\r\nABCDEFGHIJ"
+            .iter()
+            .chain(input)
+        {
+            parser.advance(&mut cw, *byte);
+        }
+
+        cw
+    }
+
+    fn margins_term(input: &[u8]) -> Crosswords<VoidListener> {
+        filled_term(&[b"\x1b[?69h\x1b[3;6s", input].concat())
+    }
+
+    fn row_text(cw: &Crosswords<VoidListener>, line: i32) -> String {
+        cw.grid[Line(line)][..]
+            .iter()
+            .map(|square| square.c)
+            .collect()
+    }
+
+    #[test]
+    fn left_right_margins() {
+        let cw = margins_term(b"");
+        assert!(cw.mode().contains(Mode::LEFT_RIGHT_MARGIN));
+        assert_eq!(cw.left_right_margin, Column(2)..Column(6));
+        assert_eq!(cw.grid.cursor.pos, Pos::new(Line(0), Column(0)));
+
+        // Without DECLRMM, `CSI s` saves the cursor.
+        let cw = margins_term(b"\x1b[?69l\x1b[4;8s");
+        assert!(!cw.mode().contains(Mode::LEFT_RIGHT_MARGIN));
+        assert_eq!(cw.left_right_margin, Column(0)..Column(10));
+
+        // Invalid margins are ignored.
+        let cw = margins_term(b"\x1b[6;6s");
+        assert_eq!(cw.left_right_margin, Column(2)..Column(6));
+
+        // Origin mode positions the cursor relative to the margins.
+        let cw = margins_term(b"\x1b[?6h\x1b[1;2H");
+        assert_eq!(cw.grid.cursor.pos, Pos::new(Line(0), Column(3)));
+    }
+
+    #[test]
+    fn left_right_margins_input() {
+        let cw = margins_term(b"\x1b[1;4Hwxyz12\r3");
+        assert_eq!(row_text(&cw, 0), "012wxy6789");
+        assert_eq!(row_text(&cw, 1), "ab312fghij");
+
+        // Past the right margin lines wrap at the edge of the screen.
+        let cw = margins_term(b"\x1b[1;9Hwxyz");
+        assert_eq!(row_text(&cw, 0), "01234567wx");
+        assert_eq!(row_text(&cw, 1), "yzcdefghij");
+    }
+
+    #[test]
+    fn left_right_margins_insert_delete() {
+        let cw = margins_term(b"\x1b[1;4H\x1b[2@");
+        assert_eq!(row_text(&cw, 0), "012  36789");
+
+        let cw = margins_term(b"\x1b[1;4H\x1b[2P");
+        assert_eq!(row_text(&cw, 0), "0125  6789");
+
+        let cw = margins_term(b"\x1b[1;4H\x1b[9P");
+        assert_eq!(row_text(&cw, 0), "012   6789");
+
+        // Outside of the margins nothing changes.
+        let cw = margins_term(b"\x1b[1;8H\x1b[2@\x1b[2P");
+        assert_eq!(row_text(&cw, 0), "0123456789");
+    }
+
+    #[test]
+    fn left_right_margins_scroll() {
+        let cw = margins_term(b"\x1b[S");
+        assert_eq!(row_text(&cw, 0), "01cdef6789");
+        assert_eq!(row_text(&cw, 1), "abCDEFghij");
+        assert_eq!(row_text(&cw, 2), "AB    GHIJ");
+        assert_eq!(cw.history_size(), 0);
+
+        let cw = margins_term(b"\x1b[T");
+        assert_eq!(row_text(&cw, 0), "01    6789");
+        assert_eq!(row_text(&cw, 1), "ab2345ghij");
+        assert_eq!(row_text(&cw, 2), "ABcdefGHIJ");
+
+        // Linefeed at the bottom of the scrolling region.
+        let cw = margins_term(b"\x1b[3;1H\n");
+        assert_eq!(row_text(&cw, 2), "AB    GHIJ");
+    }
+
+    #[test]
+    fn fill_and_erase_rectangular_area() {
+        let cw = filled_term(b"\x1b[1m\x1b[88;1;2;2;4$x");
+        assert_eq!(row_text(&cw, 0), "0XXX456789");
+        assert_eq!(row_text(&cw, 1), "aXXXefghij");
+        assert_eq!(row_text(&cw, 2), "ABCDEFGHIJ");
+        assert!(cw.grid[Line(1)][Column(3)]
+            .flags
+            .contains(square::Flags::BOLD));
+
+        // Invalid characters are ignored.
+        let cw = filled_term(b"\x1b[7;1;1;3;10$x");
+        assert_eq!(row_text(&cw, 0), "0123456789");
+
+        let cw = filled_term(b"\x1b[2;3;3;5$z");
+        assert_eq!(row_text(&cw, 0), "0123456789");
+        assert_eq!(row_text(&cw, 1), "ab   fghij");
+        assert_eq!(row_text(&cw, 2), "AB   FGHIJ");
+
+        // Empty areas are ignored.
+        let cw = filled_term(b"\x1b[3;1;2;10$z");
+        assert_eq!(row_text(&cw, 1), "abcdefghij");
+
+        // Origin mode is relative to the scrolling region.
+        let cw = filled_term(b"\x1b[2;3r\x1b[?6h\x1b[88;1;1;1;1$x");
+        assert_eq!(row_text(&cw, 0), "0123456789");
+        assert_eq!(row_text(&cw, 1), "Xbcdefghij");
+    }
+
+    #[test]
+    fn copy_rectangular_area() {
+        let cw = filled_term(b"\x1b[1;1;2;3;1;2;5$v");
+        assert_eq!(row_text(&cw, 0), "0123456789");
+        assert_eq!(row_text(&cw, 1), "abcd012hij");
+        assert_eq!(row_text(&cw, 2), "ABCDabcHIJ");
+
+        // The destination is clipped to the screen.
+        let cw = filled_term(b"\x1b[1;1;2;3;1;3;9$v");
+        assert_eq!(row_text(&cw, 1), "abcdefghij");
+        assert_eq!(row_text(&cw, 2), "ABCDEFGH01");
+    }
+
+    #[test]
+    fn change_area_attributes() {
+        let bold = |cw: &Crosswords<VoidListener>, line: i32, column: usize| {
+            cw.grid[Line(line)][Column(column)]
+                .flags
+                .contains(square::Flags::BOLD)
+        };
+
+        // The stream of characters between the corners by default.
+        let cw = filled_term(b"\x1b[1;9;2;2;1$r");
+        assert!(!bold(&cw, 0, 7));
+        assert!(bold(&cw, 0, 8));
+        assert!(bold(&cw, 0, 9));
+        assert!(bold(&cw, 1, 0));
+        assert!(bold(&cw, 1, 1));
+        assert!(!bold(&cw, 1, 2));
+
+        // The rectangle after DECSACE.
+        let cw = filled_term(b"\x1b[2*x\x1b[1;9;2;10;4;31$r");
+        assert!(!bold(&cw, 1, 0));
+        let square = &cw.grid[Line(1)][Column(9)];
+        assert!(square.flags.contains(square::Flags::UNDERLINE));
+        assert_eq!(square.fg, AnsiColor::Named(NamedColor::Red));
+        assert!(!cw.grid[Line(1)][Column(0)]
+            .flags
+            .contains(square::Flags::UNDERLINE));
+
+        // Reset keeps the layout of the line.
+        let cw =
+            filled_term(b"\x1b[Hwrapping line\x1b[2*x\x1b[1;1;1;10;1$r\x1b[1;1;1;10;0$r");
+        assert!(!bold(&cw, 0, 9));
+        assert!(cw.grid[Line(0)][Column(9)]
+            .flags
+            .contains(square::Flags::WRAPLINE));
+    }
+
+    #[test]
+    fn reverse_area_attributes() {
+        let cw = filled_term(b"\x1b[2*x\x1b[1;1;1;2;7$r\x1b[1;1;1;1$t");
+        let flags = cw.grid[Line(0)][Column(0)].flags;
+        assert!(flags.contains(square::Flags::BOLD | square::Flags::UNDERLINE));
+        assert!(!flags.contains(square::Flags::INVERSE));
+        let flags = cw.grid[Line(0)][Column(1)].flags;
+        assert!(flags.contains(square::Flags::INVERSE));
+        assert!(!flags.contains(square::Flags::BOLD));
+    }
 }
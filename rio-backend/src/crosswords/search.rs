@@ -20,10 +20,62 @@ use crate::crosswords::Crosswords;
 use crate::crosswords::{Boundary, Column, Direction, Pos, Side};
 
 /// Used to match equal brackets, when performing a bracket-pair selection.
-const BRACKET_PAIRS: [(char, char); 4] = [('(', ')'), ('[', ']'), ('{', '}'), ('<', '>')];
+const BRACKET_PAIRS: [(char, char); 9] = [
+    ('(', ')'),
+    ('[', ']'),
+    ('{', '}'),
+    ('<', '>'),
+    ('«', '»'),
+    ('‹', '›'),
+    ('⟨', '⟩'),
+    ('「', '」'),
+    ('【', '】'),
+];
 
 pub type Match = RangeInclusive<Pos>;
 
+/// Case sensitivity of a search.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SearchCase {
+    /// Case sensitive only when the search contains an uppercase character.
+    #[default]
+    Smart,
+    Sensitive,
+    Insensitive,
+}
+
+impl SearchCase {
+    /// Next mode when cycling through them.
+    pub fn next(self) -> Self {
+        match self {
+            SearchCase::Smart => SearchCase::Sensitive,
+            SearchCase::Sensitive => SearchCase::Insensitive,
+            SearchCase::Insensitive => SearchCase::Smart,
+        }
+    }
+}
+
+/// Options changing how the text of a search is matched.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SearchOptions {
+    pub case: SearchCase,
+    /// Only match whole words.
+    pub whole_word: bool,
+    /// Match the text literally instead of as a regex.
+    pub literal: bool,
+}
+
+/// Number of matches of a search.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SearchCount {
+    /// Matches found, up to the limit of the count.
+    pub total: usize,
+    /// Position of the focused match, starting at 1.
+    pub current: Option<usize>,
+    /// Whether counting stopped at the limit.
+    pub limited: bool,
+}
+
 /// Crosswordsinal regex search state.
 #[derive(Clone, Debug)]
 pub struct RegexSearch {
@@ -36,12 +88,37 @@ pub struct RegexSearch {
 impl RegexSearch {
     /// Build the forward and backward search DFAs.
     pub fn new(search: &str) -> Result<RegexSearch, Box<BuildError>> {
+        Self::with_options(search, SearchOptions::default())
+    }
+
+    /// Build the search DFAs, matching the search as described by `options`.
+    pub fn with_options(
+        search: &str,
+        options: SearchOptions,
+    ) -> Result<RegexSearch, Box<BuildError>> {
+        let case_insensitive = match options.case {
+            SearchCase::Smart => !search.chars().any(|c| c.is_uppercase()),
+            SearchCase::Sensitive => false,
+            SearchCase::Insensitive => true,
+        };
+
+        let mut pattern = if options.literal {
+            regex::escape(search)
+        } else {
+            search.to_owned()
+        };
+
+        // Unicode word boundaries are not supported by the lazy DFAs.
+        if options.whole_word {
+            pattern = format!(r"(?-u:\b)(?:{pattern})(?-u:\b)");
+        }
+        let search = pattern.as_str();
+
         // Setup configs for both DFA directions.
         //
         // Bounds are based on Regex's meta engine:
         // https://github.com/rust-lang/regex/blob/061ee815ef2c44101dba7b0b124600fcb03c1912/regex-automata/src/meta/wrappers.rs#L581-L599
-        let has_uppercase = search.chars().any(|c| c.is_uppercase());
-        let syntax_config = SyntaxConfig::new().case_insensitive(!has_uppercase);
+        let syntax_config = SyntaxConfig::new().case_insensitive(case_insensitive);
         let config = Config::new()
             .minimum_cache_clear_count(Some(3))
             .minimum_bytes_per_state(Some(10));
@@ -240,6 +317,69 @@ impl<T: event::EventListener> Crosswords<T> {
         Some(regex_match)
     }
 
+    /// Count the matches of a search in the grid, including the history.
+    ///
+    /// The whole grid is scanned, but counting stops after `limit` matches so
+    /// a frequent match stays cheap. The position of the focused match is the
+    /// number of matches before it, it is unknown when they reach the limit.
+    pub fn search_count(
+        &self,
+        regex: &mut RegexSearch,
+        focused_match: Option<&Match>,
+        limit: usize,
+    ) -> SearchCount {
+        let topmost = Pos::new(self.grid.topmost_line(), Column(0));
+        let bottommost = Pos::new(self.grid.bottommost_line(), self.grid.last_column());
+
+        let focused_match = match focused_match {
+            Some(focused_match) => focused_match,
+            None => {
+                let total =
+                    RegexIter::new(topmost, bottommost, Direction::Right, self, regex)
+                        .take(limit.saturating_add(1))
+                        .count();
+                return SearchCount {
+                    total: total.min(limit),
+                    current: None,
+                    limited: total > limit,
+                };
+            }
+        };
+
+        let before = if *focused_match.start() > topmost {
+            let end = focused_match.start().sub(self, Boundary::Grid, 1);
+            RegexIter::new(topmost, end, Direction::Right, self, regex)
+                .take(limit)
+                .count()
+        } else {
+            0
+        };
+
+        if before >= limit {
+            return SearchCount {
+                total: limit,
+                current: None,
+                limited: true,
+            };
+        }
+
+        let remaining = limit - before - 1;
+        let after = if *focused_match.end() < bottommost {
+            let origin = focused_match.end().add(self, Boundary::Grid, 1);
+            RegexIter::new(origin, bottommost, Direction::Right, self, regex)
+                .take(remaining.saturating_add(1))
+                .count()
+        } else {
+            0
+        };
+
+        SearchCount {
+            total: before + 1 + after.min(remaining),
+            current: Some(before + 1),
+            limited: after > remaining,
+        }
+    }
+
     /// Get the side of a match.
     fn match_side(regex_match: &Match, side: Side) -> Pos {
         match side {
@@ -757,6 +897,7 @@ mod tests {
     use crate::crosswords::CrosswordsSize;
     use crate::crosswords::CursorShape;
     use crate::event::VoidListener;
+    use crate::performer::handler::Handler;
     use unicode_width::UnicodeWidthChar;
 
     pub fn mock_term(content: &str) -> Crosswords<VoidListener> {
@@ -1398,4 +1539,180 @@ mod tests {
             Some(match_start..=match_end)
         );
     }
+
+    fn count_matches(
+        term: &Crosswords<VoidListener>,
+        search: &str,
+        options: SearchOptions,
+    ) -> usize {
+        let mut regex = RegexSearch::with_options(search, options).unwrap();
+        term.search_count(&mut regex, None, usize::MAX).total
+    }
+
+    #[test]
+    fn search_case() {
+        let term = mock_term("Rio rio RIO");
+
+        let smart = SearchOptions::default();
+        assert_eq!(count_matches(&term, "rio", smart), 3);
+        assert_eq!(count_matches(&term, "Rio", smart), 1);
+
+        let sensitive = SearchOptions {
+            case: SearchCase::Sensitive,
+            ..Default::default()
+        };
+        assert_eq!(count_matches(&term, "rio", sensitive), 1);
+
+        let insensitive = SearchOptions {
+            case: SearchCase::Insensitive,
+            ..Default::default()
+        };
+        assert_eq!(count_matches(&term, "Rio", insensitive), 3);
+    }
+
+    #[test]
+    fn search_whole_word() {
+        let term = mock_term("rio rioterm terminal rio_x rio");
+
+        let options = SearchOptions {
+            whole_word: true,
+            ..Default::default()
+        };
+        assert_eq!(count_matches(&term, "rio", options), 2);
+        assert_eq!(count_matches(&term, "rio|terminal", options), 3);
+        assert_eq!(count_matches(&term, "rio", SearchOptions::default()), 4);
+    }
+
+    #[test]
+    fn search_literal() {
+        let term = mock_term("a.b axb f(x)");
+
+        let options = SearchOptions {
+            literal: true,
+            ..Default::default()
+        };
+        assert_eq!(count_matches(&term, "a.b", options), 1);
+        assert_eq!(count_matches(&term, "f(x)", options), 1);
+        assert_eq!(count_matches(&term, "a.b", SearchOptions::default()), 2);
+        assert!(RegexSearch::new("f(x").is_err());
+    }
+
+    #[test]
+    fn search_count() {
+        #[rustfmt::skip]
+        let term = mock_term("\
+            rio rio\r\n\
+            rio\r\n\
+            term rio\
+        ");
+
+        let mut regex = RegexSearch::new("rio").unwrap();
+        let focused = Pos::new(Line(1), Column(0))..=Pos::new(Line(1), Column(2));
+        assert_eq!(
+            term.search_count(&mut regex, Some(&focused), 10),
+            SearchCount {
+                total: 4,
+                current: Some(3),
+                limited: false,
+            }
+        );
+
+        assert_eq!(
+            term.search_count(&mut regex, Some(&focused), 2),
+            SearchCount {
+                total: 2,
+                current: None,
+                limited: true,
+            }
+        );
+
+        assert_eq!(
+            term.search_count(&mut regex, Some(&focused), 3),
+            SearchCount {
+                total: 3,
+                current: Some(3),
+                limited: true,
+            }
+        );
+
+        let focused = Pos::new(Line(0), Column(0))..=Pos::new(Line(0), Column(2));
+        assert_eq!(
+            term.search_count(&mut regex, Some(&focused), 10),
+            SearchCount {
+                total: 4,
+                current: Some(1),
+                limited: false,
+            }
+        );
+
+        let focused = Pos::new(Line(2), Column(5))..=Pos::new(Line(2), Column(7));
+        assert_eq!(
+            term.search_count(&mut regex, Some(&focused), 10),
+            SearchCount {
+                total: 4,
+                current: Some(4),
+                limited: false,
+            }
+        );
+
+        assert_eq!(
+            term.search_count(&mut regex, None, 3),
+            SearchCount {
+                total: 3,
+                current: None,
+                limited: true,
+            }
+        );
+    }
+
+    #[test]
+    fn search_count_long_history() {
+        let mut term = mock_term("rio");
+        for i in 0..3_000 {
+            term.carriage_return();
+            term.linefeed();
+            term.input(if i % 1_000 == 0 { 'r' } else { 'x' });
+        }
+
+        // Matches are counted in the whole history, with the focused one.
+        let mut regex = RegexSearch::new("rio|r").unwrap();
+        let bottommost = term.grid.bottommost_line();
+        let focused =
+            Pos::new(bottommost - 999, Column(0))..=Pos::new(bottommost - 999, Column(0));
+        assert_eq!(
+            term.search_count(&mut regex, Some(&focused), 100),
+            SearchCount {
+                total: 4,
+                current: Some(4),
+                limited: false,
+            }
+        );
+    }
+
+    #[test]
+    fn search_history_stays_compressed() {
+        let mut term = mock_term("rio");
+        for _ in 0..2_000 {
+            term.carriage_return();
+            term.linefeed();
+            term.input('x');
+        }
+
+        let topmost = term.grid.topmost_line();
+        assert!(term.grid[topmost].is_compressed());
+
+        let mut regex = RegexSearch::new("rio").unwrap();
+        let origin = Pos::new(Line(0), Column(0));
+        let regex_match =
+            term.search_next(&mut regex, origin, Direction::Left, Side::Left, None);
+        assert_eq!(regex_match.map(|m| m.start().row), Some(topmost));
+        assert!(term.grid[topmost].is_expanded());
+
+        term.grid.release_expanded_rows();
+        for line in topmost.0..0 {
+            let row = &term.grid[Line(line)];
+            assert!(!row.is_expanded());
+        }
+        assert!(term.grid[topmost].is_compressed());
+    }
 }
@@ -3,6 +3,7 @@
 
 use crate::ansi::graphics::GraphicsCell;
 use crate::config::colors::{AnsiColor, NamedColor};
+use crate::crosswords::attr::Attr;
 use crate::crosswords::grid::GridSquare;
 use crate::crosswords::Column;
 use crate::crosswords::Row;
@@ -34,6 +35,11 @@ bitflags! {
         const ALL_UNDERLINES            = Self::UNDERLINE.bits() | Self::DOUBLE_UNDERLINE.bits()
                                         | Self::UNDERCURL.bits() | Self::DOTTED_UNDERLINE.bits()
                                         | Self::DASHED_UNDERLINE.bits();
+        /// Flags describing the layout of the grid rather than the graphic rendition.
+        const LAYOUT                    = Self::WRAPLINE.bits() | Self::WIDE_CHAR.bits()
+                                        | Self::WIDE_CHAR_SPACER.bits()
+                                        | Self::LEADING_WIDE_CHAR_SPACER.bits()
+                                        | Self::GRAPHICS.bits();
     }
 }
 
@@ -221,6 +227,67 @@ impl Square {
     pub fn hyperlink(&self) -> Option<Hyperlink> {
         self.extra.as_ref()?.hyperlink.clone()
     }
+
+    /// Apply a graphic rendition attribute, returns `false` if the attribute
+    /// is not supported.
+    pub fn apply_attribute(&mut self, attr: Attr) -> bool {
+        match attr {
+            Attr::Foreground(color) => self.fg = color,
+            Attr::Background(color) => self.bg = color,
+            Attr::UnderlineColor(color) => self.set_underline_color(color),
+            Attr::Reset => {
+                self.fg = AnsiColor::Named(NamedColor::Foreground);
+                self.bg = AnsiColor::Named(NamedColor::Background);
+                self.flags &= Flags::LAYOUT;
+                self.set_underline_color(None);
+            }
+            Attr::Reverse => self.flags.insert(Flags::INVERSE),
+            Attr::CancelReverse => self.flags.remove(Flags::INVERSE),
+            Attr::Bold => self.flags.insert(Flags::BOLD),
+            Attr::CancelBold => self.flags.remove(Flags::BOLD),
+            Attr::Dim => self.flags.insert(Flags::DIM),
+            Attr::CancelBoldDim => self.flags.remove(Flags::BOLD | Flags::DIM),
+            Attr::Italic => self.flags.insert(Flags::ITALIC),
+            Attr::CancelItalic => self.flags.remove(Flags::ITALIC),
+            Attr::Underline => self.set_underline(Flags::UNDERLINE),
+            Attr::DoubleUnderline => self.set_underline(Flags::DOUBLE_UNDERLINE),
+            Attr::Undercurl => self.set_underline(Flags::UNDERCURL),
+            Attr::DottedUnderline => self.set_underline(Flags::DOTTED_UNDERLINE),
+            Attr::DashedUnderline => self.set_underline(Flags::DASHED_UNDERLINE),
+            Attr::CancelUnderline => self.flags.remove(Flags::ALL_UNDERLINES),
+            Attr::Hidden => self.flags.insert(Flags::HIDDEN),
+            Attr::CancelHidden => self.flags.remove(Flags::HIDDEN),
+            Attr::Strike => self.flags.insert(Flags::STRIKEOUT),
+            Attr::CancelStrike => self.flags.remove(Flags::STRIKEOUT),
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// Toggle the flags of a graphic rendition attribute, a reset toggles bold,
+    /// underline and inverse.
+    pub fn reverse_attribute(&mut self, attr: Attr) {
+        let flags = match attr {
+            Attr::Reset => Flags::BOLD | Flags::UNDERLINE | Flags::INVERSE,
+            Attr::Bold => Flags::BOLD,
+            Attr::Dim => Flags::DIM,
+            Attr::Italic => Flags::ITALIC,
+            Attr::Underline => Flags::UNDERLINE,
+            Attr::Reverse => Flags::INVERSE,
+            Attr::Hidden => Flags::HIDDEN,
+            Attr::Strike => Flags::STRIKEOUT,
+            _ => return,
+        };
+
+        self.flags.toggle(flags);
+    }
+
+    #[inline]
+    fn set_underline(&mut self, underline: Flags) {
+        self.flags.remove(Flags::ALL_UNDERLINES);
+        self.flags.insert(underline);
+    }
 }
 
 impl GridSquare for Square {
@@ -258,6 +325,16 @@ impl GridSquare for Square {
     fn flags_mut(&mut self) -> &mut Flags {
         &mut self.flags
     }
+
+    #[inline]
+    fn character(&self) -> char {
+        self.c
+    }
+
+    #[inline]
+    fn set_character(&mut self, character: char) {
+        self.c = character;
+    }
 }
 
 pub trait LineLength {
@@ -0,0 +1,380 @@
+use crate::config::colors::term::List;
+use crate::config::colors::{AnsiColor, ColorArray, NamedColor};
+use crate::crosswords::square::{Flags, Square};
+use std::fmt::{self, Display, Write};
+use unicode_width::UnicodeWidthChar;
+
+/// Graphic rendition of a character copied from the grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextStyle {
+    pub fg: AnsiColor,
+    pub bg: AnsiColor,
+    pub flags: Flags,
+}
+
+impl Default for TextStyle {
+    fn default() -> TextStyle {
+        TextStyle {
+            fg: AnsiColor::Named(NamedColor::Foreground),
+            bg: AnsiColor::Named(NamedColor::Background),
+            flags: Flags::empty(),
+        }
+    }
+}
+
+impl From<&Square> for TextStyle {
+    fn from(square: &Square) -> TextStyle {
+        TextStyle {
+            fg: square.fg,
+            bg: square.bg,
+            flags: square.flags - Flags::LAYOUT,
+        }
+    }
+}
+
+/// Text copied from the grid, keeping the style of each character. Line
+/// breaks and padding use the default style.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct StyledText {
+    chars: Vec<(char, TextStyle)>,
+}
+
+impl StyledText {
+    #[inline]
+    pub fn push(&mut self, c: char, style: TextStyle) {
+        self.chars.push((c, style));
+    }
+
+    #[inline]
+    pub fn push_str(&mut self, text: &str) {
+        self.chars
+            .extend(text.chars().map(|c| (c, TextStyle::default())));
+    }
+
+    #[inline]
+    pub fn append(&mut self, other: StyledText) {
+        self.chars.extend(other.chars);
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.chars.is_empty()
+    }
+
+    /// Number of columns taken by the text.
+    pub fn width(&self) -> usize {
+        self.chars.iter().filter_map(|(c, _)| c.width()).sum()
+    }
+
+    pub fn trim_end(&mut self) {
+        while matches!(self.chars.last(), Some((c, _)) if c.is_whitespace()) {
+            self.chars.pop();
+        }
+    }
+
+    pub fn trim_end_matches(&mut self, pattern: char) {
+        while matches!(self.chars.last(), Some((c, _)) if *c == pattern) {
+            self.chars.pop();
+        }
+    }
+
+    /// Remove a single trailing character matching the pattern.
+    pub fn strip_suffix(&mut self, pattern: char) {
+        if matches!(self.chars.last(), Some((c, _)) if *c == pattern) {
+            self.chars.pop();
+        }
+    }
+
+    /// Consecutive characters sharing the same style.
+    fn runs(&self) -> Vec<(TextStyle, String)> {
+        let mut runs: Vec<(TextStyle, String)> = Vec::new();
+        for (c, style) in &self.chars {
+            match runs.last_mut() {
+                Some((run_style, text)) if run_style == style => text.push(*c),
+                _ => runs.push((*style, c.to_string())),
+            }
+        }
+        runs
+    }
+
+    /// Text with the SGR escape sequences reproducing its style.
+    pub fn to_ansi(&self) -> String {
+        let mut ansi = String::new();
+        let mut current = TextStyle::default();
+
+        for (style, text) in self.runs() {
+            if style != current {
+                ansi.push_str(&sgr(&style));
+                current = style;
+            }
+            ansi.push_str(&text);
+        }
+
+        if current != TextStyle::default() {
+            ansi.push_str("\x1b[0m");
+        }
+
+        ansi
+    }
+
+    /// HTML `pre` element with inline styles, the colors come from the list.
+    pub fn to_html(&self, colors: &List) -> String {
+        let foreground = hex(colors[NamedColor::Foreground]);
+        let background = hex(colors[NamedColor::Background]);
+        let mut html = format!(
+            "<pre style=\"font-family: monospace; color: {foreground}; background-color: {background};\">"
+        );
+
+        for (style, text) in self.runs() {
+            let text = escape_html(&text);
+            let css = css(&style, colors, &foreground, &background);
+            if css.is_empty() {
+                html.push_str(&text);
+            } else {
+                let _ = write!(html, "<span style=\"{css}\">{text}</span>");
+            }
+        }
+
+        html.push_str("</pre>");
+        html
+    }
+}
+
+impl Display for StyledText {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (c, _) in &self.chars {
+            f.write_char(*c)?;
+        }
+        Ok(())
+    }
+}
+
+/// SGR escape sequence resetting the attributes, then setting the style.
+fn sgr(style: &TextStyle) -> String {
+    let mut params = vec![String::from("0")];
+
+    for (flag, param) in [
+        (Flags::BOLD, "1"),
+        (Flags::DIM, "2"),
+        (Flags::ITALIC, "3"),
+        (Flags::UNDERLINE, "4"),
+        (Flags::DOUBLE_UNDERLINE, "21"),
+        (Flags::UNDERCURL, "4:3"),
+        (Flags::DOTTED_UNDERLINE, "4:4"),
+        (Flags::DASHED_UNDERLINE, "4:5"),
+        (Flags::INVERSE, "7"),
+        (Flags::HIDDEN, "8"),
+        (Flags::STRIKEOUT, "9"),
+    ] {
+        if style.flags.contains(flag) {
+            params.push(String::from(param));
+        }
+    }
+
+    params.extend(color_param(style.fg, 30));
+    params.extend(color_param(style.bg, 40));
+
+    format!("\x1b[{}m", params.join(";"))
+}
+
+/// SGR parameter of a color, `base` is 30 for the foreground and 40 for the
+/// background. Default colors have none.
+fn color_param(color: AnsiColor, base: u8) -> Option<String> {
+    match color {
+        AnsiColor::Named(name) => {
+            let index = name as usize;
+            let dim_index = NamedColor::DimBlack as usize;
+            match index {
+                0..=7 => Some((base as usize + index).to_string()),
+                8..=15 => Some((base as usize + 60 + index - 8).to_string()),
+                _ if (dim_index..dim_index + 8).contains(&index) => {
+                    Some((base as usize + index - dim_index).to_string())
+                }
+                _ => None,
+            }
+        }
+        AnsiColor::Spec(rgb) => {
+            Some(format!("{};2;{};{};{}", base + 8, rgb.r, rgb.g, rgb.b))
+        }
+        AnsiColor::Indexed(index) => Some(format!("{};5;{index}", base + 8)),
+    }
+}
+
+/// Inline style of a run, colors equal to the defaults are left out.
+fn css(style: &TextStyle, colors: &List, foreground: &str, background: &str) -> String {
+    let mut fg = color_hex(style.fg, style.flags, colors);
+    let mut bg = color_hex(style.bg, Flags::empty(), colors);
+    if style.flags.contains(Flags::INVERSE) {
+        std::mem::swap(&mut fg, &mut bg);
+    }
+    if style.flags.contains(Flags::HIDDEN) {
+        fg = bg.clone();
+    }
+
+    let mut css = Vec::new();
+    if fg != foreground {
+        css.push(format!("color: {fg};"));
+    }
+    if bg != background {
+        css.push(format!("background-color: {bg};"));
+    }
+    if style.flags.contains(Flags::BOLD) {
+        css.push(String::from("font-weight: bold;"));
+    }
+    if style.flags.contains(Flags::ITALIC) {
+        css.push(String::from("font-style: italic;"));
+    }
+
+    let mut decorations = Vec::new();
+    if style.flags.intersects(Flags::ALL_UNDERLINES) {
+        decorations.push("underline");
+    }
+    if style.flags.contains(Flags::STRIKEOUT) {
+        decorations.push("line-through");
+    }
+    if !decorations.is_empty() {
+        css.push(format!("text-decoration: {};", decorations.join(" ")));
+    }
+    for (flag, decoration_style) in [
+        (Flags::DOUBLE_UNDERLINE, "double"),
+        (Flags::UNDERCURL, "wavy"),
+        (Flags::DOTTED_UNDERLINE, "dotted"),
+        (Flags::DASHED_UNDERLINE, "dashed"),
+    ] {
+        if style.flags.contains(flag) {
+            css.push(format!("text-decoration-style: {decoration_style};"));
+        }
+    }
+
+    css.join(" ")
+}
+
+/// Hexadecimal notation of a color, with the bright and dim variants of the
+/// first eight colors for bold and dim text.
+fn color_hex(color: AnsiColor, flags: Flags, colors: &List) -> String {
+    let index = match color {
+        AnsiColor::Spec(rgb) => {
+            return format!("#{:02x}{:02x}{:02x}", rgb.r, rgb.g, rgb.b);
+        }
+        AnsiColor::Named(name) => name as usize,
+        AnsiColor::Indexed(index) => index as usize,
+    };
+
+    let index = match index {
+        0..=7 if flags.contains(Flags::DIM) => NamedColor::DimBlack as usize + index,
+        0..=7 if flags.contains(Flags::BOLD) => index + 8,
+        _ => index,
+    };
+
+    hex(colors[index])
+}
+
+fn hex(color: ColorArray) -> String {
+    let channel = |value: f32| (value.clamp(0., 1.) * 255.).round() as u8;
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        channel(color[0]),
+        channel(color[1]),
+        channel(color[2])
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::colors::term::TermColors;
+    use crate::config::colors::ColorRgb;
+
+    fn styled_text() -> StyledText {
+        let red = TextStyle {
+            fg: AnsiColor::Named(NamedColor::Red),
+            flags: Flags::BOLD,
+            ..TextStyle::default()
+        };
+        let rgb = TextStyle {
+            bg: AnsiColor::Spec(ColorRgb {
+                r: 255,
+                g: 128,
+                b: 0,
+            }),
+            flags: Flags::UNDERLINE,
+            ..TextStyle::default()
+        };
+
+        let mut text = StyledText::default();
+        text.push('e', red);
+        text.push('r', red);
+        text.push_str(": ");
+        text.push('<', rgb);
+        text.push('a', rgb);
+        text.push('>', rgb);
+        text.push_str("\nok");
+        text
+    }
+
+    #[test]
+    fn test_styled_text_plain() {
+        let mut text = styled_text();
+        assert_eq!(text.to_string(), "er: <a>\nok");
+
+        let mut line = StyledText::default();
+        line.push_str("a界b");
+        assert_eq!(line.width(), 4);
+
+        text.push_str("  \n");
+        text.strip_suffix('\n');
+        assert_eq!(text.to_string(), "er: <a>\nok  ");
+        text.trim_end();
+        assert_eq!(text.to_string(), "er: <a>\nok");
+    }
+
+    #[test]
+    fn test_styled_text_ansi() {
+        assert_eq!(
+            styled_text().to_ansi(),
+            "\x1b[0;1;31mer\x1b[0m: \x1b[0;4;48;2;255;128;0m<a>\x1b[0m\nok"
+        );
+
+        let mut text = StyledText::default();
+        text.push(
+            'x',
+            TextStyle {
+                fg: AnsiColor::Indexed(208),
+                ..TextStyle::default()
+            },
+        );
+        assert_eq!(text.to_ansi(), "\x1b[0;38;5;208mx\x1b[0m");
+    }
+
+    #[test]
+    fn test_styled_text_html() {
+        let colors = List::from(&TermColors::default());
+        let html = styled_text().to_html(&colors);
+
+        let foreground = hex(colors[NamedColor::Foreground]);
+        let light_red = hex(colors[NamedColor::LightRed]);
+        assert!(html.starts_with(&format!(
+            "<pre style=\"font-family: monospace; color: {foreground};"
+        )));
+        assert!(html.contains(&format!(
+            "<span style=\"color: {light_red}; font-weight: bold;\">er</span>: "
+        )));
+        assert!(html.contains(
+            "<span style=\"background-color: #ff8000; text-decoration: underline;\">&lt;a&gt;</span>\nok</pre>"
+        ));
+    }
+}
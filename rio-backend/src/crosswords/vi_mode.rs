@@ -2,6 +2,8 @@
 // which is licensed under Apache 2.0 license.
 
 use std::cmp::min;
+use std::collections::HashMap;
+use std::ops::Range;
 
 use crate::crosswords::grid::{Dimensions, GridSquare};
 use crate::crosswords::pos::{Boundary, Column, Direction, Line, Pos, Side};
@@ -50,8 +52,272 @@ pub enum ViMotion {
     WordLeftEnd,
     /// Move to end of whitespace separated word.
     WordRightEnd,
-    /// Move to opposing bracket.
+    /// Move to opposing bracket, or to the next bracket in the line.
     Bracket,
+    /// Move to the next occurrence of a character in the line, like `f`.
+    FindForward(char),
+    /// Move to the previous occurrence of a character in the line, like `F`.
+    FindBackward(char),
+    /// Move before the next occurrence of a character in the line, like `t`.
+    TillForward(char),
+    /// Move after the previous occurrence of a character in the line, like `T`.
+    TillBackward(char),
+    /// Move to the first line of the history.
+    Top,
+    /// Move to the last line of the screen.
+    Bottom,
+    /// Move to a line, counted from the first line of the history.
+    GotoLine(usize),
+    /// Move to a mark.
+    Mark(char),
+    /// Move to the first non-empty cell in the line of a mark.
+    MarkLine(char),
+}
+
+impl ViMotion {
+    /// Motions yanking whole lines.
+    pub fn is_linewise(self) -> bool {
+        matches!(
+            self,
+            ViMotion::Up
+                | ViMotion::Down
+                | ViMotion::High
+                | ViMotion::Middle
+                | ViMotion::Low
+                | ViMotion::Top
+                | ViMotion::Bottom
+                | ViMotion::GotoLine(_)
+                | ViMotion::MarkLine(_)
+        )
+    }
+
+    /// Motions leaving the cell they move to out of a yank.
+    pub fn is_exclusive(self) -> bool {
+        matches!(
+            self,
+            ViMotion::Left
+                | ViMotion::Right
+                | ViMotion::First
+                | ViMotion::FirstOccupied
+                | ViMotion::SemanticLeft
+                | ViMotion::SemanticRight
+                | ViMotion::WordLeft
+                | ViMotion::WordRight
+                | ViMotion::FindBackward(_)
+                | ViMotion::TillBackward(_)
+                | ViMotion::Mark(_)
+        )
+    }
+}
+
+/// Largest count accepted before a command.
+const MAX_COUNT: usize = 99_999;
+
+/// Operator applied to the text covered by the next motion.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ViOperator {
+    Yank,
+}
+
+/// Search for a character in the line, as done by `f`, `F`, `t` and `T`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CharSearch {
+    forward: bool,
+    till: bool,
+}
+
+impl CharSearch {
+    fn motion(self, c: char) -> ViMotion {
+        match (self.forward, self.till) {
+            (true, false) => ViMotion::FindForward(c),
+            (false, false) => ViMotion::FindBackward(c),
+            (true, true) => ViMotion::TillForward(c),
+            (false, true) => ViMotion::TillBackward(c),
+        }
+    }
+
+    /// Same search in the opposite direction, used by `,`.
+    fn reversed(self) -> Self {
+        Self {
+            forward: !self.forward,
+            till: self.till,
+        }
+    }
+}
+
+/// Key waiting for the character completing its command.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum ViPending {
+    Find(CharSearch),
+    Goto,
+    SetMark,
+    JumpMark,
+    JumpMarkLine,
+}
+
+/// Result of a key typed in vi mode.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ViInput {
+    /// Key is not part of a vi command, it is left to the bindings.
+    Ignored,
+    /// Key was consumed while a command is being typed.
+    Pending,
+    /// Key completed a command.
+    Done,
+}
+
+/// State of the vi commands typed over several keys, like `5j`, `fa` or `yy`.
+#[derive(Default, Debug, Clone)]
+pub struct ViModeState {
+    count: Option<usize>,
+    operator: Option<ViOperator>,
+    pending: Option<ViPending>,
+    last_search: Option<(char, CharSearch)>,
+    marks: HashMap<char, Pos>,
+}
+
+impl ViModeState {
+    /// Drop the command being typed, marks are kept.
+    pub fn cancel(&mut self) {
+        self.count = None;
+        self.operator = None;
+        self.pending = None;
+    }
+
+    /// Whether a count, an operator or a key of a command is pending.
+    pub fn is_pending(&self) -> bool {
+        self.count.is_some() || self.operator.is_some() || self.pending.is_some()
+    }
+
+    /// Take the count typed before the command.
+    #[inline]
+    pub fn take_count(&mut self) -> Option<usize> {
+        self.count.take()
+    }
+
+    /// Take the operator waiting for a motion.
+    #[inline]
+    pub fn take_operator(&mut self) -> Option<ViOperator> {
+        self.pending = None;
+        self.operator.take()
+    }
+
+    #[inline]
+    pub fn mark(&self, name: char) -> Option<Pos> {
+        self.marks.get(&name).copied()
+    }
+
+    /// Feed a character to the command being typed.
+    pub fn input<T: EventListener>(term: &mut Crosswords<T>, c: char) -> ViInput {
+        // Escape and other control keys abort the command.
+        if c.is_control() {
+            term.vi_mode_state.cancel();
+            return ViInput::Ignored;
+        }
+
+        if let Some(pending) = term.vi_mode_state.pending.take() {
+            match pending {
+                ViPending::Find(search) => {
+                    term.vi_mode_state.last_search = Some((c, search));
+                    term.vi_motion(search.motion(c));
+                }
+                ViPending::Goto if c == 'g' => term.vi_motion(ViMotion::Top),
+                ViPending::Goto => term.vi_mode_state.cancel(),
+                ViPending::SetMark => {
+                    let pos = term.vi_mode_cursor.pos;
+                    term.vi_mode_state.marks.insert(c, pos);
+                    term.vi_mode_state.cancel();
+                }
+                ViPending::JumpMark => term.vi_motion(ViMotion::Mark(c)),
+                ViPending::JumpMarkLine => term.vi_motion(ViMotion::MarkLine(c)),
+            }
+
+            return ViInput::Done;
+        }
+
+        let state = &mut term.vi_mode_state;
+        let pending = match c {
+            '0'..='9' if c != '0' || state.count.is_some() => {
+                let digit = c.to_digit(10).unwrap_or_default() as usize;
+                let count = state.count.unwrap_or_default();
+                state.count = Some(
+                    count
+                        .saturating_mul(10)
+                        .saturating_add(digit)
+                        .min(MAX_COUNT),
+                );
+                return ViInput::Pending;
+            }
+            'f' | 'F' | 't' | 'T' => ViPending::Find(CharSearch {
+                forward: c.is_lowercase(),
+                till: c.eq_ignore_ascii_case(&'t'),
+            }),
+            'g' => ViPending::Goto,
+            'm' => ViPending::SetMark,
+            '`' => ViPending::JumpMark,
+            '\'' => ViPending::JumpMarkLine,
+            ';' | ',' => {
+                match state.last_search {
+                    Some((target, search)) if c == ',' => {
+                        term.vi_motion(search.reversed().motion(target))
+                    }
+                    Some((target, search)) => term.vi_motion(search.motion(target)),
+                    None => state.cancel(),
+                }
+                return ViInput::Done;
+            }
+            'G' => {
+                term.vi_motion(ViMotion::Bottom);
+                return ViInput::Done;
+            }
+            'y' if state.operator == Some(ViOperator::Yank) => {
+                term.vi_yank_lines();
+                return ViInput::Done;
+            }
+            // With a selection `y` copies it, which is left to the bindings.
+            'y' if !matches!(&term.selection, Some(s) if !s.is_empty()) => {
+                state.operator = Some(ViOperator::Yank);
+                return ViInput::Pending;
+            }
+            _ => return ViInput::Ignored,
+        };
+
+        state.pending = Some(pending);
+        ViInput::Pending
+    }
+
+    /// Move the marks with the lines scrolled in a region, marks scrolled out
+    /// of the region are dropped. Positive `lines` scroll up.
+    pub fn scroll_marks(&mut self, region: &Range<Line>, lines: i32) {
+        // Lines scrolled up from the top of the screen are kept in the history.
+        let history = region.start == 0 && lines > 0;
+        let in_region = |row: Line| row < region.end && (history || row >= region.start);
+
+        self.marks.retain(|_, pos| {
+            if !in_region(pos.row) {
+                return true;
+            }
+
+            pos.row -= lines;
+            in_region(pos.row)
+        });
+    }
+
+    /// Move all the marks by a number of lines, like on resize.
+    pub fn shift_marks(&mut self, lines: i32) {
+        for pos in self.marks.values_mut() {
+            pos.row += lines;
+        }
+    }
+
+    /// Drop the marks of the lines above `line`.
+    pub fn truncate_marks(&mut self, line: Line) {
+        self.marks.retain(|_, pos| pos.row >= line);
+    }
+
+    pub fn clear_marks(&mut self) {
+        self.marks.clear();
+    }
 }
 
 /// Cursor tracking vi mode position.
@@ -157,8 +423,37 @@ impl ViModeCursor {
             ViMotion::WordRightEnd => {
                 self.pos = word(term, self.pos, Direction::Right, Side::Right);
             }
-            ViMotion::Bracket => {
-                self.pos = term.bracket_search(self.pos).unwrap_or(self.pos)
+            ViMotion::Bracket => self.pos = bracket(term, self.pos),
+            ViMotion::FindForward(c) => {
+                self.pos =
+                    find_in_line(term, self.pos, c, true, false).unwrap_or(self.pos)
+            }
+            ViMotion::FindBackward(c) => {
+                self.pos =
+                    find_in_line(term, self.pos, c, false, false).unwrap_or(self.pos)
+            }
+            ViMotion::TillForward(c) => {
+                self.pos = find_in_line(term, self.pos, c, true, true).unwrap_or(self.pos)
+            }
+            ViMotion::TillBackward(c) => {
+                self.pos =
+                    find_in_line(term, self.pos, c, false, true).unwrap_or(self.pos)
+            }
+            ViMotion::Top => self.pos = line_start(term, term.grid.topmost_line()),
+            ViMotion::Bottom => self.pos = line_start(term, term.grid.bottommost_line()),
+            ViMotion::GotoLine(number) => {
+                let line = term.grid.topmost_line() + number.saturating_sub(1);
+                self.pos = line_start(term, min(line, term.grid.bottommost_line()));
+            }
+            ViMotion::Mark(name) => {
+                if let Some(pos) = valid_mark(term, name) {
+                    self.pos = pos;
+                }
+            }
+            ViMotion::MarkLine(name) => {
+                if let Some(pos) = valid_mark(term, name) {
+                    self.pos = line_start(term, pos.row);
+                }
             }
         }
 
@@ -184,6 +479,55 @@ impl ViModeCursor {
     }
 }
 
+/// First non-empty cell of a line, or its first cell when it is empty.
+fn line_start<T: EventListener>(term: &Crosswords<T>, line: Line) -> Pos {
+    first_occupied_in_line(term, line).unwrap_or(Pos::new(line, Column(0)))
+}
+
+/// Position of a mark, unless its line was dropped from the history.
+fn valid_mark<T: EventListener>(term: &Crosswords<T>, name: char) -> Option<Pos> {
+    term.vi_mode_state.mark(name).filter(|pos| {
+        pos.row >= term.grid.topmost_line()
+            && pos.row <= term.grid.bottommost_line()
+            && pos.col <= term.grid.last_column()
+    })
+}
+
+/// Find the opposing bracket, starting from the first bracket at or after
+/// the cursor in its line.
+fn bracket<T: EventListener>(term: &Crosswords<T>, pos: Pos) -> Pos {
+    (pos.col.0..term.grid.columns())
+        .find_map(|col| term.bracket_search(Pos::new(pos.row, Column(col))))
+        .unwrap_or(pos)
+}
+
+/// Find a character in the line of `pos`, like `f` and `t` in vi.
+///
+/// The cell next to `pos` is skipped by `t` and `T`, so repeating them moves
+/// to the following occurrence instead of staying in place.
+fn find_in_line<T: EventListener>(
+    term: &Crosswords<T>,
+    pos: Pos,
+    c: char,
+    forward: bool,
+    till: bool,
+) -> Option<Pos> {
+    let row = &term.grid[pos.row];
+    let skip = 1 + till as usize;
+
+    let col = if forward {
+        (pos.col.0 + skip..term.grid.columns()).find(|&col| row[Column(col)].c == c)?
+            - till as usize
+    } else {
+        (0..=pos.col.0.checked_sub(skip)?)
+            .rev()
+            .find(|&col| row[Column(col)].c == c)?
+            + till as usize
+    };
+
+    Some(Pos::new(pos.row, Column(col)))
+}
+
 /// Find next end of line to move to.
 fn last<T: EventListener>(term: &Crosswords<T>, mut pos: Pos) -> Pos {
     // Expand across wide cells.
@@ -412,8 +756,10 @@ mod tests {
     use crate::crosswords::pos::{Column, Line};
     use crate::crosswords::CrosswordsSize;
     use crate::crosswords::{Crosswords, CursorShape};
-    use crate::event::VoidListener;
+    use crate::event::{RioEvent, VoidListener, WindowId};
     use crate::performer::handler::Handler;
+    use std::cell::RefCell;
+    use std::rc::Rc;
 
     fn term() -> Crosswords<VoidListener> {
         let size = CrosswordsSize::new(20, 20);
@@ -860,4 +1206,237 @@ mod tests {
         cursor = cursor.scroll(&term, -20);
         assert_eq!(cursor.pos, Pos::new(Line(19), Column(0)));
     }
+
+    #[derive(Clone, Default)]
+    struct ClipboardListener(Rc<RefCell<Vec<String>>>);
+
+    impl EventListener for ClipboardListener {
+        fn event(&self) -> (Option<RioEvent>, bool) {
+            (None, false)
+        }
+
+        fn send_event(&self, event: RioEvent, _id: WindowId) {
+            if let RioEvent::ClipboardStore(_, text) = event {
+                self.0.borrow_mut().push(text);
+            }
+        }
+    }
+
+    fn set_line<T: EventListener>(term: &mut Crosswords<T>, line: i32, text: &str) {
+        for (column, c) in text.chars().enumerate() {
+            term.grid[Line(line)][Column(column)].c = c;
+        }
+    }
+
+    fn input<T: EventListener>(term: &mut Crosswords<T>, keys: &str) {
+        for c in keys.chars() {
+            term.vi_input(c);
+        }
+    }
+
+    #[test]
+    fn motion_find_char() {
+        let mut term = term();
+        set_line(&mut term, 0, "a.b.c.d");
+
+        let mut cursor = ViModeCursor::new(Pos::new(Line(0), Column(0)));
+
+        cursor = cursor.motion(&mut term, ViMotion::FindForward('.'));
+        assert_eq!(cursor.pos, Pos::new(Line(0), Column(1)));
+
+        cursor = cursor.motion(&mut term, ViMotion::FindForward('.'));
+        assert_eq!(cursor.pos, Pos::new(Line(0), Column(3)));
+
+        cursor = cursor.motion(&mut term, ViMotion::TillForward('.'));
+        assert_eq!(cursor.pos, Pos::new(Line(0), Column(4)));
+
+        cursor = cursor.motion(&mut term, ViMotion::FindForward('z'));
+        assert_eq!(cursor.pos, Pos::new(Line(0), Column(4)));
+
+        cursor = cursor.motion(&mut term, ViMotion::TillBackward('a'));
+        assert_eq!(cursor.pos, Pos::new(Line(0), Column(1)));
+
+        cursor = cursor.motion(&mut term, ViMotion::FindBackward('a'));
+        assert_eq!(cursor.pos, Pos::new(Line(0), Column(0)));
+    }
+
+    #[test]
+    fn motion_bracket_pairs() {
+        let mut term = term();
+        set_line(&mut term, 0, "x «a (b) c»");
+
+        // Not on a bracket, the first one of the line is used.
+        let mut cursor = ViModeCursor::new(Pos::new(Line(0), Column(0)));
+        cursor = cursor.motion(&mut term, ViMotion::Bracket);
+        assert_eq!(cursor.pos, Pos::new(Line(0), Column(10)));
+
+        cursor = cursor.motion(&mut term, ViMotion::Bracket);
+        assert_eq!(cursor.pos, Pos::new(Line(0), Column(2)));
+
+        let mut cursor = ViModeCursor::new(Pos::new(Line(0), Column(7)));
+        cursor = cursor.motion(&mut term, ViMotion::Bracket);
+        assert_eq!(cursor.pos, Pos::new(Line(0), Column(5)));
+    }
+
+    #[test]
+    fn input_count() {
+        let mut term = term();
+        term.toggle_vi_mode();
+
+        assert_eq!(term.vi_input('5'), ViInput::Pending);
+        term.vi_motion(ViMotion::Down);
+        assert_eq!(term.vi_mode_cursor.pos, Pos::new(Line(5), Column(0)));
+
+        input(&mut term, "12");
+        term.vi_motion(ViMotion::Right);
+        assert_eq!(term.vi_mode_cursor.pos, Pos::new(Line(5), Column(12)));
+
+        // The count is consumed by the motion.
+        term.vi_motion(ViMotion::Down);
+        assert_eq!(term.vi_mode_cursor.pos, Pos::new(Line(6), Column(12)));
+
+        // A leading zero is not a count.
+        assert_eq!(term.vi_input('0'), ViInput::Ignored);
+
+        // Escape drops the count.
+        input(&mut term, "3\x1b");
+        assert!(!term.vi_input_pending());
+    }
+
+    #[test]
+    fn input_find_char() {
+        let mut term = term();
+        set_line(&mut term, 0, "a.b.c.d.e");
+        term.toggle_vi_mode();
+
+        assert_eq!(term.vi_input('f'), ViInput::Pending);
+        assert_eq!(term.vi_input('.'), ViInput::Done);
+        assert_eq!(term.vi_mode_cursor.pos, Pos::new(Line(0), Column(1)));
+
+        input(&mut term, "2;");
+        assert_eq!(term.vi_mode_cursor.pos, Pos::new(Line(0), Column(5)));
+
+        input(&mut term, ",");
+        assert_eq!(term.vi_mode_cursor.pos, Pos::new(Line(0), Column(3)));
+
+        input(&mut term, "tc");
+        assert_eq!(term.vi_mode_cursor.pos, Pos::new(Line(0), Column(3)));
+
+        input(&mut term, "Tb");
+        assert_eq!(term.vi_mode_cursor.pos, Pos::new(Line(0), Column(3)));
+
+        input(&mut term, "Fa");
+        assert_eq!(term.vi_mode_cursor.pos, Pos::new(Line(0), Column(0)));
+    }
+
+    #[test]
+    fn input_top_bottom() {
+        let mut term = term();
+
+        // Create 10 lines of scrollback.
+        for _ in 0..29 {
+            term.newline();
+        }
+        term.toggle_vi_mode();
+
+        input(&mut term, "gg");
+        assert_eq!(term.vi_mode_cursor.pos, Pos::new(Line(-10), Column(0)));
+
+        input(&mut term, "G");
+        assert_eq!(term.vi_mode_cursor.pos, Pos::new(Line(19), Column(0)));
+
+        input(&mut term, "5G");
+        assert_eq!(term.vi_mode_cursor.pos, Pos::new(Line(-6), Column(0)));
+
+        input(&mut term, "3gg");
+        assert_eq!(term.vi_mode_cursor.pos, Pos::new(Line(-8), Column(0)));
+
+        input(&mut term, "99G");
+        assert_eq!(term.vi_mode_cursor.pos, Pos::new(Line(19), Column(0)));
+
+        // Unknown `g` commands are dropped.
+        input(&mut term, "gx");
+        assert!(!term.vi_input_pending());
+        assert_eq!(term.vi_mode_cursor.pos, Pos::new(Line(19), Column(0)));
+    }
+
+    #[test]
+    fn input_marks() {
+        let mut term = term();
+        set_line(&mut term, 2, "  xyz");
+        term.toggle_vi_mode();
+
+        term.vi_goto_pos(Pos::new(Line(2), Column(4)));
+        input(&mut term, "ma");
+
+        term.vi_goto_pos(Pos::new(Line(10), Column(0)));
+        input(&mut term, "`a");
+        assert_eq!(term.vi_mode_cursor.pos, Pos::new(Line(2), Column(4)));
+
+        term.vi_goto_pos(Pos::new(Line(10), Column(0)));
+        input(&mut term, "'a");
+        assert_eq!(term.vi_mode_cursor.pos, Pos::new(Line(2), Column(2)));
+
+        // Unknown marks leave the cursor in place.
+        input(&mut term, "`b");
+        assert_eq!(term.vi_mode_cursor.pos, Pos::new(Line(2), Column(2)));
+
+        // Marks follow the scrolled lines.
+        term.scroll_up_relative(Line(0), 1);
+        term.vi_goto_pos(Pos::new(Line(10), Column(0)));
+        input(&mut term, "`a");
+        assert_eq!(term.vi_mode_cursor.pos, Pos::new(Line(1), Column(4)));
+    }
+
+    #[test]
+    fn input_yank() {
+        let listener = ClipboardListener::default();
+        let size = CrosswordsSize::new(20, 20);
+        let mut term = Crosswords::new(
+            size,
+            CursorShape::Underline,
+            listener.clone(),
+            crate::event::WindowId::from(0),
+            0,
+        );
+        set_line(&mut term, 0, "foo bar baz");
+        set_line(&mut term, 1, "second line");
+        term.toggle_vi_mode();
+
+        input(&mut term, "y");
+        term.vi_motion(ViMotion::SemanticRight);
+        assert_eq!(listener.0.borrow_mut().pop().unwrap(), "foo ");
+
+        input(&mut term, "y");
+        term.vi_motion(ViMotion::SemanticRightEnd);
+        assert_eq!(listener.0.borrow_mut().pop().unwrap(), "foo");
+
+        input(&mut term, "yy");
+        assert_eq!(listener.0.borrow_mut().pop().unwrap(), "foo bar baz\n");
+
+        input(&mut term, "2yy");
+        assert_eq!(
+            listener.0.borrow_mut().pop().unwrap(),
+            "foo bar baz\nsecond line\n"
+        );
+        assert_eq!(term.vi_mode_cursor.pos, Pos::new(Line(0), Column(0)));
+
+        // The cursor moves to the start of the yanked text.
+        input(&mut term, "fzyFb");
+        assert_eq!(listener.0.borrow_mut().pop().unwrap(), "ba");
+        assert_eq!(term.vi_mode_cursor.pos, Pos::new(Line(0), Column(8)));
+
+        input(&mut term, "y2tr");
+        assert!(listener.0.borrow().is_empty());
+        input(&mut term, "\x1b");
+
+        term.vi_goto_pos(Pos::new(Line(1), Column(7)));
+        input(&mut term, "y");
+        term.vi_motion(ViMotion::Up);
+        assert_eq!(
+            listener.0.borrow_mut().pop().unwrap(),
+            "foo bar baz\nsecond line\n"
+        );
+        assert_eq!(term.vi_mode_cursor.pos, Pos::new(Line(0), Column(7)));
+    }
 }
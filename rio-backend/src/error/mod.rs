@@ -56,6 +56,10 @@ pub enum RioErrorType {
     InvalidConfigurationFormat(String),
     // configuration invalid theme
     InvalidConfigurationTheme(String),
+    // key bindings which can't be triggered
+    KeyBindingConflicts(Vec<String>),
+    // layout requested on startup which isn't configured
+    LayoutNotFound(String),
 
     // reports that are ignored by RioErrorType
     IgnoredReport,
@@ -98,6 +102,17 @@ impl std::fmt::Display for RioErrorType {
             RioErrorType::InvalidConfigurationTheme(message) => {
                 write!(f, "Found an issue in the configured theme:\n\n{message}")
             }
+            RioErrorType::KeyBindingConflicts(conflicts) => {
+                let mut conflicts_str = String::new();
+                for conflict in conflicts {
+                    conflicts_str += format!("\n• {conflict}").as_str();
+                }
+
+                write!(f, "Found conflicting key bindings:\n{conflicts_str}\n\nhttps://raphamorim.io/rio/docs/config/bindings")
+            }
+            RioErrorType::LayoutNotFound(name) => {
+                write!(f, "Layout \"{name}\" was not found in the configuration\n\nhttps://raphamorim.io/rio/docs/config/layouts")
+            }
         }
     }
 }
@@ -4,13 +4,16 @@ use crate::clipboard::ClipboardType;
 use crate::config::colors::ColorRgb;
 use crate::crosswords::grid::Scroll;
 use crate::crosswords::pos::{Direction, Pos};
-use crate::crosswords::search::{Match, RegexSearch};
+use crate::crosswords::search::{Match, RegexSearch, SearchCount, SearchOptions};
 use crate::error::RioError;
+use crate::ipc::{Request, Response};
+use crate::performer::handler::SyncLimits;
 use rio_window::event::Event as RioWindowEvent;
 use std::borrow::Cow;
 use std::collections::VecDeque;
 use std::fmt::Debug;
 use std::fmt::Formatter;
+use std::sync::mpsc::Sender;
 use std::sync::Arc;
 use teletypewriter::WinsizeBuilder;
 
@@ -34,6 +37,9 @@ pub enum Msg {
     Shutdown,
 
     Resize(WinsizeBuilder),
+
+    /// Limits of synchronized updates changed by a configuration update.
+    SyncLimits(SyncLimits),
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -64,6 +70,10 @@ pub enum RioEvent {
     UpdateConfig,
     CreateWindow,
     CloseWindow,
+    /// Save the windows, tabs and splits to the session file.
+    SaveSession,
+    /// Request received by the IPC socket, answered through the sender.
+    Ipc(Request, Sender<Response>),
     CreateNativeTab(Option<String>),
     CreateConfigEditor,
     SelectNativeTabByIndex(usize),
@@ -73,6 +83,9 @@ pub enum RioEvent {
 
     ReportToAssistant(RioError),
 
+    /// Open the command palette.
+    CommandPalette,
+
     /// Grid has changed possibly requiring a mouse cursor shape change.
     MouseCursorDirty,
 
@@ -117,8 +130,19 @@ pub enum RioEvent {
 
     CursorBlinkingChangeOnRoute(usize),
 
-    /// Terminal bell ring.
-    Bell,
+    /// Terminal bell ring, urgency hint tells if the window should
+    /// request the user attention.
+    Bell {
+        route_id: usize,
+        urgency_hint: bool,
+    },
+
+    /// Desktop notification requested by the terminal.
+    Notification {
+        title: String,
+        body: String,
+        route_id: usize,
+    },
 
     /// Shutdown request.
     Exit,
@@ -168,12 +192,22 @@ impl Debug for RioEvent {
             RioEvent::Render => write!(f, "Render"),
             RioEvent::RenderRoute(route) => write!(f, "Render route {route}"),
             RioEvent::Scroll(scroll) => write!(f, "Scroll {scroll:?}"),
-            RioEvent::Bell => write!(f, "Bell"),
+            RioEvent::Bell {
+                route_id,
+                urgency_hint,
+            } => write!(f, "Bell({urgency_hint}) on route {route_id}"),
+            RioEvent::Notification {
+                title,
+                body,
+                route_id,
+            } => write!(f, "Notification({title}, {body}) on route {route_id}"),
             RioEvent::Exit => write!(f, "Exit"),
             RioEvent::Quit => write!(f, "Quit"),
             RioEvent::CloseTerminal(route) => write!(f, "CloseTerminal {route}"),
             RioEvent::CreateWindow => write!(f, "CreateWindow"),
             RioEvent::CloseWindow => write!(f, "CloseWindow"),
+            RioEvent::SaveSession => write!(f, "SaveSession"),
+            RioEvent::Ipc(request, _) => write!(f, "Ipc({request:?})"),
             RioEvent::CreateNativeTab(_) => write!(f, "CreateNativeTab"),
             RioEvent::SelectNativeTabByIndex(tab_index) => {
                 write!(f, "SelectNativeTabByIndex({tab_index})")
@@ -186,6 +220,7 @@ impl Debug for RioEvent {
             RioEvent::ReportToAssistant(error_report) => {
                 write!(f, "ReportToAssistant({})", error_report.report)
             }
+            RioEvent::CommandPalette => write!(f, "CommandPalette"),
             RioEvent::ToggleFullScreen => write!(f, "FullScreen"),
             RioEvent::BlinkCursor(timeout, route_id) => {
                 write!(f, "BlinkCursor {timeout} {route_id}")
@@ -302,6 +337,12 @@ pub struct SearchState {
 
     /// Compiled search automatons.
     pub dfas: Option<RegexSearch>,
+
+    /// Case, whole word and literal modes of the search.
+    pub options: SearchOptions,
+
+    /// Matches of the active search in the whole grid.
+    pub count: Option<SearchCount>,
 }
 
 impl SearchState {
@@ -352,6 +393,8 @@ impl Default for SearchState {
             history: Default::default(),
             origin: Default::default(),
             dfas: Default::default(),
+            options: Default::default(),
+            count: Default::default(),
         }
     }
 }
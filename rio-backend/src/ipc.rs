@@ -0,0 +1,113 @@
+use crate::config::layouts::LayoutSplit;
+use serde::{Deserialize, Serialize};
+
+/// Environment variable holding the path of the socket of the Rio process
+/// a terminal runs in.
+pub const SOCKET_ENV: &str = "RIO_SOCKET";
+
+/// Request sent to the socket, written as a JSON object on a single line.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(
+    tag = "type",
+    rename_all = "kebab-case",
+    rename_all_fields = "kebab-case"
+)]
+pub enum Request {
+    CreateWindow {
+        #[serde(default = "Vec::default")]
+        command: Vec<String>,
+        #[serde(default = "Option::default")]
+        working_dir: Option<String>,
+    },
+    CreateTab {
+        #[serde(default = "Option::default")]
+        window: Option<u64>,
+        #[serde(default = "Vec::default")]
+        command: Vec<String>,
+        #[serde(default = "Option::default")]
+        working_dir: Option<String>,
+    },
+    Split {
+        #[serde(flatten)]
+        target: Target,
+        #[serde(default = "LayoutSplit::default")]
+        direction: LayoutSplit,
+        #[serde(default = "Vec::default")]
+        command: Vec<String>,
+        #[serde(default = "Option::default")]
+        working_dir: Option<String>,
+    },
+    /// Write text to a pane, like if it was typed.
+    SendText {
+        #[serde(flatten)]
+        target: Target,
+        text: String,
+    },
+    ListPanes,
+    /// Text of the screen of a pane, or of its last lines including the
+    /// scrollback.
+    GetText {
+        #[serde(flatten)]
+        target: Target,
+        #[serde(default = "Option::default")]
+        lines: Option<usize>,
+    },
+    /// Change configuration values until Rio quits, each value is written in
+    /// TOML like `fonts.size = 18`.
+    SetConfig {
+        #[serde(default = "Vec::default")]
+        values: Vec<String>,
+        /// Drop the values changed by previous requests.
+        #[serde(default = "bool::default")]
+        reset: bool,
+    },
+}
+
+/// Pane a request applies to, the focused window and its current pane are
+/// used when unset.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Target {
+    #[serde(default = "Option::default")]
+    pub window: Option<u64>,
+    #[serde(default = "Option::default")]
+    pub pane: Option<usize>,
+}
+
+/// Answer to a request, written as a JSON object on a single line.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(
+    tag = "type",
+    rename_all = "kebab-case",
+    rename_all_fields = "kebab-case"
+)]
+pub enum Response {
+    Ok,
+    Error {
+        message: String,
+    },
+    /// Pane created by the request.
+    Pane {
+        window: u64,
+        pane: usize,
+    },
+    Panes {
+        panes: Vec<PaneInfo>,
+    },
+    Text {
+        text: String,
+    },
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct PaneInfo {
+    pub window: u64,
+    pub tab: usize,
+    pub pane: usize,
+    pub title: String,
+    pub working_dir: Option<String>,
+    pub columns: usize,
+    pub lines: usize,
+    /// Whether it is the current pane of the focused window.
+    pub focused: bool,
+}
@@ -4,6 +4,7 @@ pub mod config;
 pub mod crosswords;
 pub mod error;
 pub mod event;
+pub mod ipc;
 pub mod performer;
 pub mod selection;
 
@@ -1,10 +1,15 @@
 use crate::ansi::iterm2_image_protocol;
-use crate::ansi::CursorShape;
+use crate::ansi::kitty_graphics_protocol::{self, KittyGraphicsCommand};
 use crate::ansi::{sixel, KeyboardModes, KeyboardModesApplyBehavior};
+use crate::ansi::{CursorShape, PromptMark, RectangularArea};
 use crate::config::colors::{AnsiColor, ColorRgb, NamedColor};
+use crate::config::defaults::{default_sync_max_bytes, default_sync_timeout};
+use crate::config::renderer::Renderer;
 use crate::crosswords::pos::{CharsetIndex, Column, Line, StandardCharset};
 use crate::crosswords::square::Hyperlink;
+use base64::{engine::general_purpose, Engine as _};
 use cursor_icon::CursorIcon;
+use std::path::PathBuf;
 use std::str::FromStr;
 use std::time::Duration;
 use std::time::Instant;
@@ -23,12 +28,6 @@ use std::fmt::Write;
 // https://vt100.net/emu/dec_ansi_parser
 use copa::{Params, ParamsIter};
 
-/// Maximum time before a synchronized update is aborted.
-const SYNC_UPDATE_TIMEOUT: Duration = Duration::from_millis(150);
-
-/// Maximum number of bytes read in one synchronized update (2MiB).
-const SYNC_BUFFER_SIZE: usize = 0x20_0000;
-
 /// Number of bytes in the BSU/ESU CSI sequences.
 const SYNC_ESCAPE_LEN: usize = 8;
 
@@ -94,6 +93,65 @@ fn parse_legacy_color(color: &[u8]) -> Option<ColorRgb> {
     })
 }
 
+/// Parse the `file://host/path` URL reported through OSC 7, returning the
+/// hostname and the path.
+fn parse_file_url(url: &[u8]) -> Option<(String, PathBuf)> {
+    let url = url.strip_prefix(b"file://")?;
+
+    let host_end = url.iter().position(|&b| b == b'/')?;
+    let host = String::from_utf8_lossy(&url[..host_end]).into_owned();
+    let path = &url[host_end..];
+
+    let mut decoded = Vec::with_capacity(path.len());
+    let mut i = 0;
+    while i < path.len() {
+        let escaped = path
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (path[i], escaped) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8(decoded)
+        .ok()
+        .map(|path| (host, PathBuf::from(path)))
+}
+
+/// Whether the hostname of an OSC 7 URL is the local machine, a shell
+/// running through ssh reports the directory of the remote one.
+fn is_local_host(host: &str) -> bool {
+    host.is_empty()
+        || host.eq_ignore_ascii_case("localhost")
+        || local_hostname().is_some_and(|local| host.eq_ignore_ascii_case(&local))
+}
+
+#[cfg(unix)]
+fn local_hostname() -> Option<String> {
+    let mut buf = [0u8; 256];
+    // SAFETY: the buffer outlives the call and its length is given.
+    let res = unsafe { libc::gethostname(buf.as_mut_ptr().cast(), buf.len()) };
+    if res != 0 {
+        return None;
+    }
+
+    let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    std::str::from_utf8(&buf[..len]).ok().map(str::to_owned)
+}
+
+#[cfg(not(unix))]
+fn local_hostname() -> Option<String> {
+    std::env::var("COMPUTERNAME").ok()
+}
+
 fn parse_number(input: &[u8]) -> Option<u8> {
     if input.is_empty() {
         return None;
@@ -113,6 +171,54 @@ fn parse_number(input: &[u8]) -> Option<u8> {
     Some(num)
 }
 
+/// Decode a hex encoded string, like the capability names of XTGETTCAP.
+fn decode_hex(input: &[u8]) -> Option<Vec<u8>> {
+    let pairs = input.chunks_exact(2);
+    if !pairs.remainder().is_empty() {
+        return None;
+    }
+
+    pairs
+        .map(|pair| {
+            let high = (pair[0] as char).to_digit(16)?;
+            let low = (pair[1] as char).to_digit(16)?;
+            Some((high * 16 + low) as u8)
+        })
+        .collect()
+}
+
+/// Parse the next parameter of a rectangular area, zero means the default.
+fn next_area_param(params: &mut ParamsIter<'_>) -> Option<usize> {
+    match params.next() {
+        Some(&[param, ..]) if param != 0 => Some(param as usize),
+        _ => None,
+    }
+}
+
+/// Parse the `Pt ; Pl ; Pb ; Pr` parameters of a rectangular area.
+fn parse_rectangular_area(params: &mut ParamsIter<'_>) -> RectangularArea {
+    RectangularArea {
+        top: next_area_param(params).unwrap_or(1),
+        left: next_area_param(params).unwrap_or(1),
+        bottom: next_area_param(params),
+        right: next_area_param(params),
+    }
+}
+
+/// Parse the attributes of DECCARA and DECRARA, which default to a reset.
+fn parse_area_attributes(params: &mut ParamsIter<'_>) -> Vec<Attr> {
+    let attrs: Vec<Attr> = attrs_from_sgr_parameters(params)
+        .into_iter()
+        .flatten()
+        .collect();
+
+    if attrs.is_empty() {
+        vec![Attr::Reset]
+    } else {
+        attrs
+    }
+}
+
 fn parse_sgr_color(params: &mut dyn Iterator<Item = u16>) -> Option<AnsiColor> {
     match params.next() {
         Some(2) => Some(AnsiColor::Spec(ColorRgb {
@@ -288,6 +394,43 @@ pub trait Handler {
     /// DECSTBM - Set the terminal scrolling region.
     fn set_scrolling_region(&mut self, _top: usize, _bottom: Option<usize>) {}
 
+    /// A synchronized update was aborted before its end was received.
+    fn sync_update_aborted(&mut self, _reason: SyncAbort) {}
+
+    /// DECFRA - Fill a rectangular area with a character.
+    fn fill_rectangular_area(&mut self, _c: char, _area: RectangularArea) {}
+
+    /// DECERA - Erase a rectangular area.
+    fn erase_rectangular_area(&mut self, _area: RectangularArea) {}
+
+    /// DECCRA - Copy a rectangular area to the given top-left position.
+    fn copy_rectangular_area(
+        &mut self,
+        _source: RectangularArea,
+        _top: usize,
+        _left: usize,
+    ) {
+    }
+
+    /// DECCARA - Change the graphic rendition of a rectangular area.
+    fn change_area_attributes(&mut self, _area: RectangularArea, _attrs: Vec<Attr>) {}
+
+    /// DECRARA - Reverse the graphic rendition of a rectangular area.
+    fn reverse_area_attributes(&mut self, _area: RectangularArea, _attrs: Vec<Attr>) {}
+
+    /// DECSACE - Select whether DECCARA and DECRARA apply to the rectangle or to
+    /// the stream of characters between its corners.
+    fn set_attribute_change_extent(&mut self, _rectangle: bool) {}
+
+    /// DECSLRM - Set the left and right margins.
+    fn set_left_right_margins(&mut self, _left: usize, _right: Option<usize>) {}
+
+    /// Whether left and right margins are enabled (DECLRMM), which turns
+    /// `CSI s` into DECSLRM.
+    fn is_left_right_margin_mode(&self) -> bool {
+        false
+    }
+
     /// DECKPAM - Set keypad to applications mode (ESCape instead of digits).
     fn set_keypad_application_mode(&mut self) {}
 
@@ -356,15 +499,34 @@ pub trait Handler {
     /// Insert a new graphic item.
     fn insert_graphic(&mut self, _data: GraphicData, _palette: Option<Vec<ColorRgb>>) {}
 
+    /// Run a command of the kitty graphics protocol.
+    fn kitty_graphics_command(&mut self, _command: KittyGraphicsCommand) {}
+
     /// Set hyperlink.
     fn set_hyperlink(&mut self, _: Option<Hyperlink>) {}
 
+    /// Set the working directory reported by the shell, `None` when it is on
+    /// another machine.
+    fn set_current_directory(&mut self, _path: Option<PathBuf>) {}
+
+    /// Show a desktop notification.
+    fn desktop_notification(&mut self, _title: String, _body: String) {}
+
+    /// Record a shell integration mark at the cursor.
+    fn prompt_mark(&mut self, _mark: PromptMark) {}
+
     /// Set mouse cursor icon.
     fn set_mouse_cursor_icon(&mut self, _: CursorIcon) {}
 
     /// Report current keyboard mode.
     fn report_keyboard_mode(&mut self) {}
 
+    /// Report the requested terminfo capabilities (XTGETTCAP).
+    fn report_terminfo_capabilities(&mut self, _names: Vec<String>) {}
+
+    /// Report the setting of a control function (DECRQSS).
+    fn report_status_string(&mut self, _setting: &[u8]) {}
+
     /// Push keyboard mode into the keyboard mode stack.
     fn push_keyboard_mode(&mut self, _mode: KeyboardModes) {}
 
@@ -391,6 +553,78 @@ struct ProcessorState {
 
     /// State for synchronized terminal updates.
     sync_state: SyncState,
+
+    /// Desktop notification received in chunks through OSC 99.
+    pending_notification: Option<PendingNotification>,
+
+    /// Query received through DCS, dispatched once the sequence is terminated.
+    pending_query: Option<PendingQuery>,
+}
+
+/// Maximum size of a DCS query, longer queries are ignored.
+const MAX_QUERY_SIZE: usize = 4096;
+
+#[derive(Debug)]
+enum QueryKind {
+    /// XTGETTCAP, `DCS + q Pt ST`.
+    TerminfoCapabilities,
+    /// DECRQSS, `DCS $ q Pt ST`.
+    StatusString,
+}
+
+#[derive(Debug)]
+struct PendingQuery {
+    kind: QueryKind,
+    buffer: Vec<u8>,
+}
+
+/// Maximum size of the title and body of a notification sent in chunks
+/// through OSC 99, bigger notifications are dropped.
+const MAX_NOTIFICATION_SIZE: usize = 4096;
+
+#[derive(Debug, Default)]
+struct PendingNotification {
+    id: String,
+    title: String,
+    body: String,
+    /// The notification went over [`MAX_NOTIFICATION_SIZE`], its remaining
+    /// chunks are ignored.
+    dropped: bool,
+}
+
+/// Limits of synchronized updates, reaching one of them aborts the update.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyncLimits {
+    /// Maximum time before a synchronized update is aborted.
+    pub timeout: Duration,
+
+    /// Maximum number of bytes read in one synchronized update.
+    pub max_bytes: usize,
+}
+
+impl Default for SyncLimits {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_millis(default_sync_timeout()),
+            max_bytes: default_sync_max_bytes(),
+        }
+    }
+}
+
+impl From<&Renderer> for SyncLimits {
+    fn from(renderer: &Renderer) -> Self {
+        Self {
+            timeout: Duration::from_millis(renderer.sync_timeout),
+            max_bytes: renderer.sync_max_bytes,
+        }
+    }
+}
+
+/// Reason for aborting a synchronized update before its end was received.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncAbort {
+    Timeout,
+    Overflow,
 }
 
 #[derive(Debug)]
@@ -400,15 +634,31 @@ struct SyncState {
 
     /// Bytes read during the synchronized update.
     buffer: Vec<u8>,
+
+    limits: SyncLimits,
 }
 
 impl Default for SyncState {
     fn default() -> Self {
+        Self::new(SyncLimits::default())
+    }
+}
+
+impl SyncState {
+    fn new(limits: SyncLimits) -> Self {
         Self {
-            buffer: Vec::with_capacity(SYNC_BUFFER_SIZE),
+            // Large limits are only allocated once they are used.
+            buffer: Vec::with_capacity(limits.max_bytes.min(default_sync_max_bytes())),
             timeout: None,
+            limits,
         }
     }
+
+    /// Start or extend the synchronized update.
+    #[inline]
+    fn extend(&mut self) {
+        self.timeout = Some(Instant::now() + self.limits.timeout);
+    }
 }
 
 #[derive(Default)]
@@ -424,6 +674,24 @@ impl ParserProcessor {
         Self::default()
     }
 
+    /// Create a processor with custom limits for synchronized updates.
+    #[inline]
+    pub fn with_sync_limits(limits: SyncLimits) -> Self {
+        Self {
+            state: ProcessorState {
+                sync_state: SyncState::new(limits),
+                ..ProcessorState::default()
+            },
+            parser: copa::Parser::default(),
+        }
+    }
+
+    /// Change the limits of synchronized updates, like on configuration updates.
+    #[inline]
+    pub fn set_sync_limits(&mut self, limits: SyncLimits) {
+        self.state.sync_state.limits = limits;
+    }
+
     /// Process a new byte from the PTY.
     #[inline]
     pub fn advance<H>(&mut self, handler: &mut H, byte: u8)
@@ -457,6 +725,16 @@ impl ParserProcessor {
         self.state.sync_state.timeout = None;
     }
 
+    /// Abort a synchronized update, processing the bytes received so far.
+    pub fn abort_sync<H>(&mut self, handler: &mut H, reason: SyncAbort)
+    where
+        H: Handler,
+    {
+        debug!("Aborting synchronized update: {reason:?}");
+        handler.sync_update_aborted(reason);
+        self.stop_sync(handler);
+    }
+
     /// Synchronized update expiration time.
     #[inline]
     pub fn sync_timeout(&self) -> Option<&Instant> {
@@ -497,9 +775,11 @@ impl ParserProcessor {
         //
         // Check for extension/termination of the synchronized update.
         if end == BSU_CSI {
-            self.state.sync_state.timeout = Some(Instant::now() + SYNC_UPDATE_TIMEOUT);
-        } else if end == ESU_CSI || len >= SYNC_BUFFER_SIZE - 1 {
+            self.state.sync_state.extend();
+        } else if end == ESU_CSI {
             self.stop_sync(handler);
+        } else if len >= self.state.sync_state.limits.max_bytes.saturating_sub(1) {
+            self.abort_sync(handler, SyncAbort::Overflow);
         }
     }
 }
@@ -553,6 +833,18 @@ impl<U: Handler> copa::Perform for Performer<'_, U> {
             ('q', []) => {
                 self.handler.sixel_graphic_start(params);
             }
+            ('q', [b'+']) => {
+                self.state.pending_query = Some(PendingQuery {
+                    kind: QueryKind::TerminfoCapabilities,
+                    buffer: Vec::new(),
+                });
+            }
+            ('q', [b'$']) => {
+                self.state.pending_query = Some(PendingQuery {
+                    kind: QueryKind::StatusString,
+                    buffer: Vec::new(),
+                });
+            }
             _ => debug!(
                 "[unhandled hook] params={:?}, ints: {:?}, ignore: {:?}, action: {:?}",
                 params, intermediates, ignore, action
@@ -566,6 +858,13 @@ impl<U: Handler> copa::Perform for Performer<'_, U> {
                 tracing::warn!("Failed to parse Sixel data: {}", err);
                 self.handler.sixel_graphic_reset();
             }
+        } else if let Some(query) = &mut self.state.pending_query {
+            if query.buffer.len() < MAX_QUERY_SIZE {
+                query.buffer.push(byte);
+            } else {
+                debug!("[put] DCS query exceeds {MAX_QUERY_SIZE} bytes");
+                self.state.pending_query = None;
+            }
         } else {
             debug!("[unhandled put] byte={:?}", byte);
         }
@@ -575,11 +874,39 @@ impl<U: Handler> copa::Perform for Performer<'_, U> {
     fn unhook(&mut self) {
         if self.handler.is_sixel_graphic_active() {
             self.handler.sixel_graphic_finish();
+        } else if let Some(query) = self.state.pending_query.take() {
+            match query.kind {
+                QueryKind::TerminfoCapabilities => {
+                    let names = query
+                        .buffer
+                        .split(|&byte| byte == b';')
+                        .map(|name| {
+                            let name = decode_hex(name).unwrap_or_default();
+                            String::from_utf8_lossy(&name).into_owned()
+                        })
+                        .collect();
+                    self.handler.report_terminfo_capabilities(names);
+                }
+                QueryKind::StatusString => {
+                    self.handler.report_status_string(&query.buffer)
+                }
+            }
         } else {
             debug!("[unhandled dcs_unhook]");
         }
     }
 
+    fn apc_dispatch(&mut self, bytes: &[u8]) {
+        match bytes.first() {
+            // Kitty graphics protocol.
+            Some(b'G') => match kitty_graphics_protocol::parse(&bytes[1..]) {
+                Some(command) => self.handler.kitty_graphics_command(command),
+                None => debug!("[apc_dispatch] invalid kitty graphics command"),
+            },
+            _ => debug!("[unhandled apc_dispatch] bytes={:?}", bytes),
+        }
+    }
+
     fn osc_dispatch(&mut self, params: &[&[u8]], bell_terminated: bool) {
         debug!("[osc_dispatch] params={params:?} bell_terminated={bell_terminated}");
 
@@ -649,6 +976,14 @@ impl<U: Handler> copa::Perform for Performer<'_, U> {
                 }
             }
 
+            // Set current working directory.
+            b"7" if params.len() >= 2 => match parse_file_url(&params[1..].join(&b';')) {
+                Some((host, path)) => self
+                    .handler
+                    .set_current_directory(Some(path).filter(|_| is_local_host(&host))),
+                None => unhandled(params),
+            },
+
             // Hyperlink.
             b"8" if params.len() > 2 => {
                 let link_params = params[1];
@@ -670,6 +1005,12 @@ impl<U: Handler> copa::Perform for Performer<'_, U> {
                 self.handler.set_hyperlink(Some(Hyperlink::new(id, uri)));
             }
 
+            // Desktop notification, numeric payloads are ConEmu extensions.
+            b"9" if params.len() >= 2 && parse_number(params[1]).is_none() => {
+                let body = String::from_utf8_lossy(&params[1..].join(&b';')).into_owned();
+                self.handler.desktop_notification(String::new(), body);
+            }
+
             b"10" | b"11" | b"12" => {
                 if params.len() >= 2 {
                     if let Some(mut dynamic_code) = parse_number(params[0]) {
@@ -745,6 +1086,64 @@ impl<U: Handler> copa::Perform for Performer<'_, U> {
                 }
             }
 
+            // Desktop notification with metadata, as defined by kitty.
+            b"99" if params.len() >= 3 => {
+                let metadata = std::str::from_utf8(params[1]).unwrap_or_default();
+                let (mut id, mut is_done, mut is_body, mut is_base64) =
+                    ("", true, false, false);
+                for (key, value) in
+                    metadata.split(':').filter_map(|kv| kv.split_once('='))
+                {
+                    match (key, value) {
+                        ("i", value) => id = value,
+                        ("d", value) => is_done = value != "0",
+                        ("p", "title") => is_body = false,
+                        ("p", "body") => is_body = true,
+                        ("p", _) => return unhandled(params),
+                        ("e", value) => is_base64 = value == "1",
+                        _ => (),
+                    }
+                }
+
+                let mut payload = params[2..].join(&b';');
+                if is_base64 {
+                    match general_purpose::STANDARD.decode(&payload) {
+                        Ok(decoded) => payload = decoded,
+                        Err(_) => return unhandled(params),
+                    }
+                }
+                let payload = String::from_utf8_lossy(&payload);
+
+                let mut notification = self
+                    .state
+                    .pending_notification
+                    .take()
+                    .filter(|notification| notification.id == id)
+                    .unwrap_or_else(|| PendingNotification {
+                        id: id.to_owned(),
+                        ..Default::default()
+                    });
+                let size = notification.title.len() + notification.body.len();
+                if notification.dropped || size + payload.len() > MAX_NOTIFICATION_SIZE {
+                    notification.dropped = true;
+                    notification.title = String::new();
+                    notification.body = String::new();
+                } else if is_body {
+                    notification.body.push_str(&payload);
+                } else {
+                    notification.title.push_str(&payload);
+                }
+
+                if is_done {
+                    if !notification.dropped {
+                        self.handler
+                            .desktop_notification(notification.title, notification.body);
+                    }
+                } else {
+                    self.state.pending_notification = Some(notification);
+                }
+            }
+
             b"104" => {
                 // Reset all color indexes when no parameters are given.
                 if params.len() == 1 || params[1].is_empty() {
@@ -772,6 +1171,25 @@ impl<U: Handler> copa::Perform for Performer<'_, U> {
             // Reset text cursor color.
             b"112" => self.handler.reset_color(NamedColor::Cursor as usize),
 
+            // Shell integration marks.
+            b"133" if params.len() >= 2 => {
+                let mark = match params[1] {
+                    b"A" => PromptMark::PromptStart,
+                    b"B" => PromptMark::CommandStart,
+                    b"C" => PromptMark::OutputStart,
+                    b"D" => PromptMark::CommandEnd,
+                    _ => return unhandled(params),
+                };
+                self.handler.prompt_mark(mark);
+            }
+
+            // Desktop notification, as defined by urxvt.
+            b"777" if params.len() >= 3 && params[1] == b"notify" => {
+                let title = String::from_utf8_lossy(params[2]).into_owned();
+                let body = String::from_utf8_lossy(&params[3..].join(&b';')).into_owned();
+                self.handler.desktop_notification(title, body);
+            }
+
             // OSC 1337 is not necessarily only used by iTerm2 protocol
             // OSC 1337 is equal to xterm OSC 50
             b"1337" => {
@@ -804,7 +1222,9 @@ impl<U: Handler> copa::Perform for Performer<'_, U> {
             }};
         }
 
-        if should_ignore || intermediates.len() > 1 {
+        // Only private mode requests (DECRQM) carry two intermediates, a `?` and a `$`.
+        let is_private_mode_request = action == 'p' && intermediates == b"?$";
+        if should_ignore || (intermediates.len() > 1 && !is_private_mode_request) {
             return;
         }
 
@@ -868,8 +1288,7 @@ impl<U: Handler> copa::Perform for Performer<'_, U> {
                 for param in params_iter.map(|param| param[0]) {
                     // Handle sync updates opaquely.
                     if param == NamedPrivateMode::SyncUpdate as u16 {
-                        self.state.sync_state.timeout =
-                            Some(Instant::now() + SYNC_UPDATE_TIMEOUT);
+                        self.state.sync_state.extend();
                     }
 
                     handler.set_private_mode(PrivateMode::new(param))
@@ -962,10 +1381,24 @@ impl<U: Handler> copa::Perform for Performer<'_, U> {
 
                 handler.set_scrolling_region(top, bottom);
             }
+            ('r', [b'$']) => {
+                let area = parse_rectangular_area(&mut params_iter);
+                let attrs = parse_area_attributes(&mut params_iter);
+                handler.change_area_attributes(area, attrs);
+            }
             ('S', []) => handler.scroll_up(next_param_or(1) as usize),
             ('S', [b'?']) => {
                 handler.graphics_attribute(next_param_or(0), next_param_or(0))
             }
+            ('s', []) if handler.is_left_right_margin_mode() => {
+                let left = next_param_or(1) as usize;
+                let right = params_iter
+                    .next()
+                    .map(|param| param[0] as usize)
+                    .filter(|&param| param != 0);
+
+                handler.set_left_right_margins(left, right);
+            }
             ('s', []) => handler.save_cursor_position(),
             ('T', []) => handler.scroll_down(next_param_or(1) as usize),
             ('t', []) => match next_param_or(1) as usize {
@@ -976,6 +1409,11 @@ impl<U: Handler> copa::Perform for Performer<'_, U> {
                 23 => handler.pop_title(),
                 _ => csi_unhandled!(),
             },
+            ('t', [b'$']) => {
+                let area = parse_rectangular_area(&mut params_iter);
+                let attrs = parse_area_attributes(&mut params_iter);
+                handler.reverse_area_attributes(area, attrs);
+            }
             ('u', [b'?']) => handler.report_keyboard_mode(),
             ('u', [b'=']) => {
                 let mode = KeyboardModes::from_bits_truncate(next_param_or(0) as u8);
@@ -996,8 +1434,32 @@ impl<U: Handler> copa::Perform for Performer<'_, U> {
                 handler.pop_keyboard_modes(next_param_or(1));
             }
             ('u', []) => handler.restore_cursor_position(),
+            ('v', [b'$']) => {
+                let source = parse_rectangular_area(&mut params_iter);
+                // Pages are not supported, skip the source page.
+                params_iter.next();
+                let top = next_area_param(&mut params_iter).unwrap_or(1);
+                let left = next_area_param(&mut params_iter).unwrap_or(1);
+                handler.copy_rectangular_area(source, top, left);
+            }
             ('X', []) => handler.erase_chars(Column(next_param_or(1) as usize)),
+            ('x', [b'$']) => {
+                let c = match next_param_or(0) {
+                    c @ (32..=126 | 160..=255) => char::from(c as u8),
+                    _ => {
+                        csi_unhandled!();
+                        return;
+                    }
+                };
+                let area = parse_rectangular_area(&mut params_iter);
+                handler.fill_rectangular_area(c, area);
+            }
+            ('x', [b'*']) => handler.set_attribute_change_extent(next_param_or(0) == 2),
             ('Z', []) => handler.move_backward_tabs(next_param_or(1)),
+            ('z', [b'$']) => {
+                let area = parse_rectangular_area(&mut params_iter);
+                handler.erase_rectangular_area(area);
+            }
             _ => csi_unhandled!(),
         };
     }
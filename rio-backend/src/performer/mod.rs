@@ -42,6 +42,7 @@ pub struct Machine<T: teletypewriter::EventedPty, U: EventListener> {
     event_proxy: U,
     window_id: WindowId,
     route_id: usize,
+    sync_limits: handler::SyncLimits,
 }
 
 #[derive(Default)]
@@ -121,6 +122,7 @@ where
         event_proxy: U,
         window_id: WindowId,
         route_id: usize,
+        sync_limits: handler::SyncLimits,
     ) -> Result<Machine<T, U>, Box<dyn std::error::Error>> {
         let (sender, receiver) = channel::channel();
         let poll = corcovado::Poll::new()?;
@@ -134,6 +136,7 @@ where
             event_proxy,
             window_id,
             route_id,
+            sync_limits,
         })
     }
 
@@ -208,6 +211,7 @@ where
                 Msg::Resize(window_size) => {
                     let _ = self.pty.set_winsize(window_size);
                 }
+                Msg::SyncLimits(limits) => state.parser.set_sync_limits(limits),
                 Msg::Shutdown => return false,
             }
         }
@@ -273,7 +277,10 @@ where
 
     pub fn spawn(mut self) {
         spawn_named("PTY reader", move || {
-            let mut state = State::default();
+            let mut state = State {
+                parser: handler::ParserProcessor::with_sync_limits(self.sync_limits),
+                ..State::default()
+            };
             let mut buf = [0u8; READ_BUFFER_SIZE];
 
             let mut tokens = (0..).map(Into::into);
@@ -307,7 +314,10 @@ where
 
                 // Handle synchronized update timeout.
                 if events.is_empty() {
-                    state.parser.stop_sync(&mut *self.terminal.lock());
+                    state.parser.abort_sync(
+                        &mut *self.terminal.lock(),
+                        handler::SyncAbort::Timeout,
+                    );
                     self.event_proxy
                         .send_event(RioEvent::RenderRoute(self.route_id), self.window_id);
 
@@ -12,12 +12,85 @@ use std::mem;
 use std::ops::{Bound, Range, RangeBounds};
 
 use crate::ansi::CursorShape;
+use crate::config::selection::{default_semantic_escape_chars, Selection as Config};
 use crate::crosswords::grid::{Dimensions, GridSquare, Indexed};
-use crate::crosswords::pos::{Boundary, Column, Line, Pos, Side};
+use crate::crosswords::pos::{Boundary, Column, Direction, Line, Pos, Side};
+use crate::crosswords::search::{Match, RegexIter, RegexSearch};
 use crate::crosswords::square::{Flags, Square};
 use crate::crosswords::Crosswords;
 use crate::event::EventListener;
 
+/// Regex selected as a whole on double-click, like URLs or file paths.
+#[derive(Clone, Debug)]
+pub struct SmartRule {
+    regex: RegexSearch,
+    priority: i32,
+}
+
+/// Selection behavior taken from the `[selection]` configuration.
+#[derive(Clone, Debug)]
+pub struct SelectionConfig {
+    pub semantic_escape_chars: String,
+    /// Smart selection rules, sorted from the highest priority.
+    pub smart_rules: Vec<SmartRule>,
+    pub trim_block_whitespace: bool,
+}
+
+impl Default for SelectionConfig {
+    fn default() -> Self {
+        Self {
+            semantic_escape_chars: default_semantic_escape_chars(),
+            smart_rules: Vec::new(),
+            trim_block_whitespace: true,
+        }
+    }
+}
+
+impl From<&Config> for SelectionConfig {
+    fn from(config: &Config) -> Self {
+        let mut smart_rules: Vec<SmartRule> = config
+            .smart_rules
+            .iter()
+            .filter_map(|rule| match RegexSearch::new(&rule.regex) {
+                Ok(regex) => Some(SmartRule {
+                    regex,
+                    priority: rule.priority,
+                }),
+                Err(err) => {
+                    tracing::warn!(
+                        "invalid smart selection regex {:?}: {err}",
+                        rule.regex
+                    );
+                    None
+                }
+            })
+            .collect();
+
+        // Stable sort, rules with the same priority keep the configuration order.
+        smart_rules.sort_by_key(|rule| std::cmp::Reverse(rule.priority));
+
+        Self {
+            semantic_escape_chars: config.semantic_escape_chars.clone(),
+            smart_rules,
+            trim_block_whitespace: config.trim_block_whitespace,
+        }
+    }
+}
+
+/// Find the match of the smart selection rule with the highest priority
+/// containing the point, searching the line around it.
+pub fn smart_match<T: EventListener>(term: &Crosswords<T>, point: Pos) -> Option<Match> {
+    let start = term.line_search_left(point);
+    let end = term.line_search_right(point);
+
+    term.selection_config().smart_rules.iter().find_map(|rule| {
+        let mut regex = rule.regex.clone();
+        RegexIter::new(start, end, Direction::Right, term, &mut regex)
+            .take_while(|regex_match| *regex_match.start() <= point)
+            .find(|regex_match| regex_match.contains(&point))
+    })
+}
+
 /// A Pos and side within that point.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct Anchor {
@@ -316,7 +389,16 @@ impl Selection {
         mut start: Pos,
         mut end: Pos,
     ) -> SelectionRange {
+        let start_match = smart_match(term, start);
         if start == end {
+            if let Some(smart_match) = start_match {
+                return SelectionRange {
+                    start: *smart_match.start(),
+                    end: *smart_match.end(),
+                    is_block: false,
+                };
+            }
+
             if let Some(matching) = term.bracket_search(start) {
                 if (matching.row == start.row && matching.col < start.col)
                     || (matching.row < start.row)
@@ -334,8 +416,15 @@ impl Selection {
             }
         }
 
-        let start = term.semantic_search_left(start);
-        let end = term.semantic_search_right(end);
+        // Smart selection matches are never split when the selection is extended.
+        let start = match start_match {
+            Some(smart_match) => *smart_match.start(),
+            None => term.semantic_search_left(start),
+        };
+        let end = match smart_match(term, end) {
+            Some(smart_match) => *smart_match.end(),
+            None => term.semantic_search_right(end),
+        };
 
         SelectionRange {
             start,
@@ -442,6 +531,7 @@ mod tests {
     use super::*;
     use crate::crosswords::CrosswordsSize;
     use crate::event::VoidListener;
+    use crate::performer::handler::Handler;
 
     use crate::crosswords::pos::{Column, Pos, Side};
     use crate::crosswords::Crosswords;
@@ -801,4 +891,75 @@ mod tests {
         assert!(!selection.intersects_range(..=Line(2)));
         assert!(!selection.intersects_range(Line(7)..=Line(8)));
     }
+
+    fn input(term: &mut Crosswords<VoidListener>, text: &str) {
+        for c in text.chars() {
+            term.input(c);
+        }
+    }
+
+    #[test]
+    fn smart_selection() {
+        let mut term = term(1, 40);
+        term.set_selection_config(SelectionConfig::from(&Config::default()));
+        input(&mut term, r#"see https://rio.dev/x ~/a/b "x y" z"#);
+
+        let range = |term: &Crosswords<VoidListener>, start: usize, end: usize| {
+            let mut selection = Selection::new(
+                SelectionType::Semantic,
+                Pos::new(Line(0), Column(start)),
+                Side::Left,
+            );
+            selection.update(Pos::new(Line(0), Column(end)), Side::Right);
+            selection.to_range(term).unwrap()
+        };
+        let expected = |start: usize, end: usize| SelectionRange {
+            start: Pos::new(Line(0), Column(start)),
+            end: Pos::new(Line(0), Column(end)),
+            is_block: false,
+        };
+
+        // The URL wins over the path inside of it.
+        assert_eq!(range(&term, 15, 15), expected(4, 20));
+        assert_eq!(range(&term, 23, 23), expected(22, 26));
+        assert_eq!(range(&term, 30, 30), expected(28, 32));
+        assert_eq!(range(&term, 1, 1), expected(0, 2));
+
+        // Extending the selection keeps the matches whole.
+        assert_eq!(range(&term, 1, 8), expected(0, 20));
+
+        // Without smart rules, the escape chars split the words.
+        let config = Config {
+            semantic_escape_chars: String::from(" /"),
+            smart_rules: Vec::new(),
+            ..Config::default()
+        };
+        term.set_selection_config(SelectionConfig::from(&config));
+        assert_eq!(range(&term, 15, 15), expected(12, 18));
+        assert_eq!(range(&term, 5, 5), expected(4, 9));
+    }
+
+    #[test]
+    fn block_selection_whitespace() {
+        let mut term = term(2, 10);
+        input(&mut term, "ab");
+        term.goto(Line(1), Column(0));
+        input(&mut term, "abcd");
+
+        let mut selection = Selection::new(
+            SelectionType::Block,
+            Pos::new(Line(0), Column(0)),
+            Side::Left,
+        );
+        selection.update(Pos::new(Line(1), Column(3)), Side::Right);
+        term.selection = Some(selection);
+        assert_eq!(term.selection_to_string().as_deref(), Some("ab\nabcd"));
+
+        let config = Config {
+            trim_block_whitespace: false,
+            ..Config::default()
+        };
+        term.set_selection_config(SelectionConfig::from(&config));
+        assert_eq!(term.selection_to_string().as_deref(), Some("ab  \nabcd"));
+    }
 }
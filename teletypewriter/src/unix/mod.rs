@@ -398,6 +398,7 @@ pub fn create_pty_with_spawn(
     shell: &str,
     args: Vec<String>,
     working_directory: &Option<String>,
+    env: &[(String, String)],
     columns: u16,
     rows: u16,
 ) -> Result<Pty, Error> {
@@ -475,6 +476,9 @@ pub fn create_pty_with_spawn(
             }
 
             with_args.push("--env=TERM_PROGRAM=rio".to_string());
+            for (key, value) in env {
+                with_args.push(format!("--env={key}={value}"));
+            }
 
             let output = std::process::Command::new("flatpak-spawn")
                 .args(["--host", "sh", "-c", "echo $SHELL"])
@@ -502,6 +506,7 @@ pub fn create_pty_with_spawn(
 
     builder.env("USER", user.user);
     builder.env("HOME", user.home);
+    builder.envs(env.iter().map(|(key, value)| (key, value)));
 
     unsafe {
         builder.pre_exec(move || {
@@ -1,5 +1,7 @@
 use crate::Winsize;
+use std::ffi::OsString;
 use std::io::{Error, Result};
+use std::os::windows::ffi::OsStrExt;
 use std::os::windows::io::IntoRawHandle;
 use std::{mem, ptr};
 use tracing::*;
@@ -16,7 +18,7 @@ use windows_sys::{s, w};
 
 use windows_sys::Win32::System::Threading::{
     CreateProcessW, InitializeProcThreadAttributeList, UpdateProcThreadAttribute,
-    EXTENDED_STARTUPINFO_PRESENT, PROCESS_INFORMATION,
+    CREATE_UNICODE_ENVIRONMENT, EXTENDED_STARTUPINFO_PRESENT, PROCESS_INFORMATION,
     PROC_THREAD_ATTRIBUTE_PSEUDOCONSOLE, STARTF_USESTDHANDLES, STARTUPINFOEXW,
     STARTUPINFOW,
 };
@@ -103,9 +105,36 @@ impl Drop for Conpty {
 // The ConPTY handle can be sent between threads.
 unsafe impl Send for Conpty {}
 
+/// Environment block of the current process with the given variables set, in
+/// the format expected by `CreateProcessW`.
+fn environment_block(env: &[(String, String)]) -> Vec<u16> {
+    let mut vars: Vec<(OsString, OsString)> = std::env::vars_os()
+        // Variable names are case insensitive on Windows.
+        .filter(|(key, _)| {
+            !env.iter()
+                .any(|(name, _)| key.to_string_lossy().eq_ignore_ascii_case(name))
+        })
+        .collect();
+    vars.extend(
+        env.iter()
+            .map(|(key, value)| (OsString::from(key), OsString::from(value))),
+    );
+
+    let mut block = Vec::new();
+    for (key, value) in vars {
+        block.extend(key.encode_wide());
+        block.push('=' as u16);
+        block.extend(value.encode_wide());
+        block.push(0);
+    }
+    block.push(0);
+    block
+}
+
 pub fn new(
     shell: &str,
     working_directory: &Option<String>,
+    env: &[(String, String)],
     columns: u16,
     rows: u16,
 ) -> Result<Pty> {
@@ -215,6 +244,11 @@ pub fn new(
 
     let cmdline = win32_string(&cmdline(shell));
     let cwd = working_directory.as_ref().map(win32_string);
+    let env_block = (!env.is_empty()).then(|| environment_block(env));
+    let mut creation_flags = EXTENDED_STARTUPINFO_PRESENT;
+    if env_block.is_some() {
+        creation_flags |= CREATE_UNICODE_ENVIRONMENT;
+    }
 
     let mut proc_info: PROCESS_INFORMATION = unsafe { mem::zeroed() };
     unsafe {
@@ -224,8 +258,10 @@ pub fn new(
             ptr::null_mut(),
             ptr::null_mut(),
             false as i32,
-            EXTENDED_STARTUPINFO_PRESENT,
-            ptr::null_mut(),
+            creation_flags,
+            env_block
+                .as_ref()
+                .map_or_else(ptr::null, |block| block.as_ptr() as *const _),
             cwd.as_ref().map_or_else(ptr::null, |s| s.as_ptr()),
             &mut startup_info_ex.StartupInfo as *mut STARTUPINFOW,
             &mut proc_info as *mut PROCESS_INFORMATION,